@@ -5,4 +5,10 @@ pub mod static_addresses {
     pub const LOG_SERVER : &str = "ipc://tmp/log_server";
     pub const WORLD_SERVER : &str = "ipc://tmp/world_server";
     pub const PLAYER_DATA_SERVER: &str = "ipc://tmp/player_data_server";
+    pub const ENTITY_SERVER: &str = "ipc://tmp/entity_server";
+    // the entity server publishes the state of its entities here, every tick
+    pub const ENTITY_SERVER_PUBLISH: &str = "ipc://tmp/entity_server_publish";
+    pub const CHAT_SERVER: &str = "ipc://tmp/chat_server";
+    // the chat server publishes the messages of the players here, to the columns around them
+    pub const CHAT_SERVER_PUBLISH: &str = "ipc://tmp/chat_server_publish";
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position};
+
+pub const VERSION_STRING: &str = env!("CARGO_PKG_VERSION");
+
+/// Players receive the messages of the players that are at most this many columns away
+pub const CHAT_DISTANCE: i32 = 8;
+
+pub const CONNECTION_NAME_CHAT_SERVER_REQ: &str = "ChatServerRequest";
+
+#[derive(Serialize, Deserialize)]
+pub enum ChatServerReq {
+    Ping(String),
+    /// Broadcasts the message to the players near its sender
+    Say(ChatMessage),
+}
+
+pub const CONNECTION_NAME_CHAT_SERVER_REP: &str = "ChatServerReply";
+
+#[derive(Serialize, Deserialize)]
+pub enum ChatServerRep {
+    Pong(String),
+    Published,
+}
+
+/// A chat message of a player. Published by the chat server once for every column within
+/// `CHAT_DISTANCE` of its sender, preceded by the topic of the column, see `column_topic`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChatMessage {
+    pub sender_name: String,
+    /// the position of the sender, in the coordinates of our voxels
+    pub position: Position,
+    pub text: String,
+}
+
+/// The topic of the messages for a column. A player subscribes to the topic of the column that it
+/// is in, and receives every message of the players near it exactly once
+pub fn column_topic(column: ChunkColumnCoordinate) -> Vec<u8> {
+    // the terminator keeps the topic of one column from being a prefix of another
+    format!("chat/{},{};", column.x, column.z).into_bytes()
+}
+
+/// The columns that receive a message that is sent from the given column
+pub fn columns_in_range(
+    center: ChunkColumnCoordinate,
+) -> impl Iterator<Item = ChunkColumnCoordinate> {
+    (-CHAT_DISTANCE..=CHAT_DISTANCE)
+        .flat_map(move |z| (-CHAT_DISTANCE..=CHAT_DISTANCE).map(move |x| center.add(x, z)))
+}
@@ -0,0 +1,107 @@
+extern crate zmq;
+
+use sol_address_server::static_addresses;
+use sol_chat_messages::{
+    column_topic, columns_in_range, ChatMessage, ChatServerRep, ChatServerReq,
+};
+use sol_log_server::logger_mt::LoggerMt;
+use sol_log_server::Severity;
+use sol_network_lib::network::{self, NetworkError, ReplyLoop};
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+
+/// The chat server relays the chat messages of players to the players near them.
+/// Player processes send their messages with requests, and subscribe to the topic of the column
+/// that their player is in.
+fn main() {
+    let context = zmq::Context::new();
+    let logger = LoggerMt::new(
+        "Chat Server",
+        context.clone(),
+        String::from(static_addresses::LOG_SERVER),
+    )
+    .expect("Could not connect logger");
+
+    let publish_socket = match bind_publish_socket(&context) {
+        Ok(socket) => socket,
+        Err(error) => {
+            logger.log(
+                Severity::FatalError,
+                &format!("Could not bind publish socket: {error:?}"),
+            );
+            return;
+        },
+    };
+
+    let mut reply_loop = {
+        let handler_logger = logger.clone();
+        let reply_loop_result = ReplyLoop::new(
+            context.clone(),
+            String::from(static_addresses::CHAT_SERVER),
+            move |message| handle_message(&publish_socket, &handler_logger, message),
+        );
+
+        match reply_loop_result {
+            Ok(reply_loop) => reply_loop,
+            Err(error) => {
+                logger.log(
+                    Severity::FatalError,
+                    &format!("Could not create reply loop: {error}"),
+                );
+                return;
+            },
+        }
+    };
+
+    logger.send_status("Chat server online");
+
+    match reply_loop.listen_until_stop() {
+        Ok(_) => {},
+        Err(NetworkError::ZmqError(error)) => {
+            logger.log(Severity::FatalError, &format!("ZeroMQ error: {error}"))
+        },
+        Err(NetworkError::SerialisationError(error)) => logger.log(
+            Severity::FatalError,
+            &format!("Serialisation error: {error}"),
+        ),
+    }
+
+    logger.send_status("Chat server offline");
+}
+
+fn bind_publish_socket(context: &zmq::Context) -> Result<zmq::Socket, NetworkError> {
+    let socket = context.socket(zmq::PUB).map_err(NetworkError::ZmqError)?;
+    socket
+        .bind(static_addresses::CHAT_SERVER_PUBLISH)
+        .map_err(NetworkError::ZmqError)?;
+    Ok(socket)
+}
+
+fn handle_message(
+    publish_socket: &zmq::Socket,
+    logger: &LoggerMt,
+    message: ChatServerReq,
+) -> ChatServerRep {
+    match message {
+        ChatServerReq::Ping(ping) => ChatServerRep::Pong(ping),
+        ChatServerReq::Say(chat_message) => {
+            if let Err(error) = publish(publish_socket, chat_message) {
+                logger.log(
+                    Severity::RecoverableError,
+                    &format!("Could not publish chat message: {error:?}"),
+                );
+            }
+            ChatServerRep::Published
+        },
+    }
+}
+
+fn publish(publish_socket: &zmq::Socket, chat_message: ChatMessage) -> Result<(), NetworkError> {
+    let center = ChunkColumnCoordinate::containing_position(&chat_message.position);
+    for column in columns_in_range(center) {
+        publish_socket
+            .send(column_topic(column), zmq::SNDMORE)
+            .map_err(NetworkError::ZmqError)?;
+        network::send(publish_socket, chat_message.clone(), 0)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position, Vector3f};
+
+use crate::entity_type::{EntityDimensions, EntityType};
+use crate::metadata::EntityMetadata;
+
+pub type EntityId = u32;
+
+// The servers allocate entity ids from ranges that do not overlap, so that a client never sees
+// two entities with the same id
+/// The player characters; their ids are the session ids of the player server
+pub const PLAYER_CHARACTER_IDS: Range<EntityId> = 0..1 << 24;
+/// The entities that the player server spawns itself, like dropped items
+pub const PLAYER_SERVER_ENTITY_IDS: Range<EntityId> = 1 << 24..1 << 28;
+/// The entities of the entity servers. TODO the servers of all regions share this range, until
+/// entities are handed over between them
+pub const ENTITY_SERVER_ENTITY_IDS: Range<EntityId> = 1 << 28..EntityId::MAX;
+
+/// Angles in degrees, as in the minecraft protocol.
+/// A yaw of 0 faces south (+z) and increases clockwise; a positive pitch looks down
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityRotation {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub head_yaw: f32,
+}
+
+/// Positions are in the coordinates of our voxels, where y = 0 is the bottom of the world
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: EntityId,
+    pub uuid: u128,
+    pub entity_type: EntityType,
+    /// the center of the bottom of the bounding box
+    pub position: Position,
+    /// in blocks per tick
+    pub velocity: Vector3f,
+    pub rotation: EntityRotation,
+    pub on_ground: bool,
+    /// None for entities that are not living
+    pub health: Option<f32>,
+    pub metadata: EntityMetadata,
+}
+
+impl Entity {
+    pub fn new(id: EntityId, uuid: u128, entity_type: EntityType, position: Position) -> Entity {
+        Entity {
+            id,
+            uuid,
+            entity_type,
+            position,
+            velocity: Vector3f::zeros(),
+            rotation: EntityRotation::default(),
+            on_ground: false,
+            health: entity_type.max_health(),
+            metadata: EntityMetadata::default(),
+        }
+    }
+
+    pub fn dimensions(&self) -> EntityDimensions {
+        self.entity_type.dimensions()
+    }
+
+    pub fn column(&self) -> ChunkColumnCoordinate {
+        ChunkColumnCoordinate::containing_position(&self.position)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.health.is_none_or(|health| health > 0.0)
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position};
+
+use crate::entity::{Entity, EntityId};
+use crate::entity_type::EntityType;
+
+/// Owns a set of entities, allocates their ids, and finds them by the column they are in
+pub struct EntityManager {
+    entities: HashMap<EntityId, Entity>,
+    by_column: HashMap<ChunkColumnCoordinate, HashSet<EntityId>>,
+    next_id: EntityId,
+}
+
+impl EntityManager {
+    /// Ids are allocated from `first_id` upwards, so that ids below it can be used elsewhere
+    pub fn new(first_id: EntityId) -> Self {
+        EntityManager {
+            entities: HashMap::new(),
+            by_column: HashMap::new(),
+            next_id: first_id,
+        }
+    }
+
+    /// Creates an entity with a new id, and returns that id
+    pub fn spawn(&mut self, uuid: u128, entity_type: EntityType, position: Position) -> EntityId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.insert(Entity::new(id, uuid, entity_type, position));
+        id
+    }
+
+    /// Adds an entity that already has an id, replacing the entity with the same id.
+    /// Later allocated ids are higher than the id of this entity
+    pub fn insert(&mut self, entity: Entity) {
+        self.remove(entity.id);
+
+        self.next_id = self.next_id.max(entity.id + 1);
+        self.by_column
+            .entry(entity.column())
+            .or_default()
+            .insert(entity.id);
+        self.entities.insert(entity.id, entity);
+    }
+
+    pub fn remove(&mut self, id: EntityId) -> Option<Entity> {
+        let entity = self.entities.remove(&id)?;
+        self.remove_from_column(id, entity.column());
+        Some(entity)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.entities.contains_key(&id)
+    }
+
+    /// Runs `hook` on the entity, and keeps track of the column that the entity moves to.
+    /// Returns None if there is no entity with this id
+    pub fn update<R>(&mut self, id: EntityId, hook: impl FnOnce(&mut Entity) -> R) -> Option<R> {
+        let entity = self.entities.get_mut(&id)?;
+        let old_column = entity.column();
+
+        let result = hook(entity);
+
+        // the hook must not change the id
+        debug_assert_eq!(entity.id, id);
+
+        let new_column = entity.column();
+        if new_column != old_column {
+            self.remove_from_column(id, old_column);
+            self.by_column.entry(new_column).or_default().insert(id);
+        }
+
+        Some(result)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.values()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.entities.keys().copied()
+    }
+
+    pub fn in_column(&self, coord: ChunkColumnCoordinate) -> impl Iterator<Item = &Entity> {
+        self.by_column
+            .get(&coord)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.entities.get(id))
+    }
+
+    /// The entities in the square of columns with the given radius around `center`
+    pub fn in_area(&self, center: ChunkColumnCoordinate, radius: i32) -> Vec<&Entity> {
+        let mut entities = Vec::new();
+        for z in -radius..=radius {
+            for x in -radius..=radius {
+                entities.extend(self.in_column(center.add(x, z)));
+            }
+        }
+        entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    fn remove_from_column(&mut self, id: EntityId, coord: ChunkColumnCoordinate) {
+        if let Some(ids) = self.by_column.get_mut(&coord) {
+            ids.remove(&id);
+            if ids.is_empty() {
+                self.by_column.remove(&coord);
+            }
+        }
+    }
+}
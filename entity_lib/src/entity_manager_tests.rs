@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position};
+
+    use crate::entity::{
+        Entity, ENTITY_SERVER_ENTITY_IDS, PLAYER_CHARACTER_IDS, PLAYER_SERVER_ENTITY_IDS,
+    };
+    use crate::entity_manager::EntityManager;
+    use crate::entity_type::EntityType;
+
+    #[test]
+    fn test_id_allocation() {
+        let mut manager = EntityManager::new(1);
+
+        let a = manager.spawn(0, EntityType::Item, Position::new(0.0, 0.0, 0.0));
+        let b = manager.spawn(0, EntityType::Pig, Position::new(0.0, 0.0, 0.0));
+        assert_eq!(a, 1);
+        assert_eq!(b, 2);
+
+        manager.insert(Entity::new(10, 0, EntityType::Cow, Position::new(0.0, 0.0, 0.0)));
+        let c = manager.spawn(0, EntityType::Item, Position::new(0.0, 0.0, 0.0));
+        assert_eq!(c, 11);
+
+        assert_eq!(manager.len(), 4);
+        assert_eq!(manager.remove(b).map(|entity| entity.entity_type), Some(EntityType::Pig));
+        assert!(manager.remove(b).is_none());
+        assert_eq!(manager.len(), 3);
+    }
+
+    #[test]
+    fn test_spatial_lookup() {
+        let mut manager = EntityManager::new(0);
+
+        let id = manager.spawn(0, EntityType::Item, Position::new(1.0, 100.0, 1.0));
+        manager.spawn(0, EntityType::Item, Position::new(40.0, 100.0, -40.0));
+
+        let origin = ChunkColumnCoordinate { x: 0, z: 0 };
+        let far = ChunkColumnCoordinate { x: 2, z: -3 };
+        assert_eq!(manager.in_column(origin).count(), 1);
+        assert_eq!(manager.in_column(far).count(), 1);
+        assert_eq!(manager.in_area(origin, 2).len(), 1);
+        assert_eq!(manager.in_area(origin, 3).len(), 2);
+
+        // moving the entity to another column updates the lookup
+        manager.update(id, |entity| entity.position.x = -1.0);
+        assert_eq!(manager.in_column(origin).count(), 0);
+        assert_eq!(
+            manager
+                .in_column(ChunkColumnCoordinate { x: -1, z: 0 })
+                .count(),
+            1
+        );
+
+        manager.remove(id);
+        assert_eq!(
+            manager
+                .in_column(ChunkColumnCoordinate { x: -1, z: 0 })
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_update_missing_entity() {
+        let mut manager = EntityManager::new(0);
+        assert_eq!(manager.update(5, |_| ()), None);
+    }
+
+    #[test]
+    fn test_id_ranges_do_not_overlap() {
+        assert_eq!(PLAYER_CHARACTER_IDS.start, 0);
+        assert_eq!(PLAYER_CHARACTER_IDS.end, PLAYER_SERVER_ENTITY_IDS.start);
+        assert_eq!(PLAYER_SERVER_ENTITY_IDS.end, ENTITY_SERVER_ENTITY_IDS.start);
+
+        // the managers of the servers allocate from their own range
+        let mut manager = EntityManager::new(PLAYER_SERVER_ENTITY_IDS.start);
+        let id = manager.spawn(0, EntityType::Item, Position::new(0.0, 0.0, 0.0));
+        assert!(PLAYER_SERVER_ENTITY_IDS.contains(&id));
+        assert!(!PLAYER_CHARACTER_IDS.contains(&id));
+    }
+}
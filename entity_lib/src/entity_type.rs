@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EntityType {
+    Player,
+    Item,
+    FallingBlock,
+    ExperienceOrb,
+    Arrow,
+    Zombie,
+    Skeleton,
+    Creeper,
+    Pig,
+    Cow,
+    Sheep,
+    Chicken,
+}
+
+/// The size of the bounding box of an entity, in blocks.
+/// The box is centered horizontally on the position of the entity, and stands on it
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EntityDimensions {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl EntityType {
+    // the vanilla sizes
+    pub fn dimensions(&self) -> EntityDimensions {
+        let (width, height) = match self {
+            EntityType::Player => (0.6, 1.8),
+            EntityType::Item => (0.25, 0.25),
+            EntityType::FallingBlock => (0.98, 0.98),
+            EntityType::ExperienceOrb => (0.5, 0.5),
+            EntityType::Arrow => (0.5, 0.5),
+            EntityType::Zombie => (0.6, 1.95),
+            EntityType::Skeleton => (0.6, 1.99),
+            EntityType::Creeper => (0.6, 1.7),
+            EntityType::Pig => (0.9, 0.9),
+            EntityType::Cow => (0.9, 1.4),
+            EntityType::Sheep => (0.9, 1.3),
+            EntityType::Chicken => (0.4, 0.7),
+        };
+
+        EntityDimensions { width, height }
+    }
+
+    /// Living entities have health, and are affected by the gravity of mobs
+    pub fn is_living(&self) -> bool {
+        match self {
+            EntityType::Item
+            | EntityType::FallingBlock
+            | EntityType::ExperienceOrb
+            | EntityType::Arrow => false,
+            EntityType::Player
+            | EntityType::Zombie
+            | EntityType::Skeleton
+            | EntityType::Creeper
+            | EntityType::Pig
+            | EntityType::Cow
+            | EntityType::Sheep
+            | EntityType::Chicken => true,
+        }
+    }
+
+    /// None for entities that are not living
+    pub fn max_health(&self) -> Option<f32> {
+        let max_health = match self {
+            EntityType::Player | EntityType::Zombie | EntityType::Skeleton => 20.0,
+            EntityType::Creeper | EntityType::Pig | EntityType::Cow => 10.0,
+            EntityType::Sheep => 8.0,
+            EntityType::Chicken => 4.0,
+            _ => return None,
+        };
+
+        Some(max_health)
+    }
+}
@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+
+#[cfg(test)]
+mod entity_manager_tests;
+
+pub mod entity;
+pub mod entity_manager;
+pub mod entity_type;
+pub mod metadata;
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// The state of an entity that the client shows, apart from its position
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EntityMetadata {
+    pub on_fire: bool,
+    pub sneaking: bool,
+    pub sprinting: bool,
+    pub invisible: bool,
+    pub glowing: bool,
+    pub silent: bool,
+    pub no_gravity: bool,
+    pub custom_name: Option<String>,
+    /// the item that an item entity shows
+    pub item: Option<ItemData>,
+    /// the block state that a falling block entity shows
+    pub block_state: Option<u32>,
+}
+
+/// An item, as carried by an entity
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemData {
+    pub item_id: u32,
+    pub count: u8,
+}
+
+impl EntityMetadata {
+    /// The flags as in the first metadata entry of every minecraft entity
+    pub fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.on_fire {
+            flags |= 0x01;
+        }
+        if self.sneaking {
+            flags |= 0x02;
+        }
+        if self.sprinting {
+            flags |= 0x08;
+        }
+        if self.invisible {
+            flags |= 0x20;
+        }
+        if self.glowing {
+            flags |= 0x40;
+        }
+        flags
+    }
+}
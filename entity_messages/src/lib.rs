@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use sol_entity_lib::entity::{Entity, EntityId};
+use sol_entity_lib::entity_type::EntityType;
+use sol_entity_lib::metadata::EntityMetadata;
+use sol_network_lib::Tick;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position, Vector3f};
+
+pub const VERSION_STRING: &str = env!("CARGO_PKG_VERSION");
+
+pub const CONNECTION_NAME_ENTITY_SERVER_REQ: &str = "EntityServerRequest";
+
+#[derive(Serialize, Deserialize)]
+pub enum EntityServerReq {
+    Ping(String),
+    Spawn {
+        uuid: u128,
+        entity_type: EntityType,
+        position: Position,
+        velocity: Vector3f,
+        metadata: EntityMetadata,
+    },
+    Despawn(EntityId),
+    /// Replaces the state of an entity; its id and type cannot change
+    Update(Box<Entity>),
+    Get(EntityId),
+}
+
+pub const CONNECTION_NAME_ENTITY_SERVER_REP: &str = "EntityServerReply";
+
+#[derive(Serialize, Deserialize)]
+pub enum EntityServerRep {
+    Pong(String),
+    Spawned(EntityId),
+    Despawned(EntityId),
+    Updated(EntityId),
+    Entity(Box<Entity>),
+    UnknownEntity(EntityId),
+    /// the position is not in the region of this server
+    OutsideRegion(Position),
+}
+
+/// Published by the entity server every tick, for every column that has entities or that
+/// entities left. Each message is preceded by the topic of its column, see `column_topic`
+#[derive(Serialize, Deserialize)]
+pub struct EntityColumnState {
+    pub tick: Tick,
+    pub column: ChunkColumnCoordinate,
+    pub entities: Vec<Entity>,
+    /// entities that were in this column in the previous tick, and are despawned or in another
+    /// column now
+    pub left: Vec<EntityId>,
+}
+
+/// The topic of the published state of a column. Subscribe to the topics of the columns around a
+/// player to receive the entities near them
+pub fn column_topic(column: ChunkColumnCoordinate) -> Vec<u8> {
+    // the terminator keeps the topic of one column from being a prefix of another
+    format!("entities/{},{};", column.x, column.z).into_bytes()
+}
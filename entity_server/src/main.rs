@@ -1,3 +1,178 @@
+#![allow(dead_code)]
+
+extern crate zmq;
+
+mod simulation;
+#[cfg(test)]
+mod simulation_tests;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use minecraft_vanilla::registries::Registries;
+use sol_address_server::static_addresses;
+use sol_entity_messages::{column_topic, EntityServerRep, EntityServerReq};
+use sol_log_server::logger_mt::LoggerMt;
+use sol_log_server::Severity;
+use sol_network_lib::constants;
+use sol_network_lib::network::{self, NetworkError, ReplyLoop};
+use sol_network_lib::Tick;
+use sol_world_messages::{WorldServerRep, WorldServerReq};
+
+use crate::simulation::EntitySimulation;
+
+// the simulation must not stall when the world server is unavailable
+const WORLD_SERVER_TIMEOUT_MS: i32 = 1000;
+
+/// The entity server simulates the entities of one region.
+/// Other processes spawn, despawn and change entities with requests, and subscribe to the state
+/// of the entities in the columns that they are interested in.
+///
+/// Usage: `sol_entity_server [<region x> <region z>]`
 fn main() {
-    println!("Hello, world!");
+    let context = zmq::Context::new();
+    let logger = LoggerMt::new(
+        "Entity Server",
+        context.clone(),
+        String::from(static_addresses::LOG_SERVER),
+    )
+    .expect("Could not connect logger");
+
+    let mut args = std::env::args().skip(1);
+    let region_x = args.next().and_then(|x| x.parse().ok()).unwrap_or(0);
+    let region_z = args.next().and_then(|z| z.parse().ok()).unwrap_or(0);
+
+    let simulation = Arc::new(Mutex::new(EntitySimulation::new(region_x, region_z)));
+
+    // requests are handled in between ticks; the reply loop stops with the process
+    {
+        let simulation = simulation.clone();
+        let context = context.clone();
+        let logger = logger.clone();
+        thread::spawn(move || run_reply_loop(simulation, context, logger));
+    }
+
+    let registries = minecraft_vanilla::registries::get_registries();
+    logger.send_status("Entity server online");
+
+    if let Err(error) = run_simulation(&simulation, &context, &registries, &logger) {
+        logger.log(
+            Severity::FatalError,
+            &format!("Simulation stopped: {error:?}"),
+        );
+    }
+
+    logger.send_status("Entity server offline");
+}
+
+fn run_reply_loop(
+    simulation: Arc<Mutex<EntitySimulation>>,
+    context: zmq::Context,
+    logger: LoggerMt,
+) {
+    let reply_loop_result = ReplyLoop::new(
+        context,
+        String::from(static_addresses::ENTITY_SERVER),
+        move |message: EntityServerReq| -> EntityServerRep {
+            simulation.lock().unwrap().handle_request(message)
+        },
+    );
+
+    let mut reply_loop = match reply_loop_result {
+        Ok(reply_loop) => reply_loop,
+        Err(error) => {
+            logger.log(
+                Severity::FatalError,
+                &format!("Could not create reply loop: {error}"),
+            );
+            return;
+        },
+    };
+
+    match reply_loop.listen_until_stop() {
+        Ok(_) => {},
+        Err(NetworkError::ZmqError(error)) => {
+            logger.log(Severity::FatalError, &format!("ZeroMQ error: {error}"))
+        },
+        Err(NetworkError::SerialisationError(error)) => logger.log(
+            Severity::FatalError,
+            &format!("Serialisation error: {error}"),
+        ),
+    }
+}
+
+fn run_simulation(
+    simulation: &Mutex<EntitySimulation>,
+    context: &zmq::Context,
+    registries: &Registries,
+    logger: &LoggerMt,
+) -> Result<(), NetworkError> {
+    let publish_socket = context.socket(zmq::PUB).map_err(NetworkError::ZmqError)?;
+    publish_socket
+        .bind(static_addresses::ENTITY_SERVER_PUBLISH)
+        .map_err(NetworkError::ZmqError)?;
+
+    let world_server_socket = context.socket(zmq::REQ).map_err(NetworkError::ZmqError)?;
+    world_server_socket
+        .connect(static_addresses::WORLD_SERVER)
+        .map_err(NetworkError::ZmqError)?;
+    world_server_socket
+        .set_rcvtimeo(WORLD_SERVER_TIMEOUT_MS)
+        .map_err(NetworkError::ZmqError)?;
+    world_server_socket
+        .set_req_relaxed(true)
+        .map_err(NetworkError::ZmqError)?;
+    world_server_socket
+        .set_req_correlate(true)
+        .map_err(NetworkError::ZmqError)?;
+
+    let mut current_tick: Tick = 0;
+    let mut last_loop_end = Instant::now();
+
+    loop {
+        current_tick += 1;
+
+        // load the columns around the entities without holding the lock
+        let missing_columns = simulation.lock().unwrap().missing_columns();
+        for coord in missing_columns {
+            let reply = network::query::<WorldServerReq, WorldServerRep>(
+                &world_server_socket,
+                WorldServerReq::ContentChunkColumn(coord),
+            );
+
+            match reply {
+                Ok(WorldServerRep::ContentChunkColumn(_, column)) => {
+                    simulation.lock().unwrap().insert_column(*column)
+                },
+                Ok(_) => logger.log(
+                    Severity::RecoverableError,
+                    &format!("World server did not send column {coord:?}"),
+                ),
+                Err(error) => logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not request column {coord:?}: {error:?}"),
+                ),
+            }
+        }
+
+        let states = simulation.lock().unwrap().step(
+            current_tick,
+            registries.block_properties(),
+            registries.block_states(),
+        );
+
+        for state in states {
+            publish_socket
+                .send(column_topic(state.column), zmq::SNDMORE)
+                .map_err(NetworkError::ZmqError)?;
+            network::send(&publish_socket, state, 0)?;
+        }
+
+        let end = Instant::now();
+        if let Some(remaining_time) = constants::TICK_PERIOD.checked_sub(end - last_loop_end) {
+            thread::sleep(remaining_time);
+        }
+        last_loop_end = Instant::now();
+    }
 }
@@ -0,0 +1,185 @@
+use std::collections::{HashMap, HashSet};
+
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use sol_entity_lib::entity::{EntityId, ENTITY_SERVER_ENTITY_IDS};
+use sol_entity_lib::entity_manager::EntityManager;
+use sol_entity_messages::{EntityColumnState, EntityServerRep, EntityServerReq};
+use sol_game_engine::physics::collision::ColumnCollider;
+use sol_game_engine::physics::entity_physics;
+use sol_network_lib::Tick;
+use sol_voxel_lib::chunk_column::ChunkColumn;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position};
+
+/// The width of a region, in columns; the same as the region files of the world server
+pub const REGION_SIZE: i32 = 32;
+
+/// The entities of one region, and the columns that they collide with.
+/// Entities that leave the region keep being simulated here; there is no hand-over to the server
+/// of the neighbouring region yet
+pub struct EntitySimulation {
+    region_x: i32,
+    region_z: i32,
+    entities: EntityManager,
+    // the column of every entity at the end of the previous tick
+    previous_columns: HashMap<EntityId, ChunkColumnCoordinate>,
+    columns: HashMap<ChunkColumnCoordinate, ChunkColumn>,
+}
+
+impl EntitySimulation {
+    pub fn new(region_x: i32, region_z: i32) -> EntitySimulation {
+        EntitySimulation {
+            region_x,
+            region_z,
+            entities: EntityManager::new(ENTITY_SERVER_ENTITY_IDS.start),
+            previous_columns: HashMap::new(),
+            columns: HashMap::new(),
+        }
+    }
+
+    pub fn is_in_region(&self, position: &Position) -> bool {
+        let column = ChunkColumnCoordinate::containing_position(position);
+        column.x.div_euclid(REGION_SIZE) == self.region_x
+            && column.z.div_euclid(REGION_SIZE) == self.region_z
+    }
+
+    pub fn handle_request(&mut self, request: EntityServerReq) -> EntityServerRep {
+        match request {
+            EntityServerReq::Ping(msg) => EntityServerRep::Pong(msg),
+            EntityServerReq::Spawn {
+                uuid,
+                entity_type,
+                position,
+                velocity,
+                metadata,
+            } => {
+                if !self.is_in_region(&position) {
+                    return EntityServerRep::OutsideRegion(position);
+                }
+
+                let id = self.entities.spawn(uuid, entity_type, position);
+                self.entities.update(id, |entity| {
+                    entity.velocity = velocity;
+                    entity.metadata = metadata;
+                });
+                EntityServerRep::Spawned(id)
+            },
+            EntityServerReq::Despawn(id) => match self.entities.remove(id) {
+                Some(_) => EntityServerRep::Despawned(id),
+                None => EntityServerRep::UnknownEntity(id),
+            },
+            EntityServerReq::Update(new_entity) => {
+                let id = new_entity.id;
+                if !self.entities.contains(id) {
+                    return EntityServerRep::UnknownEntity(id);
+                }
+                if !self.is_in_region(&new_entity.position) {
+                    return EntityServerRep::OutsideRegion(new_entity.position);
+                }
+
+                self.entities.update(id, |entity| {
+                    let entity_type = entity.entity_type;
+                    *entity = *new_entity;
+                    entity.entity_type = entity_type;
+                });
+                EntityServerRep::Updated(id)
+            },
+            EntityServerReq::Get(id) => match self.entities.get(id) {
+                Some(entity) => EntityServerRep::Entity(Box::new(entity.clone())),
+                None => EntityServerRep::UnknownEntity(id),
+            },
+        }
+    }
+
+    /// The columns that entities may collide with, and that are not loaded yet
+    pub fn missing_columns(&self) -> Vec<ChunkColumnCoordinate> {
+        self.needed_columns()
+            .into_iter()
+            .filter(|coord| !self.columns.contains_key(coord))
+            .collect()
+    }
+
+    pub fn insert_column(&mut self, column: ChunkColumn) {
+        self.columns.insert(column.coordinate(), column);
+    }
+
+    /// Moves every entity by one tick, and returns the state of every column that has entities,
+    /// or that entities left
+    pub fn step(
+        &mut self,
+        tick: Tick,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) -> Vec<EntityColumnState> {
+        // columns without entities nearby are not needed anymore
+        let needed_columns = self.needed_columns();
+        self.columns.retain(|coord, _| needed_columns.contains(coord));
+
+        let collider = ColumnCollider::new(&self.columns, block_properties, block_states);
+        let ids: Vec<EntityId> = self.entities.ids().collect();
+        for id in ids {
+            let is_loaded = self
+                .entities
+                .get(id)
+                .is_some_and(|entity| self.columns.contains_key(&entity.column()));
+            // entities wait for their column to be loaded
+            if !is_loaded {
+                continue;
+            }
+
+            self.entities
+                .update(id, |entity| entity_physics::step_entity(entity, &collider));
+        }
+
+        let mut states: HashMap<ChunkColumnCoordinate, EntityColumnState> = HashMap::new();
+        let mut current_columns = HashMap::new();
+        for entity in self.entities.iter() {
+            let column = entity.column();
+            current_columns.insert(entity.id, column);
+            states
+                .entry(column)
+                .or_insert_with(|| EntityColumnState {
+                    tick,
+                    column,
+                    entities: Vec::new(),
+                    left: Vec::new(),
+                })
+                .entities
+                .push(entity.clone());
+        }
+
+        for (id, previous_column) in &self.previous_columns {
+            if current_columns.get(id) == Some(previous_column) {
+                continue;
+            }
+
+            states
+                .entry(*previous_column)
+                .or_insert_with(|| EntityColumnState {
+                    tick,
+                    column: *previous_column,
+                    entities: Vec::new(),
+                    left: Vec::new(),
+                })
+                .left
+                .push(*id);
+        }
+
+        self.previous_columns = current_columns;
+        states.into_values().collect()
+    }
+
+    // the columns with entities, and their neighbours
+    fn needed_columns(&self) -> HashSet<ChunkColumnCoordinate> {
+        let mut needed = HashSet::new();
+        for entity in self.entities.iter() {
+            let column = entity.column();
+            for z in -1..=1 {
+                for x in -1..=1 {
+                    needed.insert(column.add(x, z));
+                }
+            }
+        }
+        needed
+    }
+}
@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_vanilla::registries::Registries;
+    use sol_entity_lib::entity::{Entity, EntityId};
+    use sol_entity_lib::entity_type::EntityType;
+    use sol_entity_lib::metadata::EntityMetadata;
+    use sol_entity_messages::{EntityColumnState, EntityServerRep, EntityServerReq};
+    use sol_voxel_lib::chunk_column::ChunkColumn;
+    use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position, Vector3f};
+
+    use crate::simulation::{EntitySimulation, REGION_SIZE};
+
+    fn spawn(
+        simulation: &mut EntitySimulation,
+        position: Position,
+        velocity: Vector3f,
+    ) -> EntityServerRep {
+        simulation.handle_request(EntityServerReq::Spawn {
+            uuid: 0,
+            entity_type: EntityType::Item,
+            position,
+            velocity,
+            metadata: EntityMetadata::default(),
+        })
+    }
+
+    fn spawned_id(reply: EntityServerRep) -> EntityId {
+        match reply {
+            EntityServerRep::Spawned(id) => id,
+            _ => panic!("Entity was not spawned"),
+        }
+    }
+
+    // empty columns around the origin, so that the entities fall through the air
+    fn load_columns(simulation: &mut EntitySimulation) {
+        for z in -1..=1 {
+            for x in -1..=1 {
+                simulation.insert_column(ChunkColumn::new(x, z));
+            }
+        }
+    }
+
+    fn step(
+        simulation: &mut EntitySimulation,
+        tick: u64,
+        registries: &Registries,
+    ) -> Vec<EntityColumnState> {
+        simulation.step(
+            tick,
+            registries.block_properties(),
+            registries.block_states(),
+        )
+    }
+
+    fn state_of(states: &[EntityColumnState], x: i32, z: i32) -> Option<&EntityColumnState> {
+        states
+            .iter()
+            .find(|state| state.column == ChunkColumnCoordinate { x, z })
+    }
+
+    #[test]
+    fn test_spawn_outside_region() {
+        let mut simulation = EntitySimulation::new(0, 0);
+        let region_width = (REGION_SIZE * 16) as f32;
+
+        for position in [
+            Position::new(-0.5, 100.0, 5.0),
+            Position::new(5.0, 100.0, -0.5),
+            Position::new(region_width + 0.5, 100.0, 5.0),
+            Position::new(5.0, 100.0, region_width + 0.5),
+        ] {
+            let reply = spawn(&mut simulation, position, Vector3f::zeros());
+            assert!(
+                matches!(reply, EntityServerRep::OutsideRegion(rejected) if rejected == position)
+            );
+        }
+
+        let reply = spawn(
+            &mut simulation,
+            Position::new(region_width - 0.5, 100.0, 5.0),
+            Vector3f::zeros(),
+        );
+        assert!(matches!(reply, EntityServerRep::Spawned(_)));
+
+        // the regions of other servers
+        let mut simulation = EntitySimulation::new(-1, 2);
+        let position = Position::new(-0.5, 100.0, 2.0 * region_width);
+        assert!(matches!(
+            spawn(&mut simulation, position, Vector3f::zeros()),
+            EntityServerRep::Spawned(_)
+        ));
+    }
+
+    #[test]
+    fn test_update_outside_region() {
+        let mut simulation = EntitySimulation::new(0, 0);
+        let position = Position::new(5.0, 100.0, 5.0);
+        let id = spawned_id(spawn(&mut simulation, position, Vector3f::zeros()));
+
+        let mut entity = Entity::new(id, 0, EntityType::Pig, Position::new(-20.0, 100.0, 5.0));
+        let reply = simulation.handle_request(EntityServerReq::Update(Box::new(entity.clone())));
+        assert!(matches!(reply, EntityServerRep::OutsideRegion(_)));
+
+        // the entity stays where it was
+        let reply = simulation.handle_request(EntityServerReq::Get(id));
+        assert!(matches!(reply, EntityServerRep::Entity(got) if got.position == position));
+
+        // the type of an entity cannot change
+        entity.position = Position::new(6.0, 100.0, 5.0);
+        let reply = simulation.handle_request(EntityServerReq::Update(Box::new(entity.clone())));
+        assert!(matches!(reply, EntityServerRep::Updated(updated) if updated == id));
+        let reply = simulation.handle_request(EntityServerReq::Get(id));
+        assert!(matches!(reply, EntityServerRep::Entity(updated)
+            if updated.position == entity.position && updated.entity_type == EntityType::Item));
+
+        entity.id = id + 1;
+        let reply = simulation.handle_request(EntityServerReq::Update(Box::new(entity)));
+        assert!(matches!(reply, EntityServerRep::UnknownEntity(unknown) if unknown == id + 1));
+    }
+
+    #[test]
+    fn test_entities_wait_for_columns() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut simulation = EntitySimulation::new(0, 0);
+        let position = Position::new(5.0, 100.0, 5.0);
+        let id = spawned_id(spawn(
+            &mut simulation,
+            position,
+            Vector3f::new(0.5, 0.0, 0.0),
+        ));
+
+        // the column of the entity and its neighbours
+        assert_eq!(simulation.missing_columns().len(), 9);
+        let states = step(&mut simulation, 1, &registries);
+        let state = state_of(&states, 0, 0).unwrap();
+        assert_eq!(state.entities.len(), 1);
+        assert_eq!(state.entities[0].position, position);
+
+        load_columns(&mut simulation);
+        assert!(simulation.missing_columns().is_empty());
+        let states = step(&mut simulation, 2, &registries);
+        let entity = &state_of(&states, 0, 0).unwrap().entities[0];
+        assert_eq!(entity.id, id);
+        assert!(entity.position.x > position.x);
+        assert!(entity.position.y < position.y);
+    }
+
+    #[test]
+    fn test_left_across_columns() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut simulation = EntitySimulation::new(0, 0);
+        load_columns(&mut simulation);
+        // one block per tick towards the column at x = 1
+        let id = spawned_id(spawn(
+            &mut simulation,
+            Position::new(14.7, 100.0, 5.0),
+            Vector3f::new(1.0, 0.0, 0.0),
+        ));
+
+        let states = step(&mut simulation, 1, &registries);
+        assert_eq!(states.len(), 1);
+        let state = state_of(&states, 0, 0).unwrap();
+        assert_eq!(state.tick, 1);
+        assert!(state.left.is_empty());
+
+        // the entity is listed in its new column, and as left in the old one
+        let states = step(&mut simulation, 2, &registries);
+        assert_eq!(states.len(), 2);
+        let old_column = state_of(&states, 0, 0).unwrap();
+        assert!(old_column.entities.is_empty());
+        assert_eq!(old_column.left, vec![id]);
+        let new_column = state_of(&states, 1, 0).unwrap();
+        assert_eq!(new_column.entities[0].id, id);
+        assert!(new_column.left.is_empty());
+
+        // it left only once
+        let states = step(&mut simulation, 3, &registries);
+        assert!(state_of(&states, 0, 0).is_none());
+        assert!(state_of(&states, 1, 0).unwrap().left.is_empty());
+    }
+
+    #[test]
+    fn test_despawn_leaves_column() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut simulation = EntitySimulation::new(0, 0);
+        load_columns(&mut simulation);
+        let id = spawned_id(spawn(
+            &mut simulation,
+            Position::new(5.0, 100.0, 5.0),
+            Vector3f::zeros(),
+        ));
+        step(&mut simulation, 1, &registries);
+
+        let reply = simulation.handle_request(EntityServerReq::Despawn(id));
+        assert!(matches!(reply, EntityServerRep::Despawned(despawned) if despawned == id));
+        let reply = simulation.handle_request(EntityServerReq::Despawn(id));
+        assert!(matches!(reply, EntityServerRep::UnknownEntity(unknown) if unknown == id));
+
+        let states = step(&mut simulation, 2, &registries);
+        assert_eq!(states.len(), 1);
+        let state = state_of(&states, 0, 0).unwrap();
+        assert!(state.entities.is_empty());
+        assert_eq!(state.left, vec![id]);
+
+        // nothing is left to publish
+        assert!(step(&mut simulation, 3, &registries).is_empty());
+        assert!(matches!(
+            simulation.handle_request(EntityServerReq::Get(id)),
+            EntityServerRep::UnknownEntity(_)
+        ));
+    }
+}
@@ -0,0 +1,6 @@
+#![allow(dead_code)]
+
+#[cfg(test)]
+mod physics_tests;
+
+pub mod physics;
@@ -0,0 +1,7 @@
+// Entity physics, in the coordinates of our voxels: y = 0 is the bottom of the world.
+// Like vanilla, every quantity is per tick: velocities are in blocks per tick.
+
+pub mod aabb;
+pub mod body;
+pub mod collision;
+pub mod entity_physics;
@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use sol_voxel_lib::vector_alias::{Coordinate, Position, Vector3f};
+
+/// An axis-aligned bounding box
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Aabb {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Aabb {
+    pub fn new(min: Position, max: Position) -> Aabb {
+        debug_assert!(min.x <= max.x && min.y <= max.y && min.z <= max.z);
+        Aabb { min, max }
+    }
+
+    /// A box of the given size, standing on `position`: the position is the center of its bottom
+    pub fn standing_at(position: Position, width: f32, height: f32) -> Aabb {
+        let half_width = width / 2.0;
+        Aabb {
+            min: Position::new(position.x - half_width, position.y, position.z - half_width),
+            max: Position::new(position.x + half_width, position.y + height, position.z + half_width),
+        }
+    }
+
+    /// The box that fills the voxel at `coord`
+    pub fn voxel(coord: Coordinate) -> Aabb {
+        let min = Position::new(coord.x as f32, coord.y as f32, coord.z as f32);
+        Aabb {
+            min,
+            max: min + Vector3f::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn translated(&self, offset: Vector3f) -> Aabb {
+        Aabb {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    /// The smallest box that contains this box before and after moving by `offset`
+    pub fn swept(&self, offset: Vector3f) -> Aabb {
+        let mut swept = *self;
+        for axis in 0..3 {
+            if offset[axis] < 0.0 {
+                swept.min[axis] += offset[axis];
+            } else {
+                swept.max[axis] += offset[axis];
+            }
+        }
+        swept
+    }
+
+    /// Boxes that only touch do not intersect
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.overlaps_on_axis(other, axis))
+    }
+
+    /// Shortens `offset`, a movement of this box along `axis`, such that this box does not move
+    /// into `other`. Boxes that do not overlap on the other axes never block each other
+    pub fn clip_movement(&self, other: &Aabb, axis: usize, offset: f32) -> f32 {
+        let blocks = (0..3)
+            .filter(|other_axis| *other_axis != axis)
+            .all(|other_axis| self.overlaps_on_axis(other, other_axis));
+        if !blocks {
+            return offset;
+        }
+
+        if offset > 0.0 && other.min[axis] >= self.max[axis] {
+            return offset.min(other.min[axis] - self.max[axis]);
+        }
+
+        if offset < 0.0 && other.max[axis] <= self.min[axis] {
+            return offset.max(other.max[axis] - self.min[axis]);
+        }
+
+        offset
+    }
+
+    fn overlaps_on_axis(&self, other: &Aabb, axis: usize) -> bool {
+        self.min[axis] < other.max[axis] && other.min[axis] < self.max[axis]
+    }
+}
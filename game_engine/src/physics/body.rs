@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use sol_network_lib::Tick;
+use sol_voxel_lib::vector_alias::{Position, Vector3f};
+
+use super::aabb::Aabb;
+use super::collision::{self, VoxelCollider};
+
+// the vanilla values, in blocks per tick squared
+pub const ITEM_GRAVITY: f32 = 0.04;
+pub const LIVING_GRAVITY: f32 = 0.08;
+/// the fraction of the velocity that is lost every tick
+pub const AIR_DRAG: f32 = 0.02;
+/// the fraction of the horizontal velocity that is kept every tick on most blocks
+pub const GROUND_FRICTION: f32 = 0.6;
+
+/// A box that moves under gravity and drag, and collides with voxels
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PhysicsBody {
+    /// the center of the bottom of the box
+    pub position: Position,
+    /// in blocks per tick
+    pub velocity: Vector3f,
+    pub width: f32,
+    pub height: f32,
+    pub gravity: f32,
+    pub drag: f32,
+    pub on_ground: bool,
+}
+
+impl PhysicsBody {
+    pub fn new(position: Position, width: f32, height: f32, gravity: f32) -> PhysicsBody {
+        PhysicsBody {
+            position,
+            velocity: Vector3f::zeros(),
+            width,
+            height,
+            gravity,
+            drag: AIR_DRAG,
+            on_ground: false,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        Aabb::standing_at(self.position, self.width, self.height)
+    }
+
+    /// Advances the body by one tick
+    pub fn step(&mut self, world: &impl VoxelCollider) {
+        self.velocity.y -= self.gravity;
+
+        let movement = collision::sweep(&self.aabb(), self.velocity, world);
+        self.position += movement;
+
+        self.on_ground = self.velocity.y < 0.0 && movement.y > self.velocity.y;
+        // a collision stops the body along that axis
+        for axis in 0..3 {
+            if movement[axis] != self.velocity[axis] {
+                self.velocity[axis] = 0.0;
+            }
+        }
+
+        self.velocity *= 1.0 - self.drag;
+        if self.on_ground {
+            self.velocity.x *= GROUND_FRICTION;
+            self.velocity.z *= GROUND_FRICTION;
+        }
+    }
+
+    pub fn simulate(&mut self, ticks: Tick, world: &impl VoxelCollider) {
+        for _ in 0..ticks {
+            self.step(world);
+        }
+    }
+}
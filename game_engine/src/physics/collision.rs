@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use sol_voxel_lib::chunk_column::ChunkColumn;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate, Vector3f};
+
+use super::aabb::Aabb;
+
+/// The voxels that bodies collide with. Every solid voxel is a full cube
+pub trait VoxelCollider {
+    fn is_solid(&self, coord: Coordinate) -> bool;
+}
+
+/// Gives access to the loaded columns of a world
+pub trait ColumnSource {
+    fn get_column(&self, coord: &ChunkColumnCoordinate) -> Option<&ChunkColumn>;
+}
+
+impl ColumnSource for HashMap<ChunkColumnCoordinate, ChunkColumn> {
+    fn get_column(&self, coord: &ChunkColumnCoordinate) -> Option<&ChunkColumn> {
+        self.get(coord)
+    }
+}
+
+impl ColumnSource for HashMap<ChunkColumnCoordinate, Box<ChunkColumn>> {
+    fn get_column(&self, coord: &ChunkColumnCoordinate) -> Option<&ChunkColumn> {
+        self.get(coord).map(Box::as_ref)
+    }
+}
+
+/// Collides with the solid blocks of a world made of columns.
+/// Columns that are not loaded are solid, so that bodies do not fall out of the loaded world;
+/// above and below the world, nothing is solid.
+pub struct ColumnCollider<'a, Columns: ColumnSource> {
+    columns: &'a Columns,
+    block_properties: &'a BlockPropertyRegistry,
+    block_states: &'a BlockStateRegistry,
+}
+
+impl<'a, Columns: ColumnSource> ColumnCollider<'a, Columns> {
+    pub fn new(
+        columns: &'a Columns,
+        block_properties: &'a BlockPropertyRegistry,
+        block_states: &'a BlockStateRegistry,
+    ) -> ColumnCollider<'a, Columns> {
+        ColumnCollider {
+            columns,
+            block_properties,
+            block_states,
+        }
+    }
+}
+
+impl<Columns: ColumnSource> VoxelCollider for ColumnCollider<'_, Columns> {
+    fn is_solid(&self, coord: Coordinate) -> bool {
+        let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+        let Some(column) = self.columns.get_column(&column_coord) else {
+            return true;
+        };
+
+        let Ok(voxel) = column.get_voxel(coord) else {
+            return false;
+        };
+
+        let block = self.block_states.block_state_to_block(voxel.get_block());
+        self.block_properties.get_block_properties(block).is_solid
+    }
+}
+
+/// The boxes of all solid voxels that intersect or touch `area`
+pub fn solid_voxels_in(area: &Aabb, world: &impl VoxelCollider) -> Vec<Aabb> {
+    let min = area.min.map(|value| value.floor() as i32);
+    let max = area.max.map(|value| value.floor() as i32);
+
+    let mut boxes = Vec::new();
+    for y in min.y..=max.y {
+        for z in min.z..=max.z {
+            for x in min.x..=max.x {
+                let coord = Coordinate::new(x, y, z);
+                if world.is_solid(coord) {
+                    boxes.push(Aabb::voxel(coord));
+                }
+            }
+        }
+    }
+    boxes
+}
+
+/// Moves `aabb` by `movement`, stopping at solid voxels.
+/// Like vanilla, the movement is resolved one axis at a time: first y, then x, then z, so that a
+/// body that falls onto a block can still slide along it. Returns the movement that was made.
+pub fn sweep(aabb: &Aabb, movement: Vector3f, world: &impl VoxelCollider) -> Vector3f {
+    let obstacles = solid_voxels_in(&aabb.swept(movement), world);
+
+    let mut current = *aabb;
+    let mut resolved = Vector3f::zeros();
+    for axis in [1, 0, 2] {
+        let offset = obstacles.iter().fold(movement[axis], |offset, obstacle| {
+            current.clip_movement(obstacle, axis, offset)
+        });
+
+        let mut step = Vector3f::zeros();
+        step[axis] = offset;
+        current = current.translated(step);
+        resolved[axis] = offset;
+    }
+
+    resolved
+}
@@ -0,0 +1,41 @@
+use sol_entity_lib::entity::Entity;
+use sol_entity_lib::entity_type::EntityType;
+
+use super::body::{PhysicsBody, AIR_DRAG, ITEM_GRAVITY, LIVING_GRAVITY};
+use super::collision::VoxelCollider;
+
+/// The body that moves the entity
+pub fn entity_body(entity: &Entity) -> PhysicsBody {
+    let dimensions = entity.dimensions();
+    let gravity = if entity.metadata.no_gravity {
+        0.0
+    } else if entity.entity_type.is_living() {
+        LIVING_GRAVITY
+    } else {
+        ITEM_GRAVITY
+    };
+
+    PhysicsBody {
+        position: entity.position,
+        velocity: entity.velocity,
+        width: dimensions.width,
+        height: dimensions.height,
+        gravity,
+        drag: AIR_DRAG,
+        on_ground: entity.on_ground,
+    }
+}
+
+/// Moves the entity by one tick. Players are moved by their clients, not by us
+pub fn step_entity(entity: &mut Entity, world: &impl VoxelCollider) {
+    if entity.entity_type == EntityType::Player {
+        return;
+    }
+
+    let mut body = entity_body(entity);
+    body.step(world);
+
+    entity.position = body.position;
+    entity.velocity = body.velocity;
+    entity.on_ground = body.on_ground;
+}
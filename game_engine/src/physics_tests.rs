@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use sol_voxel_lib::vector_alias::{Coordinate, Position, Vector3f};
+
+    use crate::physics::aabb::Aabb;
+    use crate::physics::body::{PhysicsBody, ITEM_GRAVITY};
+    use crate::physics::collision::{self, VoxelCollider};
+
+    // everything below `floor_y` is solid, plus some single voxels
+    struct TestWorld {
+        floor_y: i32,
+        voxels: HashSet<Coordinate>,
+    }
+
+    impl VoxelCollider for TestWorld {
+        fn is_solid(&self, coord: Coordinate) -> bool {
+            coord.y < self.floor_y || self.voxels.contains(&coord)
+        }
+    }
+
+    fn flat_world(floor_y: i32) -> TestWorld {
+        TestWorld {
+            floor_y,
+            voxels: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_intersects() {
+        let a = Aabb::new(Position::new(0.0, 0.0, 0.0), Position::new(1.0, 1.0, 1.0));
+        let b = a.translated(Vector3f::new(0.5, 0.5, 0.5));
+        let touching = a.translated(Vector3f::new(1.0, 0.0, 0.0));
+
+        assert!(a.intersects(&b));
+        assert!(b.intersects(&a));
+        assert!(!a.intersects(&touching));
+    }
+
+    #[test]
+    fn test_clip_movement() {
+        let moving = Aabb::new(Position::new(0.0, 2.0, 0.0), Position::new(1.0, 3.0, 1.0));
+        let below = Aabb::voxel(Coordinate::new(0, 0, 0));
+        let beside = Aabb::voxel(Coordinate::new(2, 0, 0));
+
+        assert_eq!(moving.clip_movement(&below, 1, -5.0), -1.0);
+        assert_eq!(moving.clip_movement(&below, 1, -0.5), -0.5);
+        assert_eq!(moving.clip_movement(&below, 1, 5.0), 5.0);
+        assert_eq!(moving.clip_movement(&beside, 1, -5.0), -5.0);
+    }
+
+    #[test]
+    fn test_sweep_slides_along_wall() {
+        let mut world = flat_world(0);
+        world.voxels.insert(Coordinate::new(1, 0, 0));
+
+        let aabb = Aabb::standing_at(Position::new(0.5, 0.0, 0.5), 0.5, 0.5);
+        let movement = collision::sweep(&aabb, Vector3f::new(1.0, -1.0, 1.0), &world);
+
+        // blocked by the floor and the wall, but free to move along z
+        assert_eq!(movement, Vector3f::new(0.25, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_fall_onto_floor() {
+        let world = flat_world(10);
+        let mut body = PhysicsBody::new(Position::new(0.5, 30.0, 0.5), 0.25, 0.25, ITEM_GRAVITY);
+
+        body.simulate(200, &world);
+
+        assert!(body.on_ground);
+        assert_eq!(body.position, Position::new(0.5, 10.0, 0.5));
+        assert_eq!(body.velocity, Vector3f::zeros());
+    }
+
+    #[test]
+    fn test_free_fall() {
+        let world = flat_world(i32::MIN);
+        let mut body = PhysicsBody::new(Position::new(0.0, 100.0, 0.0), 0.25, 0.25, ITEM_GRAVITY);
+
+        let mut previous_speed = 0.0;
+        for _ in 0..50 {
+            body.step(&world);
+            assert!(!body.on_ground);
+            assert!(-body.velocity.y > previous_speed);
+            previous_speed = -body.velocity.y;
+        }
+
+        // drag limits the speed to gravity * (1 - drag) / drag
+        let terminal_speed = ITEM_GRAVITY * (1.0 - body.drag) / body.drag;
+        body.simulate(1000, &world);
+        assert!((-body.velocity.y - terminal_speed).abs() < 0.01);
+    }
+}
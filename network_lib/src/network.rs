@@ -10,7 +10,7 @@ pub enum NetworkError {
 pub struct ReplyLoop<Req, Rep> {
     socket: zmq::Socket,
     endpoint: String,
-    handler: fn(Req) -> Rep,
+    handler: Box<dyn FnMut(Req) -> Rep>,
 }
 
 impl<Req, Rep> ReplyLoop<Req, Rep>
@@ -21,17 +21,17 @@ where
     pub fn new(
         context: zmq::Context,
         endpoint: String,
-        handler: fn(Req) -> Rep,
+        handler: impl FnMut(Req) -> Rep + 'static,
     ) -> Result<ReplyLoop<Req, Rep>, zmq::Error> {
         let socket = context.socket(zmq::REP)?;
         Ok(ReplyLoop {
             socket,
             endpoint,
-            handler,
+            handler: Box::new(handler),
         })
     }
 
-    pub fn listen_until_stop(&self) -> Result<(), NetworkError> {
+    pub fn listen_until_stop(&mut self) -> Result<(), NetworkError> {
         self.socket
             .bind(self.endpoint.as_str())
             .map_err(|err| NetworkError::ZmqError(err))?;
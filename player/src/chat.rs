@@ -0,0 +1,53 @@
+// Chat is sent to the client as json text components, see https://wiki.vg/Text_formatting
+
+use std::fmt::Write;
+
+// like vanilla: longer messages are rejected by the client before they are sent
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+
+/// A chat message of a player, like vanilla: "<name> text"
+pub fn player_message(sender_name: &str, text: &str) -> String {
+    format!(
+        "{{\"translate\":\"chat.type.text\",\"with\":[{},{}]}}",
+        plain_text(sender_name),
+        plain_text(text)
+    )
+}
+
+/// The feedback of a command
+pub fn feedback(text: &str) -> String {
+    plain_text(text)
+}
+
+/// The error of a command, in red
+pub fn error(text: &str) -> String {
+    format!("{{\"text\":{},\"color\":\"red\"}}", json_string(text))
+}
+
+/// Whether the client may send the message; vanilla disconnects clients that send
+/// formatting codes or control characters
+pub fn is_valid_message(text: &str) -> bool {
+    text.len() <= MAX_MESSAGE_LENGTH && !text.chars().any(|c| c == '\u{a7}' || c.is_control())
+}
+
+fn plain_text(text: &str) -> String {
+    format!("{{\"text\":{}}}", json_string(text))
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            },
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+
+    use crate::voxels::chunk_streamer::{ChunkStreamer, MAX_VIEW_DISTANCE};
+
+    const ORIGIN: ChunkColumnCoordinate = ChunkColumnCoordinate { x: 0, z: 0 };
+
+    // sends every batch that the streamer hands out, until it has nothing left to send
+    fn send_all(streamer: &mut ChunkStreamer) -> usize {
+        let mut sent = 0;
+        loop {
+            let batch = streamer.next_batch();
+            if batch.is_empty() {
+                return sent;
+            }
+            sent += batch.len();
+            for coord in batch {
+                streamer.mark_sent(coord);
+            }
+            streamer.batch_sent();
+            streamer.batch_acknowledged(64.0);
+        }
+    }
+
+    #[test]
+    fn test_view_distance() {
+        let streamer = ChunkStreamer::new(ORIGIN, 2, 9.0, Vec::new());
+        assert_eq!(streamer.view_distance(), 2);
+        assert!(streamer.is_in_range(ChunkColumnCoordinate { x: 2, z: -2 }));
+        assert!(!streamer.is_in_range(ChunkColumnCoordinate { x: 3, z: 0 }));
+        assert!(!streamer.is_in_range(ChunkColumnCoordinate { x: 0, z: -3 }));
+
+        // a larger render distance than we announced is capped
+        let streamer = ChunkStreamer::new(ORIGIN, 32, 9.0, Vec::new());
+        assert_eq!(streamer.view_distance(), MAX_VIEW_DISTANCE as i32);
+    }
+
+    #[test]
+    fn test_sends_every_column_in_range() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 2, 9.0, Vec::new());
+        assert_eq!(send_all(&mut streamer), 25);
+        for z in -2..=2 {
+            for x in -2..=2 {
+                assert!(streamer.has_sent(ChunkColumnCoordinate { x, z }));
+            }
+        }
+        assert!(streamer.next_batch().is_empty());
+    }
+
+    #[test]
+    fn test_nearest_first() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 3, 5.0, Vec::new());
+        let batch = streamer.next_batch();
+        assert_eq!(batch.len(), 5);
+        // the center, then its direct neighbours
+        assert_eq!(batch[0], ORIGIN);
+        for coord in &batch[1..] {
+            assert_eq!(coord.x.abs() + coord.z.abs(), 1);
+        }
+    }
+
+    #[test]
+    fn test_already_sent() {
+        let already_sent = vec![ORIGIN, ChunkColumnCoordinate { x: 1, z: 0 }];
+        let mut streamer = ChunkStreamer::new(ORIGIN, 1, 9.0, already_sent);
+        let batch = streamer.next_batch();
+        assert_eq!(batch.len(), 7);
+        assert!(!batch.contains(&ORIGIN));
+    }
+
+    #[test]
+    fn test_unload_out_of_range() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 1, 9.0, Vec::new());
+        send_all(&mut streamer);
+        assert!(streamer.take_out_of_range().is_empty());
+
+        assert!(!streamer.set_center(ORIGIN));
+        assert!(streamer.set_center(ChunkColumnCoordinate { x: 1, z: 0 }));
+        let mut unloaded = streamer.take_out_of_range();
+        unloaded.sort_by_key(|coord| coord.z);
+        assert_eq!(
+            unloaded,
+            vec![
+                ChunkColumnCoordinate { x: -1, z: -1 },
+                ChunkColumnCoordinate { x: -1, z: 0 },
+                ChunkColumnCoordinate { x: -1, z: 1 },
+            ]
+        );
+        assert!(!streamer.has_sent(ChunkColumnCoordinate { x: -1, z: 0 }));
+        // unloaded once
+        assert!(streamer.take_out_of_range().is_empty());
+
+        // the new row of columns is sent next
+        let mut batch = streamer.next_batch();
+        batch.sort_by_key(|coord| coord.z);
+        assert_eq!(
+            batch,
+            vec![
+                ChunkColumnCoordinate { x: 2, z: -1 },
+                ChunkColumnCoordinate { x: 2, z: 0 },
+                ChunkColumnCoordinate { x: 2, z: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_forget() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 1, 9.0, Vec::new());
+        send_all(&mut streamer);
+        streamer.forget(ORIGIN);
+        assert!(!streamer.has_sent(ORIGIN));
+        assert_eq!(streamer.next_batch(), vec![ORIGIN]);
+    }
+
+    #[test]
+    fn test_chunks_per_tick() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 2, 0.5, Vec::new());
+        // half a column per tick: one column every other tick
+        assert!(streamer.next_batch().is_empty());
+        assert_eq!(streamer.next_batch().len(), 1);
+        assert!(streamer.next_batch().is_empty());
+        assert_eq!(streamer.next_batch().len(), 1);
+
+        // the client asks for a higher rate
+        streamer.batch_acknowledged(3.0);
+        assert_eq!(streamer.next_batch().len(), 3);
+
+        // rates that are not finite are ignored, and large ones are capped
+        streamer.batch_acknowledged(f32::NAN);
+        assert_eq!(streamer.next_batch().len(), 3);
+        streamer.batch_acknowledged(f32::INFINITY);
+        assert_eq!(streamer.next_batch().len(), 3);
+        streamer.batch_acknowledged(1000.0);
+        assert_eq!(streamer.next_batch().len(), 25);
+    }
+
+    #[test]
+    fn test_waits_for_acknowledgements() {
+        let mut streamer = ChunkStreamer::new(ORIGIN, 12, 1.0, Vec::new());
+        for _ in 0..10 {
+            let batch = streamer.next_batch();
+            assert_eq!(batch.len(), 1);
+            streamer.mark_sent(batch[0]);
+            streamer.batch_sent();
+        }
+
+        // too many batches that the client did not acknowledge yet
+        assert!(streamer.next_batch().is_empty());
+        streamer.batch_acknowledged(1.0);
+        assert_eq!(streamer.next_batch().len(), 1);
+    }
+}
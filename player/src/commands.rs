@@ -0,0 +1,67 @@
+// Commands are declared to the client as a tree, like brigadier in vanilla: the client parses and
+// completes what the player types, and only sends complete commands.
+// We parse them again with the same tree: parsing results in an action, that the game loop applies.
+
+pub mod arguments;
+#[cfg(test)]
+mod arguments_tests;
+pub mod builtin;
+#[cfg(test)]
+mod builtin_tests;
+pub mod tree;
+#[cfg(test)]
+mod tree_tests;
+
+use minecraft_protocol::components::gamemode::Gamemode;
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_protocol::data::items::Item;
+use sol_voxel_lib::vector_alias::{Coordinate, Position};
+use std::fmt;
+
+/// What a command does
+pub enum CommandAction {
+    /// to a position in minecraft coordinates
+    Teleport(Position),
+    Give {
+        item: Item,
+        count: usize,
+    },
+    SetGameMode(Gamemode),
+    /// sets every block in the box between the corners, including the corners
+    Fill {
+        from: Coordinate,
+        to: Coordinate,
+        block: BlockWithState,
+    },
+    /// the time of day, in ticks
+    SetTime(i64),
+    AddTime(i64),
+    QueryTime,
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    /// the first word is not a command
+    UnknownCommand(String),
+    /// the command lacks arguments, or has arguments that it does not expect
+    IncorrectArguments,
+    InvalidArgument {
+        argument: &'static str,
+        reason: String,
+    },
+    /// the command is valid, but cannot be run
+    Failed(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand(name) => write!(f, "Unknown command: {name}"),
+            CommandError::IncorrectArguments => write!(f, "Incorrect arguments for command"),
+            CommandError::InvalidArgument { argument, reason } => {
+                write!(f, "Invalid {argument}: {reason}")
+            },
+            CommandError::Failed(reason) => write!(f, "{reason}"),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use crate::minecraft_connection::coordinates::MINECRAFT_MIN_Y;
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_protocol::data::items::Item;
+use minecraft_vanilla::registries::Registries;
+use sol_voxel_lib::vector_alias::{Coordinate, Position};
+use std::collections::HashMap;
+
+const DEFAULT_NAMESPACE: &str = "minecraft:";
+
+/// What runs a command; relative coordinates are relative to its position
+pub struct CommandSource<'a> {
+    /// in minecraft coordinates
+    pub position: Position,
+    pub registries: &'a Registries,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ArgumentParser {
+    Integer {
+        min: i32,
+        max: i32,
+    },
+    /// three coordinates, absolute or relative like "~ 64 ~-2.5"
+    Position,
+    /// three integer coordinates, absolute or relative like "~ ~-1 ~"
+    BlockPosition,
+    /// a block name, with optional properties like "oak_stairs[facing=east]"
+    BlockState,
+    /// an item name, like "minecraft:stone"
+    Item,
+}
+
+pub enum ArgumentValue {
+    Integer(i32),
+    /// in minecraft coordinates
+    Position(Position),
+    /// in the coordinates of our voxels
+    BlockPosition(Coordinate),
+    BlockState(BlockWithState),
+    Item(Item),
+}
+
+/// Reads the input of a command word by word. Words are separated by single spaces
+#[derive(Clone)]
+pub struct CommandReader<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+impl<'a> CommandReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        CommandReader { input, cursor: 0 }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.cursor >= self.input.len()
+    }
+
+    /// The input that was not read yet
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    /// Reads up to the next space, or to the end of the input
+    pub fn read_word(&mut self) -> &'a str {
+        let remaining = self.remaining();
+        let word = remaining.split(' ').next().unwrap_or(remaining);
+        self.cursor += word.len();
+        word
+    }
+
+    /// Skips the space after a word. Returns false at the end of the input
+    pub fn skip_separator(&mut self) -> bool {
+        if self.remaining().starts_with(' ') {
+            self.cursor += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl ArgumentParser {
+    /// The id of the parser in the command tree of the protocol, see
+    /// https://wiki.vg/Command_Data#Parsers
+    pub fn protocol_id(self) -> i32 {
+        match self {
+            // brigadier:integer
+            ArgumentParser::Integer { .. } => 3,
+            // minecraft:block_pos
+            ArgumentParser::BlockPosition => 8,
+            // minecraft:vec3
+            ArgumentParser::Position => 10,
+            // minecraft:block_state
+            ArgumentParser::BlockState => 12,
+            // minecraft:item_stack
+            ArgumentParser::Item => 14,
+        }
+    }
+
+    /// Whether the client asks us to complete the argument, instead of completing it itself
+    pub fn asks_server(self) -> bool {
+        matches!(self, ArgumentParser::BlockState)
+    }
+
+    /// Returns the reason why the argument is invalid on error
+    pub fn parse(
+        self,
+        reader: &mut CommandReader,
+        source: &CommandSource,
+    ) -> Result<ArgumentValue, String> {
+        match self {
+            ArgumentParser::Integer { min, max } => {
+                let word = reader.read_word();
+                let value: i32 = word
+                    .parse()
+                    .map_err(|_| format!("expected an integer, found \"{word}\""))?;
+                if value < min {
+                    return Err(format!("must not be less than {min}, found {value}"));
+                }
+                if value > max {
+                    return Err(format!("must not be more than {max}, found {value}"));
+                }
+                Ok(ArgumentValue::Integer(value))
+            },
+            ArgumentParser::Position => {
+                let [x, y, z] = read_coordinates(reader)?;
+                let base = source.position;
+                // like vanilla: absolute integer coordinates are the center of their block
+                let x = parse_coordinate(x, base.x, true)?;
+                let y = parse_coordinate(y, base.y, false)?;
+                let z = parse_coordinate(z, base.z, true)?;
+                Ok(ArgumentValue::Position(Position::new(x, y, z)))
+            },
+            ArgumentParser::BlockPosition => {
+                let [x, y, z] = read_coordinates(reader)?;
+                let base = source.position;
+                let x = parse_block_coordinate(x, base.x)?;
+                let y = parse_block_coordinate(y, base.y)?;
+                let z = parse_block_coordinate(z, base.z)?;
+                Ok(ArgumentValue::BlockPosition(Coordinate::new(
+                    x,
+                    y - MINECRAFT_MIN_Y,
+                    z,
+                )))
+            },
+            ArgumentParser::BlockState => {
+                let word = reader.read_word();
+                parse_block_state(word, source.registries).map(ArgumentValue::BlockState)
+            },
+            ArgumentParser::Item => {
+                let word = reader.read_word();
+                let name = word.strip_prefix(DEFAULT_NAMESPACE).unwrap_or(word);
+                Item::from_text_id(name)
+                    .map(ArgumentValue::Item)
+                    .ok_or_else(|| format!("unknown item \"{word}\""))
+            },
+        }
+    }
+
+    /// Completions of the partial argument, for the parsers that ask us
+    pub fn suggest(self, partial: &str, block_names: &[String]) -> Vec<String> {
+        match self {
+            ArgumentParser::BlockState => {
+                let partial = partial.strip_prefix(DEFAULT_NAMESPACE).unwrap_or(partial);
+                block_names
+                    .iter()
+                    .filter(|name| {
+                        let name = name.strip_prefix(DEFAULT_NAMESPACE).unwrap_or(name);
+                        name.starts_with(partial)
+                    })
+                    .cloned()
+                    .collect()
+            },
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The names of all blocks, in the order of their states
+pub fn block_names(registries: &Registries) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for id in 0.. {
+        let Some((name, _)) = registries
+            .block_states()
+            .get_name_and_properties(BlockWithState::from_id(id))
+        else {
+            break;
+        };
+        // the states of a block are next to each other
+        if names.last() != Some(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+// x, y and z, separated by spaces
+fn read_coordinates<'a>(reader: &mut CommandReader<'a>) -> Result<[&'a str; 3], String> {
+    let x = reader.read_word();
+    let y = if reader.skip_separator() {
+        reader.read_word()
+    } else {
+        ""
+    };
+    let z = if reader.skip_separator() {
+        reader.read_word()
+    } else {
+        ""
+    };
+    if x.is_empty() || y.is_empty() || z.is_empty() {
+        return Err(String::from("expected three coordinates"));
+    }
+    Ok([x, y, z])
+}
+
+fn parse_coordinate(word: &str, base: f32, center: bool) -> Result<f32, String> {
+    if word.starts_with('^') {
+        return Err(String::from("local coordinates are not supported"));
+    }
+
+    if let Some(offset) = word.strip_prefix('~') {
+        if offset.is_empty() {
+            return Ok(base);
+        }
+        return offset
+            .parse::<f32>()
+            .map(|offset| base + offset)
+            .map_err(|_| format!("expected a coordinate, found \"{word}\""));
+    }
+
+    let value = word
+        .parse::<f32>()
+        .map_err(|_| format!("expected a coordinate, found \"{word}\""))?;
+    if center && !word.contains('.') {
+        Ok(value + 0.5)
+    } else {
+        Ok(value)
+    }
+}
+
+fn parse_block_coordinate(word: &str, base: f32) -> Result<i32, String> {
+    if word.starts_with('^') {
+        return Err(String::from("local coordinates are not supported"));
+    }
+
+    let (base, offset) = match word.strip_prefix('~') {
+        Some("") => return Ok(base.floor() as i32),
+        Some(offset) => (base.floor() as i32, offset),
+        None => (0, word),
+    };
+    offset
+        .parse::<i32>()
+        .map(|offset| base + offset)
+        .map_err(|_| format!("expected a block coordinate, found \"{word}\""))
+}
+
+// like "oak_stairs[facing=east,half=top]"; the properties that are not given have their default
+fn parse_block_state(word: &str, registries: &Registries) -> Result<BlockWithState, String> {
+    let (name, properties) = match word.split_once('[') {
+        Some((name, properties)) => {
+            let properties = properties
+                .strip_suffix(']')
+                .ok_or_else(|| String::from("expected ']' after the block properties"))?;
+            (name, properties)
+        },
+        None => (word, ""),
+    };
+
+    let name = if name.contains(':') {
+        String::from(name)
+    } else {
+        format!("{DEFAULT_NAMESPACE}{name}")
+    };
+
+    let mut property_map = HashMap::new();
+    for property in properties
+        .split(',')
+        .filter(|property| !property.is_empty())
+    {
+        let (key, value) = property
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, found \"{property}\""))?;
+        property_map.insert(String::from(key), String::from(value));
+    }
+
+    registries
+        .block_states()
+        .get_block_state(&name, &property_map)
+        .ok_or_else(|| format!("unknown block \"{word}\""))
+}
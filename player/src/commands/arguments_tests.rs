@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::items::Item;
+    use minecraft_vanilla::registries::Registries;
+    use sol_voxel_lib::vector_alias::{Coordinate, Position};
+
+    use crate::commands::arguments::{
+        self, ArgumentParser, ArgumentValue, CommandReader, CommandSource,
+    };
+
+    fn source(registries: &Registries, position: Position) -> CommandSource {
+        CommandSource {
+            position,
+            registries,
+        }
+    }
+
+    // parses the whole input, and returns the input that was left
+    fn parse_at(
+        parser: ArgumentParser,
+        input: &str,
+        position: Position,
+    ) -> (Result<ArgumentValue, String>, String) {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut reader = CommandReader::new(input);
+        let value = parser.parse(&mut reader, &source(&registries, position));
+        (value, String::from(reader.remaining()))
+    }
+
+    fn block_position(input: &str, position: Position) -> Option<Coordinate> {
+        match parse_at(ArgumentParser::BlockPosition, input, position).0 {
+            Ok(ArgumentValue::BlockPosition(coord)) => Some(coord),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_reader() {
+        let mut reader = CommandReader::new("give stone 64");
+        assert_eq!(reader.read_word(), "give");
+        assert_eq!(reader.cursor(), 4);
+        assert!(reader.skip_separator());
+        assert_eq!(reader.remaining(), "stone 64");
+        assert_eq!(reader.read_word(), "stone");
+        assert!(reader.skip_separator());
+        assert_eq!(reader.read_word(), "64");
+        assert!(reader.is_at_end());
+        assert!(!reader.skip_separator());
+        assert_eq!(reader.read_word(), "");
+
+        // words are separated by single spaces
+        let mut reader = CommandReader::new("a  b");
+        reader.read_word();
+        assert!(reader.skip_separator());
+        assert_eq!(reader.read_word(), "");
+        assert!(reader.skip_separator());
+        assert_eq!(reader.read_word(), "b");
+    }
+
+    #[test]
+    fn test_integer() {
+        let parser = ArgumentParser::Integer { min: -5, max: 10 };
+        let integer = |input| match parse_at(parser, input, Position::origin()).0 {
+            Ok(ArgumentValue::Integer(value)) => Ok(value),
+            Ok(_) => panic!("not an integer"),
+            Err(reason) => Err(reason),
+        };
+        assert_eq!(integer("-5"), Ok(-5));
+        assert_eq!(integer("10"), Ok(10));
+        assert!(integer("11").unwrap_err().contains("more than 10"));
+        assert!(integer("-6").unwrap_err().contains("less than -5"));
+        assert!(integer("1.5").is_err());
+        assert!(integer("").is_err());
+
+        // only one word is read
+        let (value, remaining) = parse_at(parser, "3 4", Position::origin());
+        assert!(matches!(value, Ok(ArgumentValue::Integer(3))));
+        assert_eq!(remaining, " 4");
+    }
+
+    #[test]
+    fn test_position() {
+        let base = Position::new(-2.25, 70.0, 5.75);
+        let position = |input| match parse_at(ArgumentParser::Position, input, base).0 {
+            Ok(ArgumentValue::Position(position)) => Some(position),
+            _ => None,
+        };
+
+        assert_eq!(position("~ ~ ~"), Some(base));
+        assert_eq!(
+            position("~1 ~-0.5 ~"),
+            Some(Position::new(-1.25, 69.5, 5.75))
+        );
+        // integers are the center of their block horizontally
+        assert_eq!(position("-3 64 7"), Some(Position::new(-2.5, 64.0, 7.5)));
+        assert_eq!(
+            position("-3.0 64 7.25"),
+            Some(Position::new(-3.0, 64.0, 7.25))
+        );
+
+        assert_eq!(position("^ ^ ^"), None);
+        assert_eq!(position("1 2"), None);
+        assert_eq!(position("1 2 z"), None);
+        assert_eq!(position("~x 2 3"), None);
+    }
+
+    #[test]
+    fn test_block_position() {
+        let base = Position::new(-2.25, 70.5, 5.75);
+        // relative coordinates start at the block of the source
+        assert_eq!(
+            block_position("~ ~ ~", base),
+            Some(Coordinate::new(-3, 134, 5))
+        );
+        assert_eq!(
+            block_position("~1 ~-1 ~-6", base),
+            Some(Coordinate::new(-2, 133, -1))
+        );
+        // y is moved to the coordinates of our voxels
+        assert_eq!(
+            block_position("4 -64 -4", base),
+            Some(Coordinate::new(4, 0, -4))
+        );
+        assert_eq!(
+            block_position("4 319 -4", base),
+            Some(Coordinate::new(4, 383, -4))
+        );
+
+        assert_eq!(block_position("1.5 2 3", base), None);
+        assert_eq!(block_position("^ ^ ^", base), None);
+        assert_eq!(block_position("1 2", base), None);
+    }
+
+    #[test]
+    fn test_block_state() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let block = |input| match parse_at(ArgumentParser::BlockState, input, Position::origin()).0
+        {
+            Ok(ArgumentValue::BlockState(block)) => Ok(block),
+            Ok(_) => panic!("not a block"),
+            Err(reason) => Err(reason),
+        };
+
+        let powered = block("lever[face=floor,powered=true]").unwrap();
+        let unpowered = block("lever[powered=false,face=floor]").unwrap();
+        assert_ne!(powered.id(), unpowered.id());
+        let name = registries
+            .block_states()
+            .get_name_and_properties(powered)
+            .map(|(name, _)| name);
+        assert_eq!(name.as_deref(), Some("minecraft:lever"));
+
+        assert_eq!(
+            block("minecraft:stone").unwrap().id(),
+            block("stone[]").unwrap().id()
+        );
+        let error = |input| block(input).err().unwrap();
+        assert!(error("no_such_block").contains("unknown block"));
+        assert!(error("lever[face=floor").contains("']'"));
+        assert!(error("lever[face]").contains("key=value"));
+    }
+
+    #[test]
+    fn test_item() {
+        let item = |input| match parse_at(ArgumentParser::Item, input, Position::origin()).0 {
+            Ok(ArgumentValue::Item(item)) => Some(item.id()),
+            _ => None,
+        };
+        let stone = Item::from_text_id("stone").map(|item| item.id());
+        assert_eq!(item("stone"), stone);
+        assert_eq!(item("minecraft:stone"), stone);
+        assert_eq!(item("other:stone"), None);
+        assert_eq!(item("no_such_item"), None);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let names = vec![
+            String::from("minecraft:stone"),
+            String::from("minecraft:stone_bricks"),
+            String::from("minecraft:dirt"),
+        ];
+        assert_eq!(
+            ArgumentParser::BlockState.suggest("st", &names),
+            vec!["minecraft:stone", "minecraft:stone_bricks"]
+        );
+        assert_eq!(
+            ArgumentParser::BlockState.suggest("minecraft:d", &names),
+            vec!["minecraft:dirt"]
+        );
+        assert!(ArgumentParser::BlockState.suggest("x", &names).is_empty());
+        // only blocks are completed by us
+        assert!(ArgumentParser::Item.suggest("st", &names).is_empty());
+        assert!(ArgumentParser::BlockState.asks_server());
+        assert!(!ArgumentParser::Item.asks_server());
+    }
+
+    #[test]
+    fn test_block_names() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let names = arguments::block_names(&registries);
+        assert_eq!(names[0], "minecraft:air");
+        assert!(names.contains(&String::from("minecraft:oak_stairs")));
+
+        // every block once
+        let mut sorted = names.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), names.len());
+    }
+}
@@ -0,0 +1,152 @@
+// The commands of vanilla that the player can use. They only affect the player that runs them, so
+// unlike vanilla they take no targets
+
+use crate::commands::arguments::{ArgumentParser, ArgumentValue};
+use crate::commands::tree::CommandTree;
+use crate::commands::{CommandAction, CommandError};
+use minecraft_protocol::components::gamemode::Gamemode;
+
+// like vanilla: /give gives at most 100 stacks, and /fill sets at most 32768 blocks
+const MAX_GIVE_COUNT: i32 = 6400;
+const MAX_FILL_VOLUME: i64 = 32768;
+// like vanilla: the named times of /time set
+const TIME_DAY: i64 = 1000;
+const TIME_NOON: i64 = 6000;
+const TIME_NIGHT: i64 = 13000;
+const TIME_MIDNIGHT: i64 = 18000;
+
+pub fn register(tree: &mut CommandTree) {
+    let root = tree.root();
+
+    // /tp <location>
+    let tp = tree.literal(root, "tp");
+    let location = tree.argument(tp, "location", ArgumentParser::Position);
+    tree.executes(location, |values| match values {
+        [ArgumentValue::Position(position)] => Ok(CommandAction::Teleport(*position)),
+        _ => Err(CommandError::IncorrectArguments),
+    });
+
+    // /give <item> [<count>]
+    let give = tree.literal(root, "give");
+    let item = tree.argument(give, "item", ArgumentParser::Item);
+    tree.executes(item, execute_give);
+    let count = tree.argument(
+        item,
+        "count",
+        ArgumentParser::Integer {
+            min: 1,
+            max: MAX_GIVE_COUNT,
+        },
+    );
+    tree.executes(count, execute_give);
+
+    // /gamemode <survival|creative|adventure|spectator>
+    let gamemode = tree.literal(root, "gamemode");
+    let survival = tree.literal(gamemode, "survival");
+    tree.executes(survival, |_| {
+        Ok(CommandAction::SetGameMode(Gamemode::Survival))
+    });
+    let creative = tree.literal(gamemode, "creative");
+    tree.executes(creative, |_| {
+        Ok(CommandAction::SetGameMode(Gamemode::Creative))
+    });
+    let adventure = tree.literal(gamemode, "adventure");
+    tree.executes(adventure, |_| {
+        Ok(CommandAction::SetGameMode(Gamemode::Adventure))
+    });
+    let spectator = tree.literal(gamemode, "spectator");
+    tree.executes(spectator, |_| {
+        Ok(CommandAction::SetGameMode(Gamemode::Spectator))
+    });
+
+    // /setblock <pos> <block>
+    let setblock = tree.literal(root, "setblock");
+    let pos = tree.argument(setblock, "pos", ArgumentParser::BlockPosition);
+    let block = tree.argument(pos, "block", ArgumentParser::BlockState);
+    tree.executes(block, |values| match values {
+        [ArgumentValue::BlockPosition(pos), ArgumentValue::BlockState(block)] => {
+            Ok(CommandAction::Fill {
+                from: *pos,
+                to: *pos,
+                block: *block,
+            })
+        },
+        _ => Err(CommandError::IncorrectArguments),
+    });
+
+    // /fill <from> <to> <block>
+    let fill = tree.literal(root, "fill");
+    let from = tree.argument(fill, "from", ArgumentParser::BlockPosition);
+    let to = tree.argument(from, "to", ArgumentParser::BlockPosition);
+    let block = tree.argument(to, "block", ArgumentParser::BlockState);
+    tree.executes(block, execute_fill);
+
+    // /time set <day|noon|night|midnight|<time>>, /time add <time>, /time query
+    let time = tree.literal(root, "time");
+    let set = tree.literal(time, "set");
+    let day = tree.literal(set, "day");
+    tree.executes(day, |_| Ok(CommandAction::SetTime(TIME_DAY)));
+    let noon = tree.literal(set, "noon");
+    tree.executes(noon, |_| Ok(CommandAction::SetTime(TIME_NOON)));
+    let night = tree.literal(set, "night");
+    tree.executes(night, |_| Ok(CommandAction::SetTime(TIME_NIGHT)));
+    let midnight = tree.literal(set, "midnight");
+    tree.executes(midnight, |_| Ok(CommandAction::SetTime(TIME_MIDNIGHT)));
+    let set_time = tree.argument(set, "time", time_parser());
+    tree.executes(set_time, |values| match values {
+        [ArgumentValue::Integer(time)] => Ok(CommandAction::SetTime(*time as i64)),
+        _ => Err(CommandError::IncorrectArguments),
+    });
+    let add = tree.literal(time, "add");
+    let add_time = tree.argument(add, "time", time_parser());
+    tree.executes(add_time, |values| match values {
+        [ArgumentValue::Integer(time)] => Ok(CommandAction::AddTime(*time as i64)),
+        _ => Err(CommandError::IncorrectArguments),
+    });
+    let query = tree.literal(time, "query");
+    tree.executes(query, |_| Ok(CommandAction::QueryTime));
+}
+
+// in ticks
+fn time_parser() -> ArgumentParser {
+    ArgumentParser::Integer {
+        min: 0,
+        max: i32::MAX,
+    }
+}
+
+fn execute_give(values: &[ArgumentValue]) -> Result<CommandAction, CommandError> {
+    match values {
+        [ArgumentValue::Item(item)] => Ok(CommandAction::Give {
+            item: *item,
+            count: 1,
+        }),
+        [ArgumentValue::Item(item), ArgumentValue::Integer(count)] => Ok(CommandAction::Give {
+            item: *item,
+            count: *count as usize,
+        }),
+        _ => Err(CommandError::IncorrectArguments),
+    }
+}
+
+fn execute_fill(values: &[ArgumentValue]) -> Result<CommandAction, CommandError> {
+    let [ArgumentValue::BlockPosition(from), ArgumentValue::BlockPosition(to), ArgumentValue::BlockState(block)] =
+        values
+    else {
+        return Err(CommandError::IncorrectArguments);
+    };
+
+    let length = |a: i32, b: i32| (a as i64 - b as i64).abs() + 1;
+    let volume = length(from.x, to.x) * length(from.y, to.y) * length(from.z, to.z);
+    if volume > MAX_FILL_VOLUME {
+        return Err(CommandError::Failed(format!(
+            "Too many blocks in the specified area (maximum {MAX_FILL_VOLUME}, specified {volume})"
+        )));
+    }
+
+    Ok(CommandAction::Fill {
+        from: *from,
+        to: *to,
+        block: *block,
+    })
+}
@@ -0,0 +1,318 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minecraft_protocol::components::gamemode::Gamemode;
+    use minecraft_protocol::data::items::Item;
+    use minecraft_vanilla::registries::Registries;
+    use sol_voxel_lib::vector_alias::{Coordinate, Position};
+
+    use crate::commands::arguments::CommandSource;
+    use crate::commands::tree::CommandTree;
+    use crate::commands::{CommandAction, CommandError};
+
+    // runs the commands at a position that is not on a block border, in minecraft coordinates
+    fn source(registries: &Registries) -> CommandSource {
+        CommandSource {
+            position: Position::new(10.5, 64.0, -3.5),
+            registries,
+        }
+    }
+
+    fn parse(input: &str) -> Result<CommandAction, CommandError> {
+        let registries = minecraft_vanilla::registries::get_registries();
+        CommandTree::new().parse(input, &source(&registries))
+    }
+
+    fn suggest(input: &str) -> (usize, usize, Vec<String>) {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let suggestions = CommandTree::new().suggest(input, &source(&registries));
+        (suggestions.start, suggestions.length, suggestions.matches)
+    }
+
+    fn block_id(name: &str, properties: &[(&str, &str)]) -> u32 {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let properties: HashMap<String, String> = properties
+            .iter()
+            .map(|(key, value)| (String::from(*key), String::from(*value)))
+            .collect();
+        registries
+            .block_states()
+            .get_block_state(&String::from(name), &properties)
+            .unwrap()
+            .id() as u32
+    }
+
+    fn assert_teleport(input: &str, expected: Position) {
+        match parse(input) {
+            Ok(CommandAction::Teleport(position)) => {
+                assert!((position - expected).norm() < 1e-5, "{position:?}")
+            },
+            _ => panic!("\"{input}\" does not teleport"),
+        }
+    }
+
+    fn assert_fill(input: &str, expected_from: Coordinate, expected_to: Coordinate, block: u32) {
+        match parse(input) {
+            Ok(CommandAction::Fill {
+                from,
+                to,
+                block: filled,
+            }) => {
+                assert_eq!(from, expected_from);
+                assert_eq!(to, expected_to);
+                assert_eq!(filled.id() as u32, block);
+            },
+            _ => panic!("\"{input}\" does not fill"),
+        }
+    }
+
+    fn assert_give(input: &str, name: &str, expected_count: usize) {
+        match parse(input) {
+            Ok(CommandAction::Give { item, count }) => {
+                assert_eq!(item.id(), Item::from_text_id(name).unwrap().id());
+                assert_eq!(count, expected_count);
+            },
+            _ => panic!("\"{input}\" does not give"),
+        }
+    }
+
+    fn is_invalid_argument(result: Result<CommandAction, CommandError>, name: &str) -> bool {
+        matches!(result, Err(CommandError::InvalidArgument { argument, .. }) if argument == name)
+    }
+
+    fn is_incorrect(result: Result<CommandAction, CommandError>) -> bool {
+        matches!(result, Err(CommandError::IncorrectArguments))
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        assert!(matches!(parse("fly"), Err(CommandError::UnknownCommand(name)) if name == "fly"));
+        assert!(matches!(
+            parse("fly high"),
+            Err(CommandError::UnknownCommand(name)) if name == "fly"
+        ));
+        assert!(matches!(parse(""), Err(CommandError::UnknownCommand(_))));
+    }
+
+    #[test]
+    fn test_tp() {
+        // integer coordinates are the center of their block, but not the height
+        assert_teleport("tp 1 2 3", Position::new(1.5, 2.0, 3.5));
+        assert_teleport("tp 1.25 -2 3.0", Position::new(1.25, -2.0, 3.0));
+        assert_teleport("tp ~ ~1 ~-0.5", Position::new(10.5, 65.0, -4.0));
+
+        assert!(is_incorrect(parse("tp")));
+        assert!(is_invalid_argument(parse("tp 1 2"), "location"));
+        assert!(is_invalid_argument(parse("tp ^ ^ ^1"), "location"));
+        assert!(is_invalid_argument(parse("tp a 2 3"), "location"));
+        assert!(is_incorrect(parse("tp 1 2 3 4")));
+    }
+
+    #[test]
+    fn test_give() {
+        assert_give("give stone", "stone", 1);
+        assert_give("give minecraft:dirt 64", "dirt", 64);
+        assert_give("give dirt 6400", "dirt", 6400);
+
+        assert!(is_incorrect(parse("give")));
+        assert!(is_invalid_argument(parse("give no_such_item"), "item"));
+        assert!(is_invalid_argument(parse("give stone 0"), "count"));
+        assert!(is_invalid_argument(parse("give stone 6401"), "count"));
+        assert!(is_invalid_argument(parse("give stone many"), "count"));
+        assert!(is_incorrect(parse("give stone 1 2")));
+    }
+
+    #[test]
+    fn test_gamemode() {
+        assert!(matches!(
+            parse("gamemode survival"),
+            Ok(CommandAction::SetGameMode(Gamemode::Survival))
+        ));
+        assert!(matches!(
+            parse("gamemode creative"),
+            Ok(CommandAction::SetGameMode(Gamemode::Creative))
+        ));
+        assert!(matches!(
+            parse("gamemode adventure"),
+            Ok(CommandAction::SetGameMode(Gamemode::Adventure))
+        ));
+        assert!(matches!(
+            parse("gamemode spectator"),
+            Ok(CommandAction::SetGameMode(Gamemode::Spectator))
+        ));
+
+        assert!(is_incorrect(parse("gamemode")));
+        assert!(is_incorrect(parse("gamemode hardcore")));
+        assert!(is_incorrect(parse("gamemode survival now")));
+    }
+
+    #[test]
+    fn test_setblock() {
+        let stone = block_id("minecraft:stone", &[]);
+        // the y of our voxels starts at the bottom of the world
+        let bottom = Coordinate::new(1, 0, 2);
+        assert_fill("setblock 1 -64 2 stone", bottom, bottom, stone);
+        assert_fill("setblock 1 -64 2 minecraft:stone", bottom, bottom, stone);
+
+        // relative to the block of the source
+        let below = Coordinate::new(10, 127, -4);
+        let stairs = block_id(
+            "minecraft:oak_stairs",
+            &[("facing", "east"), ("half", "top")],
+        );
+        assert_fill(
+            "setblock ~ ~-1 ~ oak_stairs[facing=east,half=top]",
+            below,
+            below,
+            stairs,
+        );
+
+        assert!(is_incorrect(parse("setblock 1 2 3")));
+        assert!(is_invalid_argument(parse("setblock 1 2 stone"), "pos"));
+        assert!(is_invalid_argument(
+            parse("setblock 1 2 3 no_such_block"),
+            "block"
+        ));
+        assert!(is_invalid_argument(
+            parse("setblock 1 2 3 oak_stairs[facing=up]"),
+            "block"
+        ));
+        assert!(is_invalid_argument(
+            parse("setblock 1 2 3 oak_stairs[facing=east"),
+            "block"
+        ));
+        assert!(is_invalid_argument(
+            parse("setblock 1 2 3 oak_stairs[facing]"),
+            "block"
+        ));
+    }
+
+    #[test]
+    fn test_fill() {
+        let dirt = block_id("minecraft:dirt", &[]);
+        assert_fill(
+            "fill 9 -64 9 0 -55 0 dirt",
+            Coordinate::new(9, 0, 9),
+            Coordinate::new(0, 9, 0),
+            dirt,
+        );
+        // the largest volume
+        assert!(parse("fill 0 0 0 31 31 31 dirt").is_ok());
+        assert!(matches!(
+            parse("fill 0 0 0 31 31 32 dirt"),
+            Err(CommandError::Failed(_))
+        ));
+
+        assert!(is_incorrect(parse("fill 0 0 0 1 1 1")));
+        assert!(is_invalid_argument(parse("fill 0 0 0 1 1 dirt"), "to"));
+    }
+
+    #[test]
+    fn test_time() {
+        let time = |input| match parse(input) {
+            Ok(CommandAction::SetTime(time)) => Some(time),
+            _ => None,
+        };
+        assert_eq!(time("time set day"), Some(1000));
+        assert_eq!(time("time set noon"), Some(6000));
+        assert_eq!(time("time set night"), Some(13000));
+        assert_eq!(time("time set midnight"), Some(18000));
+        assert_eq!(time("time set 1234"), Some(1234));
+        assert!(matches!(
+            parse("time add 100"),
+            Ok(CommandAction::AddTime(100))
+        ));
+        assert!(matches!(parse("time query"), Ok(CommandAction::QueryTime)));
+
+        assert!(is_invalid_argument(parse("time set -1"), "time"));
+        assert!(is_invalid_argument(parse("time set evening"), "time"));
+        assert!(is_incorrect(parse("time")));
+        assert!(is_incorrect(parse("time set")));
+        assert!(is_incorrect(parse("time query 1")));
+    }
+
+    #[test]
+    fn test_suggest_commands() {
+        let (start, length, matches) = suggest("");
+        assert_eq!((start, length), (0, 0));
+        assert_eq!(
+            matches,
+            vec!["tp", "give", "gamemode", "setblock", "fill", "time"]
+        );
+
+        assert_eq!(
+            suggest("g"),
+            (0, 1, vec![String::from("give"), String::from("gamemode")])
+        );
+        assert_eq!(suggest("time"), (0, 4, vec![String::from("time")]));
+        assert!(suggest("fly").2.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_literals() {
+        assert_eq!(
+            suggest("gamemode s"),
+            (
+                9,
+                1,
+                vec![String::from("survival"), String::from("spectator")]
+            )
+        );
+        assert_eq!(suggest("gamemode ").2.len(), 4);
+        assert_eq!(
+            suggest("time set n"),
+            (9, 1, vec![String::from("noon"), String::from("night")])
+        );
+        assert_eq!(
+            suggest("time "),
+            (
+                5,
+                0,
+                vec![
+                    String::from("set"),
+                    String::from("add"),
+                    String::from("query")
+                ]
+            )
+        );
+        // nothing follows a complete command
+        assert!(suggest("time query ").2.is_empty());
+        assert!(suggest("fly s").2.is_empty());
+    }
+
+    #[test]
+    fn test_suggest_arguments() {
+        // the client completes the arguments that it can parse itself
+        assert!(suggest("tp ").2.is_empty());
+        assert!(suggest("give st").2.is_empty());
+
+        // but asks us for blocks
+        let (start, length, matches) = suggest("setblock ~ ~ ~ oak_st");
+        assert_eq!((start, length), (15, 6));
+        assert_eq!(matches, vec![String::from("minecraft:oak_stairs")]);
+
+        let (start, length, matches) = suggest("fill 0 0 0 1 1 1 minecraft:stone_br");
+        assert_eq!((start, length), (17, 18));
+        assert!(matches.contains(&String::from("minecraft:stone_bricks")));
+        assert!(matches.contains(&String::from("minecraft:stone_brick_stairs")));
+        assert!(matches
+            .iter()
+            .all(|name| name.starts_with("minecraft:stone_br")));
+
+        // every block, once
+        let (_, _, matches) = suggest("setblock 0 0 0 ");
+        assert!(matches.len() > 100);
+        assert!(matches.contains(&String::from("minecraft:stone")));
+        assert_eq!(
+            matches
+                .iter()
+                .filter(|name| *name == "minecraft:oak_stairs")
+                .count(),
+            1
+        );
+
+        // not before the position is complete
+        assert!(suggest("setblock 0 0 ").2.is_empty());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::commands::arguments::{
+    self, ArgumentParser, ArgumentValue, CommandReader, CommandSource,
+};
+use crate::commands::{builtin, CommandAction, CommandError};
+use minecraft_protocol::packets::VarInt;
+use minecraft_protocol::MinecraftPacketPart;
+use std::cell::OnceCell;
+
+const ROOT_NODE: usize = 0;
+// see https://wiki.vg/Command_Data
+const NODE_TYPE_ROOT: u8 = 0;
+const NODE_TYPE_LITERAL: u8 = 1;
+const NODE_TYPE_ARGUMENT: u8 = 2;
+const FLAG_EXECUTABLE: u8 = 0x04;
+const FLAG_HAS_SUGGESTIONS_TYPE: u8 = 0x10;
+const FLAG_INTEGER_MIN: u8 = 0x01;
+const FLAG_INTEGER_MAX: u8 = 0x02;
+const ASK_SERVER_SUGGESTIONS: &str = "minecraft:ask_server";
+// the id of the clientbound play packet `CommandSuggestionsResponse`, in protocol 764
+const COMMAND_SUGGESTIONS_RESPONSE_ID: i32 = 0x10;
+
+/// Runs the command, with the values of its arguments in the order of the path through the tree
+pub type Executor = fn(&[ArgumentValue]) -> Result<CommandAction, CommandError>;
+
+enum NodeKind {
+    Root,
+    Literal(&'static str),
+    Argument {
+        name: &'static str,
+        parser: ArgumentParser,
+    },
+}
+
+struct CommandNode {
+    kind: NodeKind,
+    children: Vec<usize>,
+    // None if the command is incomplete at this node
+    executor: Option<Executor>,
+}
+
+/// Completions of the last word of a partial command
+pub struct Suggestions {
+    /// the byte offset in the input of the text that the matches replace
+    pub start: usize,
+    pub length: usize,
+    pub matches: Vec<String>,
+}
+
+pub struct CommandTree {
+    nodes: Vec<CommandNode>,
+    // collected from the registries on the first completion of a block
+    block_names: OnceCell<Vec<String>>,
+}
+
+impl CommandTree {
+    /// The tree of all commands of the player
+    pub fn new() -> CommandTree {
+        let mut tree = CommandTree {
+            nodes: vec![CommandNode {
+                kind: NodeKind::Root,
+                children: Vec::new(),
+                executor: None,
+            }],
+            block_names: OnceCell::new(),
+        };
+        builtin::register(&mut tree);
+        tree
+    }
+
+    pub fn root(&self) -> usize {
+        ROOT_NODE
+    }
+
+    pub fn literal(&mut self, parent: usize, name: &'static str) -> usize {
+        self.add_node(parent, NodeKind::Literal(name))
+    }
+
+    pub fn argument(&mut self, parent: usize, name: &'static str, parser: ArgumentParser) -> usize {
+        self.add_node(parent, NodeKind::Argument { name, parser })
+    }
+
+    /// Makes the command complete at the node
+    pub fn executes(&mut self, node: usize, executor: Executor) {
+        self.nodes[node].executor = Some(executor);
+    }
+
+    fn add_node(&mut self, parent: usize, kind: NodeKind) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(CommandNode {
+            kind,
+            children: Vec::new(),
+            executor: None,
+        });
+        self.nodes[parent].children.push(node);
+        node
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Parses a command, without the leading slash
+    pub fn parse(
+        &self,
+        input: &str,
+        source: &CommandSource,
+    ) -> Result<CommandAction, CommandError> {
+        let mut reader = CommandReader::new(input);
+        let mut node = ROOT_NODE;
+        let mut values = Vec::new();
+
+        loop {
+            if node != ROOT_NODE && !reader.skip_separator() {
+                if !reader.is_at_end() {
+                    return Err(CommandError::IncorrectArguments);
+                }
+                let executor = self.nodes[node]
+                    .executor
+                    .ok_or(CommandError::IncorrectArguments)?;
+                return executor(&values);
+            }
+
+            let (child, value) = self.parse_child(node, &mut reader, source)?;
+            values.extend(value);
+            node = child;
+        }
+    }
+
+    // literals are matched before arguments; the first child that matches is taken
+    fn parse_child(
+        &self,
+        node: usize,
+        reader: &mut CommandReader,
+        source: &CommandSource,
+    ) -> Result<(usize, Option<ArgumentValue>), CommandError> {
+        let start = reader.clone();
+        let word = reader.clone().read_word();
+
+        for &child in &self.nodes[node].children {
+            if let NodeKind::Literal(name) = self.nodes[child].kind {
+                if name == word {
+                    reader.read_word();
+                    return Ok((child, None));
+                }
+            }
+        }
+
+        let mut error = None;
+        for &child in &self.nodes[node].children {
+            let NodeKind::Argument { name, parser } = self.nodes[child].kind else {
+                continue;
+            };
+            *reader = start.clone();
+            match parser.parse(reader, source) {
+                Ok(value) => return Ok((child, Some(value))),
+                Err(reason) => {
+                    error = Some(CommandError::InvalidArgument {
+                        argument: name,
+                        reason,
+                    })
+                },
+            }
+        }
+
+        Err(match error {
+            Some(error) => error,
+            None if node == ROOT_NODE => CommandError::UnknownCommand(String::from(word)),
+            None => CommandError::IncorrectArguments,
+        })
+    }
+
+    /// Completes the last word of a partial command, without the leading slash
+    pub fn suggest(&self, input: &str, source: &CommandSource) -> Suggestions {
+        let mut suggestions = Suggestions {
+            start: input.len(),
+            length: 0,
+            matches: Vec::new(),
+        };
+        self.suggest_from(
+            ROOT_NODE,
+            CommandReader::new(input),
+            source,
+            &mut suggestions,
+        );
+        suggestions
+    }
+
+    // follows every child that matches a whole word, up to the word at the end of the input
+    fn suggest_from(
+        &self,
+        node: usize,
+        mut reader: CommandReader,
+        source: &CommandSource,
+        suggestions: &mut Suggestions,
+    ) {
+        if node != ROOT_NODE && !reader.skip_separator() {
+            return;
+        }
+
+        let start = reader.cursor();
+        let partial = reader.remaining();
+
+        for &child in &self.nodes[node].children {
+            let mut child_reader = reader.clone();
+            let completed = match self.nodes[child].kind {
+                NodeKind::Root => false,
+                NodeKind::Literal(name) => {
+                    let word = child_reader.read_word();
+                    if child_reader.is_at_end() && name.starts_with(word) {
+                        add_match(suggestions, start, partial, String::from(name));
+                    }
+                    word == name
+                },
+                NodeKind::Argument { parser, .. } => {
+                    let parsed = parser.parse(&mut child_reader, source).is_ok();
+                    if parser.asks_server() && !partial.contains(' ') {
+                        let block_names = self
+                            .block_names
+                            .get_or_init(|| arguments::block_names(source.registries));
+                        for suggestion in parser.suggest(partial, block_names) {
+                            add_match(suggestions, start, partial, suggestion);
+                        }
+                    }
+                    parsed
+                },
+            };
+
+            if completed && !child_reader.is_at_end() {
+                self.suggest_from(child, child_reader, source, suggestions);
+            }
+        }
+    }
+
+    /// The nodes of the `DeclareCommands` packet, followed by the index of the root node
+    pub fn encode(&self) -> Result<Vec<u8>, &'static str> {
+        let mut data = Vec::new();
+
+        for node in &self.nodes {
+            let (node_type, parser) = match node.kind {
+                NodeKind::Root => (NODE_TYPE_ROOT, None),
+                NodeKind::Literal(_) => (NODE_TYPE_LITERAL, None),
+                NodeKind::Argument { parser, .. } => (NODE_TYPE_ARGUMENT, Some(parser)),
+            };
+            let asks_server = parser.is_some_and(ArgumentParser::asks_server);
+
+            let mut flags = node_type;
+            if node.executor.is_some() {
+                flags |= FLAG_EXECUTABLE;
+            }
+            if asks_server {
+                flags |= FLAG_HAS_SUGGESTIONS_TYPE;
+            }
+            flags.serialize_minecraft_packet_part(&mut data)?;
+
+            VarInt(node.children.len() as i32).serialize_minecraft_packet_part(&mut data)?;
+            for &child in &node.children {
+                VarInt(child as i32).serialize_minecraft_packet_part(&mut data)?;
+            }
+
+            match node.kind {
+                NodeKind::Root => {},
+                NodeKind::Literal(name) | NodeKind::Argument { name, .. } => {
+                    name.serialize_minecraft_packet_part(&mut data)?
+                },
+            }
+
+            if let Some(parser) = parser {
+                VarInt(parser.protocol_id()).serialize_minecraft_packet_part(&mut data)?;
+                if let ArgumentParser::Integer { min, max } = parser {
+                    (FLAG_INTEGER_MIN | FLAG_INTEGER_MAX)
+                        .serialize_minecraft_packet_part(&mut data)?;
+                    min.serialize_minecraft_packet_part(&mut data)?;
+                    max.serialize_minecraft_packet_part(&mut data)?;
+                }
+            }
+
+            if asks_server {
+                ASK_SERVER_SUGGESTIONS.serialize_minecraft_packet_part(&mut data)?;
+            }
+        }
+
+        VarInt(ROOT_NODE as i32).serialize_minecraft_packet_part(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// The `CommandSuggestionsResponse` packet, with its packet id. `offset` is the length of the
+/// text before the partial command, like its leading slash
+pub fn suggestions_packet(
+    transaction_id: i32,
+    offset: usize,
+    suggestions: &Suggestions,
+) -> Result<Vec<u8>, &'static str> {
+    let mut packet = Vec::new();
+    VarInt(COMMAND_SUGGESTIONS_RESPONSE_ID).serialize_minecraft_packet_part(&mut packet)?;
+    VarInt(transaction_id).serialize_minecraft_packet_part(&mut packet)?;
+    VarInt((offset + suggestions.start) as i32).serialize_minecraft_packet_part(&mut packet)?;
+    VarInt(suggestions.length as i32).serialize_minecraft_packet_part(&mut packet)?;
+    VarInt(suggestions.matches.len() as i32).serialize_minecraft_packet_part(&mut packet)?;
+    for suggestion in &suggestions.matches {
+        suggestion
+            .as_str()
+            .serialize_minecraft_packet_part(&mut packet)?;
+        // without a tooltip
+        false.serialize_minecraft_packet_part(&mut packet)?;
+    }
+    Ok(packet)
+}
+
+// the matches of the word that starts last replace the others
+fn add_match(suggestions: &mut Suggestions, start: usize, partial: &str, suggestion: String) {
+    if suggestions.matches.is_empty() || start > suggestions.start {
+        suggestions.start = start;
+        suggestions.length = partial.len();
+        suggestions.matches.clear();
+    }
+    if start == suggestions.start && !suggestions.matches.contains(&suggestion) {
+        suggestions.matches.push(suggestion);
+    }
+}
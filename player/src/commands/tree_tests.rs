@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::packets::VarInt;
+    use minecraft_protocol::MinecraftPacketPart;
+
+    use crate::commands::tree::{self, CommandTree, Suggestions};
+
+    // a node of the `DeclareCommands` packet, see https://wiki.vg/Command_Data
+    struct EncodedNode {
+        flags: u8,
+        children: Vec<usize>,
+        name: Option<String>,
+        parser: Option<i32>,
+        // the flags, min and max of an integer parser
+        integer_range: Option<(u8, i32, i32)>,
+        suggestions_type: Option<String>,
+    }
+
+    fn read_var_int(data: &mut &[u8]) -> i32 {
+        let (value, rest) = VarInt::deserialize_minecraft_packet_part(data).unwrap();
+        *data = rest;
+        value.0
+    }
+
+    fn read<'a, T: MinecraftPacketPart<'a>>(data: &mut &'a [u8]) -> T {
+        let (value, rest) = T::deserialize_minecraft_packet_part(data).unwrap();
+        *data = rest;
+        value
+    }
+
+    // the nodes and the index of the root node
+    fn decode(mut data: &[u8]) -> (Vec<EncodedNode>, usize) {
+        let data = &mut data;
+        let mut nodes = Vec::new();
+        let node_count = CommandTree::new().node_count();
+
+        for _ in 0..node_count {
+            let flags: u8 = read(data);
+            let child_count = read_var_int(data);
+            let children = (0..child_count)
+                .map(|_| read_var_int(data) as usize)
+                .collect();
+
+            let node_type = flags & 0x03;
+            let name = (node_type != 0).then(|| String::from(read::<&str>(data)));
+            let parser = (node_type == 2).then(|| read_var_int(data));
+            // brigadier:integer
+            let integer_range = (parser == Some(3)).then(|| (read(data), read(data), read(data)));
+            let suggestions_type = (flags & 0x10 != 0).then(|| String::from(read::<&str>(data)));
+
+            nodes.push(EncodedNode {
+                flags,
+                children,
+                name,
+                parser,
+                integer_range,
+                suggestions_type,
+            });
+        }
+
+        let root = read_var_int(data) as usize;
+        assert!(data.is_empty());
+        (nodes, root)
+    }
+
+    // follows the names from the root
+    fn find<'a>(nodes: &'a [EncodedNode], root: usize, path: &[&str]) -> &'a EncodedNode {
+        let mut node = &nodes[root];
+        for name in path {
+            let child = node
+                .children
+                .iter()
+                .find(|child| nodes[**child].name.as_deref() == Some(*name))
+                .unwrap_or_else(|| panic!("{name} is missing"));
+            node = &nodes[*child];
+        }
+        node
+    }
+
+    fn names(nodes: &[EncodedNode], node: &EncodedNode) -> Vec<String> {
+        node.children
+            .iter()
+            .filter_map(|child| nodes[*child].name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_root() {
+        let data = CommandTree::new().encode().unwrap();
+        let (nodes, root) = decode(&data);
+        assert_eq!(root, 0);
+        assert_eq!(nodes[root].flags, 0);
+        assert!(nodes[root].name.is_none());
+        assert_eq!(
+            names(&nodes, &nodes[root]),
+            vec!["tp", "give", "gamemode", "setblock", "fill", "time"]
+        );
+
+        // every node but the root is the child of exactly one node
+        let mut parents = vec![0; nodes.len()];
+        for node in &nodes {
+            for &child in &node.children {
+                parents[child] += 1;
+            }
+        }
+        assert_eq!(parents[root], 0);
+        assert!(parents.iter().skip(1).all(|count| *count == 1));
+    }
+
+    #[test]
+    fn test_literals() {
+        let data = CommandTree::new().encode().unwrap();
+        let (nodes, root) = decode(&data);
+
+        // literals that do not complete a command
+        for path in [&["gamemode"][..], &["time"], &["time", "set"]] {
+            let node = find(&nodes, root, path);
+            assert_eq!(node.flags, 0x01);
+            assert!(node.parser.is_none());
+        }
+        assert_eq!(
+            names(&nodes, find(&nodes, root, &["gamemode"])),
+            vec!["survival", "creative", "adventure", "spectator"]
+        );
+
+        // executable literals
+        for path in [
+            &["gamemode", "survival"][..],
+            &["time", "set", "noon"],
+            &["time", "query"],
+        ] {
+            let node = find(&nodes, root, path);
+            assert_eq!(node.flags, 0x05);
+            assert!(node.children.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_arguments() {
+        let data = CommandTree::new().encode().unwrap();
+        let (nodes, root) = decode(&data);
+
+        // minecraft:vec3
+        let location = find(&nodes, root, &["tp", "location"]);
+        assert_eq!(location.flags, 0x06);
+        assert_eq!(location.parser, Some(10));
+
+        // minecraft:item_stack, which completes the command, or is followed by the count
+        let item = find(&nodes, root, &["give", "item"]);
+        assert_eq!(item.flags, 0x06);
+        assert_eq!(item.parser, Some(14));
+        let count = find(&nodes, root, &["give", "item", "count"]);
+        assert_eq!(count.flags, 0x06);
+        assert_eq!(count.parser, Some(3));
+        assert_eq!(count.integer_range, Some((0x03, 1, 6400)));
+
+        // minecraft:block_pos, which does not complete the command
+        let pos = find(&nodes, root, &["setblock", "pos"]);
+        assert_eq!(pos.flags, 0x02);
+        assert_eq!(pos.parser, Some(8));
+
+        // minecraft:block_state, which the client asks us to complete
+        let block = find(&nodes, root, &["fill", "from", "to", "block"]);
+        assert_eq!(block.flags, 0x16);
+        assert_eq!(block.parser, Some(12));
+        assert_eq!(
+            block.suggestions_type.as_deref(),
+            Some("minecraft:ask_server")
+        );
+
+        let time = find(&nodes, root, &["time", "set", "time"]);
+        assert_eq!(time.integer_range, Some((0x03, 0, i32::MAX)));
+    }
+
+    #[test]
+    fn test_suggestions_packet() {
+        let suggestions = Suggestions {
+            start: 9,
+            length: 1,
+            matches: vec![String::from("survival"), String::from("spectator")],
+        };
+        let packet = tree::suggestions_packet(7, 1, &suggestions).unwrap();
+
+        let data = &mut packet.as_slice();
+        assert_eq!(read_var_int(data), 0x10);
+        assert_eq!(read_var_int(data), 7);
+        // after the leading slash
+        assert_eq!(read_var_int(data), 10);
+        assert_eq!(read_var_int(data), 1);
+        assert_eq!(read_var_int(data), 2);
+        for expected in ["survival", "spectator"] {
+            assert_eq!(read::<&str>(data), expected);
+            // no tooltip
+            assert!(!read::<bool>(data));
+        }
+        assert!(data.is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+// Containers keep their stacks in the nbt of their voxel, like the block entities of vanilla:
+// the list "Items" holds every stack that is not empty, along with the index of its slot.
+
+use crate::item_stack::ItemStack;
+use minecraft_protocol::nbt::{NbtList, NbtTag};
+use sol_voxel_lib::vector_alias::Coordinate;
+use std::collections::HashMap;
+
+pub const FURNACE_RESULT_SLOT: usize = 2;
+
+const BLOCK_ENTITY_ID_TAG: &str = "id";
+const ITEMS_TAG: &str = "Items";
+const SLOT_TAG: &str = "Slot";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContainerKind {
+    /// TODO double chests: every chest opens on its own
+    Chest,
+    /// TODO smelting: the furnace only holds the stacks
+    Furnace,
+}
+
+/// A container that the player opened.
+/// Its stacks are written back into the voxel when the window closes
+pub struct OpenContainer {
+    pub window_id: i8,
+    pub kind: ContainerKind,
+    pub location: Coordinate,
+    pub stacks: Vec<ItemStack>,
+    // true once a click changed the stacks
+    pub changed: bool,
+}
+
+impl ContainerKind {
+    /// The kind of container of a block, by the name of the block
+    pub fn of_block(name: &str) -> Option<ContainerKind> {
+        match name.trim_start_matches("minecraft:") {
+            "chest" | "trapped_chest" => Some(ContainerKind::Chest),
+            "furnace" => Some(ContainerKind::Furnace),
+            _ => None,
+        }
+    }
+
+    pub fn slot_count(self) -> usize {
+        match self {
+            ContainerKind::Chest => 27,
+            ContainerKind::Furnace => 3,
+        }
+    }
+
+    /// The type of window that the client opens, see https://wiki.vg/Inventory
+    pub fn window_type(self) -> i32 {
+        match self {
+            // generic_9x3
+            ContainerKind::Chest => 2,
+            ContainerKind::Furnace => 13,
+        }
+    }
+
+    /// The title of the window, as json text
+    pub fn title(self) -> &'static str {
+        match self {
+            ContainerKind::Chest => "{\"translate\":\"container.chest\"}",
+            ContainerKind::Furnace => "{\"translate\":\"container.furnace\"}",
+        }
+    }
+}
+
+/// The stacks in the nbt of a container voxel; a voxel without nbt is an empty container
+pub fn stacks_from_nbt(nbt: &NbtTag, kind: ContainerKind) -> Vec<ItemStack> {
+    let mut stacks = vec![ItemStack::Empty; kind.slot_count()];
+    let NbtTag::Compound(tags) = nbt else {
+        return stacks;
+    };
+    let Some(NbtTag::List(NbtList::Compound(items))) = tags.get(ITEMS_TAG) else {
+        return stacks;
+    };
+
+    for item in items {
+        let slot = match item.get(SLOT_TAG) {
+            Some(NbtTag::Byte(slot)) => usize::try_from(*slot).ok(),
+            _ => None,
+        };
+        if let Some(stack) = slot.and_then(|slot| stacks.get_mut(slot)) {
+            *stack = ItemStack::from_nbt(item);
+        }
+    }
+    stacks
+}
+
+/// Writes the stacks into the nbt of a container voxel of the given block.
+/// The other tags, like a custom name, are kept
+pub fn stacks_to_nbt(nbt: NbtTag, block_name: &str, stacks: &[ItemStack]) -> NbtTag {
+    let mut tags = match nbt {
+        NbtTag::Compound(tags) => tags,
+        _ => HashMap::new(),
+    };
+
+    // like vanilla: the block entity of a container has the name of its block
+    tags.entry(String::from(BLOCK_ENTITY_ID_TAG))
+        .or_insert_with(|| NbtTag::String(String::from(block_name)));
+
+    let items = stacks
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, stack)| {
+            let mut item = stack.to_nbt()?;
+            item.insert(String::from(SLOT_TAG), NbtTag::Byte(slot as i8));
+            Some(item)
+        })
+        .collect();
+    tags.insert(
+        String::from(ITEMS_TAG),
+        NbtTag::List(NbtList::Compound(items)),
+    );
+
+    NbtTag::Compound(tags)
+}
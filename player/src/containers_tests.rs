@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minecraft_protocol::data::items::Item;
+    use minecraft_protocol::nbt::{NbtList, NbtTag};
+
+    use crate::containers::{self, ContainerKind};
+    use crate::item_stack::ItemStack;
+
+    fn stack(name: &str, count: usize) -> ItemStack {
+        ItemStack::new(Item::from_text_id(name).unwrap(), count)
+    }
+
+    // stacks are the same if their nbt is
+    fn same_stacks(a: &[ItemStack], b: &[ItemStack]) -> bool {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| a.to_nbt() == b.to_nbt() && a.count() == b.count())
+    }
+
+    fn chest_stacks() -> Vec<ItemStack> {
+        let mut stacks = vec![ItemStack::Empty; ContainerKind::Chest.slot_count()];
+        stacks[0] = stack("stone", 10);
+        stacks[5] = ItemStack::new_nbt(Item::from_text_id("diamond_sword").unwrap(), {
+            let mut tags = HashMap::new();
+            tags.insert(String::from("Damage"), NbtTag::Int(12));
+            NbtTag::Compound(tags)
+        });
+        stacks[26] = stack("dirt", 64);
+        stacks
+    }
+
+    fn item_tags(nbt: &NbtTag) -> &Vec<HashMap<String, NbtTag>> {
+        match nbt {
+            NbtTag::Compound(tags) => match tags.get("Items") {
+                Some(NbtTag::List(NbtList::Compound(items))) => items,
+                _ => panic!("The items are missing"),
+            },
+            _ => panic!("The nbt is not a compound"),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let stacks = chest_stacks();
+        let nbt = containers::stacks_to_nbt(NbtTag::Null, "minecraft:chest", &stacks);
+        let read = containers::stacks_from_nbt(&nbt, ContainerKind::Chest);
+        assert!(same_stacks(&read, &stacks));
+
+        // again, from the nbt that we wrote before
+        let nbt = containers::stacks_to_nbt(nbt, "minecraft:chest", &read);
+        assert!(same_stacks(
+            &containers::stacks_from_nbt(&nbt, ContainerKind::Chest),
+            &stacks
+        ));
+    }
+
+    #[test]
+    fn test_only_stacks_with_items_are_written() {
+        let nbt = containers::stacks_to_nbt(NbtTag::Null, "minecraft:chest", &chest_stacks());
+        let items = item_tags(&nbt);
+        assert_eq!(items.len(), 3);
+        let slots: Vec<_> = items.iter().map(|item| item.get("Slot")).collect();
+        assert!(slots[0] == Some(&NbtTag::Byte(0)));
+        assert!(slots[1] == Some(&NbtTag::Byte(5)));
+        assert!(slots[2] == Some(&NbtTag::Byte(26)));
+
+        // the slots that were emptied are removed
+        let stacks = vec![ItemStack::Empty; ContainerKind::Chest.slot_count()];
+        let nbt = containers::stacks_to_nbt(nbt, "minecraft:chest", &stacks);
+        assert!(item_tags(&nbt).is_empty());
+    }
+
+    #[test]
+    fn test_other_tags_are_kept() {
+        let mut tags = HashMap::new();
+        tags.insert(
+            String::from("CustomName"),
+            NbtTag::String(String::from("{\"text\":\"Loot\"}")),
+        );
+        tags.insert(
+            String::from("id"),
+            NbtTag::String(String::from("minecraft:trapped_chest")),
+        );
+
+        let nbt =
+            containers::stacks_to_nbt(NbtTag::Compound(tags), "minecraft:chest", &chest_stacks());
+        let NbtTag::Compound(tags) = nbt else {
+            panic!("The nbt is not a compound");
+        };
+        assert!(
+            tags.get("CustomName") == Some(&NbtTag::String(String::from("{\"text\":\"Loot\"}")))
+        );
+        assert!(tags.get("id") == Some(&NbtTag::String(String::from("minecraft:trapped_chest"))));
+
+        // a new container gets the name of its block
+        let nbt = containers::stacks_to_nbt(NbtTag::Null, "minecraft:furnace", &[]);
+        let NbtTag::Compound(tags) = nbt else {
+            panic!("The nbt is not a compound");
+        };
+        assert!(tags.get("id") == Some(&NbtTag::String(String::from("minecraft:furnace"))));
+    }
+
+    #[test]
+    fn test_from_nbt_without_items() {
+        let stacks = containers::stacks_from_nbt(&NbtTag::Null, ContainerKind::Furnace);
+        assert_eq!(stacks.len(), 3);
+        assert!(stacks.iter().all(ItemStack::is_empty));
+
+        let stacks =
+            containers::stacks_from_nbt(&NbtTag::Compound(HashMap::new()), ContainerKind::Chest);
+        assert_eq!(stacks.len(), 27);
+        assert!(stacks.iter().all(ItemStack::is_empty));
+    }
+
+    #[test]
+    fn test_from_nbt_ignores_other_slots() {
+        // a chest that was turned into a furnace keeps the stacks of the chest in its nbt
+        let nbt = containers::stacks_to_nbt(NbtTag::Null, "minecraft:chest", &chest_stacks());
+        let stacks = containers::stacks_from_nbt(&nbt, ContainerKind::Furnace);
+        assert_eq!(stacks.len(), 3);
+        assert_eq!(stacks[0].count(), 10);
+        assert!(stacks[1].is_empty());
+        assert!(stacks[2].is_empty());
+
+        let NbtTag::Compound(mut tags) = nbt else {
+            panic!("The nbt is not a compound");
+        };
+        let mut item = stack("stone", 1).to_nbt().unwrap();
+        item.insert(String::from("Slot"), NbtTag::Byte(-1));
+        tags.insert(
+            String::from("Items"),
+            NbtTag::List(NbtList::Compound(vec![item])),
+        );
+        let stacks = containers::stacks_from_nbt(&NbtTag::Compound(tags), ContainerKind::Chest);
+        assert!(stacks.iter().all(ItemStack::is_empty));
+    }
+}
@@ -1,3 +0,0 @@
-pub struct Entity {
-    pub entity_id: u32,
-}
@@ -1,8 +1,15 @@
-use crate::player_events::PlayerPlaceBlockEvent;
+use crate::item_stack::ItemStack;
+use crate::player_events::{
+    PlayerClickWindowEvent, PlayerDigBlockEvent, PlayerMoveEvent, PlayerPlaceBlockEvent,
+};
+use crate::player_session::SessionId;
+use sol_entity_lib::entity::EntityId;
 use sol_network_lib::Tick;
-use sol_voxel_lib::vector_alias::Coordinate;
+use sol_voxel_lib::vector_alias::{Coordinate, Position, Vector3f};
 use sol_voxel_lib::voxel::Voxel;
 use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
 
 pub struct ScheduledEvent {
     pub tick: Tick,
@@ -12,13 +19,68 @@ pub struct ScheduledEvent {
 pub enum Event {
     VoxelChange { coord: Coordinate, new_voxel: Voxel },
     VoxelUpdate { coord: Coordinate },
-    EntityUpdate { entity_id: u32 },
-    PlayerPlaceBlock(PlayerPlaceBlockEvent)
+    EntityUpdate { entity_id: EntityId },
+    /// an event of the client of a session; it is dropped if the session ended meanwhile
+    Player(SessionId, PlayerEvent),
+    /// an item entity appears at the given position, in the coordinates of our voxels
+    ItemEntitySpawn {
+        position: Position,
+        velocity: Vector3f,
+        stack: ItemStack,
+        pickup_delay: Tick,
+    },
 }
 
+pub enum PlayerEvent {
+    PlaceBlock(PlayerPlaceBlockEvent),
+    DigBlock(PlayerDigBlockEvent),
+    /// the client processed a chunk batch, and asks for a new rate of columns per tick
+    ChunkBatchReceived { chunks_per_tick: f32 },
+    Move(PlayerMoveEvent),
+    ConfirmTeleport { teleport_id: i32 },
+    /// the player drops one item, or the whole stack, of the selected slot
+    DropItem { whole_stack: bool },
+    ClickWindow(PlayerClickWindowEvent),
+    CloseWindow { window_id: i8 },
+    /// the slot is 0 to 8 in the hotbar
+    SetHeldItem { slot: i16 },
+    /// a chat message that the player typed, to broadcast to the players near it
+    ChatMessage { text: String },
+    /// a command that the player typed, without the leading slash
+    ChatCommand { command: String },
+    /// the client asks for completions of a partial command, including the leading slash
+    CommandSuggestions { transaction_id: i32, text: String },
+    /// in creative mode, the client sets the stacks of the inventory itself
+    SetCreativeSlot { slot: i16, stack: ItemStack },
+    /// the client echoed a keep-alive id, which the receiver read at `received`
+    KeepAlive { id: u64, received: Instant },
+}
+
+/// The scheduled events of the game loop, the earliest tick first
+#[derive(Default)]
+pub struct EventQueue {
+    events: BinaryHeap<ScheduledEvent>,
+}
+
+impl EventQueue {
+    pub fn push(&mut self, event: ScheduledEvent) {
+        self.events.push(event);
+    }
+
+    /// Removes an event that is due at `current_tick`. Events of later ticks stay queued, even
+    /// if they were pushed while the events of this tick are handled
+    pub fn pop_due(&mut self, current_tick: Tick) -> Option<Event> {
+        if self.events.peek()?.tick > current_tick {
+            return None;
+        }
+        self.events.pop().map(|scheduled| scheduled.event)
+    }
+}
+
+// reversed, as the binary heap pops the greatest element first
 impl Ord for ScheduledEvent {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.tick.cmp(&other.tick)
+        other.tick.cmp(&self.tick)
     }
 }
 
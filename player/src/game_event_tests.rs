@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use sol_network_lib::Tick;
+    use sol_voxel_lib::vector_alias::Coordinate;
+
+    use crate::game_event::{Event, EventQueue, ScheduledEvent};
+
+    fn schedule(queue: &mut EventQueue, tick: Tick, event: Event) {
+        queue.push(ScheduledEvent { tick, event });
+    }
+
+    fn voxel_update(x: i32) -> Event {
+        Event::VoxelUpdate {
+            coord: Coordinate::new(x, 0, 0),
+        }
+    }
+
+    fn describe(event: &Event) -> String {
+        match event {
+            Event::EntityUpdate { entity_id } => format!("entity {entity_id}"),
+            Event::VoxelUpdate { coord } => format!("voxel {}", coord.x),
+            _ => String::from("other"),
+        }
+    }
+
+    // handles the events that are due like the game loop: entities are updated again next tick
+    fn run_tick(queue: &mut EventQueue, tick: Tick) -> Vec<String> {
+        let mut handled = Vec::new();
+        while let Some(event) = queue.pop_due(tick) {
+            handled.push(describe(&event));
+            if let Event::EntityUpdate { entity_id } = event {
+                schedule(queue, tick + 1, Event::EntityUpdate { entity_id });
+            }
+        }
+        handled.sort();
+        handled
+    }
+
+    #[test]
+    fn test_entities_and_immediate_events_run_in_one_tick() {
+        let mut queue = EventQueue::default();
+        schedule(&mut queue, 10, Event::EntityUpdate { entity_id: 1 });
+        schedule(&mut queue, 10, Event::EntityUpdate { entity_id: 2 });
+        schedule(&mut queue, 10, voxel_update(7));
+
+        assert_eq!(
+            run_tick(&mut queue, 10),
+            vec!["entity 1", "entity 2", "voxel 7"]
+        );
+
+        // the entities are updated every tick, and do not hold back the events of the clients
+        schedule(&mut queue, 11, voxel_update(8));
+        assert_eq!(
+            run_tick(&mut queue, 11),
+            vec!["entity 1", "entity 2", "voxel 8"]
+        );
+        assert_eq!(run_tick(&mut queue, 12), vec!["entity 1", "entity 2"]);
+    }
+
+    #[test]
+    fn test_earliest_tick_first() {
+        let mut queue = EventQueue::default();
+        schedule(&mut queue, 12, voxel_update(12));
+        schedule(&mut queue, 10, voxel_update(10));
+        schedule(&mut queue, 11, voxel_update(11));
+
+        // events that are late run first
+        let mut handled = Vec::new();
+        while let Some(event) = queue.pop_due(12) {
+            handled.push(describe(&event));
+        }
+        assert_eq!(handled, vec!["voxel 10", "voxel 11", "voxel 12"]);
+    }
+
+    #[test]
+    fn test_future_events_wait() {
+        let mut queue = EventQueue::default();
+        assert!(queue.pop_due(0).is_none());
+
+        schedule(&mut queue, 5, voxel_update(5));
+        assert!(queue.pop_due(4).is_none());
+        assert!(queue.pop_due(5).is_some());
+        assert!(queue.pop_due(5).is_none());
+    }
+}
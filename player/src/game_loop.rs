@@ -1,33 +1,121 @@
-use crate::entities::entity_manager::EntityManager;
-use crate::game_event::{Event, ScheduledEvent};
-use crate::minecraft_connection::client_connection::ClientSendCommand;
+use crate::chat;
+use crate::commands::arguments::CommandSource;
+use crate::commands::tree::{self, CommandTree};
+use crate::commands::{CommandAction, CommandError};
+use crate::containers::{self, ContainerKind, OpenContainer};
+use crate::game_event::{Event, EventQueue, PlayerEvent, ScheduledEvent};
+use crate::inventory::{self, ClickOutcome, InventoryClick};
+use crate::item_entities::{self, ItemEntities};
+use crate::item_stack::ItemStack;
+use crate::keep_alive::KeepAliveAction;
+use crate::minecraft_connection::chunk_data;
+use crate::minecraft_connection::coordinates::{self, MINECRAFT_MIN_Y};
+use crate::player_events::{
+    DigStatus, PlayerClickWindowEvent, PlayerDigBlockEvent, PlayerMoveEvent,
+};
 use crate::player_handler;
-use crate::player_handler::PlayerHandler;
+use crate::player_movement;
+use crate::player_session::{DigProgress, PlayerSession, SessionId, NO_DESTROY_STAGE};
+use crate::player_state::{PLAYER_HOTBAR_SLOTS, PLAYER_OFF_HAND_SLOT};
+use crate::tool_requirements::ToolRequirements;
+use crate::voxels::column_loader::{ColumnLoad, ColumnLoader};
 use crate::voxels::world::World;
+use minecraft_protocol::components::gamemode::Gamemode;
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_protocol::data::items::Item;
+use minecraft_protocol::nbt::NbtTag;
+use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
+use minecraft_protocol::packets::{Array, VarInt, VarLong};
+use minecraft_vanilla::ids::blocks::BlockId;
 use minecraft_vanilla::registries::Registries;
+use sol_chat_messages::{ChatMessage, ChatServerRep, ChatServerReq};
+use sol_entity_lib::entity::{EntityId, PLAYER_SERVER_ENTITY_IDS};
+use sol_entity_lib::entity_manager::EntityManager;
+use sol_game_engine::physics::aabb::Aabb;
+use sol_game_engine::physics::collision::ColumnCollider;
+use sol_game_engine::physics::entity_physics;
 use sol_log_server::logger_mt::LoggerMt;
+use sol_log_server::Severity;
 use sol_network_lib::constants;
+use sol_network_lib::network;
 use sol_network_lib::Tick;
-use std::collections::BinaryHeap;
+use sol_voxel_lib::vector_alias::{
+    ChunkColumnCoordinate, Coordinate, Coordinate16, Position, Vector3f,
+};
+use sol_voxel_lib::voxel::Voxel;
+use sol_world_messages::{WorldServerRep, WorldServerReq};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::mpsc::{self, TryRecvError};
 use std::time::Instant;
 
+// like vanilla: digging with the right tool takes 30 ticks per point of hardness, and 100 otherwise
+const TICKS_PER_HARDNESS_HARVESTABLE: f32 = 30.0;
+const TICKS_PER_HARDNESS_NOT_HARVESTABLE: f32 = 100.0;
+// like vanilla: the client may finish a bit early, as its ticks are not in lockstep with ours.
+// The tick in which the digging started counts as well
+const MIN_DIG_PROGRESS_TO_FINISH: f32 = 0.7;
+const NUM_DESTROY_STAGES: u32 = 10;
+// like vanilla: the size of the player
+const PLAYER_WIDTH: f32 = 0.6;
+const PLAYER_HEIGHT: f32 = 1.8;
+// like vanilla: the window of a container closes when the player is farther away from it
+const MAX_CONTAINER_DISTANCE: f32 = 8.0;
+// like vanilla: the time is sent to the client every second, which advances it on its own
+const TIME_UPDATE_PERIOD: Tick = 20;
+const TICKS_PER_DAY: i64 = 24000;
+// the columns that no player sees are dropped this often; the world server has all our changes
+const COLUMN_EVICTION_PERIOD: Tick = 100;
+// the event of `ChangeGameState` that changes the game mode
+const GAME_STATE_CHANGE_GAME_MODE: u8 = 3;
+// the text before a command in `CommandSuggestionsRequest`
+const COMMAND_PREFIX: &str = "/";
+// like vanilla: the abilities of `PlayerAbilities` in each game mode; 0x01 is invulnerable,
+// 0x02 is flying, 0x04 is allowed to fly and 0x08 is breaking blocks instantly
+const ABILITIES_SURVIVAL: u8 = 0x00;
+const ABILITIES_CREATIVE: u8 = 0x0D;
+const ABILITIES_SPECTATOR: u8 = 0x07;
+// like vanilla: the reason for a client that did not echo a keep-alive in time, or echoed a wrong one
+const TIMEOUT_REASON: &str = "{\"translate\":\"disconnect.timeout\"}";
+
 pub struct GameLoop {
     logger: LoggerMt,
     current_tick: Tick,
     message_queue: mpsc::Receiver<GameCommand>,
-    client_comm_channel: mpsc::Sender<ClientSendCommand>,
-    event_queue: BinaryHeap<ScheduledEvent>,
+    event_queue: EventQueue,
     world: World,
     entities: EntityManager,
-    player: PlayerHandler,
+    item_entities: ItemEntities,
+    // the players that are connected; they all see the same world
+    sessions: BTreeMap<SessionId, PlayerSession>,
     registries: Registries,
+    tool_requirements: ToolRequirements,
+    // our changes are stored here
+    world_server_socket: zmq::Socket,
+    // columns that are not in `world` yet are requested here
+    column_loader: ColumnLoader,
+    // changes of this tick per section, sent to the clients at the end of the tick
+    block_changes: HashMap<Coordinate16, HashMap<Coordinate, BlockWithState>>,
+    // changes of this tick in the order they happened, sent to the world server at the end of
+    // the tick
+    voxel_changes: Vec<(Coordinate, Voxel)>,
+    command_tree: CommandTree,
+    // chat messages of the players are sent here, and received from the subscriber socket of
+    // each session
+    chat_server_socket: zmq::Socket,
+    world_age: i64,
+    time_of_day: i64,
+    // a command changed the time, which is sent to every client at the end of the tick
+    time_changed: bool,
 }
 
 pub enum GameCommand {
     Stop,
     ImmediateEvent(Event),
     FutureEvent(ScheduledEvent),
+    /// a client joined; its events are handled from now on
+    AddPlayer(Box<PlayerSession>),
+    /// the client of the session disconnected
+    RemovePlayer(SessionId),
 }
 
 impl GameLoop {
@@ -35,19 +123,33 @@ impl GameLoop {
         world: World,
         logger: LoggerMt,
         game_command_receiver: mpsc::Receiver<GameCommand>,
-        client_comm_channel: mpsc::Sender<ClientSendCommand>,
         registries: Registries,
+        world_server_socket: zmq::Socket,
+        column_loader: ColumnLoader,
+        chat_server_socket: zmq::Socket,
     ) -> GameLoop {
         GameLoop {
             logger,
             current_tick: 0,
             message_queue: game_command_receiver,
-            client_comm_channel,
             world,
-            entities: EntityManager::new(),
-            player: PlayerHandler::new(todo!(), logger),
-            event_queue: BinaryHeap::new(),
+            entities: EntityManager::new(PLAYER_SERVER_ENTITY_IDS.start),
+            item_entities: ItemEntities::new(),
+            sessions: BTreeMap::new(),
+            event_queue: EventQueue::default(),
+            tool_requirements: ToolRequirements::new(&registries),
             registries,
+            world_server_socket,
+            column_loader,
+            block_changes: HashMap::new(),
+            voxel_changes: Vec::new(),
+            command_tree: CommandTree::new(),
+            chat_server_socket,
+            // the time that we send at login; clients that join later are corrected with the next
+            // time update
+            world_age: 0,
+            time_of_day: 0,
+            time_changed: false,
         }
     }
 
@@ -64,6 +166,8 @@ impl GameLoop {
                     Ok(GameCommand::FutureEvent(e)) => {
                         self.event_queue.push(e);
                     },
+                    Ok(GameCommand::AddPlayer(session)) => self.add_player(*session),
+                    Ok(GameCommand::RemovePlayer(session_id)) => self.remove_player(session_id),
                     Ok(GameCommand::Stop) | Err(TryRecvError::Disconnected) => {
                         // queue has closed: game should stop
                         return;
@@ -74,26 +178,44 @@ impl GameLoop {
                 }
             }
 
-            // now run every event that happened this tick
-            while let Some(ScheduledEvent { tick, .. }) = self.event_queue.peek() {
-                if *tick > self.current_tick {
-                    break;
-                }
+            self.receive_columns();
 
-                let game_event = self.event_queue.pop().unwrap().event;
+            // now run every event that happened this tick
+            while let Some(game_event) = self.event_queue.pop_due(self.current_tick) {
                 self.handle_event(game_event);
             }
 
+            self.advance_time();
+            self.update_item_entities();
+            for session_id in self.session_ids() {
+                self.with_session(session_id, |game, session| {
+                    session.move_budget.tick();
+                    game.send_keep_alive(session);
+                    game.receive_chat(session);
+                    game.check_open_container(session);
+                    game.send_dig_progress(session);
+                });
+            }
+            self.store_voxel_changes();
+            self.send_block_changes();
+            for session_id in self.session_ids() {
+                self.with_session(session_id, |game, session| game.stream_chunks(session));
+            }
+            self.send_entity_changes();
+            if self.current_tick % COLUMN_EVICTION_PERIOD == 0 {
+                self.evict_columns();
+            }
+
             let end = Instant::now();
 
-            // number of milliseconds remaining in this loop
-            let remaining_time = (end - last_loop_end).checked_sub(constants::TICK_PERIOD);
+            // the time remaining in this tick; a tick that took too long is not made up for
+            let remaining_time = constants::TICK_PERIOD.checked_sub(end - last_loop_end);
 
             if let Some(remaining_time) = remaining_time {
                 std::thread::sleep(remaining_time);
             }
 
-            last_loop_end = end;
+            last_loop_end = Instant::now();
         }
     }
 
@@ -104,23 +226,1252 @@ impl GameLoop {
         })
     }
 
+    fn session_ids(&self) -> Vec<SessionId> {
+        self.sessions.keys().copied().collect()
+    }
+
+    // the session is taken out of the map while it is used, so that it can be borrowed along
+    // with the rest of the game loop. Returns None if there is no such session
+    fn with_session<R>(
+        &mut self,
+        session_id: SessionId,
+        action: impl FnOnce(&mut Self, &mut PlayerSession) -> R,
+    ) -> Option<R> {
+        let mut session = self.sessions.remove(&session_id)?;
+        let result = action(self, &mut session);
+        self.sessions.insert(session_id, session);
+        Some(result)
+    }
+
+    fn add_player(&mut self, mut session: PlayerSession) {
+        session.send_window();
+        self.logger.log(
+            Severity::Activity,
+            &format!("{} joined the game", session.character.name),
+        );
+        self.sessions.insert(session.id, session);
+    }
+
+    // the client is gone already, so nothing is sent to it
+    fn remove_player(&mut self, session_id: SessionId) {
+        let Some(mut session) = self.sessions.remove(&session_id) else {
+            return;
+        };
+
+        // like closing the window: the carried stack drops, and the container keeps its stacks
+        let player_state = session.player.player_state_mut();
+        let container = player_state.open_container.take();
+        let outcome = inventory::close_window(player_state);
+        for stack in outcome.dropped {
+            let event = throw_stack(&session, stack);
+            self.schedule_for_this_tick(event);
+        }
+        if let Some(container) = container {
+            self.store_container(container);
+        }
+
+        // TODO store the inventory and position of the player in the player data server
+        self.logger.log(
+            Severity::Activity,
+            &format!("{} left the game", session.character.name),
+        );
+    }
+
     fn handle_event(&mut self, game_event: Event) {
         let event = match game_event {
-            Event::VoxelChange { .. } => { None },
+            Event::VoxelChange { coord, new_voxel } => self.apply_voxel_change(coord, new_voxel),
             Event::VoxelUpdate { .. } => { None },
-            Event::EntityUpdate { .. } => { None },
-            Event::PlayerPlaceBlock(command) => {
-                player_handler::handle_block_place_event(
-                    command,
-                    &mut self.player,
-                    &self.world,
-                    &self.registries,
+            Event::EntityUpdate { entity_id } => self.update_entity(entity_id),
+            Event::Player(session_id, event) => self
+                .with_session(session_id, |game, session| {
+                    game.handle_player_event(session, event)
+                })
+                .flatten(),
+            Event::ItemEntitySpawn {
+                position,
+                velocity,
+                stack,
+                pickup_delay,
+            } => self
+                .item_entities
+                .spawn(
+                    &mut self.entities,
+                    position,
+                    velocity,
+                    stack,
+                    pickup_delay,
+                    self.current_tick,
                 )
-            },
+                .map(|entity_id| Event::EntityUpdate { entity_id }),
         };
 
         if let Some(event) = event {
             self.schedule_for_this_tick(event);
         }
     }
+
+    fn handle_player_event(
+        &mut self,
+        session: &mut PlayerSession,
+        event: PlayerEvent,
+    ) -> Option<Event> {
+        match event {
+            PlayerEvent::PlaceBlock(command) => {
+                let sequence = command.sequence;
+                // TODO sneaking: a sneaking player places blocks against containers instead
+                if !self.open_container(session, command.location) {
+                    let player_position = session.player_position();
+                    let events = player_handler::handle_block_place_event(
+                        command,
+                        player_position,
+                        session.player.player_state_mut(),
+                        &self.world,
+                        &self.registries,
+                    );
+                    for event in events {
+                        self.schedule_for_this_tick(event);
+                    }
+                }
+                acknowledge_block_change(session, sequence);
+                None
+            },
+            PlayerEvent::DigBlock(command) => {
+                let sequence = command.sequence;
+                let event = self.handle_dig_event(session, command);
+                acknowledge_block_change(session, sequence);
+                event
+            },
+            PlayerEvent::ChunkBatchReceived { chunks_per_tick } => {
+                session.chunk_streamer.batch_acknowledged(chunks_per_tick);
+                None
+            },
+            PlayerEvent::Move(movement) => {
+                self.handle_player_move(session, movement);
+                None
+            },
+            PlayerEvent::ConfirmTeleport { teleport_id } => {
+                if session.pending_teleport == Some(teleport_id) {
+                    session.pending_teleport = None;
+                }
+                None
+            },
+            PlayerEvent::DropItem { whole_stack } => drop_item(session, whole_stack),
+            PlayerEvent::ClickWindow(click) => {
+                self.handle_window_click(session, click);
+                None
+            },
+            PlayerEvent::CloseWindow { window_id } => {
+                if window_id == session.player.player_state().open_window_id() {
+                    if let Some(container) = self.close_window(session) {
+                        self.store_container(container);
+                    }
+                }
+                None
+            },
+            PlayerEvent::SetHeldItem { slot } => {
+                self.select_hotbar_slot(session, slot);
+                None
+            },
+            PlayerEvent::ChatMessage { text } => {
+                self.send_chat_message(session, text);
+                None
+            },
+            PlayerEvent::ChatCommand { command } => {
+                self.run_command(session, &command);
+                None
+            },
+            PlayerEvent::CommandSuggestions {
+                transaction_id,
+                text,
+            } => {
+                self.send_command_suggestions(session, transaction_id, &text);
+                None
+            },
+            PlayerEvent::SetCreativeSlot { slot, stack } => {
+                match inventory::set_creative_slot(session.player.player_state_mut(), slot, stack) {
+                    Some(outcome) => self.apply_click_outcome(session, outcome),
+                    None => session.send_window(),
+                }
+                None
+            },
+            PlayerEvent::KeepAlive { id, received } => {
+                if !session.keep_alive.receive(id, received) {
+                    self.logger.log(
+                        Severity::RecoverableError,
+                        &format!("{} echoed unknown keep-alive {id}", session.character.name),
+                    );
+                    session.disconnect(TIMEOUT_REASON);
+                }
+                None
+            },
+        }
+    }
+
+    fn apply_voxel_change(&mut self, coord: Coordinate, new_voxel: Voxel) -> Option<Event> {
+        let block = new_voxel.get_block();
+        let result = self.world.set_voxel(
+            coord,
+            new_voxel.clone(),
+            self.registries.block_properties(),
+            self.registries.block_states(),
+        );
+
+        if let Err(error) = result {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not apply voxel change: {error}"),
+            );
+            return None;
+        }
+
+        // a later change of the same voxel in this tick replaces this one
+        self.block_changes
+            .entry(Coordinate16::containing_coord(&coord))
+            .or_default()
+            .insert(coord, block);
+        self.voxel_changes.push((coord, new_voxel));
+
+        None
+    }
+
+    // entities are updated every tick, until they are removed
+    fn update_entity(&mut self, entity_id: EntityId) -> Option<Event> {
+        let collider = ColumnCollider::new(
+            &self.world,
+            self.registries.block_properties(),
+            self.registries.block_states(),
+        );
+        self.entities
+            .update(entity_id, |entity| entity_physics::step_entity(entity, &collider))?;
+
+        self.event_queue.push(ScheduledEvent {
+            tick: self.current_tick + 1,
+            event: Event::EntityUpdate { entity_id },
+        });
+        None
+    }
+
+    fn handle_player_move(&mut self, session: &mut PlayerSession, movement: PlayerMoveEvent) {
+        if session.pending_teleport.is_some() {
+            return;
+        }
+
+        if let Some((yaw, pitch)) = movement.rotation {
+            session.character.head_rotation = player_movement::head_rotation(yaw, pitch);
+            session.player.player_state_mut().look_direction =
+                player_movement::look_direction(yaw, pitch);
+        }
+
+        if let Some(position) = movement.position {
+            let is_valid = player_movement::is_valid_move(
+                session.character.position,
+                position,
+                &mut session.move_budget,
+                &self.world,
+                &self.registries,
+            );
+            if !is_valid {
+                session.synchronize_player_position();
+                return;
+            }
+
+            session.character.position = position;
+        }
+
+        session.character.on_ground = movement.on_ground;
+    }
+
+    fn handle_dig_event(
+        &mut self,
+        session: &mut PlayerSession,
+        command: PlayerDigBlockEvent,
+    ) -> Option<Event> {
+        match command.status {
+            DigStatus::Started => {
+                // a new dig replaces an unfinished one
+                session.cancel_digging();
+
+                let block = self.world.get_block(command.location)?;
+                if matches!(session.player.player_state().game_mode, Gamemode::Creative) {
+                    return self.break_block(session, command.location, block, false);
+                }
+
+                let (required_ticks, harvestable) = self.dig_duration(block)?;
+                if required_ticks == 0 {
+                    return self.break_block(session, command.location, block, harvestable);
+                }
+
+                session.digging = Some(DigProgress {
+                    location: command.location,
+                    start_tick: self.current_tick,
+                    required_ticks,
+                    harvestable,
+                    stage: NO_DESTROY_STAGE,
+                });
+                None
+            },
+            DigStatus::Cancelled => {
+                session.cancel_digging();
+                None
+            },
+            DigStatus::Finished => {
+                let progress = session.digging.take()?;
+                session.send_destroy_stage(progress.location, NO_DESTROY_STAGE);
+
+                // the acknowledgement makes the client revert a block that it broke too early
+                let elapsed_ticks = self.current_tick - progress.start_tick + 1;
+                let dig_progress = elapsed_ticks as f32 / progress.required_ticks as f32;
+                if progress.location != command.location || dig_progress < MIN_DIG_PROGRESS_TO_FINISH
+                {
+                    return None;
+                }
+
+                let block = self.world.get_block(command.location)?;
+                self.break_block(session, command.location, block, progress.harvestable)
+            },
+        }
+    }
+
+    /// Returns the number of ticks to dig the block by hand, and whether it drops an item when
+    /// dug by hand. Returns None for unbreakable blocks
+    fn dig_duration(&self, block: BlockWithState) -> Option<(u32, bool)> {
+        let block_id = self.registries.block_states().block_state_to_block(block);
+        let properties = self.registries.block_properties().get_block_properties(block_id);
+
+        let hardness = properties.hardness?;
+        // TODO tools: for now every block is dug by hand
+        let harvestable = !self.tool_requirements.requires_tool(block);
+        let ticks_per_hardness = if harvestable {
+            TICKS_PER_HARDNESS_HARVESTABLE
+        } else {
+            TICKS_PER_HARDNESS_NOT_HARVESTABLE
+        };
+
+        Some(((hardness * ticks_per_hardness).ceil() as u32, harvestable))
+    }
+
+    fn break_block(
+        &mut self,
+        session: &mut PlayerSession,
+        location: Coordinate,
+        block: BlockWithState,
+        drops_item: bool,
+    ) -> Option<Event> {
+        if drops_item {
+            if let Some(item) = self.block_drop(block) {
+                self.drop_at_block(location, ItemStack::one_of(item));
+            }
+        }
+
+        // like vanilla: a container drops its stacks, even in creative mode
+        for stack in self.take_container_stacks(session, location) {
+            if !stack.is_empty() {
+                self.drop_at_block(location, stack);
+            }
+        }
+
+        Some(Event::VoxelChange {
+            coord: location,
+            new_voxel: Voxel::from_block(BlockId::Air.into()),
+        })
+    }
+
+    fn drop_at_block(&mut self, location: Coordinate, stack: ItemStack) {
+        let position = Position::new(
+            location.x as f32 + 0.5,
+            location.y as f32 + 0.25,
+            location.z as f32 + 0.5,
+        );
+        // like vanilla: the item pops out in a random horizontal direction
+        let velocity = Vector3f::new(
+            rand::random::<f32>() * 0.2 - 0.1,
+            0.2,
+            rand::random::<f32>() * 0.2 - 0.1,
+        );
+        self.schedule_for_this_tick(Event::ItemEntitySpawn {
+            position,
+            velocity,
+            stack,
+            pickup_delay: item_entities::BLOCK_DROP_PICKUP_DELAY,
+        });
+    }
+
+    // TODO loot tables: for now, a block drops the item with the same name, if there is one
+    fn block_drop(&self, block: BlockWithState) -> Option<Item> {
+        let (name, _) = self.registries.block_states().get_name_and_properties(block)?;
+        Item::from_text_id(name.trim_start_matches("minecraft:"))
+    }
+
+    fn handle_window_click(&mut self, session: &mut PlayerSession, click: PlayerClickWindowEvent) {
+        let player_state = session.player.player_state_mut();
+        // clicks in a window that we closed already
+        if click.window_id != player_state.open_window_id() {
+            return;
+        }
+
+        let layout = player_state.open_window_layout();
+        let Some(decoded) = InventoryClick::decode(layout, click.slot, click.button, click.mode)
+        else {
+            session.send_window();
+            return;
+        };
+
+        let player_state = session.player.player_state_mut();
+        let is_outdated = player_state.is_outdated(click.state_id);
+        let outcome = inventory::handle_click(player_state, decoded);
+
+        // like vanilla: a client that missed our last change gets the whole window again
+        if is_outdated {
+            for stack in outcome.dropped {
+                let event = throw_stack(session, stack);
+                self.schedule_for_this_tick(event);
+            }
+            session.send_window();
+        } else {
+            self.apply_click_outcome(session, outcome);
+        }
+    }
+
+    // the client predicted the outcome of its click; we send ours, in case they differ
+    fn apply_click_outcome(&mut self, session: &mut PlayerSession, outcome: ClickOutcome) {
+        for stack in outcome.dropped {
+            let event = throw_stack(session, stack);
+            self.schedule_for_this_tick(event);
+        }
+        let layout = session.player.player_state().open_window_layout();
+        for slot_idx in outcome.changed_slots {
+            if slot_idx < layout.slot_count() {
+                session.send_window_slot(slot_idx);
+            } else if slot_idx == layout.off_hand_slot() {
+                // container windows do not show the off hand, so it goes to the inventory window
+                session.send_slot(PLAYER_OFF_HAND_SLOT);
+            }
+        }
+        session.send_carried();
+    }
+
+    // returns false if there is no container at the location
+    fn open_container(&mut self, session: &mut PlayerSession, location: Coordinate) -> bool {
+        let Some(kind) = self.container_kind_at(location) else {
+            return false;
+        };
+
+        // each window has its own copy of the stacks, so only one player may use a container
+        if self.container_user(location).is_some() {
+            session.send_system_message(&chat::error("Another player is using this container"));
+            return true;
+        }
+
+        // a window that is still open closes first
+        if let Some(container) = self.close_window(session) {
+            self.store_container(container);
+        }
+
+        let nbt = self
+            .world
+            .get_voxel(location)
+            .map_or(NbtTag::Null, |voxel| voxel.get_nbt_data());
+        let player_state = session.player.player_state_mut();
+        let window_id = player_state.next_window_id();
+        player_state.open_container = Some(OpenContainer {
+            window_id,
+            kind,
+            location,
+            stacks: containers::stacks_from_nbt(&nbt, kind),
+            changed: false,
+        });
+
+        session.send_to_client(ClientboundPacket::OpenScreen {
+            window_id: VarInt(window_id as i32),
+            window_type: VarInt(kind.window_type()),
+            window_title: kind.title(),
+        });
+        session.send_window();
+        true
+    }
+
+    // the other session that has a window of the container open
+    fn container_user(&self, location: Coordinate) -> Option<SessionId> {
+        self.sessions
+            .values()
+            .find(|session| {
+                let container = &session.player.player_state().open_container;
+                container
+                    .as_ref()
+                    .is_some_and(|container| container.location == location)
+            })
+            .map(|session| session.id)
+    }
+
+    // like vanilla: closing a window drops the carried stack.
+    // Returns the container of the window, if it was one
+    fn close_window(&mut self, session: &mut PlayerSession) -> Option<OpenContainer> {
+        let player_state = session.player.player_state_mut();
+        let container = player_state.open_container.take();
+        let outcome = inventory::close_window(player_state);
+        self.apply_click_outcome(session, outcome);
+        container
+    }
+
+    // closes the window of the container when the player walked away from it, or when it is gone
+    fn check_open_container(&mut self, session: &mut PlayerSession) {
+        let Some(container) = &session.player.player_state().open_container else {
+            return;
+        };
+
+        let location = container.location;
+        let center = Position::new(
+            location.x as f32 + 0.5,
+            location.y as f32 + 0.5,
+            location.z as f32 + 0.5,
+        );
+        let in_reach = (session.player_position() - center).norm() <= MAX_CONTAINER_DISTANCE;
+        if in_reach && self.container_kind_at(location) == Some(container.kind) {
+            return;
+        }
+
+        session.send_to_client(ClientboundPacket::CloseContainer {
+            window_id: container.window_id,
+        });
+        if let Some(container) = self.close_window(session) {
+            self.store_container(container);
+        }
+    }
+
+    // the stacks of a container that is broken; an open window of it is closed, also when another
+    // player has it open
+    fn take_container_stacks(
+        &mut self,
+        session: &mut PlayerSession,
+        location: Coordinate,
+    ) -> Vec<ItemStack> {
+        if let Some(stacks) = self.take_window_stacks(session, location) {
+            return stacks;
+        }
+        if let Some(user) = self.container_user(location) {
+            let stacks = self
+                .with_session(user, |game, user| game.take_window_stacks(user, location))
+                .flatten();
+            if let Some(stacks) = stacks {
+                return stacks;
+            }
+        }
+
+        let Some(kind) = self.container_kind_at(location) else {
+            return Vec::new();
+        };
+        let nbt = self
+            .world
+            .get_voxel(location)
+            .map_or(NbtTag::Null, |voxel| voxel.get_nbt_data());
+        containers::stacks_from_nbt(&nbt, kind)
+    }
+
+    // closes the window of the session if it shows the container at the location
+    fn take_window_stacks(
+        &mut self,
+        session: &mut PlayerSession,
+        location: Coordinate,
+    ) -> Option<Vec<ItemStack>> {
+        let container = session.player.player_state().open_container.as_ref()?;
+        if container.location != location {
+            return None;
+        }
+
+        session.send_to_client(ClientboundPacket::CloseContainer {
+            window_id: container.window_id,
+        });
+        Some(
+            self.close_window(session)
+                .map(|container| container.stacks)
+                .unwrap_or_default(),
+        )
+    }
+
+    fn container_kind_at(&self, location: Coordinate) -> Option<ContainerKind> {
+        let block = self.world.get_block(location)?;
+        let (name, _) = self
+            .registries
+            .block_states()
+            .get_name_and_properties(block)?;
+        ContainerKind::of_block(&name)
+    }
+
+    // writes the stacks of the container into its voxel, here and in the world server.
+    // The clients do not see the stacks, so they are not sent a block change
+    fn store_container(&mut self, container: OpenContainer) {
+        if !container.changed {
+            return;
+        }
+
+        let location = container.location;
+        let Some(voxel) = self.world.get_voxel(location) else {
+            return;
+        };
+        let block = voxel.get_block();
+        let Some((name, _)) = self
+            .registries
+            .block_states()
+            .get_name_and_properties(block)
+        else {
+            return;
+        };
+        if ContainerKind::of_block(&name) != Some(container.kind) {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Container at {location:?} is gone, its stacks are lost"),
+            );
+            return;
+        }
+
+        let nbt = containers::stacks_to_nbt(voxel.get_nbt_data(), &name, &container.stacks);
+        let voxel = Voxel::from_nbt(block, nbt);
+        let result = self.world.set_voxel(
+            location,
+            voxel.clone(),
+            self.registries.block_properties(),
+            self.registries.block_states(),
+        );
+        if let Err(error) = result {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not store container: {error}"),
+            );
+            return;
+        }
+        self.voxel_changes.push((location, voxel));
+    }
+
+    // the slot is 0 to 8 in the hotbar
+    fn select_hotbar_slot(&mut self, session: &mut PlayerSession, slot: i16) {
+        let slot_idx = usize::try_from(slot)
+            .ok()
+            .map(|slot| PLAYER_HOTBAR_SLOTS.start() + slot)
+            .filter(|slot_idx| PLAYER_HOTBAR_SLOTS.contains(slot_idx));
+
+        match slot_idx {
+            Some(slot_idx) => session.player.player_state_mut().select_slot(slot_idx),
+            None => self.logger.log(
+                Severity::RecoverableError,
+                &format!("Client selected invalid hotbar slot {slot}"),
+            ),
+        }
+    }
+
+    fn send_chat_message(&mut self, session: &mut PlayerSession, text: String) {
+        if !chat::is_valid_message(&text) {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Client sent invalid chat message {text:?}"),
+            );
+            return;
+        }
+
+        // the player receives its own message from the chat server, like every player near it
+        let message = ChatMessage {
+            sender_name: session.character.name.clone(),
+            position: session.player_position(),
+            text,
+        };
+        let reply = network::query::<ChatServerReq, ChatServerRep>(
+            &self.chat_server_socket,
+            ChatServerReq::Say(message),
+        );
+        match reply {
+            Ok(ChatServerRep::Published) => {},
+            Ok(_) => self.logger.log(
+                Severity::RecoverableError,
+                "Chat server did not publish chat message",
+            ),
+            Err(error) => {
+                self.logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not send chat message: {error:?}"),
+                );
+                session.send_system_message(&chat::error("Could not send your message"));
+            },
+        }
+    }
+
+    // sends the chat messages of the players near the player to the client.
+    // The subscription follows the player from column to column
+    fn receive_chat(&mut self, session: &mut PlayerSession) {
+        let column = ChunkColumnCoordinate::containing_position(&session.player_position());
+        if session.chat_column != Some(column) {
+            if let Err(error) = subscribe_chat(session, column) {
+                self.logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not subscribe to chat of column {column:?}: {error}"),
+                );
+            }
+        }
+
+        loop {
+            // each message is preceded by its topic
+            match session.chat_subscriber_socket.recv_bytes(zmq::DONTWAIT) {
+                Ok(_) => {},
+                Err(zmq::Error::EAGAIN) => return,
+                Err(error) => {
+                    self.logger.log(
+                        Severity::EnvironmentIssue,
+                        &format!("Could not receive chat: {error}"),
+                    );
+                    return;
+                },
+            }
+
+            match network::await_receive::<ChatMessage>(&session.chat_subscriber_socket, 0) {
+                Ok(message) => session.send_system_message(&chat::player_message(
+                    &message.sender_name,
+                    &message.text,
+                )),
+                Err(error) => self.logger.log(
+                    Severity::RecoverableError,
+                    &format!("Could not receive chat message: {error:?}"),
+                ),
+            }
+        }
+    }
+
+    fn run_command(&mut self, session: &mut PlayerSession, command: &str) {
+        let source = CommandSource {
+            position: session.character.position,
+            registries: &self.registries,
+        };
+        let action = self.command_tree.parse(command, &source);
+
+        match action.and_then(|action| self.apply_command(session, action)) {
+            Ok(feedback) => session.send_system_message(&chat::feedback(&feedback)),
+            Err(error) => session.send_system_message(&chat::error(&error.to_string())),
+        }
+    }
+
+    // returns the feedback for the player
+    fn apply_command(
+        &mut self,
+        session: &mut PlayerSession,
+        action: CommandAction,
+    ) -> Result<String, CommandError> {
+        match action {
+            CommandAction::Teleport(position) => {
+                session.cancel_digging();
+                session.character.position = position;
+                session.synchronize_player_position();
+                Ok(format!(
+                    "Teleported {} to {:.2}, {:.2}, {:.2}",
+                    session.character.name, position.x, position.y, position.z
+                ))
+            },
+            CommandAction::Give { item, count } => {
+                let (changed_slots, mut leftover) = session
+                    .player
+                    .player_state_mut()
+                    .insert(ItemStack::new(item, count));
+                for slot_idx in changed_slots {
+                    session.send_slot(slot_idx);
+                }
+
+                // like vanilla: the items that do not fit are dropped at the feet of the player
+                while !leftover.is_empty() {
+                    let stack = leftover.split_off(leftover.max_stack_size());
+                    self.schedule_for_this_tick(Event::ItemEntitySpawn {
+                        position: session.player_position(),
+                        velocity: Vector3f::zeros(),
+                        stack,
+                        pickup_delay: 0,
+                    });
+                }
+
+                Ok(format!(
+                    "Gave {count} [{}] to {}",
+                    item.text_id(),
+                    session.character.name
+                ))
+            },
+            CommandAction::SetGameMode(game_mode) => {
+                let (game_mode_id, name, abilities) = match game_mode {
+                    Gamemode::Survival => (0, "Survival", ABILITIES_SURVIVAL),
+                    Gamemode::Creative => (1, "Creative", ABILITIES_CREATIVE),
+                    Gamemode::Adventure => (2, "Adventure", ABILITIES_SURVIVAL),
+                    Gamemode::Spectator => (3, "Spectator", ABILITIES_SPECTATOR),
+                };
+                session.player.player_state_mut().game_mode = game_mode;
+                // digging works differently in creative mode
+                session.cancel_digging();
+
+                session.send_to_client(ClientboundPacket::ChangeGameState {
+                    reason: GAME_STATE_CHANGE_GAME_MODE,
+                    value: game_mode_id as f32,
+                });
+                session.send_to_client(ClientboundPacket::PlayerAbilities {
+                    flags: abilities,
+                    flying_speed: 0.05,
+                    field_of_view_modifier: 0.1,
+                });
+                Ok(format!("Set own game mode to {name} Mode"))
+            },
+            CommandAction::Fill { from, to, block } => {
+                let changed_count = self.fill(from, to, block);
+                if changed_count == 0 {
+                    return Err(CommandError::Failed(String::from("No blocks were changed")));
+                }
+
+                if from == to {
+                    Ok(format!(
+                        "Changed the block at {}, {}, {}",
+                        from.x,
+                        from.y + MINECRAFT_MIN_Y,
+                        from.z
+                    ))
+                } else {
+                    Ok(format!("Successfully filled {changed_count} block(s)"))
+                }
+            },
+            CommandAction::SetTime(time) => {
+                self.time_of_day = time;
+                self.time_changed = true;
+                Ok(format!("Set the time to {time}"))
+            },
+            CommandAction::AddTime(time) => {
+                self.time_of_day += time;
+                self.time_changed = true;
+                Ok(format!(
+                    "Set the time to {}",
+                    self.time_of_day.rem_euclid(TICKS_PER_DAY)
+                ))
+            },
+            CommandAction::QueryTime => Ok(format!(
+                "The time is {}",
+                self.time_of_day.rem_euclid(TICKS_PER_DAY)
+            )),
+        }
+    }
+
+    // sets the blocks of the box between the corners.
+    // Returns the number of blocks that changed; unloaded blocks are skipped
+    fn fill(&mut self, from: Coordinate, to: Coordinate, block: BlockWithState) -> usize {
+        let min = from.inf(&to);
+        let max = from.sup(&to);
+        let mut changed_count = 0;
+
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    let coord = Coordinate::new(x, y, z);
+                    match self.world.get_block(coord) {
+                        Some(previous) if previous != block => {},
+                        _ => continue,
+                    }
+                    self.apply_voxel_change(coord, Voxel::from_block(block));
+                    changed_count += 1;
+                }
+            }
+        }
+        changed_count
+    }
+
+    fn send_command_suggestions(
+        &mut self,
+        session: &mut PlayerSession,
+        transaction_id: i32,
+        text: &str,
+    ) {
+        let command = text.strip_prefix(COMMAND_PREFIX).unwrap_or(text);
+        let source = CommandSource {
+            position: session.character.position,
+            registries: &self.registries,
+        };
+        let suggestions = self.command_tree.suggest(command, &source);
+
+        let offset = text.len() - command.len();
+        match tree::suggestions_packet(transaction_id, offset, &suggestions) {
+            Ok(packet) => session.send_raw_to_client(packet),
+            Err(error) => self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not serialize command suggestions: {error}"),
+            ),
+        }
+    }
+
+    // the clients advance the time on their own, we only correct them from time to time
+    fn advance_time(&mut self) {
+        self.world_age += 1;
+        self.time_of_day += 1;
+        if self.current_tick % TIME_UPDATE_PERIOD != 0 && !self.time_changed {
+            return;
+        }
+
+        self.time_changed = false;
+        for session in self.sessions.values() {
+            session.send_to_client(ClientboundPacket::UpdateTime {
+                world_age: self.world_age,
+                time_of_day: self.time_of_day,
+            });
+        }
+    }
+
+    fn update_item_entities(&mut self) {
+        self.item_entities
+            .despawn_old(&mut self.entities, self.current_tick);
+        self.item_entities.merge_nearby(&mut self.entities);
+
+        for session in self.sessions.values_mut() {
+            let player_box =
+                Aabb::standing_at(session.player_position(), PLAYER_WIDTH, PLAYER_HEIGHT);
+            let pickups = self.item_entities.pick_up(
+                &mut self.entities,
+                &player_box,
+                session.player.player_state_mut(),
+                self.current_tick,
+            );
+
+            for pickup in pickups {
+                session.send_to_client(ClientboundPacket::PickupItem {
+                    collected_entity_id: VarInt(pickup.entity_id as i32),
+                    collector_entity_id: VarInt(session.character.entity_id as i32),
+                    pickup_item_count: VarInt(pickup.count as i32),
+                });
+                for slot_idx in pickup.changed_slots {
+                    session.send_slot(slot_idx);
+                }
+            }
+        }
+    }
+
+    fn send_keep_alive(&mut self, session: &mut PlayerSession) {
+        match session.keep_alive.tick(Instant::now()) {
+            KeepAliveAction::Wait => {},
+            KeepAliveAction::Send(id) => {
+                session.send_to_client(ClientboundPacket::KeepAlive { keep_alive_id: id })
+            },
+            KeepAliveAction::TimedOut => {
+                self.logger.log(
+                    Severity::Activity,
+                    &format!("{} timed out", session.character.name),
+                );
+                session.disconnect(TIMEOUT_REASON);
+            },
+        }
+    }
+
+    fn send_dig_progress(&mut self, session: &mut PlayerSession) {
+        let Some(progress) = &session.digging else {
+            return;
+        };
+
+        let elapsed_ticks = self.current_tick - progress.start_tick;
+        let stage = (elapsed_ticks as u32 * NUM_DESTROY_STAGES / progress.required_ticks)
+            .min(NUM_DESTROY_STAGES - 1) as i8;
+        if stage == progress.stage {
+            return;
+        }
+
+        let location = progress.location;
+        session.send_destroy_stage(location, stage);
+        if let Some(progress) = &mut session.digging {
+            progress.stage = stage;
+        }
+    }
+
+    // the world server keeps the changes of this tick, so that they outlive this server.
+    // A column with a denied change is reverted to the copy of the world server
+    fn store_voxel_changes(&mut self) {
+        if self.voxel_changes.is_empty() {
+            return;
+        }
+
+        let changes = std::mem::take(&mut self.voxel_changes);
+        let num_changes = changes.len();
+        let reply = network::query::<WorldServerReq, WorldServerRep>(
+            &self.world_server_socket,
+            WorldServerReq::SetVoxels(changes),
+        );
+
+        match reply {
+            Ok(WorldServerRep::SetVoxelsDenied(denied)) => {
+                if denied.is_empty() {
+                    return;
+                }
+                self.logger.log(
+                    Severity::RecoverableError,
+                    &format!("World server denied {} voxel change(s)", denied.len()),
+                );
+
+                let columns: HashSet<ChunkColumnCoordinate> = denied
+                    .iter()
+                    .map(ChunkColumnCoordinate::containing_coord)
+                    .collect();
+                for column in columns {
+                    self.revert_column(column);
+                }
+            },
+            Ok(_) => self.logger.log(
+                Severity::RecoverableError,
+                &format!("World server did not store {num_changes} voxel change(s)"),
+            ),
+            Err(error) => self.logger.log(
+                Severity::EnvironmentIssue,
+                &format!("Could not store {num_changes} voxel change(s): {error:?}"),
+            ),
+        }
+    }
+
+    // drops our copy of the column; it is loaded again, and sent again to the clients that have it
+    fn revert_column(&mut self, coord: ChunkColumnCoordinate) {
+        self.world.remove_column(&coord);
+        for session in self.sessions.values_mut() {
+            session.chunk_streamer.forget(coord);
+        }
+    }
+
+    // every client receives the changes of the columns that it has
+    fn send_block_changes(&mut self) {
+        for (section, changes) in std::mem::take(&mut self.block_changes) {
+            let column_coord = ChunkColumnCoordinate::from(section);
+            let receivers = || {
+                self.sessions
+                    .values()
+                    .filter(move |session| session.chunk_streamer.has_sent(column_coord))
+            };
+
+            if changes.len() == 1 {
+                let (coord, block) = changes.into_iter().next().unwrap();
+                for session in receivers() {
+                    session.send_to_client(ClientboundPacket::BlockUpdate {
+                        location: coordinates::coordinate_to_minecraft(coord),
+                        block_state: VarInt(block.id() as i32),
+                    });
+                }
+                continue;
+            }
+
+            // each entry is the block state id, followed by 12 bits of x, z and y in the section
+            let blocks: Vec<VarLong> = changes
+                .iter()
+                .map(|(coord, block)| {
+                    let x = coord.x.rem_euclid(16) as i64;
+                    let y = coord.y.rem_euclid(16) as i64;
+                    let z = coord.z.rem_euclid(16) as i64;
+                    VarLong(((block.id() as i64) << 12) | (x << 8) | (z << 4) | y)
+                })
+                .collect();
+
+            for session in receivers() {
+                session.send_to_client(ClientboundPacket::UpdateSectionBlocks {
+                    chunk_section_position: coordinates::section_position_to_minecraft(section),
+                    blocks: Array::from(blocks.clone()),
+                });
+            }
+        }
+
+        // the light of the neighbouring columns may change too, and only some of their sections
+        for (column_coord, sections) in self.world.take_light_changes() {
+            let Some(column) = self.world.get_chunk(&column_coord) else {
+                continue;
+            };
+
+            for session in self.sessions.values() {
+                if !session.chunk_streamer.has_sent(column_coord) {
+                    continue;
+                }
+
+                let light = column.get_light().to_minecraft_sections(sections);
+                session.send_to_client(ClientboundPacket::UpdateLight {
+                    chunk_x: VarInt(column_coord.x),
+                    chunk_z: VarInt(column_coord.z),
+                    sky_light_mask: Array::from(light.sky_light_mask),
+                    block_light_mask: Array::from(light.block_light_mask),
+                    empty_sky_light_mask: Array::from(light.empty_sky_light_mask),
+                    empty_block_light_mask: Array::from(light.empty_block_light_mask),
+                    sky_light: Array::from(
+                        light
+                            .sky_light
+                            .into_iter()
+                            .map(Array::from)
+                            .collect::<Vec<_>>(),
+                    ),
+                    block_light: Array::from(
+                        light
+                            .block_light
+                            .into_iter()
+                            .map(Array::from)
+                            .collect::<Vec<_>>(),
+                    ),
+                });
+            }
+        }
+
+        for session in self.sessions.values_mut() {
+            if let Some(sequence) = session.block_change_sequence.take() {
+                session.send_to_client(ClientboundPacket::AcknowledgeBlockChange {
+                    id: VarInt(sequence),
+                });
+            }
+        }
+    }
+
+    // TODO the clients do not see the characters of the other players yet
+    fn send_entity_changes(&mut self) {
+        for session in self.sessions.values_mut() {
+            let packets = session.entity_tracker.update(
+                &self.entities,
+                session.character.position,
+                session.chunk_streamer.view_distance(),
+            );
+            for packet in packets {
+                session.send_to_client(packet);
+            }
+        }
+    }
+
+    // sends the columns that came into view of the player, and unloads the ones that left it
+    fn stream_chunks(&mut self, session: &mut PlayerSession) {
+        let center = ChunkColumnCoordinate::containing_position(&session.character.position);
+        if session.chunk_streamer.set_center(center) {
+            session.send_to_client(ClientboundPacket::SetCenterChunk {
+                chunk_x: VarInt(center.x),
+                chunk_z: VarInt(center.z),
+            });
+        }
+
+        for coord in session.chunk_streamer.take_out_of_range() {
+            session.send_to_client(ClientboundPacket::UnloadChunk {
+                chunk_x: coord.x,
+                chunk_z: coord.z,
+            });
+        }
+
+        // the columns that are not loaded yet are sent in a later batch
+        let now = Instant::now();
+        let (batch, missing): (Vec<_>, Vec<_>) = session
+            .chunk_streamer
+            .next_batch()
+            .into_iter()
+            .partition(|coord| self.world.contains_column(coord));
+        for coord in missing {
+            if let Err(error) = self.column_loader.request(coord, now) {
+                self.logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not request column {coord:?}: {error:?}"),
+                );
+            }
+        }
+        if batch.is_empty() {
+            return;
+        }
+
+        session.send_to_client(ClientboundPacket::ChunkBatchStart);
+        let mut batch_size = 0;
+        for coord in batch {
+            let Some(chunk_column) = self.world.get_chunk(&coord) else {
+                continue;
+            };
+
+            match chunk_data::chunk_data_packet(chunk_column) {
+                Ok(packet) => session.send_to_client(packet),
+                Err(error) => {
+                    self.logger.log(
+                        Severity::RecoverableError,
+                        &format!("Could not serialize column {coord:?}: {error:?}"),
+                    );
+                    continue;
+                },
+            }
+
+            session.chunk_streamer.mark_sent(coord);
+            batch_size += 1;
+        }
+        session.send_to_client(ClientboundPacket::ChunkBatchFinished {
+            batch_size: VarInt(batch_size),
+        });
+        session.chunk_streamer.batch_sent();
+    }
+
+    // adds the columns that the world server sent since the last tick
+    fn receive_columns(&mut self) {
+        for load in self.column_loader.receive(Instant::now()) {
+            match load {
+                ColumnLoad::Loaded(mut chunk_column) => {
+                    // the column may have been loaded meanwhile
+                    if self.world.contains_column(&chunk_column.coordinate()) {
+                        continue;
+                    }
+                    // light is not sent along with the column
+                    chunk_column.compute_light(
+                        self.registries.block_properties(),
+                        self.registries.block_states(),
+                    );
+                    self.world.insert_column(
+                        chunk_column,
+                        self.registries.block_properties(),
+                        self.registries.block_states(),
+                    );
+                },
+                ColumnLoad::Failed(coord, reason) => self.logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not load column {coord:?}: {reason}"),
+                ),
+            }
+        }
+    }
+
+    // drops the columns that no player sees; they are loaded again when a player comes near
+    fn evict_columns(&mut self) {
+        let unseen: Vec<ChunkColumnCoordinate> = self
+            .world
+            .column_coordinates()
+            .filter(|coord| {
+                !self
+                    .sessions
+                    .values()
+                    .any(|session| session.chunk_streamer.is_in_range(*coord))
+            })
+            .collect();
+
+        for coord in unseen {
+            self.world.remove_column(&coord);
+        }
+    }
+}
+
+fn drop_item(session: &mut PlayerSession, whole_stack: bool) -> Option<Event> {
+    let player_state = session.player.player_state_mut();
+    let stack = player_state.drop_selected(whole_stack);
+    if stack.is_empty() {
+        return None;
+    }
+    let selected_slot = player_state.selected_slot;
+    session.send_slot(selected_slot);
+
+    Some(throw_stack(session, stack))
+}
+
+fn throw_stack(session: &PlayerSession, stack: ItemStack) -> Event {
+    let direction = session.player.player_state().look_direction;
+    item_entities::throw(session.player_position(), direction, stack)
+}
+
+// the client reverts its own predicted block changes up to this sequence,
+// unless we sent the same change before the acknowledgement
+fn acknowledge_block_change(session: &mut PlayerSession, sequence: i32) {
+    session.block_change_sequence = Some(match session.block_change_sequence {
+        Some(previous) => previous.max(sequence),
+        None => sequence,
+    });
+}
+
+fn subscribe_chat(
+    session: &mut PlayerSession,
+    column: ChunkColumnCoordinate,
+) -> Result<(), zmq::Error> {
+    if let Some(previous_column) = session.chat_column.take() {
+        session
+            .chat_subscriber_socket
+            .set_unsubscribe(&sol_chat_messages::column_topic(previous_column))?;
+    }
+    session
+        .chat_subscriber_socket
+        .set_subscribe(&sol_chat_messages::column_topic(column))?;
+    session.chat_column = Some(column);
+    Ok(())
 }
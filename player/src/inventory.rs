@@ -0,0 +1,582 @@
+// The clicks in the inventory window of the player and in container windows, as sent with
+// ClickWindowSlot.
+// We handle the clicks like vanilla, and correct the client if it predicted another outcome.
+
+use crate::containers::{ContainerKind, FURNACE_RESULT_SLOT};
+use crate::item_stack::ItemStack;
+use crate::player_state::{
+    PlayerState, PLAYER_ARMOR_SLOTS, PLAYER_CRAFTING_RESULT_SLOT, PLAYER_CRAFTING_SLOTS,
+    PLAYER_HOTBAR_SLOTS, PLAYER_MAIN_SLOTS, PLAYER_OFF_HAND_SLOT, PLAYER_SLOT_COUNT,
+};
+use minecraft_protocol::components::gamemode::Gamemode;
+use std::ops::Range;
+
+/// The inventory of the player is always open as this window
+pub const PLAYER_INVENTORY_WINDOW: i8 = 0;
+/// Sending a slot of this window sets the stack that the mouse carries
+pub const CARRIED_ITEM_WINDOW: i8 = -1;
+pub const CARRIED_ITEM_SLOT: i16 = -1;
+
+// clicks outside of the window drop the carried stack
+const OUTSIDE_SLOT: i16 = -999;
+// number keys swap with the hotbar slots 0 to 8, and this button swaps with the off hand
+const OFF_HAND_BUTTON: i8 = 40;
+// creative mode drops stacks on this slot
+const CREATIVE_DROP_SLOT: i16 = -1;
+// container windows show these slots of the player after their own slots. The off hand is not
+// shown, but follows the other slots, so that stacks can be swapped with it
+const CONTAINER_PLAYER_SLOTS: Range<usize> = *PLAYER_MAIN_SLOTS.start()..PLAYER_SLOT_COUNT;
+
+/// The slots of a window, see https://wiki.vg/Inventory
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowLayout {
+    /// the slots of the player, as in `PlayerState::slots`
+    PlayerInventory,
+    /// the slots of the container, followed by the main inventory and the hotbar of the player
+    Container(ContainerKind),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DragKind {
+    /// left mouse button: splits the carried stack evenly
+    Split,
+    /// right mouse button: puts one item into every slot
+    One,
+    /// middle mouse button, in creative mode: puts a full stack into every slot
+    Clone,
+}
+
+pub struct Drag {
+    kind: DragKind,
+    slots: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InventoryClick {
+    /// left click takes or puts the whole stack, right click half of it or one item
+    Pickup {
+        slot: usize,
+        whole_stack: bool,
+    },
+    DropCarried {
+        whole_stack: bool,
+    },
+    /// shift click
+    QuickMove {
+        slot: usize,
+    },
+    /// number keys and the off hand key
+    Swap {
+        slot: usize,
+        with: usize,
+    },
+    /// middle click in creative mode
+    Clone {
+        slot: usize,
+    },
+    Throw {
+        slot: usize,
+        whole_stack: bool,
+    },
+    DragStart(DragKind),
+    DragAdd {
+        kind: DragKind,
+        slot: usize,
+    },
+    DragEnd(DragKind),
+    /// double click
+    PickupAll {
+        slot: usize,
+    },
+}
+
+/// The slots of the window that a click changed, and the stacks that it dropped out of the
+/// inventory
+#[derive(Default)]
+pub struct ClickOutcome {
+    pub changed_slots: Vec<usize>,
+    pub dropped: Vec<ItemStack>,
+}
+
+// the stacks of the open window, and the mouse that clicks in it
+struct Window<'a> {
+    layout: WindowLayout,
+    slots: &'a mut [ItemStack],
+    carried: &'a mut ItemStack,
+    drag: &'a mut Option<Drag>,
+    creative: bool,
+}
+
+impl WindowLayout {
+    /// The number of slots that the client sees
+    pub fn slot_count(self) -> usize {
+        match self {
+            WindowLayout::PlayerInventory => PLAYER_SLOT_COUNT,
+            WindowLayout::Container(_) => self.window_slot(PLAYER_OFF_HAND_SLOT),
+        }
+    }
+
+    /// The slot of the player that a slot of the window shows; None for the slots of a container
+    pub fn player_slot(self, slot: usize) -> Option<usize> {
+        match self {
+            WindowLayout::PlayerInventory => Some(slot),
+            WindowLayout::Container(kind) => slot
+                .checked_sub(kind.slot_count())
+                .map(|offset| CONTAINER_PLAYER_SLOTS.start + offset)
+                .filter(|slot| CONTAINER_PLAYER_SLOTS.contains(slot)),
+        }
+    }
+
+    /// The slot of the window that stands for the off hand. Container windows do not show it, so
+    /// it follows their last slot
+    pub fn off_hand_slot(self) -> usize {
+        self.window_slot(PLAYER_OFF_HAND_SLOT)
+    }
+
+    // the slot of the window that shows a slot of the main inventory, the hotbar or the off hand
+    fn window_slot(self, player_slot: usize) -> usize {
+        match self {
+            WindowLayout::PlayerInventory => player_slot,
+            WindowLayout::Container(kind) => {
+                kind.slot_count() + player_slot - CONTAINER_PLAYER_SLOTS.start
+            },
+        }
+    }
+
+    fn is_result_slot(self, slot: usize) -> bool {
+        match self {
+            WindowLayout::PlayerInventory => slot == PLAYER_CRAFTING_RESULT_SLOT,
+            WindowLayout::Container(ContainerKind::Furnace) => slot == FURNACE_RESULT_SLOT,
+            WindowLayout::Container(ContainerKind::Chest) => false,
+        }
+    }
+
+    // the number of items of the stack that fit into the slot
+    fn slot_capacity(self, slot: usize, stack: &ItemStack) -> usize {
+        if self.is_result_slot(slot) {
+            // TODO crafting and smelting: nothing can be put into result slots
+            0
+        } else if self == WindowLayout::PlayerInventory && PLAYER_ARMOR_SLOTS.contains(&slot) {
+            // TODO the item registry does not tell which items are armor, so any item fits
+            1
+        } else {
+            stack.max_stack_size()
+        }
+    }
+
+    // like vanilla: the slots that shift clicking moves a stack to
+    fn quick_move_targets(self, slot: usize) -> Vec<usize> {
+        let main = self.window_slot(*PLAYER_MAIN_SLOTS.start())
+            ..=self.window_slot(*PLAYER_MAIN_SLOTS.end());
+        let hotbar = self.window_slot(*PLAYER_HOTBAR_SLOTS.start())
+            ..=self.window_slot(*PLAYER_HOTBAR_SLOTS.end());
+
+        let kind = match self {
+            WindowLayout::PlayerInventory if main.contains(&slot) => return hotbar.collect(),
+            WindowLayout::PlayerInventory if hotbar.contains(&slot) => return main.collect(),
+            WindowLayout::PlayerInventory => return main.chain(hotbar).collect(),
+            WindowLayout::Container(kind) => kind,
+        };
+
+        if slot < kind.slot_count() {
+            // into the inventory of the player, starting at the end of the hotbar
+            return main.chain(hotbar).rev().collect();
+        }
+        match kind {
+            ContainerKind::Chest => (0..kind.slot_count()).collect(),
+            // TODO smelting: smeltable items and fuel go into the furnace
+            ContainerKind::Furnace if main.contains(&slot) => hotbar.collect(),
+            ContainerKind::Furnace => main.collect(),
+        }
+    }
+}
+
+impl InventoryClick {
+    /// See https://wiki.vg/Protocol#Click_Container.
+    /// Returns None for clicks that do not exist, or that are on slots outside of the window
+    pub fn decode(
+        layout: WindowLayout,
+        slot: i16,
+        button: i8,
+        mode: i32,
+    ) -> Option<InventoryClick> {
+        let slot_idx = || {
+            usize::try_from(slot)
+                .ok()
+                .filter(|slot| *slot < layout.slot_count())
+        };
+
+        let click = match (mode, button) {
+            (0, 0 | 1) if slot == OUTSIDE_SLOT => InventoryClick::DropCarried {
+                whole_stack: button == 0,
+            },
+            (0, 0 | 1) => InventoryClick::Pickup {
+                slot: slot_idx()?,
+                whole_stack: button == 0,
+            },
+            (1, 0 | 1) => InventoryClick::QuickMove { slot: slot_idx()? },
+            (2, 0..=8) => InventoryClick::Swap {
+                slot: slot_idx()?,
+                with: layout.window_slot(PLAYER_HOTBAR_SLOTS.start() + button as usize),
+            },
+            (2, OFF_HAND_BUTTON) => InventoryClick::Swap {
+                slot: slot_idx()?,
+                with: layout.window_slot(PLAYER_OFF_HAND_SLOT),
+            },
+            (3, 2) => InventoryClick::Clone { slot: slot_idx()? },
+            (4, 0 | 1) => InventoryClick::Throw {
+                slot: slot_idx()?,
+                whole_stack: button == 1,
+            },
+            // the button is 4 times the kind of drag, plus the stage of the drag
+            (5, 0..=10) => {
+                let kind = match button / 4 {
+                    0 => DragKind::Split,
+                    1 => DragKind::One,
+                    _ => DragKind::Clone,
+                };
+                match button % 4 {
+                    0 => InventoryClick::DragStart(kind),
+                    1 => InventoryClick::DragAdd {
+                        kind,
+                        slot: slot_idx()?,
+                    },
+                    2 => InventoryClick::DragEnd(kind),
+                    _ => return None,
+                }
+            },
+            (6, 0) => InventoryClick::PickupAll { slot: slot_idx()? },
+            _ => return None,
+        };
+
+        Some(click)
+    }
+}
+
+/// Handles a click in the open window: the container of `PlayerState::open_container`, or else the
+/// inventory of the player
+pub fn handle_click(player: &mut PlayerState, click: InventoryClick) -> ClickOutcome {
+    let creative = is_creative(player);
+    let Some(container) = &mut player.open_container else {
+        let mut window = Window {
+            layout: WindowLayout::PlayerInventory,
+            slots: &mut player.slots,
+            carried: &mut player.carried,
+            drag: &mut player.drag,
+            creative,
+        };
+        return click_in(&mut window, click);
+    };
+
+    // the stacks of the player are moved into the window, and back afterwards
+    let mut slots = std::mem::take(&mut container.stacks);
+    slots.extend(CONTAINER_PLAYER_SLOTS.map(|slot| std::mem::take(&mut player.slots[slot])));
+
+    let mut window = Window {
+        layout: WindowLayout::Container(container.kind),
+        slots: &mut slots,
+        carried: &mut player.carried,
+        drag: &mut player.drag,
+        creative,
+    };
+    let outcome = click_in(&mut window, click);
+
+    let player_stacks = slots.split_off(container.kind.slot_count());
+    for (slot, stack) in CONTAINER_PLAYER_SLOTS.zip(player_stacks) {
+        player.slots[slot] = stack;
+    }
+    container.stacks = slots;
+    container.changed |= outcome
+        .changed_slots
+        .iter()
+        .any(|slot| *slot < container.kind.slot_count());
+    outcome
+}
+
+fn click_in(window: &mut Window, click: InventoryClick) -> ClickOutcome {
+    let mut outcome = ClickOutcome::default();
+    let layout = window.layout;
+
+    // like vanilla: any other click ends a drag
+    if !matches!(
+        click,
+        InventoryClick::DragStart(_) | InventoryClick::DragAdd { .. } | InventoryClick::DragEnd(_)
+    ) {
+        *window.drag = None;
+    }
+
+    match click {
+        InventoryClick::Pickup { slot, whole_stack } => {
+            if pickup(window, slot, whole_stack) {
+                outcome.changed_slots.push(slot);
+            }
+        },
+        InventoryClick::DropCarried { whole_stack } => {
+            let count = if whole_stack { usize::MAX } else { 1 };
+            outcome.dropped.push(window.carried.split_off(count));
+        },
+        InventoryClick::QuickMove { slot } => {
+            let stack = std::mem::take(&mut window.slots[slot]);
+            let targets = layout.quick_move_targets(slot);
+            let (changed_slots, leftover) = insert_into(window.slots, stack, targets.into_iter());
+            window.slots[slot] = leftover;
+
+            if !changed_slots.is_empty() {
+                outcome.changed_slots.push(slot);
+                outcome.changed_slots.extend(changed_slots);
+            }
+        },
+        InventoryClick::Swap { slot, with } => {
+            let fits =
+                |stack: &ItemStack, slot: usize| stack.count() <= layout.slot_capacity(slot, stack);
+            if slot != with && fits(&window.slots[with], slot) && fits(&window.slots[slot], with) {
+                window.slots.swap(slot, with);
+                outcome.changed_slots.extend([slot, with]);
+            }
+        },
+        InventoryClick::Clone { slot } => {
+            let stack = &window.slots[slot];
+            if window.creative && window.carried.is_empty() {
+                *window.carried = stack.with_count(stack.max_stack_size());
+            }
+        },
+        InventoryClick::Throw { slot, whole_stack } => {
+            // like vanilla: the stack on the mouse stops the keys from throwing
+            if window.carried.is_empty() {
+                let count = if whole_stack { usize::MAX } else { 1 };
+                outcome.dropped.push(window.slots[slot].split_off(count));
+                outcome.changed_slots.push(slot);
+            }
+        },
+        InventoryClick::DragStart(kind) => {
+            if !window.carried.is_empty() && (kind != DragKind::Clone || window.creative) {
+                *window.drag = Some(Drag {
+                    kind,
+                    slots: Vec::new(),
+                });
+            }
+        },
+        InventoryClick::DragAdd { kind, slot } => drag_add(window, kind, slot),
+        InventoryClick::DragEnd(kind) => outcome.changed_slots = drag_end(window, kind),
+        InventoryClick::PickupAll { .. } => outcome.changed_slots = pickup_all(window),
+    }
+
+    outcome.dropped.retain(|stack| !stack.is_empty());
+    outcome
+}
+
+/// Like vanilla, closing the window drops the carried stack, and moves the stacks of the crafting
+/// grid back into the inventory
+pub fn close_window(player: &mut PlayerState) -> ClickOutcome {
+    let mut outcome = ClickOutcome::default();
+    player.drag = None;
+
+    for slot in PLAYER_CRAFTING_SLOTS {
+        let stack = std::mem::take(&mut player.slots[slot]);
+        if stack.is_empty() {
+            continue;
+        }
+
+        outcome.changed_slots.push(slot);
+        let (changed_slots, leftover) = player.insert(stack);
+        outcome.changed_slots.extend(changed_slots);
+        outcome.dropped.push(leftover);
+    }
+
+    outcome.dropped.push(std::mem::take(&mut player.carried));
+    outcome.dropped.retain(|stack| !stack.is_empty());
+    outcome
+}
+
+/// In creative mode, the client sets the stacks of slots itself.
+/// Returns None if the client may not set this stack
+pub fn set_creative_slot(
+    player: &mut PlayerState,
+    slot: i16,
+    stack: ItemStack,
+) -> Option<ClickOutcome> {
+    if !is_creative(player) || stack.count() > stack.max_stack_size() {
+        return None;
+    }
+
+    let mut outcome = ClickOutcome::default();
+    if slot == CREATIVE_DROP_SLOT {
+        outcome.dropped.push(stack);
+        outcome.dropped.retain(|stack| !stack.is_empty());
+        return Some(outcome);
+    }
+
+    // the crafting result can not be set
+    let slot = usize::try_from(slot)
+        .ok()
+        .filter(|slot| (1..PLAYER_SLOT_COUNT).contains(slot))?;
+    player.slots[slot] = stack;
+    outcome.changed_slots.push(slot);
+    Some(outcome)
+}
+
+/// Puts the stack into the given slots: first onto stacks of the same item, then into empty
+/// slots, both in the order of `targets`.
+/// Returns the indices of the changed slots, and the part of the stack that did not fit
+pub fn insert_into(
+    slots: &mut [ItemStack],
+    mut stack: ItemStack,
+    targets: impl Iterator<Item = usize> + Clone,
+) -> (Vec<usize>, ItemStack) {
+    let mut changed_slots = merge_into(slots, &mut stack, targets.clone());
+    for slot in targets {
+        if stack.is_empty() {
+            break;
+        }
+        if slots[slot].is_empty() && slots[slot].merge_from(&mut stack) {
+            changed_slots.push(slot);
+        }
+    }
+    (changed_slots, stack)
+}
+
+/// Moves as many items of the stack as fit onto the stacks of the same item in the given slots.
+/// Returns the indices of the changed slots
+pub fn merge_into(
+    slots: &mut [ItemStack],
+    stack: &mut ItemStack,
+    targets: impl Iterator<Item = usize>,
+) -> Vec<usize> {
+    let mut changed_slots = Vec::new();
+    for slot in targets {
+        if stack.is_empty() {
+            break;
+        }
+        if !slots[slot].is_empty() && slots[slot].merge_from(stack) {
+            changed_slots.push(slot);
+        }
+    }
+    changed_slots
+}
+
+// returns true if the slot changed
+fn pickup(window: &mut Window, slot: usize, whole_stack: bool) -> bool {
+    if window.carried.is_empty() {
+        let count = window.slots[slot].count();
+        let count = if whole_stack {
+            count
+        } else {
+            count.div_ceil(2)
+        };
+        *window.carried = window.slots[slot].split_off(count);
+        return !window.carried.is_empty();
+    }
+
+    let stack = &mut window.slots[slot];
+    if stack.is_empty() || stack.can_stack_with(window.carried) {
+        let count = if whole_stack { usize::MAX } else { 1 };
+        return put(window.layout, stack, slot, window.carried, count);
+    }
+
+    // different items swap places, if the carried stack fits into the slot
+    if window.carried.count() <= window.layout.slot_capacity(slot, window.carried) {
+        std::mem::swap(stack, window.carried);
+        return true;
+    }
+    false
+}
+
+fn drag_add(window: &mut Window, kind: DragKind, slot: usize) {
+    let carried = &*window.carried;
+    let stack = &window.slots[slot];
+    let Some(drag) = &mut *window.drag else {
+        return;
+    };
+
+    let accepts = (stack.is_empty() || stack.can_stack_with(carried))
+        && window.layout.slot_capacity(slot, carried) > stack.count();
+    // like vanilla: a split or one by one drag can not cover more slots than there are items
+    let enough_items = kind == DragKind::Clone || carried.count() > drag.slots.len();
+
+    if drag.kind == kind && accepts && enough_items && !drag.slots.contains(&slot) {
+        drag.slots.push(slot);
+    }
+}
+
+// returns the changed slots
+fn drag_end(window: &mut Window, kind: DragKind) -> Vec<usize> {
+    let Some(drag) = window.drag.take() else {
+        return Vec::new();
+    };
+    if drag.kind != kind || drag.slots.is_empty() {
+        return Vec::new();
+    }
+
+    let per_slot = match kind {
+        DragKind::Split => window.carried.count() / drag.slots.len(),
+        DragKind::One => 1,
+        DragKind::Clone => window.carried.max_stack_size(),
+    };
+
+    let mut changed_slots = Vec::new();
+    for slot in drag.slots {
+        let stack = &mut window.slots[slot];
+        let changed = match kind {
+            // the carried stack is copied, not used up
+            DragKind::Clone => {
+                let mut copy = window.carried.with_count(per_slot);
+                put(window.layout, stack, slot, &mut copy, per_slot)
+            },
+            DragKind::Split | DragKind::One => {
+                put(window.layout, stack, slot, window.carried, per_slot)
+            },
+        };
+        if changed {
+            changed_slots.push(slot);
+        }
+    }
+    changed_slots
+}
+
+// like vanilla: collects the items of the carried type, from stacks that are not full first
+fn pickup_all(window: &mut Window) -> Vec<usize> {
+    let mut changed_slots = Vec::new();
+    if window.carried.is_empty() {
+        return changed_slots;
+    }
+
+    for take_full_stacks in [false, true] {
+        for slot in 0..window.layout.slot_count() {
+            if window.carried.count() >= window.carried.max_stack_size() {
+                return changed_slots;
+            }
+
+            let stack = &mut window.slots[slot];
+            let is_full = stack.count() >= stack.max_stack_size();
+            if window.layout.is_result_slot(slot)
+                || !stack.can_stack_with(window.carried)
+                || (is_full && !take_full_stacks)
+            {
+                continue;
+            }
+
+            if window.carried.merge_from(stack) {
+                changed_slots.push(slot);
+            }
+        }
+    }
+    changed_slots
+}
+
+// moves up to `count` items of `from` onto the stack in the slot; returns true if any item moved
+fn put(
+    layout: WindowLayout,
+    stack: &mut ItemStack,
+    slot: usize,
+    from: &mut ItemStack,
+    count: usize,
+) -> bool {
+    let space = layout
+        .slot_capacity(slot, from)
+        .saturating_sub(stack.count());
+    stack.merge_up_to(from, count.min(space))
+}
+
+fn is_creative(player: &PlayerState) -> bool {
+    matches!(player.game_mode, Gamemode::Creative)
+}
@@ -0,0 +1,513 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::components::gamemode::Gamemode;
+    use minecraft_protocol::data::items::Item;
+    use sol_voxel_lib::vector_alias::Coordinate;
+
+    use crate::containers::{ContainerKind, OpenContainer};
+    use crate::inventory::{self, DragKind, InventoryClick, WindowLayout};
+    use crate::item_stack::ItemStack;
+    use crate::player_state::PlayerState;
+
+    const INVENTORY: WindowLayout = WindowLayout::PlayerInventory;
+    const CHEST: WindowLayout = WindowLayout::Container(ContainerKind::Chest);
+
+    fn stack(name: &str, count: usize) -> ItemStack {
+        ItemStack::new(Item::from_text_id(name).unwrap(), count)
+    }
+
+    fn is_stack_of(stack: &ItemStack, name: &str, count: usize) -> bool {
+        let item = Item::from_text_id(name).unwrap();
+        stack.item_type().map(|item| item.id()) == Some(item.id()) && stack.count() == count
+    }
+
+    fn survival_player() -> PlayerState {
+        let mut player = PlayerState::new();
+        player.game_mode = Gamemode::Survival;
+        player
+    }
+
+    fn open_container(player: &mut PlayerState, kind: ContainerKind) {
+        player.open_container = Some(OpenContainer {
+            window_id: 1,
+            kind,
+            location: Coordinate::new(0, 100, 0),
+            stacks: vec![ItemStack::Empty; kind.slot_count()],
+            changed: false,
+        });
+    }
+
+    fn container_stacks(player: &PlayerState) -> &[ItemStack] {
+        &player.open_container.as_ref().unwrap().stacks
+    }
+
+    fn click(player: &mut PlayerState, click: InventoryClick) -> Vec<usize> {
+        inventory::handle_click(player, click).changed_slots
+    }
+
+    #[test]
+    fn test_decode_modes() {
+        let decode = |slot, button, mode| InventoryClick::decode(INVENTORY, slot, button, mode);
+
+        assert_eq!(
+            decode(9, 0, 0),
+            Some(InventoryClick::Pickup {
+                slot: 9,
+                whole_stack: true
+            })
+        );
+        assert_eq!(
+            decode(9, 1, 0),
+            Some(InventoryClick::Pickup {
+                slot: 9,
+                whole_stack: false
+            })
+        );
+        assert_eq!(
+            decode(-999, 0, 0),
+            Some(InventoryClick::DropCarried { whole_stack: true })
+        );
+        assert_eq!(
+            decode(-999, 1, 0),
+            Some(InventoryClick::DropCarried { whole_stack: false })
+        );
+        assert_eq!(decode(9, 2, 0), None);
+
+        assert_eq!(decode(9, 0, 1), Some(InventoryClick::QuickMove { slot: 9 }));
+        assert_eq!(decode(9, 1, 1), Some(InventoryClick::QuickMove { slot: 9 }));
+
+        assert_eq!(decode(9, 2, 3), Some(InventoryClick::Clone { slot: 9 }));
+        assert_eq!(decode(9, 0, 3), None);
+
+        assert_eq!(
+            decode(9, 0, 4),
+            Some(InventoryClick::Throw {
+                slot: 9,
+                whole_stack: false
+            })
+        );
+        assert_eq!(
+            decode(9, 1, 4),
+            Some(InventoryClick::Throw {
+                slot: 9,
+                whole_stack: true
+            })
+        );
+
+        assert_eq!(decode(9, 0, 6), Some(InventoryClick::PickupAll { slot: 9 }));
+        assert_eq!(decode(9, 1, 6), None);
+        assert_eq!(decode(9, 0, 7), None);
+        assert_eq!(decode(9, 0, -1), None);
+    }
+
+    #[test]
+    fn test_decode_slots() {
+        // the inventory has 46 slots, the chest window 27 of its own and 36 of the player
+        assert!(InventoryClick::decode(INVENTORY, 45, 0, 0).is_some());
+        assert_eq!(InventoryClick::decode(INVENTORY, 46, 0, 0), None);
+        assert_eq!(InventoryClick::decode(INVENTORY, -1, 0, 0), None);
+        assert!(InventoryClick::decode(CHEST, 62, 0, 0).is_some());
+        assert_eq!(InventoryClick::decode(CHEST, 63, 0, 0), None);
+        // only pickup clicks may be outside of the window
+        assert_eq!(InventoryClick::decode(INVENTORY, -999, 0, 1), None);
+    }
+
+    #[test]
+    fn test_decode_swap() {
+        for button in 0..=8 {
+            assert_eq!(
+                InventoryClick::decode(INVENTORY, 9, button, 2),
+                Some(InventoryClick::Swap {
+                    slot: 9,
+                    with: 36 + button as usize
+                })
+            );
+            // the hotbar follows the main inventory, after the slots of the chest
+            assert_eq!(
+                InventoryClick::decode(CHEST, 0, button, 2),
+                Some(InventoryClick::Swap {
+                    slot: 0,
+                    with: 54 + button as usize
+                })
+            );
+        }
+
+        assert_eq!(
+            InventoryClick::decode(INVENTORY, 9, 40, 2),
+            Some(InventoryClick::Swap { slot: 9, with: 45 })
+        );
+        // the off hand is not shown in the chest window, but follows its last slot
+        assert_eq!(
+            InventoryClick::decode(CHEST, 0, 40, 2),
+            Some(InventoryClick::Swap { slot: 0, with: 63 })
+        );
+        assert_eq!(InventoryClick::decode(INVENTORY, 9, 9, 2), None);
+        assert_eq!(InventoryClick::decode(INVENTORY, 9, 39, 2), None);
+    }
+
+    #[test]
+    fn test_off_hand_slot() {
+        assert_eq!(INVENTORY.off_hand_slot(), 45);
+        // right after the last slot of the window
+        assert_eq!(CHEST.off_hand_slot(), CHEST.slot_count());
+        assert_eq!(CHEST.off_hand_slot(), 63);
+        let furnace = WindowLayout::Container(ContainerKind::Furnace);
+        assert_eq!(furnace.off_hand_slot(), 39);
+    }
+
+    #[test]
+    fn test_decode_drag() {
+        let decode = |slot, button| InventoryClick::decode(INVENTORY, slot, button, 5);
+        let kinds = [DragKind::Split, DragKind::One, DragKind::Clone];
+
+        for (stage_button, kind) in [0, 4, 8].into_iter().zip(kinds) {
+            assert_eq!(
+                decode(-999, stage_button),
+                Some(InventoryClick::DragStart(kind))
+            );
+            assert_eq!(
+                decode(9, stage_button + 1),
+                Some(InventoryClick::DragAdd { kind, slot: 9 })
+            );
+            assert_eq!(
+                decode(-999, stage_button + 2),
+                Some(InventoryClick::DragEnd(kind))
+            );
+            assert_eq!(decode(9, stage_button + 3), None);
+            // slots are only added within the window
+            assert_eq!(decode(46, stage_button + 1), None);
+        }
+        assert_eq!(decode(9, 11), None);
+        assert_eq!(decode(9, -1), None);
+    }
+
+    #[test]
+    fn test_pickup_and_put() {
+        let mut player = survival_player();
+        player.slots[9] = stack("stone", 10);
+
+        let pickup = |slot, whole_stack| InventoryClick::Pickup { slot, whole_stack };
+        assert_eq!(click(&mut player, pickup(9, true)), vec![9]);
+        assert!(is_stack_of(&player.carried, "stone", 10));
+        assert!(player.slots[9].is_empty());
+
+        // one item with the right button, the rest with the left one
+        assert_eq!(click(&mut player, pickup(10, false)), vec![10]);
+        assert!(is_stack_of(&player.slots[10], "stone", 1));
+        assert_eq!(click(&mut player, pickup(10, true)), vec![10]);
+        assert!(is_stack_of(&player.slots[10], "stone", 10));
+        assert!(player.carried.is_empty());
+
+        // the right button takes the larger half
+        player.slots[11] = stack("dirt", 5);
+        assert_eq!(click(&mut player, pickup(11, false)), vec![11]);
+        assert!(is_stack_of(&player.carried, "dirt", 3));
+        assert!(is_stack_of(&player.slots[11], "dirt", 2));
+
+        // different items swap
+        assert_eq!(click(&mut player, pickup(10, true)), vec![10]);
+        assert!(is_stack_of(&player.carried, "stone", 10));
+        assert!(is_stack_of(&player.slots[10], "dirt", 3));
+
+        // nothing fits onto a full stack
+        player.slots[12] = stack("stone", 64);
+        assert!(click(&mut player, pickup(12, true)).is_empty());
+        assert!(is_stack_of(&player.carried, "stone", 10));
+
+        // nothing can be put into the crafting result
+        assert!(click(&mut player, pickup(0, true)).is_empty());
+    }
+
+    #[test]
+    fn test_drop_and_throw() {
+        let mut player = survival_player();
+        player.slots[9] = stack("stone", 10);
+
+        let outcome = inventory::handle_click(
+            &mut player,
+            InventoryClick::Throw {
+                slot: 9,
+                whole_stack: false,
+            },
+        );
+        assert_eq!(outcome.changed_slots, vec![9]);
+        assert_eq!(outcome.dropped.len(), 1);
+        assert!(is_stack_of(&outcome.dropped[0], "stone", 1));
+
+        // the stack on the mouse stops the keys from throwing
+        player.carried = stack("dirt", 5);
+        let outcome = inventory::handle_click(
+            &mut player,
+            InventoryClick::Throw {
+                slot: 9,
+                whole_stack: true,
+            },
+        );
+        assert!(outcome.dropped.is_empty());
+        assert!(is_stack_of(&player.slots[9], "stone", 9));
+
+        let outcome = inventory::handle_click(
+            &mut player,
+            InventoryClick::DropCarried { whole_stack: false },
+        );
+        assert!(is_stack_of(&outcome.dropped[0], "dirt", 1));
+        let outcome = inventory::handle_click(
+            &mut player,
+            InventoryClick::DropCarried { whole_stack: true },
+        );
+        assert!(is_stack_of(&outcome.dropped[0], "dirt", 4));
+        assert!(player.carried.is_empty());
+
+        // nothing is dropped without a carried stack
+        let outcome = inventory::handle_click(
+            &mut player,
+            InventoryClick::DropCarried { whole_stack: true },
+        );
+        assert!(outcome.dropped.is_empty());
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut player = survival_player();
+        player.slots[9] = stack("stone", 10);
+        player.slots[38] = stack("dirt", 5);
+
+        let changed = click(&mut player, InventoryClick::Swap { slot: 9, with: 38 });
+        assert_eq!(changed, vec![9, 38]);
+        assert!(is_stack_of(&player.slots[9], "dirt", 5));
+        assert!(is_stack_of(&player.slots[38], "stone", 10));
+
+        // a stack does not fit into an armor slot
+        let changed = click(&mut player, InventoryClick::Swap { slot: 5, with: 38 });
+        assert!(changed.is_empty());
+        assert!(is_stack_of(&player.slots[38], "stone", 10));
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut player = PlayerState::new();
+        player.slots[9] = stack("stone", 1);
+        click(&mut player, InventoryClick::Clone { slot: 9 });
+        assert!(is_stack_of(&player.carried, "stone", 64));
+        assert!(is_stack_of(&player.slots[9], "stone", 1));
+
+        // only in creative mode
+        let mut player = survival_player();
+        player.slots[9] = stack("stone", 1);
+        click(&mut player, InventoryClick::Clone { slot: 9 });
+        assert!(player.carried.is_empty());
+    }
+
+    #[test]
+    fn test_drag_split() {
+        let mut player = survival_player();
+        player.carried = stack("stone", 10);
+        player.slots[11] = stack("stone", 2);
+
+        click(&mut player, InventoryClick::DragStart(DragKind::Split));
+        for slot in [9, 10, 11, 10] {
+            let add = InventoryClick::DragAdd {
+                kind: DragKind::Split,
+                slot,
+            };
+            assert!(click(&mut player, add).is_empty());
+        }
+        let changed = click(&mut player, InventoryClick::DragEnd(DragKind::Split));
+
+        // each slot gets the same share, and the rest stays on the mouse
+        assert_eq!(changed, vec![9, 10, 11]);
+        assert!(is_stack_of(&player.slots[9], "stone", 3));
+        assert!(is_stack_of(&player.slots[10], "stone", 3));
+        assert!(is_stack_of(&player.slots[11], "stone", 5));
+        assert!(is_stack_of(&player.carried, "stone", 1));
+        assert!(player.drag.is_none());
+    }
+
+    #[test]
+    fn test_drag_one() {
+        let mut player = survival_player();
+        player.carried = stack("stone", 2);
+        player.slots[12] = stack("dirt", 1);
+
+        click(&mut player, InventoryClick::DragStart(DragKind::One));
+        // other items, and more slots than there are items, are left out
+        for slot in [9, 12, 10, 11] {
+            click(
+                &mut player,
+                InventoryClick::DragAdd {
+                    kind: DragKind::One,
+                    slot,
+                },
+            );
+        }
+        let changed = click(&mut player, InventoryClick::DragEnd(DragKind::One));
+        assert_eq!(changed, vec![9, 10]);
+        assert!(is_stack_of(&player.slots[9], "stone", 1));
+        assert!(is_stack_of(&player.slots[10], "stone", 1));
+        assert!(player.slots[11].is_empty());
+        assert!(player.carried.is_empty());
+    }
+
+    #[test]
+    fn test_drag_clone() {
+        let mut player = PlayerState::new();
+        player.carried = stack("stone", 1);
+        click(&mut player, InventoryClick::DragStart(DragKind::Clone));
+        for slot in [9, 10] {
+            click(
+                &mut player,
+                InventoryClick::DragAdd {
+                    kind: DragKind::Clone,
+                    slot,
+                },
+            );
+        }
+        let changed = click(&mut player, InventoryClick::DragEnd(DragKind::Clone));
+        assert_eq!(changed, vec![9, 10]);
+        assert!(is_stack_of(&player.slots[9], "stone", 64));
+        assert!(is_stack_of(&player.slots[10], "stone", 64));
+        assert!(is_stack_of(&player.carried, "stone", 1));
+
+        // only in creative mode
+        let mut player = survival_player();
+        player.carried = stack("stone", 1);
+        click(&mut player, InventoryClick::DragStart(DragKind::Clone));
+        assert!(player.drag.is_none());
+    }
+
+    #[test]
+    fn test_interrupted_drag() {
+        let mut player = survival_player();
+        player.carried = stack("stone", 10);
+        player.slots[20] = stack("dirt", 1);
+        let add = |slot| InventoryClick::DragAdd {
+            kind: DragKind::Split,
+            slot,
+        };
+
+        // any other click ends the drag
+        click(&mut player, InventoryClick::DragStart(DragKind::Split));
+        click(&mut player, add(9));
+        click(&mut player, InventoryClick::QuickMove { slot: 20 });
+        click(&mut player, add(10));
+        assert!(click(&mut player, InventoryClick::DragEnd(DragKind::Split)).is_empty());
+        assert!(player.slots[9].is_empty());
+
+        // the stages of another kind of drag are ignored
+        click(&mut player, InventoryClick::DragStart(DragKind::Split));
+        click(
+            &mut player,
+            InventoryClick::DragAdd {
+                kind: DragKind::One,
+                slot: 9,
+            },
+        );
+        assert!(click(&mut player, InventoryClick::DragEnd(DragKind::One)).is_empty());
+
+        // a drag needs a carried stack
+        player.carried = ItemStack::Empty;
+        click(&mut player, InventoryClick::DragStart(DragKind::Split));
+        assert!(player.drag.is_none());
+    }
+
+    #[test]
+    fn test_quick_move_in_inventory() {
+        let mut player = survival_player();
+        player.slots[9] = stack("stone", 10);
+        player.slots[40] = stack("stone", 60);
+
+        // from the main inventory onto the same items in the hotbar, then into empty slots
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 9 });
+        assert_eq!(changed, vec![9, 40, 36]);
+        assert!(player.slots[9].is_empty());
+        assert!(is_stack_of(&player.slots[40], "stone", 64));
+        assert!(is_stack_of(&player.slots[36], "stone", 6));
+
+        // from the hotbar into the main inventory
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 36 });
+        assert_eq!(changed, vec![36, 9]);
+        assert!(is_stack_of(&player.slots[9], "stone", 6));
+
+        // from the armor into the main inventory
+        player.slots[5] = stack("dirt", 1);
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 5 });
+        assert_eq!(changed, vec![5, 10]);
+        assert!(is_stack_of(&player.slots[10], "dirt", 1));
+
+        // nothing changes if nothing moves
+        assert!(click(&mut player, InventoryClick::QuickMove { slot: 5 }).is_empty());
+    }
+
+    #[test]
+    fn test_quick_move_in_chest() {
+        let mut player = survival_player();
+        open_container(&mut player, ContainerKind::Chest);
+        player.open_container.as_mut().unwrap().stacks[0] = stack("stone", 10);
+
+        // from the chest into the end of the hotbar
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 0 });
+        assert_eq!(changed, vec![0, 62]);
+        assert!(container_stacks(&player)[0].is_empty());
+        assert!(is_stack_of(&player.slots[44], "stone", 10));
+        assert!(player.open_container.as_ref().unwrap().changed);
+
+        // from the inventory of the player into the chest
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 62 });
+        assert_eq!(changed, vec![62, 0]);
+        assert!(is_stack_of(&container_stacks(&player)[0], "stone", 10));
+        assert!(player.slots[44].is_empty());
+    }
+
+    #[test]
+    fn test_quick_move_in_furnace() {
+        let mut player = survival_player();
+        open_container(&mut player, ContainerKind::Furnace);
+        // the main inventory starts after the 3 slots of the furnace
+        player.slots[9] = stack("stone", 10);
+
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 3 });
+        assert_eq!(changed, vec![3, 30]);
+        assert!(is_stack_of(&player.slots[36], "stone", 10));
+        assert!(!player.open_container.as_ref().unwrap().changed);
+
+        let changed = click(&mut player, InventoryClick::QuickMove { slot: 30 });
+        assert_eq!(changed, vec![30, 3]);
+        assert!(is_stack_of(&player.slots[9], "stone", 10));
+    }
+
+    #[test]
+    fn test_pickup_all() {
+        let mut player = survival_player();
+        player.carried = stack("stone", 10);
+        player.slots[9] = stack("stone", 64);
+        player.slots[10] = stack("stone", 30);
+        player.slots[11] = stack("dirt", 5);
+        player.slots[12] = stack("stone", 20);
+
+        // stacks that are not full come first, until the carried stack is full
+        let changed = click(&mut player, InventoryClick::PickupAll { slot: 9 });
+        assert_eq!(changed, vec![10, 12, 9]);
+        assert!(is_stack_of(&player.carried, "stone", 64));
+        assert!(player.slots[10].is_empty());
+        assert!(player.slots[12].is_empty());
+        assert!(is_stack_of(&player.slots[9], "stone", 60));
+        assert!(is_stack_of(&player.slots[11], "dirt", 5));
+    }
+
+    #[test]
+    fn test_state_id_resync() {
+        let mut player = PlayerState::new();
+        assert!(!player.is_outdated(0));
+
+        // the client did not get our last change yet
+        let state_id = player.next_state_id();
+        assert!(player.is_outdated(state_id - 1));
+        assert!(!player.is_outdated(state_id));
+
+        // the state id wraps around, like vanilla
+        player.state_id = 0x7FFF;
+        assert_eq!(player.next_state_id(), 0);
+        assert!(player.is_outdated(0x7FFF));
+        assert!(!player.is_outdated(0));
+    }
+}
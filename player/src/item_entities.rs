@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use crate::game_event::Event;
+use crate::item_stack::ItemStack;
+use crate::player_state::PlayerState;
+use sol_entity_lib::entity::{Entity, EntityId};
+use sol_entity_lib::entity_manager::EntityManager;
+use sol_entity_lib::entity_type::EntityType;
+use sol_entity_lib::metadata::ItemData;
+use sol_game_engine::physics::aabb::Aabb;
+use sol_network_lib::Tick;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Direction, Position, Vector3f};
+
+// like vanilla: the ticks before an item can be picked up, and before it disappears
+pub const BLOCK_DROP_PICKUP_DELAY: Tick = 10;
+pub const THROWN_ITEM_PICKUP_DELAY: Tick = 40;
+const DESPAWN_DELAY: Tick = 6000;
+// the distance around the bounding box of the player, in which items are picked up
+const PICKUP_REACH_HORIZONTAL: f32 = 1.0;
+const PICKUP_REACH_VERTICAL: f32 = 0.5;
+// the horizontal distance around an item entity, in which other items merge with it
+const MERGE_REACH: f32 = 0.5;
+// like vanilla: items are thrown from a bit below the eyes of the player
+const THROW_HEIGHT: f32 = 1.62 - 0.3;
+const THROW_SPEED: f32 = 0.3;
+
+struct ItemEntity {
+    stack: ItemStack,
+    // the item can not be picked up before this tick
+    pickup_tick: Tick,
+    despawn_tick: Tick,
+}
+
+/// An item entity that was picked up, completely or in part
+pub struct Pickup {
+    pub entity_id: EntityId,
+    pub count: usize,
+    pub changed_slots: Vec<usize>,
+}
+
+/// The stacks that item entities carry.
+/// The entities themselves are in the `EntityManager`; their metadata shows the item to the client
+#[derive(Default)]
+pub struct ItemEntities {
+    items: HashMap<EntityId, ItemEntity>,
+}
+
+impl ItemEntities {
+    pub fn new() -> ItemEntities {
+        ItemEntities::default()
+    }
+
+    /// Returns None for an empty stack
+    pub fn spawn(
+        &mut self,
+        entities: &mut EntityManager,
+        position: Position,
+        velocity: Vector3f,
+        stack: ItemStack,
+        pickup_delay: Tick,
+        current_tick: Tick,
+    ) -> Option<EntityId> {
+        let item_data = item_data(&stack)?;
+
+        let id = entities.spawn(rand::random(), EntityType::Item, position);
+        entities.update(id, |entity| {
+            entity.velocity = velocity;
+            entity.metadata.item = Some(item_data);
+        });
+
+        self.items.insert(
+            id,
+            ItemEntity {
+                stack,
+                pickup_tick: current_tick + pickup_delay,
+                despawn_tick: current_tick + DESPAWN_DELAY,
+            },
+        );
+        Some(id)
+    }
+
+    /// Removes the items that lay around for too long, and forgets the ones whose entity is gone
+    pub fn despawn_old(&mut self, entities: &mut EntityManager, current_tick: Tick) {
+        self.items.retain(|id, item| {
+            let keep = item.despawn_tick > current_tick && entities.contains(*id);
+            if !keep {
+                entities.remove(*id);
+            }
+            keep
+        });
+    }
+
+    /// Merges the stacks of item entities that touch each other; the smaller stack moves onto the
+    /// larger one
+    pub fn merge_nearby(&mut self, entities: &mut EntityManager) {
+        let mut ids: Vec<EntityId> = self.items.keys().copied().collect();
+        // merge in the same order every time
+        ids.sort_unstable();
+
+        for id in ids {
+            let Some(entity) = entities.get(id) else {
+                continue;
+            };
+
+            let reach = expanded(&entity_box(entity), MERGE_REACH, 0.0);
+            let nearby: Vec<EntityId> = entities
+                .in_area(entity.column(), 1)
+                .into_iter()
+                .filter(|other| {
+                    other.id != id
+                        && self.items.contains_key(&other.id)
+                        && reach.intersects(&entity_box(other))
+                })
+                .map(|other| other.id)
+                .collect();
+
+            for other_id in nearby {
+                self.merge(entities, id, other_id);
+                // this item may have moved onto the other one
+                if !self.items.contains_key(&id) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Moves the items within reach of the player into the inventory, as far as they fit.
+    /// The box of the player is in the coordinates of our voxels
+    pub fn pick_up(
+        &mut self,
+        entities: &mut EntityManager,
+        player_box: &Aabb,
+        player: &mut PlayerState,
+        current_tick: Tick,
+    ) -> Vec<Pickup> {
+        let reach = expanded(player_box, PICKUP_REACH_HORIZONTAL, PICKUP_REACH_VERTICAL);
+        let column = ChunkColumnCoordinate::containing_position(&player_box.min);
+        let in_reach: Vec<EntityId> = entities
+            .in_area(column, 1)
+            .into_iter()
+            .filter(|entity| reach.intersects(&entity_box(entity)))
+            .map(|entity| entity.id)
+            .collect();
+
+        let mut pickups = Vec::new();
+        for id in in_reach {
+            let Some(item) = self.items.get_mut(&id) else {
+                continue;
+            };
+            if item.pickup_tick > current_tick {
+                continue;
+            }
+
+            let count_before = item.stack.count();
+            let (changed_slots, leftover) = player.insert(std::mem::take(&mut item.stack));
+            item.stack = leftover;
+
+            let count = count_before - item.stack.count();
+            if count == 0 {
+                continue;
+            }
+
+            if item.stack.is_empty() {
+                self.items.remove(&id);
+                entities.remove(id);
+            } else {
+                update_metadata(entities, id, &item.stack);
+            }
+
+            pickups.push(Pickup {
+                entity_id: id,
+                count,
+                changed_slots,
+            });
+        }
+
+        pickups
+    }
+
+    fn merge(&mut self, entities: &mut EntityManager, id: EntityId, other_id: EntityId) {
+        let (Some(item), Some(other)) = (self.items.get(&id), self.items.get(&other_id)) else {
+            return;
+        };
+        let (target_id, source_id) = if other.stack.count() > item.stack.count() {
+            (other_id, id)
+        } else {
+            (id, other_id)
+        };
+
+        let Some(mut source) = self.items.remove(&source_id) else {
+            return;
+        };
+        let Some(target) = self.items.get_mut(&target_id) else {
+            self.items.insert(source_id, source);
+            return;
+        };
+
+        if !target.stack.merge_from(&mut source.stack) {
+            self.items.insert(source_id, source);
+            return;
+        }
+
+        // like vanilla: the merged item waits for the longer delay, and lives as long as the
+        // younger item
+        target.pickup_tick = target.pickup_tick.max(source.pickup_tick);
+        target.despawn_tick = target.despawn_tick.max(source.despawn_tick);
+        update_metadata(entities, target_id, &target.stack);
+
+        if source.stack.is_empty() {
+            entities.remove(source_id);
+        } else {
+            update_metadata(entities, source_id, &source.stack);
+            self.items.insert(source_id, source);
+        }
+    }
+}
+
+/// Throws the stack from the eyes of the player, in the direction that it looks.
+/// The position of the player is in the coordinates of our voxels
+pub fn throw(player_position: Position, look_direction: Direction, stack: ItemStack) -> Event {
+    Event::ItemEntitySpawn {
+        position: player_position + Vector3f::new(0.0, THROW_HEIGHT, 0.0),
+        velocity: look_direction.into_inner() * THROW_SPEED + Vector3f::new(0.0, 0.1, 0.0),
+        stack,
+        pickup_delay: THROWN_ITEM_PICKUP_DELAY,
+    }
+}
+
+fn item_data(stack: &ItemStack) -> Option<ItemData> {
+    let item = stack.item_type()?;
+    Some(ItemData {
+        item_id: item.id() as u32,
+        count: stack.count().min(u8::MAX as usize) as u8,
+    })
+}
+
+// the tracker sends the changed metadata to the client
+fn update_metadata(entities: &mut EntityManager, id: EntityId, stack: &ItemStack) {
+    entities.update(id, |entity| entity.metadata.item = item_data(stack));
+}
+
+fn entity_box(entity: &Entity) -> Aabb {
+    let dimensions = entity.dimensions();
+    Aabb::standing_at(entity.position, dimensions.width, dimensions.height)
+}
+
+fn expanded(aabb: &Aabb, horizontal: f32, vertical: f32) -> Aabb {
+    let offset = Vector3f::new(horizontal, vertical, horizontal);
+    Aabb::new(aabb.min - offset, aabb.max + offset)
+}
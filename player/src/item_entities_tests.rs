@@ -0,0 +1,250 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::items::Item;
+    use sol_entity_lib::entity::EntityId;
+    use sol_entity_lib::entity_manager::EntityManager;
+    use sol_entity_lib::metadata::ItemData;
+    use sol_game_engine::physics::aabb::Aabb;
+    use sol_voxel_lib::vector_alias::{Position, Vector3f};
+
+    use crate::item_entities::ItemEntities;
+    use crate::item_stack::ItemStack;
+    use crate::player_state::{PlayerState, PLAYER_HOTBAR_SLOTS, PLAYER_MAIN_SLOTS};
+
+    fn stack(name: &str, count: usize) -> ItemStack {
+        ItemStack::new(Item::from_text_id(name).unwrap(), count)
+    }
+
+    fn spawn_at(
+        item_entities: &mut ItemEntities,
+        entities: &mut EntityManager,
+        x: f32,
+        stack: ItemStack,
+        pickup_delay: u64,
+    ) -> EntityId {
+        item_entities
+            .spawn(
+                entities,
+                Position::new(x, 100.0, 0.5),
+                Vector3f::zeros(),
+                stack,
+                pickup_delay,
+                0,
+            )
+            .unwrap()
+    }
+
+    // the item that the client sees
+    fn shown_item(entities: &EntityManager, id: EntityId) -> Option<ItemData> {
+        entities.get(id)?.metadata.item.clone()
+    }
+
+    fn shown(name: &str, count: u8) -> Option<ItemData> {
+        Some(ItemData {
+            item_id: Item::from_text_id(name).unwrap().id() as u32,
+            count,
+        })
+    }
+
+    fn player_box() -> Aabb {
+        Aabb::standing_at(Position::new(0.5, 100.0, 0.5), 0.6, 1.8)
+    }
+
+    #[test]
+    fn test_spawn() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let id = spawn_at(&mut item_entities, &mut entities, 0.5, stack("stone", 3), 0);
+        assert_eq!(shown_item(&entities, id), shown("stone", 3));
+
+        // there is nothing to show for an empty stack
+        let empty = item_entities.spawn(
+            &mut entities,
+            Position::new(0.5, 100.0, 0.5),
+            Vector3f::zeros(),
+            ItemStack::Empty,
+            0,
+            0,
+        );
+        assert!(empty.is_none());
+        assert_eq!(entities.len(), 1);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let small = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.5,
+            stack("stone", 10),
+            0,
+        );
+        let large = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.9,
+            stack("stone", 20),
+            0,
+        );
+        let other_item = spawn_at(&mut item_entities, &mut entities, 0.7, stack("dirt", 5), 0);
+        let far = spawn_at(&mut item_entities, &mut entities, 3.0, stack("stone", 5), 0);
+
+        // the smaller stack moves onto the larger one
+        item_entities.merge_nearby(&mut entities);
+        assert!(!entities.contains(small));
+        assert_eq!(shown_item(&entities, large), shown("stone", 30));
+        assert_eq!(shown_item(&entities, other_item), shown("dirt", 5));
+        assert_eq!(shown_item(&entities, far), shown("stone", 5));
+    }
+
+    #[test]
+    fn test_merge_up_to_full_stack() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let a = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.5,
+            stack("stone", 60),
+            0,
+        );
+        let b = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.9,
+            stack("stone", 10),
+            0,
+        );
+
+        // both items stay, as the larger one is full
+        item_entities.merge_nearby(&mut entities);
+        assert_eq!(shown_item(&entities, a), shown("stone", 64));
+        assert_eq!(shown_item(&entities, b), shown("stone", 6));
+
+        item_entities.merge_nearby(&mut entities);
+        assert_eq!(shown_item(&entities, a), shown("stone", 64));
+        assert_eq!(shown_item(&entities, b), shown("stone", 6));
+    }
+
+    #[test]
+    fn test_pickup_delay() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let mut player = PlayerState::new();
+        let id = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.5,
+            stack("stone", 3),
+            10,
+        );
+
+        assert!(item_entities
+            .pick_up(&mut entities, &player_box(), &mut player, 9)
+            .is_empty());
+        assert!(entities.contains(id));
+
+        let pickups = item_entities.pick_up(&mut entities, &player_box(), &mut player, 10);
+        assert_eq!(pickups.len(), 1);
+        assert_eq!(pickups[0].entity_id, id);
+        assert_eq!(pickups[0].count, 3);
+        assert_eq!(pickups[0].changed_slots, vec![*PLAYER_HOTBAR_SLOTS.start()]);
+        assert!(!entities.contains(id));
+        assert_eq!(player.slots[*PLAYER_HOTBAR_SLOTS.start()].count(), 3);
+    }
+
+    #[test]
+    fn test_merged_items_wait_for_the_longer_delay() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let mut player = PlayerState::new();
+        let large = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.5,
+            stack("stone", 20),
+            0,
+        );
+        spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.9,
+            stack("stone", 10),
+            40,
+        );
+        item_entities.merge_nearby(&mut entities);
+
+        assert!(item_entities
+            .pick_up(&mut entities, &player_box(), &mut player, 39)
+            .is_empty());
+        let pickups = item_entities.pick_up(&mut entities, &player_box(), &mut player, 40);
+        assert_eq!(pickups.len(), 1);
+        assert_eq!(pickups[0].entity_id, large);
+        assert_eq!(pickups[0].count, 30);
+    }
+
+    #[test]
+    fn test_partial_pickup() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let mut player = PlayerState::new();
+        // every slot is full, but one that has room for 4 more stones
+        for slot_idx in PLAYER_HOTBAR_SLOTS.chain(PLAYER_MAIN_SLOTS) {
+            player.slots[slot_idx] = stack("dirt", 64);
+        }
+        player.slots[20] = stack("stone", 60);
+        let id = spawn_at(
+            &mut item_entities,
+            &mut entities,
+            0.5,
+            stack("stone", 10),
+            0,
+        );
+
+        let pickups = item_entities.pick_up(&mut entities, &player_box(), &mut player, 0);
+        assert_eq!(pickups.len(), 1);
+        assert_eq!(pickups[0].count, 4);
+        assert_eq!(pickups[0].changed_slots, vec![20]);
+        assert_eq!(player.slots[20].count(), 64);
+
+        // the rest stays on the ground
+        assert_eq!(shown_item(&entities, id), shown("stone", 6));
+        assert!(item_entities
+            .pick_up(&mut entities, &player_box(), &mut player, 1)
+            .is_empty());
+        assert_eq!(shown_item(&entities, id), shown("stone", 6));
+    }
+
+    #[test]
+    fn test_out_of_reach() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let mut player = PlayerState::new();
+        let id = spawn_at(&mut item_entities, &mut entities, 3.0, stack("stone", 3), 0);
+
+        assert!(item_entities
+            .pick_up(&mut entities, &player_box(), &mut player, 0)
+            .is_empty());
+        assert!(entities.contains(id));
+    }
+
+    #[test]
+    fn test_despawn_old() {
+        let mut item_entities = ItemEntities::new();
+        let mut entities = EntityManager::new(1);
+        let old = spawn_at(&mut item_entities, &mut entities, 0.5, stack("stone", 3), 0);
+        let removed = spawn_at(&mut item_entities, &mut entities, 8.5, stack("dirt", 3), 0);
+
+        // items whose entity is gone are forgotten
+        entities.remove(removed);
+        item_entities.despawn_old(&mut entities, 5999);
+        assert!(entities.contains(old));
+        assert_eq!(entities.len(), 1);
+
+        item_entities.despawn_old(&mut entities, 6000);
+        assert!(!entities.contains(old));
+        assert!(entities.is_empty());
+    }
+}
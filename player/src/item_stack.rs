@@ -1,20 +1,31 @@
+use minecraft_protocol::components::slots::{Slot, SlotItem};
 use minecraft_protocol::data::items::Item;
 use minecraft_protocol::nbt::NbtTag;
+use std::collections::HashMap;
 
+// the tags of a stack in the nbt of a container
+const ID_TAG: &str = "id";
+const COUNT_TAG: &str = "Count";
+const NBT_DATA_TAG: &str = "tag";
+
+#[derive(Clone)]
 pub enum ItemStack {
     Empty,
     Simple(SimpleItemStack),
     NbtItem(NbtItem),
 }
 
+#[derive(Clone)]
 pub struct SimpleItemStack {
     id: u16,
     count: u16,
 }
 
-// always one
+// only stacks with items that have the same nbt data
+#[derive(Clone)]
 pub struct NbtItem {
     id: u16,
+    count: u16,
     nbt: NbtTag,
 }
 
@@ -36,32 +47,196 @@ impl ItemStack {
     pub fn new_nbt(source: Item, nbt: NbtTag) -> Self {
         ItemStack::NbtItem(NbtItem {
             id: source.id() as u16,
+            count: 1,
             nbt,
         })
     }
 
+    /// The stack in a slot that the client sent
+    pub fn from_slot(slot: Slot) -> Self {
+        match slot.item {
+            None => ItemStack::Empty,
+            Some(item) if item.item_count <= 0 => ItemStack::Empty,
+            Some(SlotItem {
+                item_id,
+                item_count,
+                nbt_data: NbtTag::Null,
+            }) => ItemStack::new(item_id, item_count as usize),
+            Some(SlotItem {
+                item_id,
+                item_count,
+                nbt_data,
+            }) => ItemStack::NbtItem(NbtItem {
+                id: item_id.id() as u16,
+                count: item_count as u16,
+                nbt: nbt_data,
+            }),
+        }
+    }
+
+    /// The stack in the nbt of a container, like vanilla: the name of the item, the count, and the
+    /// nbt data of the item if it has any. Unknown items result in an empty stack
+    pub fn from_nbt(tags: &HashMap<String, NbtTag>) -> Self {
+        let item = match tags.get(ID_TAG) {
+            Some(NbtTag::String(id)) => Item::from_text_id(id.trim_start_matches("minecraft:")),
+            _ => None,
+        };
+        let count = match tags.get(COUNT_TAG) {
+            Some(NbtTag::Byte(count)) => usize::try_from(*count).unwrap_or(0),
+            _ => 0,
+        };
+
+        match (item, tags.get(NBT_DATA_TAG)) {
+            (None, _) => ItemStack::Empty,
+            (Some(item), Some(nbt)) => ItemStack::new_nbt(item, nbt.clone()).with_count(count),
+            (Some(item), None) => ItemStack::one_of(item).with_count(count),
+        }
+    }
+
     // splits one item off the stack, but returns an empty stack if no item was present
     pub fn take_one(&mut self) -> ItemStack {
-        match self {
-            ItemStack::Empty => ItemStack::Empty,
-            ItemStack::Simple(s) => {
-                if s.count == 1 {
-                    std::mem::replace(self, ItemStack::Empty)
-                } else {
-                    ItemStack::Simple(s.take(1))
-                }
-            },
-            ItemStack::NbtItem(_) => std::mem::replace(self, ItemStack::Empty),
-        }
+        self.split_off(1)
     }
 
     pub fn remove_one(&mut self) {
         self.take_one();
     }
 
+    /// Splits up to `count` items off the stack
+    pub fn split_off(&mut self, count: usize) -> ItemStack {
+        if count == 0 {
+            return ItemStack::Empty;
+        }
+        if count >= self.count() {
+            return std::mem::take(self);
+        }
+
+        let split = self.with_count(count);
+        self.set_count(self.count() - count);
+        split
+    }
+
+    /// A stack of the same item, with another count
+    pub fn with_count(&self, count: usize) -> ItemStack {
+        match self {
+            _ if count == 0 => ItemStack::Empty,
+            ItemStack::Empty => ItemStack::Empty,
+            ItemStack::Simple(stack) => ItemStack::Simple(SimpleItemStack {
+                id: stack.id,
+                count: count as u16,
+            }),
+            ItemStack::NbtItem(item) => ItemStack::NbtItem(NbtItem {
+                id: item.id,
+                count: count as u16,
+                nbt: item.nbt.clone(),
+            }),
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         matches!(self, ItemStack::Empty)
     }
+
+    pub fn item_type(&self) -> Option<Item> {
+        match self {
+            ItemStack::Empty => None,
+            ItemStack::Simple(stack) => Some(stack.item_type()),
+            ItemStack::NbtItem(item) => Some(item.item_type()),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        match self {
+            ItemStack::Empty => 0,
+            ItemStack::Simple(stack) => stack.count as usize,
+            ItemStack::NbtItem(item) => item.count as usize,
+        }
+    }
+
+    /// The most items that a stack of this item can hold, from the item registry
+    pub fn max_stack_size(&self) -> usize {
+        self.item_type()
+            .map_or(0, |item| item.stack_size() as usize)
+    }
+
+    /// Items with nbt data only stack with items that have the same nbt data
+    pub fn can_stack_with(&self, other: &ItemStack) -> bool {
+        match (self, other) {
+            (ItemStack::Simple(stack), ItemStack::Simple(other)) => stack.id == other.id,
+            (ItemStack::NbtItem(item), ItemStack::NbtItem(other)) => {
+                item.id == other.id && item.nbt == other.nbt
+            },
+            _ => false,
+        }
+    }
+
+    pub fn to_slot(&self) -> Slot {
+        let item = match self {
+            ItemStack::Empty => None,
+            ItemStack::Simple(stack) => Some(SlotItem {
+                item_id: stack.item_type(),
+                item_count: stack.count as i8,
+                nbt_data: NbtTag::Null,
+            }),
+            ItemStack::NbtItem(item) => Some(SlotItem {
+                item_id: item.item_type(),
+                item_count: item.count as i8,
+                nbt_data: item.nbt.clone(),
+            }),
+        };
+        Slot { item }
+    }
+
+    /// The nbt of the stack in a container, see `from_nbt`. Returns None for an empty stack
+    pub fn to_nbt(&self) -> Option<HashMap<String, NbtTag>> {
+        let item = self.item_type()?;
+
+        let mut tags = HashMap::new();
+        tags.insert(
+            String::from(ID_TAG),
+            NbtTag::String(format!("minecraft:{}", item.text_id())),
+        );
+        tags.insert(String::from(COUNT_TAG), NbtTag::Byte(self.count() as i8));
+        if let ItemStack::NbtItem(item) = self {
+            tags.insert(String::from(NBT_DATA_TAG), item.nbt.clone());
+        }
+        Some(tags)
+    }
+
+    /// Moves as many items of `other` onto this stack as fit, if the items can stack.
+    /// Returns true if any item was moved
+    pub fn merge_from(&mut self, other: &mut ItemStack) -> bool {
+        self.merge_up_to(other, usize::MAX)
+    }
+
+    /// Like `merge_from`, but moves at most `limit` items
+    pub fn merge_up_to(&mut self, other: &mut ItemStack, limit: usize) -> bool {
+        if other.is_empty() || limit == 0 {
+            return false;
+        }
+        if self.is_empty() {
+            *self = other.split_off(limit.min(other.max_stack_size()));
+            return !self.is_empty();
+        }
+        if !self.can_stack_with(other) {
+            return false;
+        }
+
+        let space = self.max_stack_size().saturating_sub(self.count());
+        let moved = other.split_off(space.min(limit)).count();
+        self.set_count(self.count() + moved);
+        moved > 0
+    }
+
+    // an empty stack stays empty
+    fn set_count(&mut self, count: usize) {
+        match self {
+            ItemStack::Empty => {},
+            _ if count == 0 => *self = ItemStack::Empty,
+            ItemStack::Simple(stack) => stack.count = count as u16,
+            ItemStack::NbtItem(item) => item.count = count as u16,
+        }
+    }
 }
 
 impl Default for ItemStack {
@@ -87,9 +262,19 @@ impl SimpleItemStack {
         }
     }
 
-    pub fn add(&mut self, other: SimpleItemStack) {
+    /// Adds as many items of `other` as fit into the stack, and returns the rest
+    pub fn add(&mut self, other: SimpleItemStack) -> Option<SimpleItemStack> {
         assert_eq!(self.id, other.id);
-        self.count += other.count;
+
+        let max_stack_size = self.item_type().stack_size() as u16;
+        let added = other.count.min(max_stack_size.saturating_sub(self.count));
+        self.count += added;
+
+        let rest = other.count - added;
+        (rest > 0).then_some(SimpleItemStack {
+            id: self.id,
+            count: rest,
+        })
     }
 
     pub fn item_type(&self) -> Item {
@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minecraft_protocol::data::items::Item;
+    use minecraft_protocol::nbt::NbtTag;
+
+    use crate::item_stack::ItemStack;
+
+    fn stack(name: &str, count: usize) -> ItemStack {
+        ItemStack::new(Item::from_text_id(name).unwrap(), count)
+    }
+
+    fn is_stack_of(stack: &ItemStack, name: &str, count: usize) -> bool {
+        let item = Item::from_text_id(name).unwrap();
+        stack.item_type().map(|item| item.id()) == Some(item.id()) && stack.count() == count
+    }
+
+    #[test]
+    fn test_merge_up_to_same_item() {
+        let mut target = stack("stone", 50);
+        let mut source = stack("stone", 20);
+        assert!(target.merge_up_to(&mut source, 3));
+        assert!(is_stack_of(&target, "stone", 53));
+        assert!(is_stack_of(&source, "stone", 17));
+
+        // the stack size limits the move
+        assert!(target.merge_up_to(&mut source, 100));
+        assert!(is_stack_of(&target, "stone", 64));
+        assert!(is_stack_of(&source, "stone", 6));
+
+        // nothing moves onto a full stack
+        assert!(!target.merge_up_to(&mut source, 100));
+        assert!(is_stack_of(&source, "stone", 6));
+    }
+
+    #[test]
+    fn test_merge_up_to_empties_source() {
+        let mut target = stack("stone", 10);
+        let mut source = stack("stone", 5);
+        assert!(target.merge_up_to(&mut source, 5));
+        assert!(is_stack_of(&target, "stone", 15));
+        assert!(source.is_empty());
+
+        // nothing to move
+        assert!(!target.merge_up_to(&mut source, 5));
+        assert!(is_stack_of(&target, "stone", 15));
+    }
+
+    #[test]
+    fn test_merge_up_to_into_empty() {
+        let mut target = ItemStack::Empty;
+        let mut source = stack("stone", 40);
+        assert!(target.merge_up_to(&mut source, 10));
+        assert!(is_stack_of(&target, "stone", 10));
+        assert!(is_stack_of(&source, "stone", 30));
+
+        // an empty stack takes at most a full stack
+        let mut target = ItemStack::Empty;
+        let mut source = stack("ender_pearl", 40);
+        assert!(target.merge_up_to(&mut source, usize::MAX));
+        assert!(is_stack_of(&target, "ender_pearl", 16));
+        assert!(is_stack_of(&source, "ender_pearl", 24));
+    }
+
+    #[test]
+    fn test_merge_up_to_nothing() {
+        let mut target = stack("stone", 10);
+        let mut source = stack("stone", 10);
+        assert!(!target.merge_up_to(&mut source, 0));
+        assert!(is_stack_of(&target, "stone", 10));
+        assert!(is_stack_of(&source, "stone", 10));
+
+        // other items do not stack
+        let mut source = stack("dirt", 10);
+        assert!(!target.merge_up_to(&mut source, 10));
+        assert!(is_stack_of(&target, "stone", 10));
+        assert!(is_stack_of(&source, "dirt", 10));
+
+        let mut target = ItemStack::Empty;
+        let mut source = ItemStack::Empty;
+        assert!(!target.merge_up_to(&mut source, 10));
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn test_merge_up_to_nbt() {
+        let stone = Item::from_text_id("stone").unwrap();
+        let mut target = ItemStack::new_nbt(stone, NbtTag::Int(1));
+        let mut other_nbt = ItemStack::new_nbt(stone, NbtTag::Int(2));
+        let mut same_nbt = ItemStack::new_nbt(stone, NbtTag::Int(1)).with_count(3);
+        let mut without_nbt = stack("stone", 3);
+
+        assert!(!target.merge_up_to(&mut other_nbt, 10));
+        assert!(!target.merge_up_to(&mut without_nbt, 10));
+        assert!(target.merge_up_to(&mut same_nbt, 2));
+        assert_eq!(target.count(), 3);
+        assert_eq!(same_nbt.count(), 1);
+        assert!(matches!(target, ItemStack::NbtItem(_)));
+    }
+
+    #[test]
+    fn test_nbt_round_trip() {
+        let stone = stack("stone", 10);
+        let tags = stone.to_nbt().unwrap();
+        assert!(tags.get("id") == Some(&NbtTag::String(String::from("minecraft:stone"))));
+        assert!(tags.get("Count") == Some(&NbtTag::Byte(10)));
+        assert!(!tags.contains_key("tag"));
+        let read = ItemStack::from_nbt(&tags);
+        assert!(is_stack_of(&read, "stone", 10));
+        assert!(matches!(read, ItemStack::Simple(_)));
+
+        let mut damage = HashMap::new();
+        damage.insert(String::from("Damage"), NbtTag::Int(12));
+        let sword = ItemStack::new_nbt(
+            Item::from_text_id("diamond_sword").unwrap(),
+            NbtTag::Compound(damage.clone()),
+        );
+        let tags = sword.to_nbt().unwrap();
+        assert!(tags.get("tag") == Some(&NbtTag::Compound(damage)));
+        let read = ItemStack::from_nbt(&tags);
+        assert!(is_stack_of(&read, "diamond_sword", 1));
+        assert!(read.can_stack_with(&sword));
+        assert!(read.to_nbt() == Some(tags));
+
+        assert!(ItemStack::Empty.to_nbt().is_none());
+    }
+
+    #[test]
+    fn test_from_nbt() {
+        let tags = |id: &str, count: NbtTag| {
+            let mut tags = HashMap::new();
+            tags.insert(String::from("id"), NbtTag::String(String::from(id)));
+            tags.insert(String::from("Count"), count);
+            tags
+        };
+
+        // the namespace is optional
+        assert!(is_stack_of(
+            &ItemStack::from_nbt(&tags("dirt", NbtTag::Byte(3))),
+            "dirt",
+            3
+        ));
+        assert!(ItemStack::from_nbt(&tags("minecraft:no_such_item", NbtTag::Byte(3))).is_empty());
+        assert!(ItemStack::from_nbt(&tags("minecraft:dirt", NbtTag::Byte(0))).is_empty());
+        assert!(ItemStack::from_nbt(&tags("minecraft:dirt", NbtTag::Byte(-3))).is_empty());
+        assert!(ItemStack::from_nbt(&tags("minecraft:dirt", NbtTag::Int(3))).is_empty());
+        assert!(ItemStack::from_nbt(&HashMap::new()).is_empty());
+    }
+}
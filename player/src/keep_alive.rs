@@ -0,0 +1,78 @@
+// Keep-alives, see https://wiki.vg/Protocol#Clientbound_Keep_Alive_(play)
+// We send an id every few seconds, and the client echoes it. The vanilla client disconnects when
+// it does not hear from us; we disconnect a client that does not answer in time.
+
+use std::time::{Duration, Instant};
+
+const KEEP_ALIVE_PERIOD: Duration = Duration::from_secs(5);
+// like vanilla: the time that the client has to echo the id
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub enum KeepAliveAction {
+    Wait,
+    /// send the id to the client
+    Send(u64),
+    /// the client did not echo the last id in time
+    TimedOut,
+}
+
+/// Schedules the keep-alives of one client, and measures its latency with them
+pub struct KeepAlive {
+    next_id: u64,
+    // the id that the client has not echoed yet, and when it was sent
+    pending: Option<(u64, Instant)>,
+    last_sent: Instant,
+    latency: Duration,
+}
+
+impl KeepAlive {
+    pub fn new(now: Instant) -> Self {
+        KeepAlive {
+            next_id: 0,
+            pending: None,
+            last_sent: now,
+            latency: Duration::ZERO,
+        }
+    }
+
+    /// Call once per tick
+    pub fn tick(&mut self, now: Instant) -> KeepAliveAction {
+        if let Some((_, sent)) = self.pending {
+            if now.saturating_duration_since(sent) >= KEEP_ALIVE_TIMEOUT {
+                return KeepAliveAction::TimedOut;
+            }
+            return KeepAliveAction::Wait;
+        }
+
+        if now.saturating_duration_since(self.last_sent) < KEEP_ALIVE_PERIOD {
+            return KeepAliveAction::Wait;
+        }
+
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.pending = Some((id, now));
+        self.last_sent = now;
+        KeepAliveAction::Send(id)
+    }
+
+    /// The client echoed an id at `received`.
+    /// Returns false if we did not send it, or it was echoed already
+    pub fn receive(&mut self, id: u64, received: Instant) -> bool {
+        match self.pending {
+            Some((pending_id, sent)) if pending_id == id => {
+                self.pending = None;
+                // like vanilla: each round trip counts a quarter, so that the latency changes
+                // smoothly
+                let round_trip = received.saturating_duration_since(sent);
+                self.latency = (self.latency * 3 + round_trip) / 4;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// The smoothed round trip time of the keep-alives
+    pub fn latency(&self) -> Duration {
+        self.latency
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::keep_alive::{KeepAlive, KeepAliveAction};
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn is_wait(action: KeepAliveAction) -> bool {
+        matches!(action, KeepAliveAction::Wait)
+    }
+
+    fn sent_id(action: KeepAliveAction) -> Option<u64> {
+        match action {
+            KeepAliveAction::Send(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_period() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        assert!(is_wait(keep_alive.tick(start)));
+        assert!(is_wait(keep_alive.tick(start + millis(4999))));
+        assert_eq!(sent_id(keep_alive.tick(start + millis(5000))), Some(0));
+
+        // nothing is sent while the client did not echo the id
+        assert!(is_wait(keep_alive.tick(start + millis(5050))));
+        assert!(is_wait(keep_alive.tick(start + millis(10000))));
+        assert!(keep_alive.receive(0, start + millis(10100)));
+
+        // the next id follows the period after the last one was sent
+        assert_eq!(sent_id(keep_alive.tick(start + millis(10100))), Some(1));
+        assert!(keep_alive.receive(1, start + millis(10200)));
+        assert!(is_wait(keep_alive.tick(start + millis(15099))));
+        assert_eq!(sent_id(keep_alive.tick(start + millis(15100))), Some(2));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        let sent = start + millis(5000);
+        assert_eq!(sent_id(keep_alive.tick(sent)), Some(0));
+        assert!(is_wait(keep_alive.tick(sent + millis(14999))));
+        assert!(matches!(
+            keep_alive.tick(sent + millis(15000)),
+            KeepAliveAction::TimedOut
+        ));
+        // until the client echoes the id
+        assert!(matches!(
+            keep_alive.tick(sent + millis(20000)),
+            KeepAliveAction::TimedOut
+        ));
+
+        // an echo in time prevents the timeout
+        let mut keep_alive = KeepAlive::new(start);
+        assert_eq!(sent_id(keep_alive.tick(sent)), Some(0));
+        assert!(keep_alive.receive(0, sent + millis(14999)));
+        assert!(!matches!(
+            keep_alive.tick(sent + millis(15000)),
+            KeepAliveAction::TimedOut
+        ));
+    }
+
+    #[test]
+    fn test_unknown_id() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        // nothing was sent yet
+        assert!(!keep_alive.receive(0, start));
+
+        let sent = start + millis(5000);
+        assert_eq!(sent_id(keep_alive.tick(sent)), Some(0));
+        assert!(!keep_alive.receive(1, sent + millis(10)));
+        assert!(!keep_alive.receive(u64::MAX, sent + millis(10)));
+        // the pending id is still accepted
+        assert!(keep_alive.receive(0, sent + millis(20)));
+    }
+
+    #[test]
+    fn test_duplicate_id() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        let sent = start + millis(5000);
+        assert_eq!(sent_id(keep_alive.tick(sent)), Some(0));
+        assert!(keep_alive.receive(0, sent + millis(100)));
+        assert!(!keep_alive.receive(0, sent + millis(200)));
+        // the duplicate does not count as a round trip
+        assert_eq!(keep_alive.latency(), millis(25));
+
+        // nor does an earlier id, once the next one was sent
+        assert_eq!(sent_id(keep_alive.tick(sent + millis(5100))), Some(1));
+        assert!(!keep_alive.receive(0, sent + millis(5200)));
+        assert!(keep_alive.receive(1, sent + millis(5200)));
+    }
+
+    #[test]
+    fn test_latency() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        assert_eq!(keep_alive.latency(), Duration::ZERO);
+
+        // each round trip counts a quarter
+        let mut sent = start + millis(5000);
+        let mut expected = Duration::ZERO;
+        for _ in 0..3 {
+            let id = sent_id(keep_alive.tick(sent)).unwrap();
+            assert!(keep_alive.receive(id, sent + millis(400)));
+            expected = (expected * 3 + millis(400)) / 4;
+            assert_eq!(keep_alive.latency(), expected);
+            sent += millis(5400);
+        }
+        assert_eq!(
+            keep_alive.latency(),
+            millis(231) + Duration::from_micros(250)
+        );
+
+        // a fast round trip lowers it smoothly
+        let id = sent_id(keep_alive.tick(sent)).unwrap();
+        assert!(keep_alive.receive(id, sent));
+        assert_eq!(keep_alive.latency(), expected * 3 / 4);
+    }
+}
@@ -1,31 +1,77 @@
 #![allow(dead_code)]
 
 extern crate zmq;
-pub mod entities;
+mod chat;
+#[cfg(test)]
+mod chunk_streamer_tests;
+mod commands;
+mod containers;
+#[cfg(test)]
+mod containers_tests;
 pub mod game_event;
+#[cfg(test)]
+mod game_event_tests;
 mod game_logic;
 pub mod game_loop;
+mod inventory;
+#[cfg(test)]
+mod inventory_tests;
+mod item_entities;
+#[cfg(test)]
+mod item_entities_tests;
 mod item_stack;
+#[cfg(test)]
+mod item_stack_tests;
+mod keep_alive;
+#[cfg(test)]
+mod keep_alive_tests;
 pub mod minecraft_connection;
 mod player_handler;
 mod player_state;
+#[cfg(test)]
+mod player_state_tests;
 pub mod voxels;
 mod player_events;
+mod player_movement;
+#[cfg(test)]
+mod player_movement_tests;
+mod player_session;
+#[cfg(test)]
+mod session_tests;
+mod tool_requirements;
+#[cfg(test)]
+mod tool_requirements_tests;
+#[cfg(test)]
+mod world_tests;
 
 use crate::game_loop::GameCommand;
-use crate::minecraft_connection::client_connection::ClientSendCommand;
 use crate::minecraft_connection::client_connection::McClientSender;
-use crate::player_handler::PlayerCommand;
+use crate::minecraft_connection::login::CommunicationError;
+use crate::minecraft_connection::network::McStream;
+use crate::player_handler::PlayerHandler;
+use crate::player_session::{PlayerSession, SessionId};
 use crate::player_state::PlayerState;
+use crate::voxels::chunk_streamer::{ChunkStreamer, INITIAL_CHUNKS_PER_TICK};
+use crate::voxels::column_loader::ColumnLoader;
 use minecraft_connection::{
-    client_connection::McClientReceiver, player_character,
+    authentication::{MojangAuthenticator, OnlineMode},
+    client_connection::{McClientReceiver, ReceiveEnd},
+    player_character::PlayerCharacter,
     player_connect_handler::PLayerConnectHandler,
 };
 use sol_address_server::static_addresses;
+use sol_entity_lib::entity::PLAYER_CHARACTER_IDS;
 use sol_log_server::logger_mt::LoggerMt;
-use sol_voxel_lib::vector_alias::{Position, Rotation};
+use sol_log_server::Severity;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Position, Rotation};
+use std::sync::{mpsc, Arc};
 use std::thread;
 
+const WORLD_SERVER_TIMEOUT_MS: i32 = 1000;
+const CHAT_SERVER_TIMEOUT_MS: i32 = 1000;
+// like vanilla: packets of at least this many bytes are compressed
+const DEFAULT_COMPRESSION_THRESHOLD: i32 = 256;
+
 /**
  * OK, here's what happens when a player server boots.
  *
@@ -37,7 +83,11 @@ use std::thread;
  * We connect to the "Player position server" to get a list of nearby players (their UUIDs)
  * We connect to the "Load balancer" to query nearby chunks and entities.
  * We send this information to the java client.
- * We start the main game loop
+ * The player joins the main game loop, which is shared by all players of this server.
+ * Each client gets its own session, and any number of clients may be connected at once
+ *
+ * Usage: `sol_player [<compression threshold> [online]]`, a negative threshold disables compression.
+ * In online mode, players are authenticated with the session service of Mojang
  */
 fn main() {
     let context = zmq::Context::new();
@@ -50,85 +100,242 @@ fn main() {
 
     let registries = minecraft_vanilla::registries::get_registries();
 
-    let (connection, client_socket) = PLayerConnectHandler::await_connect().unwrap();
+    let compression_threshold = std::env::args()
+        .nth(1)
+        .and_then(|threshold| threshold.parse::<i32>().ok())
+        .unwrap_or(DEFAULT_COMPRESSION_THRESHOLD);
+    let compression_threshold = usize::try_from(compression_threshold).ok();
+
+    let online_mode = match std::env::args().nth(2).as_deref() {
+        Some("online") => Some(
+            OnlineMode::new(Box::new(MojangAuthenticator {})).expect("Could not generate key pair"),
+        ),
+        _ => None,
+    };
+
+    let connect_handler = Arc::new(
+        PLayerConnectHandler::bind(compression_threshold, online_mode).expect("Failed to listen"),
+    );
 
     let world_server_socket = context.socket(zmq::REQ).unwrap();
     world_server_socket
         .connect(static_addresses::WORLD_SERVER)
         .unwrap();
+    // the game loop must not hang when the world server is unavailable
+    world_server_socket
+        .set_rcvtimeo(WORLD_SERVER_TIMEOUT_MS)
+        .unwrap();
+    world_server_socket.set_req_relaxed(true).unwrap();
+    world_server_socket.set_req_correlate(true).unwrap();
+    // columns are loaded on their own socket, so that the game loop does not wait for them
+    let column_loader =
+        ColumnLoader::new(context.clone()).expect("Could not connect to world server");
+
+    let chat_server_socket = context.socket(zmq::REQ).unwrap();
+    chat_server_socket
+        .connect(static_addresses::CHAT_SERVER)
+        .unwrap();
+    // nor when the chat server is unavailable
+    chat_server_socket
+        .set_rcvtimeo(CHAT_SERVER_TIMEOUT_MS)
+        .unwrap();
+    chat_server_socket.set_req_relaxed(true).unwrap();
+    chat_server_socket.set_req_correlate(true).unwrap();
 
     // TODO get world data from world_server_socket
-    let mut world = voxels::world::World::new();
-    let character = player_character::PlayerCharacter {
-        entity_id: 0,
-        uuid: [0; 4],
-        position: Position::new(0.0, 60.0, 0.0),
-        head_rotation: Rotation::identity(),
-    };
+    let world =
+        voxels::world::World::new(registries.block_properties(), registries.block_states());
 
-    let player_data_socket = context.socket(zmq::REQ).unwrap();
-    player_data_socket
-        .connect(static_addresses::PLAYER_DATA_SERVER)
-        .unwrap();
+    // all players share the game loop, and see the same world
+    let (game_command_channel, game_command_receiver) = mpsc::channel();
+    let mut game_loop = game_loop::GameLoop::new(
+        world,
+        logger.clone(),
+        game_command_receiver,
+        registries,
+        world_server_socket,
+        column_loader,
+        chat_server_socket,
+    );
+    let game_thread = thread::spawn(move || game_loop.run());
 
-    // TODO get player data from player_data_server
-    let player_state = PlayerState::new();
+    logger.send_status("Player server online");
+
+    // the session ids are also the entity ids of the player characters; clients that only ask
+    // for the status of the server use up an id as well
+    for session_id in PLAYER_CHARACTER_IDS {
+        let client_socket = match connect_handler.accept() {
+            Ok(client_socket) => client_socket,
+            Err(error) => {
+                logger.log(
+                    Severity::EnvironmentIssue,
+                    &format!("Could not accept connection: {error}"),
+                );
+                continue;
+            },
+        };
+
+        let connect_handler = Arc::clone(&connect_handler);
+        let context = context.clone();
+        let logger = logger.clone();
+        let game_command_channel = game_command_channel.clone();
+        thread::spawn(move || {
+            let result = run_session(
+                session_id,
+                client_socket,
+                &connect_handler,
+                &context,
+                &logger,
+                game_command_channel,
+            );
+            if let Err(error) = result {
+                logger.log(
+                    Severity::RecoverableError,
+                    &format!("Session {session_id} ended with error: {error:?}"),
+                );
+            }
+        });
+    }
+
+    // initiate stop
+    game_command_channel.send(GameCommand::Stop).unwrap();
+
+    // await stop
+    game_thread.join().unwrap();
+
+    logger.send_status("Player server offline");
+}
+
+// handles the client of one player, from its handshake until it disconnects
+fn run_session(
+    session_id: SessionId,
+    mut client_socket: McStream,
+    connect_handler: &PLayerConnectHandler,
+    context: &zmq::Context,
+    logger: &LoggerMt,
+    game_command_channel: mpsc::Sender<GameCommand>,
+) -> Result<(), CommunicationError> {
+    let Some(connection) = connect_handler.handshake(&mut client_socket)? else {
+        return Ok(());
+    };
+
+    let username = connection.username.clone();
+    let uuid = connection.uuid;
+    let mut character = spawn_character(session_id, &username, uuid);
 
     // start player join
     let player_connection_data =
-        PLayerConnectHandler::send_player_join(connection, &character, &mut world, client_socket)
-            .expect("Could not send player join packages");
+        PLayerConnectHandler::send_player_join(connection, &character, client_socket, logger)?;
+    let mut client_socket = player_connection_data.socket;
+    let mut render_distance = player_connection_data.render_distance;
 
-    logger.send_status("Player online");
+    loop {
+        let end = play(
+            session_id,
+            character,
+            client_socket.try_clone()?,
+            render_distance,
+            context,
+            logger,
+            &game_command_channel,
+        )?;
 
-    let (game_command_channel, game_command_receiver) = std::sync::mpsc::channel();
-    let (client_comm_channel, client_comm_receiver) = std::sync::mpsc::channel();
-    let (player_comm_channel, player_comm_receiver) = std::sync::mpsc::channel();
+        match end {
+            ReceiveEnd::Disconnected => return Ok(()),
+            // the client is configured again, and joins a new session
+            ReceiveEnd::Configuration => {
+                character = spawn_character(session_id, &username, uuid);
+                let client_information = PLayerConnectHandler::send_player_rejoin(
+                    &character,
+                    &mut client_socket,
+                    logger,
+                )?;
+                if let Some(client_information) = client_information {
+                    render_distance = client_information.render_distance;
+                }
+            },
+        }
+    }
+}
 
-    let mut game_loop = game_loop::GameLoop::new(
-        world,
-        logger.clone(),
-        game_command_receiver,
-        client_comm_channel.clone(),
-        registries.clone(),
-    );
-    let mut player_loop = player_handler::PlayerHandler::new(
-        player_state,
-        logger.clone(),
-        player_comm_receiver,
-        client_comm_channel.clone(),
-        game_command_channel.clone(),
-        registries,
+// TODO get the character from the player data server
+fn spawn_character(session_id: SessionId, username: &str, uuid: u128) -> PlayerCharacter {
+    PlayerCharacter {
+        entity_id: session_id,
+        uuid,
+        name: String::from(username),
+        position: Position::new(0.0, 60.0, 0.0),
+        head_rotation: Rotation::identity(),
+        on_ground: false,
+    }
+}
+
+// adds the session of the player to the game loop, until the client leaves the play phase
+fn play(
+    session_id: SessionId,
+    character: PlayerCharacter,
+    client_socket: McStream,
+    render_distance: usize,
+    context: &zmq::Context,
+    logger: &LoggerMt,
+    game_command_channel: &mpsc::Sender<GameCommand>,
+) -> Result<ReceiveEnd, CommunicationError> {
+    // TODO get player data from player_data_server
+    let player_state = PlayerState::new();
+
+    // the game loop subscribes to the chat of the column that the player is in
+    let chat_subscriber_socket = context
+        .socket(zmq::SUB)
+        .and_then(|socket| {
+            socket.connect(static_addresses::CHAT_SERVER_PUBLISH)?;
+            Ok(socket)
+        })
+        .map_err(|e| CommunicationError::InternalError(format!("{e:?}")))?;
+
+    // the game loop sends the columns around the player
+    let chunk_streamer = ChunkStreamer::new(
+        ChunkColumnCoordinate::containing_position(&character.position),
+        render_distance,
+        INITIAL_CHUNKS_PER_TICK,
+        Vec::new(),
     );
+
+    let (client_comm_channel, client_comm_receiver) = mpsc::channel();
     let mut client_sender = McClientSender::new(
-        player_connection_data.socket.try_clone().unwrap(),
+        client_socket.try_clone()?,
         logger.clone(),
         client_comm_receiver,
     );
     let mut client_receiver = McClientReceiver::new(
-        player_connection_data.socket,
+        client_socket,
         logger.clone(),
         game_command_channel.clone(),
-        player_comm_channel.clone(),
+        session_id,
     );
 
-    let connection_send_thread = thread::spawn(move || client_sender.execute_send());
-    let connection_receive_thread = thread::spawn(move || client_receiver.execute_receive());
-    let player_thread = thread::spawn(move || player_loop.run());
-    let game_thread = thread::spawn(move || game_loop.run());
-
-    // when the connection is closed, the game stops
-    connection_receive_thread.join().unwrap();
+    let session = PlayerSession::new(
+        session_id,
+        logger.clone(),
+        client_comm_channel,
+        PlayerHandler::new(player_state, logger.clone()),
+        character,
+        chunk_streamer,
+        chat_subscriber_socket,
+    );
+    game_command_channel
+        .send(GameCommand::AddPlayer(Box::new(session)))
+        .map_err(|e| CommunicationError::InternalError(e.to_string()))?;
 
-    // initiate stop
-    game_command_channel.send(GameCommand::Stop).unwrap();
-    player_comm_channel.send(PlayerCommand::Stop).unwrap();
-    client_comm_channel.send(ClientSendCommand::Stop).unwrap();
+    let connection_send_thread = thread::spawn(move || client_sender.execute_send());
 
-    // await stop
-    connection_send_thread.join().unwrap();
-    game_thread.join().unwrap();
-    player_thread.join().unwrap();
+    // when the connection is closed, the session ends
+    let end = client_receiver.execute_receive();
 
-    logger.send_status("Player offline");
+    // the sender stops when the game loop drops the session, along with its channel
+    let removed = game_command_channel.send(GameCommand::RemovePlayer(session_id));
+    connection_send_thread
+        .join()
+        .map_err(|_| CommunicationError::InternalError(String::from("Sender panicked")))?;
+    removed.map_err(|e| CommunicationError::InternalError(e.to_string()))?;
+    Ok(end)
 }
@@ -1,5 +1,18 @@
+pub mod authentication;
+#[cfg(test)]
+mod authentication_tests;
+pub mod chunk_data;
 pub mod client_connection;
-mod login;
+pub mod configuration;
+#[cfg(test)]
+mod configuration_tests;
+pub mod coordinates;
+pub mod entity_tracker;
+#[cfg(test)]
+mod entity_tracker_tests;
+pub mod login;
 pub mod network;
+#[cfg(test)]
+mod network_tests;
 pub mod player_character;
 pub mod player_connect_handler;
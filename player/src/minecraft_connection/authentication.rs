@@ -0,0 +1,259 @@
+// Online mode, see https://wiki.vg/Protocol_Encryption
+// The client proves to the session service that it owns the account, and we ask the service
+// whether it did. The shared secret of the handshake encrypts the rest of the connection.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::login::CommunicationError;
+use super::network::{self, McStream};
+use minecraft_protocol::packets::{self as mc_packets, Array};
+use rand::RngCore;
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+// like vanilla
+const KEY_BITS: usize = 1024;
+const VERIFY_TOKEN_LENGTH: usize = 4;
+// AES-128
+const SHARED_SECRET_LENGTH: usize = 16;
+// vanilla servers send an empty id since 1.7
+const SERVER_ID: &str = "";
+const SESSION_SERVICE_URL: &str = "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+
+#[derive(Debug)]
+pub enum AuthenticationError {
+    /// the client did not join through the session service
+    NotAuthenticated,
+    /// the client answered the encryption request with an invalid secret or token
+    InvalidResponse(String),
+    /// the session service could not be asked
+    ServiceUnavailable(String),
+    KeyError(String),
+}
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthenticationError::NotAuthenticated => write!(f, "not authenticated"),
+            AuthenticationError::InvalidResponse(reason) => write!(f, "invalid response: {reason}"),
+            AuthenticationError::ServiceUnavailable(reason) => {
+                write!(f, "session service unavailable: {reason}")
+            },
+            AuthenticationError::KeyError(reason) => write!(f, "key error: {reason}"),
+        }
+    }
+}
+
+/// The account of a player, as the session service knows it
+pub struct AuthenticatedProfile {
+    pub username: String,
+    pub uuid: u128,
+}
+
+/// Asks whether a player joined the server, with the hash that the client sent to the service
+pub trait SessionAuthenticator: Send + Sync {
+    fn has_joined(
+        &self,
+        username: &str,
+        server_hash: &str,
+    ) -> Result<AuthenticatedProfile, AuthenticationError>;
+}
+
+/// The session service of Mojang, like vanilla
+pub struct MojangAuthenticator {}
+
+#[derive(Deserialize)]
+struct HasJoinedResponse {
+    // the uuid in hexadecimal, without dashes
+    id: String,
+    name: String,
+}
+
+impl SessionAuthenticator for MojangAuthenticator {
+    fn has_joined(
+        &self,
+        username: &str,
+        server_hash: &str,
+    ) -> Result<AuthenticatedProfile, AuthenticationError> {
+        let response = ureq::get(SESSION_SERVICE_URL)
+            .query("username", username)
+            .query("serverId", server_hash)
+            .call()
+            .map_err(|e| AuthenticationError::ServiceUnavailable(e.to_string()))?;
+
+        // the service answers "204 No Content" when the player did not join
+        if response.status() != 200 {
+            return Err(AuthenticationError::NotAuthenticated);
+        }
+
+        let body = response
+            .into_string()
+            .map_err(|e| AuthenticationError::ServiceUnavailable(e.to_string()))?;
+        let profile: HasJoinedResponse = serde_json::from_str(&body)
+            .map_err(|e| AuthenticationError::ServiceUnavailable(e.to_string()))?;
+        let uuid = u128::from_str_radix(&profile.id, 16)
+            .map_err(|e| AuthenticationError::ServiceUnavailable(e.to_string()))?;
+
+        Ok(AuthenticatedProfile {
+            username: profile.name,
+            uuid,
+        })
+    }
+}
+
+/// Stands in for the session service: accepts the players that were added to it.
+/// For tests, and for servers without access to the session service
+#[derive(Default)]
+pub struct LocalAuthenticator {
+    profiles: HashMap<String, u128>,
+}
+
+impl LocalAuthenticator {
+    pub fn new() -> Self {
+        LocalAuthenticator {
+            profiles: HashMap::new(),
+        }
+    }
+
+    pub fn add_profile(&mut self, username: &str, uuid: u128) {
+        self.profiles.insert(String::from(username), uuid);
+    }
+}
+
+impl SessionAuthenticator for LocalAuthenticator {
+    fn has_joined(
+        &self,
+        username: &str,
+        _server_hash: &str,
+    ) -> Result<AuthenticatedProfile, AuthenticationError> {
+        self.profiles
+            .get(username)
+            .map(|&uuid| AuthenticatedProfile {
+                username: String::from(username),
+                uuid,
+            })
+            .ok_or(AuthenticationError::NotAuthenticated)
+    }
+}
+
+/// The key pair of the server, and the service that authenticates the players.
+/// Like vanilla, the key pair is generated once, when the server starts
+pub struct OnlineMode {
+    private_key: RsaPrivateKey,
+    // in the DER encoding that the client expects
+    public_key: Vec<u8>,
+    authenticator: Box<dyn SessionAuthenticator>,
+}
+
+impl OnlineMode {
+    pub fn new(authenticator: Box<dyn SessionAuthenticator>) -> Result<Self, AuthenticationError> {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), KEY_BITS)
+            .map_err(|e| AuthenticationError::KeyError(e.to_string()))?;
+        let public_key = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .map_err(|e| AuthenticationError::KeyError(e.to_string()))?
+            .into_vec();
+
+        Ok(OnlineMode {
+            private_key,
+            public_key,
+            authenticator,
+        })
+    }
+
+    /// Exchanges the shared secret with the client, and enables encryption on the stream.
+    /// Returns the account of the player, as the session service knows it
+    pub fn authenticate(
+        &self,
+        stream: &mut McStream,
+        username: &str,
+    ) -> Result<AuthenticatedProfile, CommunicationError> {
+        let mut verify_token = [0; VERIFY_TOKEN_LENGTH];
+        rand::thread_rng().fill_bytes(&mut verify_token);
+
+        // Send encryption request
+        let encryption_request = mc_packets::login::ClientboundPacket::EncryptionRequest {
+            server_id: SERVER_ID,
+            public_key: Array::from(self.public_key.clone()),
+            verify_token: Array::from(verify_token.to_vec()),
+        };
+        network::send_packet(stream, encryption_request)?;
+        println!("EncryptionRequest sent");
+
+        // Receive encryption response
+        let mut buffer = Vec::new();
+        let packet: mc_packets::login::ServerboundPacket =
+            network::receive_packet(stream, &mut buffer)?;
+        let mc_packets::login::ServerboundPacket::EncryptionResponse {
+            shared_secret,
+            verify_token: encrypted_verify_token,
+        } = packet
+        else {
+            return Err(CommunicationError::wrong_package(
+                "EncryptionResponse",
+                packet,
+            ));
+        };
+        println!("EncryptionResponse received");
+
+        let shared_secret = self.decrypt(&shared_secret.items)?;
+        if shared_secret.len() != SHARED_SECRET_LENGTH {
+            return Err(AuthenticationError::InvalidResponse(String::from(
+                "the shared secret has an invalid length",
+            ))
+            .into());
+        }
+        if self.decrypt(&encrypted_verify_token.items)? != verify_token {
+            return Err(AuthenticationError::InvalidResponse(String::from(
+                "the verify token does not match",
+            ))
+            .into());
+        }
+
+        // the following packets are encrypted in both directions
+        stream.set_encryption(&shared_secret)?;
+
+        let server_hash = server_hash(&shared_secret, &self.public_key);
+        let profile = self.authenticator.has_joined(username, &server_hash)?;
+        Ok(profile)
+    }
+
+    fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, AuthenticationError> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|e| AuthenticationError::InvalidResponse(e.to_string()))
+    }
+}
+
+// the sha1 of the server id, the shared secret and the public key, as a signed hexadecimal number
+// like java's `BigInteger.toString(16)`
+pub(crate) fn server_hash(shared_secret: &[u8], public_key: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(SERVER_ID.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key);
+    let mut digest: [u8; 20] = hasher.finalize().into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // two's complement
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            let (value, overflow) = (!*byte).overflowing_add(carry as u8);
+            *byte = value;
+            carry = overflow;
+        }
+    }
+
+    let hex: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    let hex = hex.trim_start_matches('0');
+    if negative {
+        format!("-{hex}")
+    } else {
+        String::from(hex)
+    }
+}
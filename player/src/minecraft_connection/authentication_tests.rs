@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use aes::cipher::{inout::InOutBuf, BlockDecryptMut, KeyIvInit};
+    use aes::Aes128;
+    use minecraft_protocol::packets::{self as mc_packets, Array};
+    use rsa::pkcs8::DecodePublicKey;
+    use rsa::{Pkcs1v15Encrypt, RsaPublicKey};
+
+    use crate::minecraft_connection::authentication::{
+        self, AuthenticationError, LocalAuthenticator, OnlineMode,
+    };
+    use crate::minecraft_connection::login::CommunicationError;
+    use crate::minecraft_connection::network::{self, McStream};
+
+    const SHARED_SECRET: [u8; 16] = [
+        0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32,
+        0x10,
+    ];
+    const UUID: u128 = 0x069a79f444e94726a5befca90e38aaf5;
+
+    // the server side and the client side of a connection
+    fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (server, client)
+    }
+
+    fn online_mode() -> OnlineMode {
+        let mut authenticator = LocalAuthenticator::new();
+        authenticator.add_profile("Notch", UUID);
+        OnlineMode::new(Box::new(authenticator)).unwrap()
+    }
+
+    // answers the encryption request like a vanilla client, and enables encryption
+    fn answer_encryption_request(mut stream: McStream, valid_token: bool) -> McStream {
+        let mut buffer = Vec::new();
+        let packet: mc_packets::login::ClientboundPacket =
+            network::receive_packet(&mut stream, &mut buffer).unwrap();
+        let mc_packets::login::ClientboundPacket::EncryptionRequest {
+            server_id,
+            public_key,
+            verify_token,
+        } = packet
+        else {
+            panic!("The encryption request is missing");
+        };
+        assert_eq!(server_id, "");
+
+        let public_key = RsaPublicKey::from_public_key_der(&public_key.items).unwrap();
+        let mut verify_token = verify_token.items.to_vec();
+        assert_eq!(verify_token.len(), 4);
+        if !valid_token {
+            verify_token[0] ^= 0xff;
+        }
+
+        let mut rng = rand::thread_rng();
+        let shared_secret = public_key
+            .encrypt(&mut rng, Pkcs1v15Encrypt, &SHARED_SECRET)
+            .unwrap();
+        let verify_token = public_key
+            .encrypt(&mut rng, Pkcs1v15Encrypt, &verify_token)
+            .unwrap();
+        let encryption_response = mc_packets::login::ServerboundPacket::EncryptionResponse {
+            shared_secret: Array::from(shared_secret),
+            verify_token: Array::from(verify_token),
+        };
+        network::send_packet(&mut stream, encryption_response).unwrap();
+
+        stream.set_encryption(&SHARED_SECRET).unwrap();
+        stream
+    }
+
+    // authenticates the player, while the client answers on its own thread
+    fn authenticate(
+        username: &str,
+        valid_token: bool,
+    ) -> (McStream, McStream, Result<u128, CommunicationError>) {
+        let (server, client) = socket_pair();
+        let client =
+            thread::spawn(move || answer_encryption_request(McStream::new(client), valid_token));
+
+        let mut server = McStream::new(server);
+        let result = online_mode()
+            .authenticate(&mut server, username)
+            .map(|profile| profile.uuid);
+        (server, client.join().unwrap(), result)
+    }
+
+    #[test]
+    fn test_server_hash() {
+        // the examples of https://wiki.vg/Protocol_Encryption
+        assert_eq!(
+            authentication::server_hash(b"Notch", b""),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            authentication::server_hash(b"jeb_", b""),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        // the leading zero is removed
+        assert_eq!(
+            authentication::server_hash(b"simon", b""),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+
+        // the public key follows the shared secret
+        assert_eq!(
+            authentication::server_hash(b"je", b"b_"),
+            authentication::server_hash(b"jeb_", b"")
+        );
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let (mut server, mut client, result) = authenticate("Notch", true);
+        assert_eq!(result.unwrap(), UUID);
+
+        // both directions are encrypted, and keep their state from one packet to the next
+        for packet in [vec![1, 2, 3], vec![0; 300], vec![4]] {
+            network::send_packet_raw(&mut server, &packet).unwrap();
+            assert_eq!(network::receive_packet_raw(&mut client).unwrap(), packet);
+            network::send_packet_raw(&mut client, &packet).unwrap();
+            assert_eq!(network::receive_packet_raw(&mut server).unwrap(), packet);
+        }
+    }
+
+    #[test]
+    fn test_authenticate_unknown_player() {
+        let (_, _, result) = authenticate("jeb_", true);
+        assert!(matches!(
+            result,
+            Err(CommunicationError::AuthenticationError(
+                AuthenticationError::NotAuthenticated
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_authenticate_invalid_token() {
+        let (_, _, result) = authenticate("Notch", false);
+        assert!(matches!(
+            result,
+            Err(CommunicationError::AuthenticationError(
+                AuthenticationError::InvalidResponse(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_cfb8_stream() {
+        let (server, mut client) = socket_pair();
+        let mut server = McStream::new(server);
+        server.set_encryption(&SHARED_SECRET).unwrap();
+
+        // the secret is both the key and the initial vector
+        let mut decryptor =
+            cfb8::Decryptor::<Aes128>::new_from_slices(&SHARED_SECRET, &SHARED_SECRET).unwrap();
+        let mut decrypt = |data: &mut [u8]| {
+            let (blocks, _) = InOutBuf::from(data).into_chunks();
+            decryptor.decrypt_blocks_inout_mut(blocks);
+        };
+
+        // the frames follow each other in the same stream
+        for packet in [[0x12, 0x34, 0x56], [0x12, 0x34, 0x56]] {
+            network::send_packet_raw(&mut server, &packet).unwrap();
+            let mut data = [0; 4];
+            client.read_exact(&mut data).unwrap();
+            assert_ne!(data, [3, 0x12, 0x34, 0x56]);
+            decrypt(&mut data);
+            assert_eq!(data, [3, 0x12, 0x34, 0x56]);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use super::login::CommunicationError;
+use minecraft_protocol::components::chunk as mc_chunk;
+use minecraft_protocol::nbt::NbtTag;
+use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
+use minecraft_protocol::packets::Array;
+use sol_voxel_lib::chunk_column::ChunkColumn;
+
+/// Builds the packet that sends a whole column to the client, including its light
+pub fn chunk_data_packet<'a>(
+    chunk_column: &ChunkColumn,
+) -> Result<ClientboundPacket<'a>, CommunicationError> {
+    let serialized = chunk_column
+        .to_minecraft()
+        .map_err(|e| CommunicationError::SerializationError(String::from(e)))?;
+
+    let mut heightmaps = HashMap::new();
+    heightmaps.insert(
+        String::from("WORLD_SURFACE"),
+        NbtTag::LongArray(serialized.heightmap_world_surface),
+    );
+    heightmaps.insert(
+        String::from("MOTION_BLOCKING"),
+        NbtTag::LongArray(serialized.heightmap_motion_blocking),
+    );
+
+    let light = serialized.light;
+
+    Ok(ClientboundPacket::ChunkData {
+        value: mc_chunk::ChunkData {
+            chunk_x: serialized.chunk_x_16,
+            chunk_z: serialized.chunk_z_16,
+            heightmaps: NbtTag::Compound(heightmaps),
+            data: Array::from(serialized.chunk_sections),
+            block_entities: Array::from(serialized.block_entities),
+            sky_light_mask: Array::from(light.sky_light_mask),
+            block_light_mask: Array::from(light.block_light_mask),
+            empty_sky_light_mask: Array::from(light.empty_sky_light_mask),
+            empty_block_light_mask: Array::from(light.empty_block_light_mask),
+            sky_light: Array::from(
+                light
+                    .sky_light
+                    .into_iter()
+                    .map(Array::from)
+                    .collect::<Vec<_>>(),
+            ),
+            block_light: Array::from(
+                light
+                    .block_light
+                    .into_iter()
+                    .map(Array::from)
+                    .collect::<Vec<_>>(),
+            ),
+        },
+    })
+}
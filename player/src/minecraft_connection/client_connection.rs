@@ -2,28 +2,44 @@
 // main function is to abstract the mc protocol for the game loop
 
 use super::login::CommunicationError;
+use crate::game_event::{Event, PlayerEvent};
 use crate::game_loop::GameCommand;
-use crate::minecraft_connection::network;
-use crate::player_handler::{PlaceBlockCommand, PlayerCommand};
+use crate::item_stack::ItemStack;
+use crate::minecraft_connection::coordinates;
+use crate::minecraft_connection::network::{self, McStream};
+use crate::player_events::{
+    DigStatus, PlayerClickWindowEvent, PlayerDigBlockEvent, PlayerMoveEvent, PlayerPlaceBlockEvent,
+};
+use crate::player_session::SessionId;
+use minecraft_protocol::components::players::DiggingState;
 use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
 use minecraft_protocol::packets::play_serverbound::ServerboundPacket;
 use minecraft_protocol::MinecraftPacketPart;
 use sol_log_server::logger_mt::LoggerMt;
-use sol_voxel_lib::vector_alias::Coordinate;
-use std::io::Write;
-use std::net::TcpStream;
+use sol_voxel_lib::vector_alias::Position;
 use std::sync::mpsc;
+use std::time::Instant;
 
 pub struct McClientReceiver {
-    socket: TcpStream,
+    socket: McStream,
     logger: LoggerMt,
     world_event_channel: mpsc::Sender<GameCommand>,
-    player_event_channel: mpsc::Sender<PlayerCommand>,
+    // the events of the client are handled by this session of the game loop
+    session_id: SessionId,
+}
+
+/// Why the receiver stopped
+pub enum ReceiveEnd {
+    Disconnected,
+    /// the client acknowledged `StartConfiguration`, and is in the configuration phase again
+    Configuration,
 }
 
 pub enum ClientSendCommand {
     Stop,
     Message(Vec<u8>),
+    /// sends the last packet, and closes the connection; the receiver notices and ends the session
+    Disconnect(Vec<u8>),
 }
 
 impl ClientSendCommand {
@@ -37,27 +53,28 @@ impl ClientSendCommand {
 }
 
 pub struct McClientSender {
-    socket: TcpStream,
+    socket: McStream,
     logger: LoggerMt,
     client_comm_queue: mpsc::Receiver<ClientSendCommand>,
 }
 
 impl McClientReceiver {
     pub fn new(
-        socket: TcpStream,
+        socket: McStream,
         logger: LoggerMt,
         world_event_channel: mpsc::Sender<GameCommand>,
-        player_event_channel: mpsc::Sender<PlayerCommand>,
+        session_id: SessionId,
     ) -> Self {
         McClientReceiver {
             socket,
             logger,
             world_event_channel,
-            player_event_channel,
+            session_id,
         }
     }
 
-    pub fn execute_receive(&mut self) {
+    /// Returns when the client disconnected, or left the play phase
+    pub fn execute_receive(&mut self) -> ReceiveEnd {
         loop {
             let mut buffer = Vec::new();
 
@@ -65,7 +82,12 @@ impl McClientReceiver {
 
             let packet = match packet {
                 Ok(p) => p,
-                Err(CommunicationError::ConnectionClosed) => return,
+                Err(CommunicationError::ConnectionClosed) => return ReceiveEnd::Disconnected,
+                // the connection broke, unlike a read that timed out
+                Err(CommunicationError::IoError(err)) if !is_read_timeout(&err) => {
+                    println!("Connection lost: {err:?}");
+                    return ReceiveEnd::Disconnected;
+                },
                 Err(err) => {
                     println!("Error while receiving message: {err:?}");
                     continue;
@@ -92,56 +114,143 @@ impl McClientReceiver {
                     cursor_position_z,
                     inside_block,
                     sequence,
-                } => self
-                    .player_event_channel
-                    .send(PlayerCommand::PlaceBlock(PlaceBlockCommand {
-                        hand,
-                        location: Coordinate::new(location.x, location.y as i32, location.z),
-                        face,
-                        cursor_position_x,
-                        cursor_position_y,
-                        cursor_position_z,
-                        inside_block,
-                    }))
-                    .map_err(|e| CommunicationError::InternalError(format!("{e:?}"))),
+                } => self.send_event(PlayerEvent::PlaceBlock(PlayerPlaceBlockEvent {
+                    hand,
+                    location: coordinates::coordinate_from_minecraft(&location),
+                    face,
+                    cursor_position_x,
+                    cursor_position_y,
+                    cursor_position_z,
+                    inside_block,
+                    sequence: sequence.0,
+                })),
+                ServerboundPacket::DigBlock {
+                    status,
+                    location,
+                    face: _,
+                    sequence,
+                } => self.handle_dig_block(status, &location, sequence.0),
+                ServerboundPacket::ChunkBatchReceived { chunks_per_tick } => {
+                    self.send_event(PlayerEvent::ChunkBatchReceived { chunks_per_tick })
+                },
+                ServerboundPacket::SetPlayerPosition {
+                    x,
+                    feet_y,
+                    z,
+                    on_ground,
+                } => self.send_player_move(PlayerMoveEvent {
+                    position: Some(Position::new(x as f32, feet_y as f32, z as f32)),
+                    rotation: None,
+                    on_ground,
+                }),
+                ServerboundPacket::SetPlayerPositionAndRotation {
+                    x,
+                    feet_y,
+                    z,
+                    yaw,
+                    pitch,
+                    on_ground,
+                } => self.send_player_move(PlayerMoveEvent {
+                    position: Some(Position::new(x as f32, feet_y as f32, z as f32)),
+                    rotation: Some((yaw, pitch)),
+                    on_ground,
+                }),
+                ServerboundPacket::SetPlayerRotation {
+                    yaw,
+                    pitch,
+                    on_ground,
+                } => self.send_player_move(PlayerMoveEvent {
+                    position: None,
+                    rotation: Some((yaw, pitch)),
+                    on_ground,
+                }),
+                ServerboundPacket::SetPlayerOnGround { on_ground } => {
+                    self.send_player_move(PlayerMoveEvent {
+                        position: None,
+                        rotation: None,
+                        on_ground,
+                    })
+                },
+                ServerboundPacket::ConfirmTeleportation { teleport_id } => {
+                    self.send_event(PlayerEvent::ConfirmTeleport {
+                        teleport_id: teleport_id.0,
+                    })
+                },
                 ServerboundPacket::UseItem { .. } => Ok(()),
 
+                // the time of arrival measures the latency; the game loop only sees it in its next tick
+                ServerboundPacket::KeepAlive { keep_alive_id } => {
+                    self.send_event(PlayerEvent::KeepAlive {
+                        id: keep_alive_id,
+                        received: Instant::now(),
+                    })
+                },
+
                 // may be ignored
-                ServerboundPacket::KeepAlive { .. } => Ok(()),
                 ServerboundPacket::Pong { .. } => Ok(()),
 
                 // TODO
-                ServerboundPacket::ConfirmTeleportation { .. } => Ok(()),
                 ServerboundPacket::QueryBlockNbt { .. } => Ok(()),
                 ServerboundPacket::ChangeDifficulty { .. } => Ok(()),
                 ServerboundPacket::AcknowledgeMessage { .. } => Ok(()),
-                ServerboundPacket::ChatCommand { .. } => Ok(()),
-                ServerboundPacket::ChatMessage { .. } => Ok(()),
+                // TODO secure chat: commands and messages are neither signed nor acknowledged
+                ServerboundPacket::ChatCommand { command, .. } => {
+                    self.send_event(PlayerEvent::ChatCommand {
+                        command: String::from(command),
+                    })
+                },
+                ServerboundPacket::ChatMessage { message, .. } => {
+                    self.send_event(PlayerEvent::ChatMessage {
+                        text: String::from(message),
+                    })
+                },
                 ServerboundPacket::PlayerSession { .. } => Ok(()),
-                ServerboundPacket::ChunkBatchReceived { .. } => Ok(()),
                 ServerboundPacket::ClientStatus { .. } => Ok(()),
                 ServerboundPacket::ClientSettings { .. } => Ok(()),
-                ServerboundPacket::CommandSuggestionsRequest { .. } => Ok(()),
-                ServerboundPacket::AcknowledgeConfiguration => Ok(()),
+                ServerboundPacket::CommandSuggestionsRequest {
+                    transaction_id,
+                    text,
+                } => self.send_event(PlayerEvent::CommandSuggestions {
+                    transaction_id: transaction_id.0,
+                    text: String::from(text),
+                }),
+                // the following packets belong to the configuration phase, which the session
+                // does not handle
+                ServerboundPacket::AcknowledgeConfiguration => return ReceiveEnd::Configuration,
                 ServerboundPacket::ClickWindowButton { .. } => Ok(()),
-                ServerboundPacket::ClickWindowSlot { .. } => Ok(()),
-                ServerboundPacket::CloseWindow { .. } => Ok(()),
+                // we compute the outcome of clicks ourselves, instead of trusting the prediction
+                // of the client
+                ServerboundPacket::ClickWindowSlot {
+                    window_id,
+                    state_id,
+                    slot,
+                    button,
+                    mode,
+                    ..
+                } => self.send_event(PlayerEvent::ClickWindow(PlayerClickWindowEvent {
+                    window_id: window_id as i8,
+                    state_id: state_id.0,
+                    slot,
+                    button,
+                    mode: mode.0,
+                })),
+                ServerboundPacket::CloseWindow { window_id } => {
+                    self.send_event(PlayerEvent::CloseWindow {
+                        window_id: window_id as i8,
+                    })
+                },
                 ServerboundPacket::PluginMessage { .. } => Ok(()),
                 ServerboundPacket::EditBook { .. } => Ok(()),
                 ServerboundPacket::QueryEntityNbt { .. } => Ok(()),
                 ServerboundPacket::InteractEntity { .. } => Ok(()),
                 ServerboundPacket::GenerateStructure { .. } => Ok(()),
                 ServerboundPacket::LockDifficulty { .. } => Ok(()),
-                ServerboundPacket::SetPlayerPosition { .. } => Ok(()),
-                ServerboundPacket::SetPlayerPositionAndRotation { .. } => Ok(()),
-                ServerboundPacket::SetPlayerRotation { .. } => Ok(()),
-                ServerboundPacket::SetPlayerOnGround { .. } => Ok(()),
                 ServerboundPacket::MoveVehicle { .. } => Ok(()),
                 ServerboundPacket::PaddleBoat { .. } => Ok(()),
                 ServerboundPacket::PickItem { .. } => Ok(()),
                 ServerboundPacket::PlaceRecipe { .. } => Ok(()),
                 ServerboundPacket::PlayerAbilities { .. } => Ok(()),
-                ServerboundPacket::DigBlock { .. } => Ok(()),
+                // sneaking, sprinting and leaving a bed; digging is sent as DigBlock
                 ServerboundPacket::PlayerAction { .. } => Ok(()),
                 ServerboundPacket::SteerVehicle { .. } => Ok(()),
                 ServerboundPacket::ChangeRecipeBookSettings { .. } => Ok(()),
@@ -151,10 +260,17 @@ impl McClientReceiver {
                 ServerboundPacket::SetSeenAdvancements { .. } => Ok(()),
                 ServerboundPacket::SelectTrade { .. } => Ok(()),
                 ServerboundPacket::SetBeaconEffect { .. } => Ok(()),
-                ServerboundPacket::SetHeldItem { .. } => Ok(()),
+                ServerboundPacket::SetHeldItem { slot } => {
+                    self.send_event(PlayerEvent::SetHeldItem { slot })
+                },
                 ServerboundPacket::ProgramCommandBlock { .. } => Ok(()),
                 ServerboundPacket::ProgramCommandBlockMinecart { .. } => Ok(()),
-                ServerboundPacket::SetCreativeModeSlot { .. } => Ok(()),
+                ServerboundPacket::SetCreativeModeSlot { slot, clicked_item } => {
+                    self.send_event(PlayerEvent::SetCreativeSlot {
+                        slot,
+                        stack: ItemStack::from_slot(clicked_item),
+                    })
+                },
                 ServerboundPacket::ProgramJigsawBlock { .. } => Ok(()),
                 ServerboundPacket::ProgramStrutureBlock { .. } => Ok(()),
                 ServerboundPacket::UpdateSign { .. } => Ok(()),
@@ -167,11 +283,51 @@ impl McClientReceiver {
             }
         }
     }
+
+    fn send_player_move(&self, event: PlayerMoveEvent) -> Result<(), CommunicationError> {
+        self.send_event(PlayerEvent::Move(event))
+    }
+
+    fn send_event(&self, event: PlayerEvent) -> Result<(), CommunicationError> {
+        self.world_event_channel
+            .send(GameCommand::ImmediateEvent(Event::Player(
+                self.session_id,
+                event,
+            )))
+            .map_err(|e| CommunicationError::InternalError(format!("{e:?}")))
+    }
+
+    fn handle_dig_block(
+        &self,
+        status: DiggingState,
+        location: &minecraft_protocol::packets::Position,
+        sequence: i32,
+    ) -> Result<(), CommunicationError> {
+        let status = match status {
+            DiggingState::Started => DigStatus::Started,
+            DiggingState::Cancelled => DigStatus::Cancelled,
+            DiggingState::Finished => DigStatus::Finished,
+            DiggingState::DropItemStack => return self.send_drop_item(true),
+            DiggingState::DropItem => return self.send_drop_item(false),
+            // TODO eating and swapping hands share this packet
+            _ => return Ok(()),
+        };
+
+        self.send_event(PlayerEvent::DigBlock(PlayerDigBlockEvent {
+            status,
+            location: coordinates::coordinate_from_minecraft(location),
+            sequence,
+        }))
+    }
+
+    fn send_drop_item(&self, whole_stack: bool) -> Result<(), CommunicationError> {
+        self.send_event(PlayerEvent::DropItem { whole_stack })
+    }
 }
 
 impl McClientSender {
     pub fn new(
-        socket: TcpStream,
+        socket: McStream,
         logger: LoggerMt,
         client_comm_queue: mpsc::Receiver<ClientSendCommand>,
     ) -> Self {
@@ -182,19 +338,39 @@ impl McClientSender {
         }
     }
 
+    /// Returns when it is stopped, or when the session that sends the packets is gone
     pub fn execute_send(&mut self) {
         loop {
-            let command = self.client_comm_queue.recv().unwrap();
+            let Ok(command) = self.client_comm_queue.recv() else {
+                return;
+            };
             match command {
                 ClientSendCommand::Stop => return,
                 ClientSendCommand::Message(msg) => {
-                    let result = self.socket.write_all(&msg);
+                    let result = network::send_packet_raw(&mut self.socket, &msg);
 
                     if result.is_err() {
                         println!("Error while sending message: {result:?}");
                     }
                 },
+                ClientSendCommand::Disconnect(msg) => {
+                    let result = network::send_packet_raw(&mut self.socket, &msg)
+                        .and_then(|_| self.socket.shutdown());
+
+                    if result.is_err() {
+                        println!("Error while disconnecting: {result:?}");
+                    }
+                    return;
+                },
             }
         }
     }
 }
+
+// the socket of a client has a read timeout
+fn is_read_timeout(error: &std::io::Error) -> bool {
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
@@ -0,0 +1,116 @@
+// The configuration phase, see https://wiki.vg/Protocol#Configuration
+// The client enters it after the login, and again when we send `StartConfiguration` during play,
+// for example before it is transferred to another server. Both sides end it with
+// `FinishConfiguration`, after which the client expects to join the game.
+
+use super::login::CommunicationError;
+use super::network::{self, McStream};
+use minecraft_protocol::{
+    components as mc_components,
+    packets::{self as mc_packets, Array},
+};
+use sol_log_server::logger_mt::LoggerMt;
+
+// the dimension types, biomes, damage types, chat types and armor trims of a vanilla server of
+// protocol 764, as a serialized `RegistryData` packet.
+// TODO generate it from `minecraft_vanilla::registries` once they hold these registries; for now
+// they only know blocks and items, and the client rejects a codec that misses one of the vanilla
+// entries
+const REGISTRY_DATA: &[u8] = include_bytes!("raw/registry_codec.mc_packet");
+const FEATURE_FLAGS: [&str; 1] = ["minecraft:vanilla"];
+// "Spigot", prefixed by its length
+const SERVER_BRAND: &[u8] = &[6, 83, 112, 105, 103, 111, 116];
+
+/// The settings of the client. It sends them when it enters the configuration phase after the
+/// login, and whenever they change
+pub struct ClientInformation {
+    pub locale: String,
+    pub render_distance: usize,
+    pub chat_mode: mc_components::chat::ChatMode,
+    pub chat_colors: bool,
+    pub displayed_skin_parts: u8,
+    pub main_hand: mc_components::players::MainHand,
+    pub enable_text_filtering: bool,
+    pub allow_server_listing: bool,
+}
+
+/// Sends the registries and the enabled features, and waits until the client finished the
+/// configuration. Returns the settings of the client if it sent them meanwhile.
+/// The registries are the ones of vanilla, and no tags are sent.
+/// Known packs are only negotiated since protocol 766; with ours, the client takes the registry
+/// data as it is
+pub fn configure(
+    stream: &mut McStream,
+    logger: &LoggerMt,
+) -> Result<Option<ClientInformation>, CommunicationError> {
+    // Send server agent
+    let server_agent = mc_packets::config::ClientboundPacket::PluginMessage {
+        channel: "minecraft:brand",
+        data: mc_packets::RawBytes { data: SERVER_BRAND },
+    };
+    network::send_packet(stream, server_agent)?;
+    logger.send_debug("PluginMessage sent");
+
+    // Send feature flags
+    let feature_flags = mc_packets::config::ClientboundPacket::FeatureFlags {
+        features: Array::from(FEATURE_FLAGS.to_vec()),
+    };
+    network::send_packet(stream, feature_flags)?;
+    logger.send_debug("FeatureFlags sent");
+
+    // Send registry data
+    network::send_packet_raw(stream, REGISTRY_DATA)?;
+    logger.send_debug("RegistryData sent");
+
+    // Update tags
+    // TODO send the vanilla tags once minecraft-vanilla knows them. Until then the client treats
+    // every tag as empty, so e.g. no block is climbable and water does not slow it down
+    let update_tags = mc_packets::config::ClientboundPacket::UpdateTags {
+        tags: mc_packets::Map::default(),
+    };
+    network::send_packet(stream, update_tags)?;
+    logger.send_debug("UpdateTags sent");
+
+    // Send finish configuration
+    let finish_configuration = mc_packets::config::ClientboundPacket::FinishConfiguration;
+    network::send_packet(stream, finish_configuration)?;
+    logger.send_debug("FinishConfiguration sent");
+
+    // Receive until finish configuration; the client sends its settings and brand meanwhile
+    let mut client_information = None;
+    loop {
+        let mut buffer = Vec::new();
+        let packet: mc_packets::config::ServerboundPacket =
+            network::receive_packet(stream, &mut buffer)?;
+
+        match packet {
+            mc_packets::config::ServerboundPacket::ClientInformations {
+                locale,
+                render_distance,
+                chat_mode,
+                chat_colors,
+                displayed_skin_parts,
+                main_hand,
+                enable_text_filtering,
+                allow_server_listing,
+            } => {
+                client_information = Some(ClientInformation {
+                    locale: locale.to_owned(),
+                    render_distance: render_distance.try_into().unwrap_or(5),
+                    chat_mode,
+                    chat_colors,
+                    displayed_skin_parts,
+                    main_hand,
+                    enable_text_filtering,
+                    allow_server_listing,
+                });
+                logger.send_debug("ClientInformation received");
+            },
+            mc_packets::config::ServerboundPacket::FinishConfiguration => {
+                logger.send_debug("FinishConfiguration received");
+                return Ok(client_information);
+            },
+            packet => logger.send_debug(&format!("Ignored {packet:?} during configuration")),
+        }
+    }
+}
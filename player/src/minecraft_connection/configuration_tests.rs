@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+
+    use minecraft_protocol::components::chat::ChatMode;
+    use minecraft_protocol::components::players::MainHand;
+    use minecraft_protocol::packets::{self as mc_packets, play_serverbound};
+    use sol_log_server::logger_mt::LoggerMt;
+
+    use crate::minecraft_connection::client_connection::{McClientReceiver, ReceiveEnd};
+    use crate::minecraft_connection::configuration;
+    use crate::minecraft_connection::network::{self, McStream};
+
+    // the ids of the clientbound configuration packets of protocol 764
+    const PLUGIN_MESSAGE: u8 = 0x00;
+    const FINISH_CONFIGURATION: u8 = 0x02;
+    const REGISTRY_DATA: u8 = 0x05;
+    const FEATURE_FLAGS: u8 = 0x07;
+    const UPDATE_TAGS: u8 = 0x08;
+
+    // the server side and the client side of a connection
+    fn socket_pair() -> (McStream, McStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (McStream::new(server), McStream::new(client))
+    }
+
+    // receives the configuration like a vanilla client, and finishes it.
+    // Returns the ids of the packets that it received
+    fn finish_configuration(stream: &mut McStream, render_distance: Option<i8>) -> Vec<u8> {
+        let mut ids = Vec::new();
+        loop {
+            let packet = network::receive_packet_raw(stream).unwrap();
+            ids.push(packet[0]);
+            if packet[0] == FINISH_CONFIGURATION {
+                break;
+            }
+        }
+
+        if let Some(render_distance) = render_distance {
+            let client_information = mc_packets::config::ServerboundPacket::ClientInformations {
+                locale: "en_us",
+                render_distance,
+                chat_mode: ChatMode::Enabled,
+                chat_colors: true,
+                displayed_skin_parts: 0x7f,
+                main_hand: MainHand::Right,
+                enable_text_filtering: false,
+                allow_server_listing: true,
+            };
+            network::send_packet(stream, client_information).unwrap();
+        }
+        network::send_packet(
+            stream,
+            mc_packets::config::ServerboundPacket::FinishConfiguration,
+        )
+        .unwrap();
+        ids
+    }
+
+    fn logger() -> LoggerMt {
+        LoggerMt::new("test", zmq::Context::new(), String::from("inproc://logger")).unwrap()
+    }
+
+    #[test]
+    fn test_configure() {
+        let (mut server, mut client) = socket_pair();
+        let client = thread::spawn(move || finish_configuration(&mut client, Some(12)));
+
+        let client_information = configuration::configure(&mut server, &logger())
+            .unwrap()
+            .unwrap();
+        assert_eq!(client_information.locale, "en_us");
+        assert_eq!(client_information.render_distance, 12);
+        assert_eq!(
+            client.join().unwrap(),
+            vec![
+                PLUGIN_MESSAGE,
+                FEATURE_FLAGS,
+                REGISTRY_DATA,
+                UPDATE_TAGS,
+                FINISH_CONFIGURATION
+            ]
+        );
+    }
+
+    #[test]
+    fn test_configure_again() {
+        let logger = logger();
+        let (mut server, mut client) = socket_pair();
+        let (game_command_sender, _game_command_receiver) = mpsc::channel();
+        let client = thread::spawn(move || {
+            let first = finish_configuration(&mut client, Some(12));
+            // the client acknowledges `StartConfiguration` during play
+            network::send_packet(
+                &mut client,
+                play_serverbound::ServerboundPacket::AcknowledgeConfiguration,
+            )
+            .unwrap();
+            // and is configured again, without changing its settings
+            let second = finish_configuration(&mut client, None);
+            (first, second)
+        });
+
+        assert!(configuration::configure(&mut server, &logger)
+            .unwrap()
+            .is_some());
+        let mut receiver = McClientReceiver::new(
+            server.try_clone().unwrap(),
+            logger.clone(),
+            game_command_sender,
+            1,
+        );
+        assert!(matches!(
+            receiver.execute_receive(),
+            ReceiveEnd::Configuration
+        ));
+        assert!(configuration::configure(&mut server, &logger)
+            .unwrap()
+            .is_none());
+
+        // the client receives the same configuration both times
+        let (first, second) = client.join().unwrap();
+        assert_eq!(first, second);
+    }
+}
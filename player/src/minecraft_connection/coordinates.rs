@@ -0,0 +1,34 @@
+// Our coordinates start at y = 0 at the bottom of the world. Minecraft starts at y = -64.
+
+use minecraft_protocol::packets as mc_packets;
+use sol_voxel_lib::vector_alias::{Coordinate, Coordinate16};
+
+/// the minecraft y of the bottom of the world
+pub const MINECRAFT_MIN_Y: i32 = -64;
+
+pub fn coordinate_from_minecraft(position: &mc_packets::Position) -> Coordinate {
+    Coordinate::new(
+        position.x,
+        position.y as i32 - MINECRAFT_MIN_Y,
+        position.z,
+    )
+}
+
+pub fn coordinate_to_minecraft(coord: Coordinate) -> mc_packets::Position {
+    mc_packets::Position {
+        x: coord.x,
+        y: (coord.y + MINECRAFT_MIN_Y) as i16,
+        z: coord.z,
+    }
+}
+
+/// The section position as encoded in `UpdateSectionBlocks`:
+/// 22 bits x, 22 bits z and 20 bits y, from most to least significant
+pub fn section_position_to_minecraft(section: Coordinate16) -> u64 {
+    let section = section.inner();
+    let y = section.y + MINECRAFT_MIN_Y / 16;
+
+    ((section.x as u64 & 0x3FFFFF) << 42)
+        | ((section.z as u64 & 0x3FFFFF) << 20)
+        | (y as u64 & 0xFFFFF)
+}
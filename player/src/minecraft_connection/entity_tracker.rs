@@ -0,0 +1,357 @@
+// Translates our entities to the entities of the client: spawns the entities that come into
+// range, sends their movement and metadata, and removes the ones that leave the range.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::coordinates::MINECRAFT_MIN_Y;
+use minecraft_protocol::components::entity::{EntityMetadata, EntityMetadataValue};
+use minecraft_protocol::components::slots::{Slot, SlotItem};
+use minecraft_protocol::data::items::Item;
+use minecraft_protocol::nbt::NbtTag;
+use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
+use minecraft_protocol::packets::{Array, VarInt};
+use sol_entity_lib::entity::{Entity, EntityId};
+use sol_entity_lib::entity_manager::EntityManager;
+use sol_entity_lib::entity_type::EntityType;
+use sol_entity_lib::metadata::ItemData;
+use sol_voxel_lib::vector_alias::{Position, Vector3f};
+
+// relative moves are in 1/4096 of a block
+const POSITION_SCALE: f64 = 4096.0;
+// velocities are in 1/8000 of a block per tick
+const VELOCITY_SCALE: f32 = 8000.0;
+const MAX_VELOCITY: f32 = 3.9;
+
+// metadata indices of the protocol of 1.20.2
+const METADATA_FLAGS: u8 = 0;
+const METADATA_SILENT: u8 = 4;
+const METADATA_NO_GRAVITY: u8 = 5;
+const METADATA_ITEM: u8 = 8;
+const METADATA_HEALTH: u8 = 9;
+
+/// The state of an entity, as the client knows it
+struct TrackedEntity {
+    // in 1/4096 blocks, in minecraft coordinates
+    position: [i64; 3],
+    yaw: u8,
+    pitch: u8,
+    head_yaw: u8,
+    velocity: [i16; 3],
+    on_ground: bool,
+    // the metadata is sent again when any of these change
+    metadata: sol_entity_lib::metadata::EntityMetadata,
+    health: Option<f32>,
+}
+
+#[derive(Default)]
+pub struct EntityTracker {
+    tracked: HashMap<EntityId, TrackedEntity>,
+}
+
+impl EntityTracker {
+    pub fn new() -> EntityTracker {
+        EntityTracker::default()
+    }
+
+    pub fn is_tracked(&self, id: EntityId) -> bool {
+        self.tracked.contains_key(&id)
+    }
+
+    /// Returns the packets that bring the client up to date with the entities around the viewer.
+    /// The viewer position is in minecraft coordinates
+    pub fn update(
+        &mut self,
+        entities: &EntityManager,
+        viewer: Position,
+        view_distance: i32,
+    ) -> Vec<ClientboundPacket<'static>> {
+        let mut packets = Vec::new();
+        let mut in_range = HashSet::new();
+
+        for entity in entities.iter() {
+            let position = to_minecraft_position(entity.position);
+            let range = tracking_range(entity.entity_type).min(view_distance as f32 * 16.0);
+            let dx = position.x - viewer.x;
+            let dz = position.z - viewer.z;
+            if dx.abs() > range || dz.abs() > range {
+                continue;
+            }
+            in_range.insert(entity.id);
+
+            match self.tracked.get_mut(&entity.id) {
+                Some(tracked) => update_entity(entity, tracked, &mut packets),
+                None => {
+                    let tracked = spawn_entity(entity, &mut packets);
+                    self.tracked.insert(entity.id, tracked);
+                },
+            }
+        }
+
+        let removed: Vec<EntityId> = self
+            .tracked
+            .keys()
+            .copied()
+            .filter(|id| !in_range.contains(id))
+            .collect();
+        if !removed.is_empty() {
+            for id in &removed {
+                self.tracked.remove(id);
+            }
+            packets.push(ClientboundPacket::RemoveEntities {
+                entity_ids: Array::from(
+                    removed
+                        .into_iter()
+                        .map(|id| VarInt(id as i32))
+                        .collect::<Vec<_>>(),
+                ),
+            });
+        }
+
+        packets
+    }
+}
+
+fn spawn_entity(entity: &Entity, packets: &mut Vec<ClientboundPacket<'static>>) -> TrackedEntity {
+    let position = to_minecraft_position(entity.position);
+    let tracked = TrackedEntity {
+        position: encode_position(position),
+        yaw: encode_angle(entity.rotation.yaw),
+        pitch: encode_angle(entity.rotation.pitch),
+        head_yaw: encode_angle(entity.rotation.head_yaw),
+        velocity: encode_velocity(entity.velocity),
+        on_ground: entity.on_ground,
+        metadata: entity.metadata.clone(),
+        health: entity.health,
+    };
+
+    // the data field depends on the type of the entity
+    let data = match entity.entity_type {
+        EntityType::FallingBlock => entity.metadata.block_state.unwrap_or(0) as i32,
+        _ => 0,
+    };
+
+    packets.push(ClientboundPacket::SpawnEntity {
+        id: VarInt(entity.id as i32),
+        uuid: entity.uuid,
+        entity_type: VarInt(protocol_entity_type(entity.entity_type)),
+        x: position.x as f64,
+        y: position.y as f64,
+        z: position.z as f64,
+        pitch: tracked.pitch,
+        yaw: tracked.yaw,
+        head_yaw: tracked.head_yaw,
+        data: VarInt(data),
+        velocity_x: tracked.velocity[0],
+        velocity_y: tracked.velocity[1],
+        velocity_z: tracked.velocity[2],
+    });
+
+    packets.push(ClientboundPacket::SetEntityMetadata {
+        entity_id: VarInt(entity.id as i32),
+        metadata: EntityMetadata {
+            items: metadata(entity),
+        },
+    });
+
+    tracked
+}
+
+fn update_entity(
+    entity: &Entity,
+    tracked: &mut TrackedEntity,
+    packets: &mut Vec<ClientboundPacket<'static>>,
+) {
+    let entity_id = VarInt(entity.id as i32);
+    let position = to_minecraft_position(entity.position);
+    let new_position = encode_position(position);
+    let yaw = encode_angle(entity.rotation.yaw);
+    let pitch = encode_angle(entity.rotation.pitch);
+
+    let moved = new_position != tracked.position;
+    let rotated = yaw != tracked.yaw || pitch != tracked.pitch;
+    let landed = entity.on_ground != tracked.on_ground;
+
+    let delta = [0, 1, 2].map(|axis| new_position[axis] - tracked.position[axis]);
+    let fits_relative_move = delta.iter().all(|delta| i16::try_from(*delta).is_ok());
+
+    if moved && !fits_relative_move {
+        packets.push(ClientboundPacket::TeleportEntity {
+            entity_id,
+            x: position.x as f64,
+            y: position.y as f64,
+            z: position.z as f64,
+            yaw,
+            pitch,
+            on_ground: entity.on_ground,
+        });
+    } else if moved && rotated {
+        packets.push(ClientboundPacket::UpdateEntityPositionAndRotation {
+            entity_id,
+            delta_x: delta[0] as i16,
+            delta_y: delta[1] as i16,
+            delta_z: delta[2] as i16,
+            yaw,
+            pitch,
+            on_ground: entity.on_ground,
+        });
+    } else if rotated {
+        packets.push(ClientboundPacket::UpdateEntityRotation {
+            entity_id,
+            yaw,
+            pitch,
+            on_ground: entity.on_ground,
+        });
+    } else if moved || landed {
+        packets.push(ClientboundPacket::UpdateEntityPosition {
+            entity_id,
+            delta_x: delta[0] as i16,
+            delta_y: delta[1] as i16,
+            delta_z: delta[2] as i16,
+            on_ground: entity.on_ground,
+        });
+    }
+
+    // the client adds the deltas to what it knows, so we must do the same to not drift away
+    tracked.position = new_position;
+    tracked.yaw = yaw;
+    tracked.pitch = pitch;
+    tracked.on_ground = entity.on_ground;
+
+    let head_yaw = encode_angle(entity.rotation.head_yaw);
+    if head_yaw != tracked.head_yaw {
+        packets.push(ClientboundPacket::SetHeadRotation {
+            entity_id,
+            head_yaw,
+        });
+        tracked.head_yaw = head_yaw;
+    }
+
+    let velocity = encode_velocity(entity.velocity);
+    if velocity != tracked.velocity {
+        packets.push(ClientboundPacket::SetEntityVelocity {
+            entity_id,
+            velocity_x: velocity[0],
+            velocity_y: velocity[1],
+            velocity_z: velocity[2],
+        });
+        tracked.velocity = velocity;
+    }
+
+    if entity.metadata != tracked.metadata || entity.health != tracked.health {
+        packets.push(ClientboundPacket::SetEntityMetadata {
+            entity_id,
+            metadata: EntityMetadata {
+                items: metadata(entity),
+            },
+        });
+        tracked.metadata = entity.metadata.clone();
+        tracked.health = entity.health;
+    }
+}
+
+fn metadata(entity: &Entity) -> BTreeMap<u8, EntityMetadataValue> {
+    let mut items = BTreeMap::new();
+    items.insert(
+        METADATA_FLAGS,
+        EntityMetadataValue::Byte {
+            value: entity.metadata.flags(),
+        },
+    );
+    items.insert(
+        METADATA_SILENT,
+        EntityMetadataValue::Boolean {
+            value: entity.metadata.silent,
+        },
+    );
+    items.insert(
+        METADATA_NO_GRAVITY,
+        EntityMetadataValue::Boolean {
+            value: entity.metadata.no_gravity,
+        },
+    );
+
+    if entity.entity_type == EntityType::Item {
+        if let Some(item) = &entity.metadata.item {
+            items.insert(
+                METADATA_ITEM,
+                EntityMetadataValue::Slot {
+                    value: item_slot(item),
+                },
+            );
+        }
+    }
+
+    if entity.entity_type.is_living() {
+        if let Some(health) = entity.health {
+            items.insert(METADATA_HEALTH, EntityMetadataValue::Float { value: health });
+        }
+    }
+
+    items
+}
+
+// an item entity without items shows an empty slot
+fn item_slot(item: &ItemData) -> Slot {
+    Slot {
+        item: (item.count > 0).then(|| SlotItem {
+            item_id: Item::from_id(item.item_id),
+            item_count: item.count.min(i8::MAX as u8) as i8,
+            nbt_data: NbtTag::Null,
+        }),
+    }
+}
+
+/// The ids of the entity registry of 1.20.2
+fn protocol_entity_type(entity_type: EntityType) -> i32 {
+    match entity_type {
+        EntityType::Arrow => 3,
+        EntityType::Chicken => 15,
+        EntityType::Cow => 18,
+        EntityType::Creeper => 19,
+        EntityType::ExperienceOrb => 34,
+        EntityType::FallingBlock => 36,
+        EntityType::Item => 54,
+        EntityType::Pig => 72,
+        EntityType::Sheep => 82,
+        EntityType::Skeleton => 86,
+        EntityType::Zombie => 118,
+        EntityType::Player => 122,
+    }
+}
+
+/// Like vanilla: the distance in blocks, beyond which the client does not see an entity
+fn tracking_range(entity_type: EntityType) -> f32 {
+    let chunks = match entity_type {
+        EntityType::Item | EntityType::ExperienceOrb => 6,
+        EntityType::Arrow => 4,
+        EntityType::Zombie | EntityType::Skeleton | EntityType::Creeper => 8,
+        EntityType::FallingBlock
+        | EntityType::Pig
+        | EntityType::Cow
+        | EntityType::Sheep
+        | EntityType::Chicken
+        | EntityType::Player => 10,
+    };
+    chunks as f32 * 16.0
+}
+
+fn to_minecraft_position(position: Position) -> Position {
+    Position::new(
+        position.x,
+        position.y + MINECRAFT_MIN_Y as f32,
+        position.z,
+    )
+}
+
+fn encode_position(position: Position) -> [i64; 3] {
+    [position.x, position.y, position.z].map(|value| (value as f64 * POSITION_SCALE).round() as i64)
+}
+
+fn encode_angle(degrees: f32) -> u8 {
+    (degrees / 360.0 * 256.0).rem_euclid(256.0) as u8
+}
+
+fn encode_velocity(velocity: Vector3f) -> [i16; 3] {
+    [velocity.x, velocity.y, velocity.z]
+        .map(|value| (value.clamp(-MAX_VELOCITY, MAX_VELOCITY) * VELOCITY_SCALE) as i16)
+}
@@ -0,0 +1,314 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::components::entity::EntityMetadataValue;
+    use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
+    use sol_entity_lib::entity::{Entity, EntityId};
+    use sol_entity_lib::entity_manager::EntityManager;
+    use sol_entity_lib::entity_type::EntityType;
+    use sol_entity_lib::metadata::ItemData;
+    use sol_voxel_lib::vector_alias::{Position, Vector3f};
+
+    use crate::minecraft_connection::entity_tracker::EntityTracker;
+
+    const VIEW_DISTANCE: i32 = 10;
+
+    // the viewer is at the origin, in minecraft coordinates
+    fn viewer() -> Position {
+        Position::origin()
+    }
+
+    // an entity at y = 36 in minecraft coordinates, which the tracker knows already
+    fn tracked_pig() -> (EntityTracker, EntityManager, EntityId) {
+        let mut entities = EntityManager::new(1);
+        let id = entities.spawn(7, EntityType::Pig, Position::new(0.5, 100.0, 0.5));
+        let mut tracker = EntityTracker::new();
+        tracker.update(&entities, viewer(), VIEW_DISTANCE);
+        (tracker, entities, id)
+    }
+
+    fn update(
+        tracker: &mut EntityTracker,
+        entities: &mut EntityManager,
+        id: EntityId,
+        change: impl FnOnce(&mut Entity),
+    ) -> Vec<ClientboundPacket<'static>> {
+        entities.update(id, change);
+        tracker.update(entities, viewer(), VIEW_DISTANCE)
+    }
+
+    #[test]
+    fn test_spawn() {
+        let mut entities = EntityManager::new(1);
+        let id = entities.spawn(7, EntityType::Pig, Position::new(0.5, 100.0, -2.5));
+        entities.update(id, |entity| {
+            entity.rotation.yaw = 90.0;
+            entity.rotation.pitch = -45.0;
+            entity.velocity = Vector3f::new(0.5, -0.25, 0.0);
+        });
+
+        let mut tracker = EntityTracker::new();
+        let packets = tracker.update(&entities, viewer(), VIEW_DISTANCE);
+        assert!(tracker.is_tracked(id));
+        assert_eq!(packets.len(), 2);
+        match &packets[0] {
+            ClientboundPacket::SpawnEntity {
+                id: spawned_id,
+                uuid,
+                entity_type,
+                x,
+                y,
+                z,
+                pitch,
+                yaw,
+                velocity_x,
+                velocity_y,
+                velocity_z,
+                ..
+            } => {
+                assert_eq!(spawned_id.0, id as i32);
+                assert_eq!(*uuid, 7);
+                assert_eq!(entity_type.0, 72);
+                assert_eq!((*x, *y, *z), (0.5, 36.0, -2.5));
+                // in 1/256 of a turn
+                assert_eq!(*yaw, 64);
+                assert_eq!(*pitch, 224);
+                // in 1/8000 of a block per tick
+                assert_eq!((*velocity_x, *velocity_y, *velocity_z), (4000, -2000, 0));
+            },
+            _ => panic!("The entity was not spawned"),
+        }
+        assert!(matches!(
+            packets[1],
+            ClientboundPacket::SetEntityMetadata { .. }
+        ));
+
+        // nothing changed since
+        assert!(tracker
+            .update(&entities, viewer(), VIEW_DISTANCE)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_relative_move() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position += Vector3f::new(0.5, -1.0, 0.25);
+        });
+        assert_eq!(packets.len(), 1);
+        // in 1/4096 of a block
+        assert!(matches!(
+            packets[0],
+            ClientboundPacket::UpdateEntityPosition {
+                delta_x: 2048,
+                delta_y: -4096,
+                delta_z: 1024,
+                on_ground: false,
+                ..
+            }
+        ));
+
+        // landing is sent even without a move
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.on_ground = true;
+        });
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::UpdateEntityPosition {
+                delta_x: 0,
+                delta_y: 0,
+                delta_z: 0,
+                on_ground: true,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_deltas_do_not_drift() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        let mut total = 0;
+        // moves that are not a whole number of 1/4096 blocks
+        for _ in 0..100 {
+            let packets = update(&mut tracker, &mut entities, id, |entity| {
+                entity.position.x += 0.0003;
+            });
+            for packet in packets {
+                if let ClientboundPacket::UpdateEntityPosition { delta_x, .. } = packet {
+                    total += delta_x as i64;
+                }
+            }
+        }
+
+        let end_x = entities.get(id).unwrap().position.x as f64;
+        let expected = (end_x * 4096.0).round() as i64 - (0.5 * 4096.0) as i64;
+        assert_eq!(total, expected);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.rotation.yaw = -90.0;
+        });
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::UpdateEntityRotation {
+                yaw: 192,
+                pitch: 0,
+                ..
+            }]
+        ));
+
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position.x += 1.0;
+            entity.rotation.pitch = 90.0;
+        });
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::UpdateEntityPositionAndRotation {
+                delta_x: 4096,
+                delta_y: 0,
+                delta_z: 0,
+                yaw: 192,
+                pitch: 64,
+                ..
+            }]
+        ));
+
+        // a full turn is the same angle
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.rotation.yaw = 270.0;
+            entity.rotation.head_yaw = 180.0;
+        });
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::SetHeadRotation { head_yaw: 128, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_teleport_when_delta_overflows() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        // 8 blocks are 32768 / 4096 blocks, one more than an i16 holds
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position.z += 8.0;
+        });
+        match &packets[..] {
+            [ClientboundPacket::TeleportEntity { x, y, z, .. }] => {
+                assert_eq!((*x, *y, *z), (0.5, 36.0, 8.5));
+            },
+            _ => panic!("The entity was not teleported"),
+        }
+
+        // the largest relative move
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position.z -= 32767.0 / 4096.0;
+        });
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::UpdateEntityPosition {
+                delta_z: -32767,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_velocity() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.velocity = Vector3f::new(0.125, 10.0, -10.0);
+        });
+        // velocities are capped, like vanilla
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::SetEntityVelocity {
+                velocity_x: 1000,
+                velocity_y: 31200,
+                velocity_z: -31200,
+                ..
+            }]
+        ));
+
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.velocity = Vector3f::new(0.125, 20.0, -20.0);
+        });
+        assert!(packets.is_empty());
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut entities = EntityManager::new(1);
+        let id = entities.spawn(7, EntityType::Item, Position::new(0.5, 100.0, 0.5));
+        entities.update(id, |entity| {
+            entity.metadata.item = Some(ItemData {
+                item_id: 1,
+                count: 3,
+            });
+        });
+        let mut tracker = EntityTracker::new();
+        tracker.update(&entities, viewer(), VIEW_DISTANCE);
+
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.metadata.item = Some(ItemData {
+                item_id: 1,
+                count: 5,
+            });
+        });
+        match &packets[..] {
+            [ClientboundPacket::SetEntityMetadata { metadata, .. }] => {
+                match metadata.items.get(&8) {
+                    Some(EntityMetadataValue::Slot { value }) => {
+                        assert_eq!(value.item.as_ref().unwrap().item_count, 5);
+                    },
+                    _ => panic!("The item is missing"),
+                }
+            },
+            _ => panic!("The metadata was not sent"),
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let (mut tracker, mut entities, id) = tracked_pig();
+        let far = entities.spawn(8, EntityType::Pig, Position::new(500.0, 100.0, 0.5));
+        let packets = tracker.update(&entities, viewer(), VIEW_DISTANCE);
+        assert!(packets.is_empty());
+        assert!(!tracker.is_tracked(far));
+
+        // the entity moves out of the range of pigs
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position.x = 161.0;
+        });
+        assert!(!tracker.is_tracked(id));
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::RemoveEntities { .. }]
+        ));
+
+        // and is spawned again when it comes back
+        let packets = update(&mut tracker, &mut entities, id, |entity| {
+            entity.position.x = 0.5;
+        });
+        assert!(matches!(packets[0], ClientboundPacket::SpawnEntity { .. }));
+
+        // a small view distance limits the range
+        let packets = tracker.update(&entities, Position::new(40.0, 0.0, 0.0), 2);
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::RemoveEntities { .. }]
+        ));
+        assert!(!tracker.is_tracked(id));
+        tracker.update(&entities, viewer(), VIEW_DISTANCE);
+        assert!(tracker.is_tracked(id));
+
+        // despawned entities are removed as well
+        entities.remove(id);
+        let packets = tracker.update(&entities, viewer(), VIEW_DISTANCE);
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::RemoveEntities { .. }]
+        ));
+        assert!(!tracker.is_tracked(id));
+    }
+}
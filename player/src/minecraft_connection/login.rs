@@ -1,21 +1,19 @@
-use crate::voxels::world::World;
-use std::{
-    collections::{BTreeMap, HashMap},
-    io,
-    net::TcpStream,
-};
-
-use super::{network, player_character::PlayerCharacter};
+use crate::commands::tree::CommandTree;
+use crate::player_movement;
+use crate::voxels::chunk_streamer::{INITIAL_CHUNKS_PER_TICK, MAX_VIEW_DISTANCE};
+use std::{collections::BTreeMap, io};
+
+use super::authentication::{AuthenticationError, OnlineMode};
+use super::network::{self, McStream};
+use super::{configuration, player_character::PlayerCharacter};
 use minecraft_protocol::{
     components as mc_components,
-    nbt::NbtTag,
     packets::{
-        self as mc_packets, play_clientbound::ClientboundPacket as PlayClientbound,
-        play_serverbound::ServerboundPacket as PlayServerbound, status, Array,
+        self as mc_packets, play_clientbound::ClientboundPacket as PlayClientbound, status, Array,
     },
     MinecraftPacketPart,
 };
-use sol_voxel_lib::vector_alias::Position;
+use sol_log_server::logger_mt::LoggerMt;
 
 #[derive(Debug)]
 pub enum CommunicationError {
@@ -31,6 +29,7 @@ pub enum CommunicationError {
     SerializationError(String),
     DeserializationError(String),
     IoError(io::Error),
+    AuthenticationError(AuthenticationError),
     InternalError(String),
 }
 
@@ -52,12 +51,22 @@ impl From<io::Error> for CommunicationError {
     }
 }
 
+impl From<AuthenticationError> for CommunicationError {
+    fn from(value: AuthenticationError) -> Self {
+        Self::AuthenticationError(value)
+    }
+}
+
 pub struct PlayerConnectionData {
-    username: String,
-    uuid: u128,
+    pub username: String,
+    pub uuid: u128,
 }
 
-pub fn login(stream: &mut TcpStream) -> Result<PlayerConnectionData, CommunicationError> {
+pub fn login(
+    stream: &mut McStream,
+    compression_threshold: Option<usize>,
+    online_mode: Option<&OnlineMode>,
+) -> Result<PlayerConnectionData, CommunicationError> {
     // Receive login start
     let mut buffer = Vec::new();
     let packet: mc_packets::login::ServerboundPacket =
@@ -77,9 +86,31 @@ pub fn login(stream: &mut TcpStream) -> Result<PlayerConnectionData, Communicati
     let username = username.to_owned();
     drop(buffer);
 
-    // OPTIONAL encryption
+    // Encryption; in online mode, the account of the session service replaces what the client sent
+    let (username, player_uuid) = match online_mode {
+        Some(online_mode) => match online_mode.authenticate(stream, &username) {
+            Ok(profile) => (profile.username, profile.uuid),
+            Err(error) => {
+                let disconnect = mc_packets::login::ClientboundPacket::Disconnect {
+                    reason: "{\"translate\":\"multiplayer.disconnect.unverified_username\"}",
+                };
+                // the client may be gone already
+                let _ = network::send_packet(stream, disconnect);
+                return Err(error);
+            },
+        },
+        None => (username, player_uuid),
+    };
 
-    // OPTIONAL compression
+    // Set compression; the packet itself is not compressed yet
+    if let Some(threshold) = compression_threshold {
+        let set_compression = mc_packets::login::ClientboundPacket::SetCompression {
+            threshold: mc_packets::VarInt::from(threshold),
+        };
+        network::send_packet(stream, set_compression)?;
+        stream.set_compression(threshold);
+        println!("SetCompression sent");
+    }
 
     // Send login success
     let login_success = mc_packets::login::ClientboundPacket::LoginSuccess {
@@ -103,14 +134,6 @@ pub fn login(stream: &mut TcpStream) -> Result<PlayerConnectionData, Communicati
     };
     println!("LoginAcknowledged received");
 
-    // Ignore encryption response if any
-    let mut buffer = Vec::new();
-    let packet: mc_packets::login::ServerboundPacket =
-        network::receive_packet(stream, &mut buffer)?;
-    if let mc_packets::login::ServerboundPacket::EncryptionResponse { .. } = packet {
-        println!("EncryptionResponse received and ignored");
-    }
-
     Ok(PlayerConnectionData {
         username,
         uuid: player_uuid,
@@ -118,7 +141,7 @@ pub fn login(stream: &mut TcpStream) -> Result<PlayerConnectionData, Communicati
 }
 
 pub struct PlayerInfo {
-    pub socket: TcpStream,
+    pub socket: McStream,
     pub username: String,
     pub uuid: u128,
     pub locale: String,
@@ -133,88 +156,53 @@ pub struct PlayerInfo {
 }
 
 pub fn initialize_client(
-    mut socket: TcpStream,
+    mut socket: McStream,
     logged_in_player_info: PlayerConnectionData,
     character: &PlayerCharacter,
+    logger: &LoggerMt,
 ) -> Result<PlayerInfo, CommunicationError> {
     let stream = &mut socket;
 
-    // Receive client information
-    let mut buffer = Vec::new();
-    let packet = network::receive_packet(stream, &mut buffer)?;
-    let mc_packets::config::ServerboundPacket::ClientInformations {
-        locale,
-        render_distance,
-        chat_mode,
-        chat_colors,
-        displayed_skin_parts,
-        main_hand,
-        enable_text_filtering,
-        allow_server_listing,
-    } = packet
-    else {
-        return Err(CommunicationError::wrong_package(
-            "ClientInformations",
-            packet,
-        ));
-    };
-    println!("ClientInformation received");
-
-    // Send server agent
-    let server_agent = mc_packets::config::ClientboundPacket::PluginMessage {
-        channel: "minecraft:brand",
-        data: mc_packets::RawBytes {
-            data: &[6, 83, 112, 105, 103, 111, 116],
-        },
-    };
-    network::send_packet(stream, server_agent)?;
-    println!("PluginMessage sent");
-
-    // Send feature flags
-    let feature_flags = mc_packets::config::ClientboundPacket::FeatureFlags {
-        features: Array::from(vec!["minecraft:vanilla"]),
-    };
-    network::send_packet(stream, feature_flags)?;
-    println!("FeatureFlags sent");
-
-    // Send registry data
-    // TODO this can be used to make our own block set
-    network::send_packet_raw(stream, include_bytes!("raw/registry_codec.mc_packet"))?;
-    println!("RegistryData sent");
-
-    // Update tags
-    let update_tags = mc_packets::config::ClientboundPacket::UpdateTags {
-        tags: mc_packets::Map::default(),
-    };
-    network::send_packet(stream, update_tags)?;
-    println!("UpdateTags sent");
+    let client_information = configuration::configure(stream, logger)?.ok_or_else(|| {
+        CommunicationError::UnexpectedPackage {
+            expected: String::from("ClientInformations"),
+            received: String::from("FinishConfiguration"),
+        }
+    })?;
 
-    // Send finish configuration
-    let finish_configuration = mc_packets::config::ClientboundPacket::FinishConfiguration;
-    network::send_packet(stream, finish_configuration)?;
-    println!("FinishConfiguration sent");
+    send_join_game(stream, character)?;
 
-    // Receive finish configuration
-    let mut buffer = Vec::new();
-    let packet: mc_packets::config::ServerboundPacket =
-        network::receive_packet(stream, &mut buffer)?;
-    let mc_packets::config::ServerboundPacket::FinishConfiguration = packet else {
-        return Err(CommunicationError::wrong_package(
-            "FinishConfiguration",
-            packet,
-        ));
-    };
-    println!("FinishConfiguration received");
+    Ok(PlayerInfo {
+        socket,
+        username: logged_in_player_info.username,
+        uuid: logged_in_player_info.uuid,
+        locale: client_information.locale,
+        render_distance: client_information.render_distance,
+        chat_mode: client_information.chat_mode,
+        chat_colors: client_information.chat_colors,
+        displayed_skin_parts: client_information.displayed_skin_parts,
+        main_hand: client_information.main_hand,
+        enable_text_filtering: client_information.enable_text_filtering,
+        allow_server_listing: client_information.allow_server_listing,
+        chunks_per_tick: INITIAL_CHUNKS_PER_TICK,
+    })
+}
 
+/// Spawns the player character; the client has finished the configuration phase
+pub fn send_join_game(
+    stream: &mut McStream,
+    character: &PlayerCharacter,
+) -> Result<(), CommunicationError> {
     // Send join game
-    let player_id: usize = 0; // TODO how does player_id work with other players?
+    // the entity id of the player character
+    let player_id = character.entity_id as usize;
 
     let join_game = PlayClientbound::JoinGame {
         player_id: player_id as i32,
         is_hardcore: false,
         dimensions_names: Array::from(vec!["minecraft:overworld"]),
         max_players: mc_packets::VarInt::from(1000),
-        render_distance: mc_packets::VarInt::from(12),
+        render_distance: mc_packets::VarInt::from(MAX_VIEW_DISTANCE),
         simulation_distance: mc_packets::VarInt::from(8),
         reduced_debug_info: false,
         enable_respawn_screen: true,
@@ -272,9 +260,15 @@ pub fn initialize_client(
     println!("EntityEvent sent");
 
     // Declare commands
+    let command_tree = CommandTree::new();
+    let command_data = command_tree
+        .encode()
+        .map_err(|e| CommunicationError::SerializationError(String::from(e)))?;
     let declare_commands = PlayClientbound::DeclareCommands {
-        count: mc_packets::VarInt(0),
-        data: mc_packets::RawBytes { data: &[0] },
+        count: mc_packets::VarInt(command_tree.node_count() as i32),
+        data: mc_packets::RawBytes {
+            data: &command_data,
+        },
     };
     network::send_packet(stream, declare_commands)?;
     println!("DeclareCommands sent");
@@ -299,9 +293,7 @@ pub fn initialize_client(
 
     // Spawn player
     let player_position = character.position;
-    let player_look_dir = character.head_rotation;
-
-    let (player_yaw, player_pitch, _) = player_look_dir.euler_angles();
+    let (player_yaw, player_pitch) = player_movement::yaw_and_pitch(&character.head_rotation);
     let player_position_packet = PlayClientbound::PlayerPositionAndLook {
         x: player_position.x as f64,
         y: player_position.y as f64,
@@ -309,7 +301,7 @@ pub fn initialize_client(
         yaw: player_yaw,
         pitch: player_pitch,
         flags: 0x00,
-        teleport_id: mc_packets::VarInt(1),
+        teleport_id: mc_packets::VarInt(player_movement::INITIAL_TELEPORT_ID),
     };
     network::send_packet(stream, player_position_packet)?;
     println!("PlayerPositionAndLook sent");
@@ -474,90 +466,10 @@ pub fn initialize_client(
     network::send_packet(stream, set_experience)?;
     println!("SetExperience sent");
 
-    Ok(PlayerInfo {
-        socket,
-        username: logged_in_player_info.username,
-        uuid: logged_in_player_info.uuid,
-        locale: locale.to_owned(),
-        render_distance: render_distance.try_into().unwrap_or(5),
-        chat_mode,
-        chat_colors,
-        displayed_skin_parts,
-        main_hand,
-        enable_text_filtering,
-        allow_server_listing,
-        chunks_per_tick: 1.0,
-    })
-}
-
-pub fn send_initial_chunk_data(
-    stream: &mut TcpStream,
-    world: &World,
-    player_position: Position,
-) -> Result<f32, CommunicationError> {
-    // Chunk batch start
-    let chunk_data = PlayClientbound::ChunkBatchStart;
-    network::send_packet(stream, chunk_data)?;
-    println!("ChunkBatchStart sent");
-
-    let chunks = world.get_area(player_position);
-    for chunk_column in chunks {
-        let serialized = chunk_column
-            .to_minecraft()
-            .map_err(|e| CommunicationError::SerializationError(String::from(e)))?;
-
-        let mut heightmaps = HashMap::new();
-        heightmaps.insert(
-            String::from("WORLD_SURFACE"),
-            NbtTag::LongArray(serialized.heightmap_world_surface),
-        );
-        heightmaps.insert(
-            String::from("MOTION_BLOCKING"),
-            NbtTag::LongArray(serialized.heightmap_motion_blocking),
-        );
-
-        let chunk_data = PlayClientbound::ChunkData {
-            value: mc_components::chunk::ChunkData {
-                chunk_x: serialized.chunk_x_16,
-                chunk_z: serialized.chunk_z_16,
-                heightmaps: NbtTag::Compound(heightmaps),
-                data: Array::from(serialized.chunk_sections),
-                block_entities: Array::from(serialized.block_entities),
-                sky_light_mask: Array::default(),
-                block_light_mask: Array::default(),
-                empty_sky_light_mask: Array::default(),
-                empty_block_light_mask: Array::default(),
-                sky_light: Array::default(),
-                block_light: Array::default(),
-            },
-        };
-        network::send_packet(stream, chunk_data)?;
-    }
-
-    println!("ChunkData sent");
-
-    // Chunk batch end
-    let chunk_data = PlayClientbound::ChunkBatchFinished {
-        batch_size: mc_packets::VarInt(49),
-    };
-    network::send_packet(stream, chunk_data)?;
-    println!("ChunkBatchFinished sent");
-
-    // Get chunk batch acknoledgement
-    let mut buffer = Vec::new();
-    let packet: PlayServerbound = network::receive_packet(stream, &mut buffer)?;
-    let PlayServerbound::ChunkBatchReceived { chunks_per_tick } = packet else {
-        return Err(CommunicationError::wrong_package(
-            "ChunkBatchReceived",
-            packet,
-        ));
-    };
-    println!("ChunkBatchReceived received");
-
-    Ok(chunks_per_tick)
+    Ok(())
 }
 
-pub fn send_status_response(stream: &mut TcpStream) -> Result<(), CommunicationError> {
+pub fn send_status_response(stream: &mut McStream) -> Result<(), CommunicationError> {
     let status_msg = status::ClientboundPacket::Response {
         json_response: include_str!("raw/status_response.json"),
     };
@@ -565,7 +477,7 @@ pub fn send_status_response(stream: &mut TcpStream) -> Result<(), CommunicationE
     Ok(())
 }
 
-pub(crate) fn pong(stream: &mut TcpStream, payload: i64) -> Result<(), CommunicationError> {
+pub(crate) fn pong(stream: &mut McStream, payload: i64) -> Result<(), CommunicationError> {
     network::send_packet(
         stream,
         mc_packets::status::ClientboundPacket::Pong { payload },
@@ -1,13 +1,111 @@
 use std::{
     io::{self, Read, Write},
-    net::TcpStream,
+    net::{Shutdown, TcpStream},
+    sync::{Arc, Mutex},
 };
 
 use crate::minecraft_connection::login::CommunicationError;
+use aes::cipher::{inout::InOutBuf, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::Aes128;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use minecraft_protocol::{packets as mc_packets, MinecraftPacketPart};
 
+// like vanilla: larger packets are rejected, even if their compressed data is smaller
+const MAX_UNCOMPRESSED_LENGTH: usize = 8 * 1024 * 1024;
+// like vanilla: the length of a frame fits into 3 bytes
+const MAX_FRAME_LENGTH: usize = 2097151;
+
+type Encryptor = cfb8::Encryptor<Aes128>;
+type Decryptor = cfb8::Decryptor<Aes128>;
+
+/// The connection to a minecraft client.
+/// Once compression is enabled, every frame starts with the length of the uncompressed packet,
+/// or 0 if the packet is not compressed, see https://wiki.vg/Protocol#With_compression
+/// Once encryption is enabled, every byte is encrypted, see https://wiki.vg/Protocol_Encryption
+pub struct McStream {
+    socket: TcpStream,
+    // packets of at least this many bytes are compressed; None before `SetCompression`
+    compression_threshold: Option<usize>,
+    // the ciphers are shared by the clones of the stream, as their state depends on every byte
+    // that was sent or received before. None before `EncryptionResponse`
+    encryptor: Option<Arc<Mutex<Encryptor>>>,
+    decryptor: Option<Arc<Mutex<Decryptor>>>,
+}
+
+impl McStream {
+    pub fn new(socket: TcpStream) -> Self {
+        McStream {
+            socket,
+            compression_threshold: None,
+            encryptor: None,
+            decryptor: None,
+        }
+    }
+
+    /// Must be called right after the `EncryptionResponse` was received, the following bytes are
+    /// encrypted. The shared secret is both the key and the initial vector, in both directions
+    pub fn set_encryption(&mut self, shared_secret: &[u8]) -> io::Result<()> {
+        let invalid_secret = |_| io::Error::new(io::ErrorKind::InvalidInput, "invalid secret");
+        let encryptor =
+            Encryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid_secret)?;
+        let decryptor =
+            Decryptor::new_from_slices(shared_secret, shared_secret).map_err(invalid_secret)?;
+        self.encryptor = Some(Arc::new(Mutex::new(encryptor)));
+        self.decryptor = Some(Arc::new(Mutex::new(decryptor)));
+        Ok(())
+    }
+
+    // the decrypted bytes of the client
+    fn read_exact(&mut self, data: &mut [u8]) -> io::Result<()> {
+        self.socket.read_exact(data)?;
+        if let Some(decryptor) = &self.decryptor {
+            // cfb8 has blocks of a single byte
+            let (blocks, _) = InOutBuf::from(data).into_chunks();
+            decryptor.lock().unwrap().decrypt_blocks_inout_mut(blocks);
+        }
+        Ok(())
+    }
+
+    // the frame is encrypted in place
+    fn write_all(&mut self, data: &mut [u8]) -> io::Result<()> {
+        match &self.encryptor {
+            Some(encryptor) => {
+                // the bytes must be sent in the order in which they were encrypted
+                let mut encryptor = encryptor.lock().unwrap();
+                let (blocks, _) = InOutBuf::from(&mut *data).into_chunks();
+                encryptor.encrypt_blocks_inout_mut(blocks);
+                self.socket.write_all(data)?;
+            },
+            None => self.socket.write_all(data)?,
+        }
+        self.socket.flush()
+    }
+
+    /// Must be called right after `SetCompression` was sent, the following packets are compressed
+    pub fn set_compression(&mut self, threshold: usize) {
+        self.compression_threshold = Some(threshold);
+    }
+
+    /// Closes the connection for all handles; a handle that waits for the client stops waiting
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.socket.shutdown(Shutdown::Both)
+    }
+
+    /// Another handle to the same connection, with the same compression and encryption
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(McStream {
+            socket: self.socket.try_clone()?,
+            compression_threshold: self.compression_threshold,
+            encryptor: self.encryptor.clone(),
+            decryptor: self.decryptor.clone(),
+        })
+    }
+}
+
 pub fn send_packet<'a>(
-    stream: &mut TcpStream,
+    stream: &mut McStream,
     packet: impl MinecraftPacketPart<'a>,
 ) -> io::Result<()> {
     let packet = packet.serialize_minecraft_packet().unwrap();
@@ -15,21 +113,36 @@ pub fn send_packet<'a>(
     Ok(())
 }
 
-pub fn send_packet_raw(stream: &mut TcpStream, packet: &[u8]) -> io::Result<()> {
-    let length = mc_packets::VarInt::from(packet.len());
+pub fn send_packet_raw(stream: &mut McStream, packet: &[u8]) -> io::Result<()> {
+    let frame = match stream.compression_threshold {
+        None => packet.to_vec(),
+        Some(threshold) if packet.len() < threshold => {
+            let mut frame = serialize_var_int(0)?;
+            frame.extend_from_slice(packet);
+            frame
+        },
+        Some(_) => {
+            // the compressed data follows the uncompressed length
+            let mut encoder =
+                ZlibEncoder::new(serialize_var_int(packet.len())?, Compression::default());
+            encoder.write_all(packet)?;
+            encoder.finish()?
+        },
+    };
 
-    let length_packet = length
-        .serialize_minecraft_packet()
-        .map_err(|s| io::Error::new(io::ErrorKind::InvalidInput, s))?;
+    let mut data = serialize_var_int(frame.len())?;
+    data.extend_from_slice(&frame);
+    stream.write_all(&mut data)
+}
 
-    stream.write_all(length_packet.as_slice())?;
-    stream.write_all(packet)?;
-    stream.flush()?;
-    Ok(())
+fn serialize_var_int(value: usize) -> io::Result<Vec<u8>> {
+    mc_packets::VarInt::from(value)
+        .serialize_minecraft_packet()
+        .map_err(|s| io::Error::new(io::ErrorKind::InvalidInput, s))
 }
 
 pub fn receive_packet<'a, PacketType>(
-    stream: &mut TcpStream,
+    stream: &mut McStream,
     buffer: &'a mut Vec<u8>,
 ) -> Result<PacketType, CommunicationError>
 where
@@ -40,7 +153,52 @@ where
         .map_err(|s| CommunicationError::DeserializationError(s.to_string()))
 }
 
-pub fn receive_packet_raw(stream: &mut TcpStream) -> Result<Vec<u8>, CommunicationError> {
+pub fn receive_packet_raw(stream: &mut McStream) -> Result<Vec<u8>, CommunicationError> {
+    let frame = receive_frame(stream)?;
+    match stream.compression_threshold {
+        Some(_) => decompress_frame(&frame),
+        None => Ok(frame),
+    }
+}
+
+pub(crate) fn decompress_frame(frame: &[u8]) -> Result<Vec<u8>, CommunicationError> {
+    let (data_length, compressed) = mc_packets::VarInt::deserialize_minecraft_packet_part(frame)
+        .map_err(|s| CommunicationError::DeserializationError(s.to_string()))?;
+
+    // packets below the threshold are sent uncompressed, with a data length of 0
+    if data_length.0 == 0 {
+        return Ok(compressed.to_vec());
+    }
+
+    let data_length = usize::try_from(data_length.0)
+        .ok()
+        .filter(|&length| length <= MAX_UNCOMPRESSED_LENGTH)
+        .ok_or_else(|| {
+            CommunicationError::DeserializationError(format!(
+                "invalid uncompressed length {}",
+                data_length.0
+            ))
+        })?;
+
+    let mut data = Vec::with_capacity(data_length);
+    // read one byte more than announced, to detect packets that are longer
+    ZlibDecoder::new(compressed)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| CommunicationError::DeserializationError(e.to_string()))?;
+
+    if data.len() != data_length {
+        return Err(CommunicationError::DeserializationError(format!(
+            "uncompressed length {} does not match the announced length {data_length}",
+            data.len()
+        )));
+    }
+
+    Ok(data)
+}
+
+// the packet, or the compressed frame once compression is enabled, without its length
+fn receive_frame(stream: &mut McStream) -> Result<Vec<u8>, CommunicationError> {
     let mut length: Vec<u8> = Vec::with_capacity(2);
 
     loop {
@@ -72,10 +230,15 @@ pub fn receive_packet_raw(stream: &mut TcpStream) -> Result<Vec<u8>, Communicati
         mc_packets::VarInt::deserialize_uncompressed_minecraft_packet(length.as_mut_slice())
             .map_err(|s| io::Error::new(io::ErrorKind::InvalidData, s))?;
 
-    let mut data = Vec::with_capacity(length.0 as usize);
-    unsafe {
-        data.set_len(length.0 as usize);
-    }
+    // checked before allocating, the length is sent by the client
+    let length = usize::try_from(length.0)
+        .ok()
+        .filter(|&length| length > 0 && length <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| {
+            CommunicationError::DeserializationError(format!("invalid frame length {}", length.0))
+        })?;
+
+    let mut data = vec![0; length];
     stream.read_exact(&mut data)?;
 
     Ok(data)
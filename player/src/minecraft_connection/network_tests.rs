@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use minecraft_protocol::packets::VarInt;
+    use minecraft_protocol::MinecraftPacketPart;
+
+    use crate::minecraft_connection::login::CommunicationError;
+    use crate::minecraft_connection::network::{self, McStream};
+
+    fn var_int(value: usize) -> Vec<u8> {
+        VarInt::from(value).serialize_minecraft_packet().unwrap()
+    }
+
+    // the announced length, followed by the compressed data
+    fn compressed_frame(data_length: usize, data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(var_int(data_length), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn is_deserialization_error<T>(result: Result<T, CommunicationError>) -> bool {
+        matches!(result, Err(CommunicationError::DeserializationError(_)))
+    }
+
+    // receives a single frame that the client sent, without compression
+    fn receive(sent: &[u8]) -> Result<Vec<u8>, CommunicationError> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (socket, _) = listener.accept().unwrap();
+        client.write_all(sent).unwrap();
+        // the client closes the connection after the frame
+        drop(client);
+        network::receive_packet_raw(&mut McStream::new(socket))
+    }
+
+    #[test]
+    fn test_decompress_round_trip() {
+        let packet: Vec<u8> = (0..1000).map(|i| (i % 7) as u8).collect();
+        let frame = compressed_frame(packet.len(), &packet);
+        assert!(frame.len() < packet.len());
+        assert_eq!(network::decompress_frame(&frame).unwrap(), packet);
+    }
+
+    #[test]
+    fn test_decompress_uncompressed() {
+        // packets below the threshold only have a data length of 0
+        let mut frame = var_int(0);
+        frame.extend_from_slice(&[0x12, 0x34]);
+        assert_eq!(network::decompress_frame(&frame).unwrap(), vec![0x12, 0x34]);
+        assert_eq!(network::decompress_frame(&[0]).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decompress_length_mismatch() {
+        let packet = [1, 2, 3, 4, 5];
+        // the packet is longer than announced
+        assert!(is_deserialization_error(network::decompress_frame(
+            &compressed_frame(4, &packet)
+        )));
+        // or shorter
+        assert!(is_deserialization_error(network::decompress_frame(
+            &compressed_frame(6, &packet)
+        )));
+    }
+
+    #[test]
+    fn test_decompress_invalid_length() {
+        // larger than vanilla accepts, even if the data would be smaller
+        let packet = [0; 16];
+        assert!(is_deserialization_error(network::decompress_frame(
+            &compressed_frame(8 * 1024 * 1024 + 1, &packet)
+        )));
+
+        let mut frame = VarInt(-1).serialize_minecraft_packet().unwrap();
+        frame.extend_from_slice(&packet);
+        assert!(is_deserialization_error(network::decompress_frame(&frame)));
+
+        // no length at all, or data that is not compressed
+        assert!(is_deserialization_error(network::decompress_frame(&[])));
+        assert!(is_deserialization_error(network::decompress_frame(&[
+            3, 1, 2, 3
+        ])));
+    }
+
+    #[test]
+    fn test_receive_frame() {
+        assert_eq!(receive(&[3, 1, 2, 3]).unwrap(), vec![1, 2, 3]);
+        assert!(matches!(
+            receive(&[]),
+            Err(CommunicationError::ConnectionClosed)
+        ));
+        // the frame is cut short
+        assert!(matches!(
+            receive(&[3, 1]),
+            Err(CommunicationError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_receive_invalid_frame_length() {
+        assert!(is_deserialization_error(receive(&[0])));
+        assert!(is_deserialization_error(receive(&var_int(2097152))));
+        assert!(is_deserialization_error(receive(
+            &VarInt(-1).serialize_minecraft_packet().unwrap()
+        )));
+        // more than 5 bytes
+        assert!(is_deserialization_error(receive(&[0x80; 6])));
+
+        // the largest frame is accepted, and then waits for its data
+        assert!(matches!(
+            receive(&var_int(2097151)),
+            Err(CommunicationError::IoError(_))
+        ));
+    }
+}
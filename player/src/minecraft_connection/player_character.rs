@@ -2,8 +2,10 @@ use sol_voxel_lib::vector_alias::{Position, Rotation};
 
 pub struct PlayerCharacter {
     pub entity_id: u32,
-    pub uuid: [i32; 4],
+    pub uuid: u128,
+    pub name: String,
     pub position: Position,
     pub head_rotation: Rotation,
+    pub on_ground: bool,
     // TODO
 }
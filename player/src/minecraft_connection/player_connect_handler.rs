@@ -1,65 +1,87 @@
-use std::{
-    net::{TcpListener, TcpStream},
-    time::Duration,
-};
+use std::{io, net::TcpListener, time::Duration};
 
-use crate::voxels::world::World;
 use minecraft_protocol::packets as mc_packets;
 use sol_address_server::static_addresses;
+use sol_log_server::logger_mt::LoggerMt;
 
-use super::network;
+use super::network::{self, McStream};
 use super::{
+    authentication::OnlineMode,
+    configuration::{self, ClientInformation},
     login::{self, CommunicationError, PlayerConnectionData},
     player_character::PlayerCharacter,
 };
 
 const CLIENT_CONNECTION_TIMEOUT: Duration = Duration::from_millis(5000);
 
-pub struct PLayerConnectHandler {}
+/// Accepts the connections of the clients; each connection is handled on its own thread
+pub struct PLayerConnectHandler {
+    listener: TcpListener,
+    compression_threshold: Option<usize>,
+    online_mode: Option<OnlineMode>,
+}
 
 impl PLayerConnectHandler {
-    pub fn await_connect() -> Result<(PlayerConnectionData, TcpStream), CommunicationError> {
-        loop {
-            let listener = TcpListener::bind(static_addresses::MINECRAFT_SERVER_BIND)
-                .expect("Failed to listen");
+    /// Packets of at least `compression_threshold` bytes are compressed, none if it is None.
+    /// In online mode, the connection is encrypted and the player is authenticated
+    pub fn bind(
+        compression_threshold: Option<usize>,
+        online_mode: Option<OnlineMode>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(static_addresses::MINECRAFT_SERVER_BIND)?;
+        Ok(PLayerConnectHandler {
+            listener,
+            compression_threshold,
+            online_mode,
+        })
+    }
 
-            // Accept 1 incoming connections
-            let (mut stream, _addr) = listener.accept()?;
-            stream.set_read_timeout(Some(CLIENT_CONNECTION_TIMEOUT))?;
+    /// Waits for the next client to connect
+    pub fn accept(&self) -> io::Result<McStream> {
+        let (stream, _addr) = self.listener.accept()?;
+        stream.set_read_timeout(Some(CLIENT_CONNECTION_TIMEOUT))?;
+        Ok(McStream::new(stream))
+    }
 
-            let mut buffer = Vec::new();
-            let handshake_packet: mc_packets::handshake::ServerboundPacket =
-                network::receive_packet(&mut stream, &mut buffer)?;
+    /// Logs the client in. Returns None if the client only asked for the status of the server
+    pub fn handshake(
+        &self,
+        stream: &mut McStream,
+    ) -> Result<Option<PlayerConnectionData>, CommunicationError> {
+        let mut buffer = Vec::new();
+        let handshake_packet: mc_packets::handshake::ServerboundPacket =
+            network::receive_packet(stream, &mut buffer)?;
 
-            let mc_packets::handshake::ServerboundPacket::Hello {
-                protocol_version: _,
-                server_address: _,
-                server_port: _,
-                next_state,
-            } = handshake_packet;
+        let mc_packets::handshake::ServerboundPacket::Hello {
+            protocol_version: _,
+            server_address: _,
+            server_port: _,
+            next_state,
+        } = handshake_packet;
 
-            match next_state {
-                mc_packets::ConnectionState::Status => {
-                    Self::handle_status_state(&mut stream)?;
-                    // continue waiting for connections
-                },
-                mc_packets::ConnectionState::Login => {
-                    let player_info = login::login(&mut stream)?;
+        match next_state {
+            mc_packets::ConnectionState::Status => {
+                Self::handle_status_state(stream)?;
+                Ok(None)
+            },
+            mc_packets::ConnectionState::Login => {
+                let player_info = login::login(
+                    stream,
+                    self.compression_threshold,
+                    self.online_mode.as_ref(),
+                )?;
 
-                    // TODO keep the player busy while we connect to the back-end
-                    return Ok((player_info, stream));
-                },
-                _ => {
-                    return Err(CommunicationError::UnexpectedState {
-                        from: mc_packets::ConnectionState::HandShake,
-                        to: next_state,
-                    })
-                },
-            };
+                // TODO keep the player busy while we connect to the back-end
+                Ok(Some(player_info))
+            },
+            _ => Err(CommunicationError::UnexpectedState {
+                from: mc_packets::ConnectionState::HandShake,
+                to: next_state,
+            }),
         }
     }
 
-    fn handle_status_state(stream: &mut TcpStream) -> Result<(), CommunicationError> {
+    fn handle_status_state(stream: &mut McStream) -> Result<(), CommunicationError> {
         let mut buffer = Vec::new();
         loop {
             let packet: mc_packets::status::ServerboundPacket =
@@ -78,19 +100,26 @@ impl PLayerConnectHandler {
         }
     }
 
+    /// The columns around the player are streamed by the game loop afterwards
     pub fn send_player_join(
         player: PlayerConnectionData,
         character: &PlayerCharacter,
-        world: &mut World,
-        socket: TcpStream,
+        socket: McStream,
+        logger: &LoggerMt,
     ) -> Result<login::PlayerInfo, CommunicationError> {
         // player is spawning
+        login::initialize_client(socket, player, character, logger)
+    }
 
-        let mut player_info = login::initialize_client(socket, player, character)?;
-        let chunks_per_tick =
-            login::send_initial_chunk_data(&mut player_info.socket, world, character.position)?;
-        player_info.chunks_per_tick = chunks_per_tick;
-
-        Ok(player_info)
+    /// Configures the client again after it left the play phase, and spawns the character.
+    /// Returns the settings of the client if they changed
+    pub fn send_player_rejoin(
+        character: &PlayerCharacter,
+        socket: &mut McStream,
+        logger: &LoggerMt,
+    ) -> Result<Option<ClientInformation>, CommunicationError> {
+        let client_information = configuration::configure(socket, logger)?;
+        login::send_join_game(socket, character)?;
+        Ok(client_information)
     }
 }
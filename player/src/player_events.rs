@@ -1,5 +1,5 @@
 use minecraft_protocol::components::slots::Hand;
-use sol_voxel_lib::vector_alias::Coordinate;
+use sol_voxel_lib::vector_alias::{Coordinate, Position};
 
 pub struct PlayerPlaceBlockEvent {
     pub hand: Hand,
@@ -9,4 +9,39 @@ pub struct PlayerPlaceBlockEvent {
     pub cursor_position_y: f32,
     pub cursor_position_z: f32,
     pub inside_block: bool,
-}
\ No newline at end of file
+    /// acknowledged to the client once the placement is handled
+    pub sequence: i32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DigStatus {
+    Started,
+    Cancelled,
+    Finished,
+}
+
+/// A move reported by the client; absent fields did not change
+pub struct PlayerMoveEvent {
+    /// in minecraft coordinates
+    pub position: Option<Position>,
+    /// yaw and pitch in degrees
+    pub rotation: Option<(f32, f32)>,
+    pub on_ground: bool,
+}
+
+/// A click in a window; see `inventory::InventoryClick`
+pub struct PlayerClickWindowEvent {
+    pub window_id: i8,
+    /// the last state id that the client received for the window
+    pub state_id: i32,
+    pub slot: i16,
+    pub button: i8,
+    pub mode: i32,
+}
+
+pub struct PlayerDigBlockEvent {
+    pub status: DigStatus,
+    pub location: Coordinate,
+    /// acknowledged to the client once the dig action is handled
+    pub sequence: i32,
+}
@@ -1,4 +1,5 @@
 use crate::game_event::Event;
+use crate::item_entities;
 use crate::item_stack::ItemStack;
 use crate::player_events::PlayerPlaceBlockEvent;
 use crate::player_state::{PlayerState, PLAYER_OFF_HAND_SLOT};
@@ -9,7 +10,7 @@ use minecraft_registries::block_placement_registry::BlockPlacementParameters;
 use minecraft_registries::item_click_registry::ItemClickEvent;
 use minecraft_vanilla::registries::Registries;
 use sol_log_server::logger_mt::LoggerMt;
-use sol_voxel_lib::vector_alias::Coordinate;
+use sol_voxel_lib::vector_alias::{Coordinate, Position, Vector3f};
 use sol_voxel_lib::voxel::Voxel;
 
 pub struct PlayerHandler {
@@ -24,15 +25,27 @@ impl PlayerHandler {
             player_state,
         }
     }
+
+    pub fn player_state(&self) -> &PlayerState {
+        &self.player_state
+    }
+
+    pub fn player_state_mut(&mut self) -> &mut PlayerState {
+        &mut self.player_state
+    }
 }
 
-// NOTE: removes the item from the player but does not add the block to the world
+// NOTE: removes the item from the player but does not add the block to the world.
+// Items that would spawn or throw an entity are dropped as item entities instead, as are
+// transformed items that do not fit into the inventory; the position of the player is in the
+// coordinates of our voxels
 pub fn handle_block_place_event(
     command: PlayerPlaceBlockEvent,
+    player_position: Position,
     player: &mut PlayerState,
     world: &World,
     registries: &Registries,
-) -> Option<Event> {
+) -> Vec<Event> {
     let slot_idx = match command.hand {
         Hand::MainHand => player.selected_slot,
         Hand::OffHand => PLAYER_OFF_HAND_SLOT,
@@ -40,8 +53,9 @@ pub fn handle_block_place_event(
 
     let stack = &mut player.slots[slot_idx];
 
-    let placed_item = match stack.take_one() {
-        ItemStack::Empty => return None,
+    let taken = stack.take_one();
+    let placed_item = match &taken {
+        ItemStack::Empty => return Vec::new(),
         ItemStack::Simple(stack) => stack.item_type(),
         ItemStack::NbtItem(item) => item.item_type(),
     };
@@ -64,30 +78,53 @@ pub fn handle_block_place_event(
     let event = registries.get_item_click_event(placed_item, target_block, how);
 
     match event {
-        ItemClickEvent::Nothing => None,
+        ItemClickEvent::Nothing => Vec::new(),
         ItemClickEvent::Something => todo!(),
         ItemClickEvent::BlockPlacement {
             block,
             change,
             replace,
         } => {
-            player.handle_item_change(slot_idx, change);
-            if replace {
-                Some(Event::VoxelChange {
-                    coord: command.location,
-                    new_voxel: Voxel::from_block(block),
-                })
+            let leftover = player.handle_item_change(slot_idx, change);
+            let coord = if replace {
+                command.location
             } else {
-                let new_position = command.location + block_face_to_difference(command.face);
-                Some(Event::VoxelChange {
-                    coord: new_position,
-                    new_voxel: Voxel::from_block(block),
-                })
+                command.location + block_face_to_difference(command.face)
+            };
+
+            let mut events = vec![Event::VoxelChange {
+                coord,
+                new_voxel: Voxel::from_block(block),
+            }];
+            if !leftover.is_empty() {
+                events.push(item_entities::throw(
+                    player_position,
+                    player.look_direction,
+                    leftover,
+                ));
             }
+            events
+        },
+        ItemClickEvent::Eat { .. } => Vec::new(),
+        // TODO entities: spawn eggs and projectiles need entity types that we do not have yet
+        ItemClickEvent::EntitySpawn { .. } => {
+            let target = command.location + block_face_to_difference(command.face);
+            vec![Event::ItemEntitySpawn {
+                position: Position::new(
+                    target.x as f32 + 0.5,
+                    target.y as f32,
+                    target.z as f32 + 0.5,
+                ),
+                velocity: Vector3f::zeros(),
+                stack: taken,
+                pickup_delay: item_entities::BLOCK_DROP_PICKUP_DELAY,
+            }]
         },
-        ItemClickEvent::Eat { .. } => None,
-        ItemClickEvent::EntitySpawn { .. } => None,
-        ItemClickEvent::EntityThrow { .. } => None,
+        ItemClickEvent::EntityThrow { .. } => vec![item_entities::throw(
+            player_position,
+            player.look_direction,
+            taken,
+        )],
     }
 }
 
@@ -0,0 +1,130 @@
+use crate::minecraft_connection::coordinates::MINECRAFT_MIN_Y;
+use crate::voxels::world::World;
+use minecraft_vanilla::registries::Registries;
+use sol_voxel_lib::vector_alias::{
+    ChunkColumnCoordinate, Coordinate, Direction, Position, Rotation, Vector3f,
+};
+
+/// The teleport id of the position that is sent at login
+pub const INITIAL_TELEPORT_ID: i32 = 1;
+
+// like vanilla: a player moves at most 10 blocks per tick, however many moves the client sends
+const MAX_MOVE_DISTANCE_PER_TICK: f32 = 10.0;
+// the distance that is not used in a tick carries over up to this, as the moves of a client do
+// not arrive exactly once per tick
+const MAX_SAVED_MOVE_DISTANCE: f32 = 3.0 * MAX_MOVE_DISTANCE_PER_TICK;
+const PLAYER_HALF_WIDTH: f32 = 0.3;
+const PLAYER_HEIGHT: f32 = 1.8;
+// standing exactly on (or next to) a block is not a collision
+const COLLISION_EPSILON: f32 = 1e-5;
+
+/// The distance that a player may still move, so that many small moves in one tick are not
+/// faster than a single large one
+pub struct MoveBudget {
+    remaining: f32,
+}
+
+impl MoveBudget {
+    pub fn new() -> MoveBudget {
+        MoveBudget {
+            remaining: MAX_MOVE_DISTANCE_PER_TICK,
+        }
+    }
+
+    /// Call once per tick
+    pub fn tick(&mut self) {
+        self.remaining = (self.remaining + MAX_MOVE_DISTANCE_PER_TICK).min(MAX_SAVED_MOVE_DISTANCE);
+    }
+
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+}
+
+/// Checks a move that the client reports, in minecraft coordinates, and takes its distance from
+/// the budget if it is valid.
+/// Moves that are too fast, that end in an unloaded column or that end inside a solid block are
+/// invalid. A player that is already stuck inside blocks may move freely to get out.
+pub fn is_valid_move(
+    from: Position,
+    to: Position,
+    budget: &mut MoveBudget,
+    world: &World,
+    registries: &Registries,
+) -> bool {
+    if !(to.x.is_finite() && to.y.is_finite() && to.z.is_finite()) {
+        return false;
+    }
+
+    let distance = (to - from).norm();
+    if distance > budget.remaining {
+        return false;
+    }
+
+    if !world.contains_column(&ChunkColumnCoordinate::containing_position(&to)) {
+        return false;
+    }
+
+    if collides(to, world, registries) && !collides(from, world, registries) {
+        return false;
+    }
+
+    budget.remaining -= distance;
+    true
+}
+
+/// Whether the bounding box of a player at the given position intersects a solid block
+pub fn collides(position: Position, world: &World, registries: &Registries) -> bool {
+    let min_x = (position.x - PLAYER_HALF_WIDTH + COLLISION_EPSILON).floor() as i32;
+    let max_x = (position.x + PLAYER_HALF_WIDTH - COLLISION_EPSILON).floor() as i32;
+    let min_y = (position.y + COLLISION_EPSILON).floor() as i32;
+    let max_y = (position.y + PLAYER_HEIGHT - COLLISION_EPSILON).floor() as i32;
+    let min_z = (position.z - PLAYER_HALF_WIDTH + COLLISION_EPSILON).floor() as i32;
+    let max_z = (position.z + PLAYER_HALF_WIDTH - COLLISION_EPSILON).floor() as i32;
+
+    for y in min_y..=max_y {
+        for z in min_z..=max_z {
+            for x in min_x..=max_x {
+                // blocks above and below the world do not exist
+                let Some(block) = world.get_block(Coordinate::new(x, y - MINECRAFT_MIN_Y, z))
+                else {
+                    continue;
+                };
+
+                let block = registries.block_states().block_state_to_block(block);
+                if registries.block_properties().get_block_properties(block).is_solid {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Yaw and pitch are in degrees, as in the protocol.
+/// A yaw of 0 faces south (+z) and increases clockwise; a positive pitch looks down
+pub fn look_direction(yaw: f32, pitch: f32) -> Direction {
+    let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+    Direction::new_normalize(nalgebra::vector![
+        -yaw.sin() * pitch.cos(),
+        -pitch.sin(),
+        yaw.cos() * pitch.cos()
+    ])
+}
+
+/// The rotation that turns south (+z) into the look direction: the pitch about +x, then the yaw
+/// about +y. Yaw and pitch are in degrees, as in `look_direction`
+pub fn head_rotation(yaw: f32, pitch: f32) -> Rotation {
+    // a clockwise yaw, seen from above, is a negative angle about +y
+    Rotation::from_axis_angle(&Vector3f::y_axis(), -yaw.to_radians())
+        * Rotation::from_axis_angle(&Vector3f::x_axis(), pitch.to_radians())
+}
+
+/// The inverse of `head_rotation`, in degrees. The yaw is between -180 and 180
+pub fn yaw_and_pitch(rotation: &Rotation) -> (f32, f32) {
+    let direction = rotation * Vector3f::z();
+    let yaw = (-direction.x).atan2(direction.z);
+    let pitch = (-direction.y).atan2(direction.x.hypot(direction.z));
+    (yaw.to_degrees(), pitch.to_degrees())
+}
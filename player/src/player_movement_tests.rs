@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_vanilla::registries::Registries;
+    use sol_voxel_lib::vector_alias::{Position, Vector3f};
+
+    use crate::player_movement::{self, MoveBudget};
+    use crate::voxels::world::World;
+
+    // a stone floor whose top is at y = -1, in minecraft coordinates
+    fn floor() -> (World, Registries) {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let world = World::new(registries.block_properties(), registries.block_states());
+        (world, registries)
+    }
+
+    fn assert_near(a: Vector3f, b: Vector3f) {
+        assert!((a - b).norm() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_head_rotation() {
+        let south = Vector3f::z();
+        // the yaw turns about +y: clockwise from south, seen from above
+        assert_near(player_movement::head_rotation(0.0, 0.0) * south, south);
+        assert_near(
+            player_movement::head_rotation(90.0, 0.0) * south,
+            Vector3f::new(-1.0, 0.0, 0.0),
+        );
+        assert_near(
+            player_movement::head_rotation(-90.0, 0.0) * south,
+            Vector3f::new(1.0, 0.0, 0.0),
+        );
+        // a positive pitch looks down
+        assert_near(
+            player_movement::head_rotation(0.0, 90.0) * south,
+            Vector3f::new(0.0, -1.0, 0.0),
+        );
+
+        for (yaw, pitch) in [(30.0, 20.0), (135.0, -45.0), (-100.0, 60.0), (180.0, 0.0)] {
+            let rotation = player_movement::head_rotation(yaw, pitch);
+            let direction = player_movement::look_direction(yaw, pitch);
+            assert_near(rotation * south, direction.into_inner());
+        }
+    }
+
+    #[test]
+    fn test_yaw_and_pitch() {
+        for (yaw, pitch) in [(0.0, 0.0), (30.0, 20.0), (135.0, -45.0), (-100.0, 60.0)] {
+            let rotation = player_movement::head_rotation(yaw, pitch);
+            let (new_yaw, new_pitch) = player_movement::yaw_and_pitch(&rotation);
+            assert!((new_yaw - yaw).abs() < 1e-3, "{new_yaw} != {yaw}");
+            assert!((new_pitch - pitch).abs() < 1e-3, "{new_pitch} != {pitch}");
+        }
+
+        // yaws outside of -180 to 180 face the same way as their counterparts inside
+        let rotation = player_movement::head_rotation(270.0, 0.0);
+        let (yaw, _) = player_movement::yaw_and_pitch(&rotation);
+        assert!((yaw + 90.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_collides() {
+        let (world, registries) = floor();
+        assert!(!player_movement::collides(
+            Position::new(0.5, 0.0, 0.5),
+            &world,
+            &registries
+        ));
+        assert!(player_movement::collides(
+            Position::new(0.5, -0.5, 0.5),
+            &world,
+            &registries
+        ));
+        // far above the world
+        assert!(!player_movement::collides(
+            Position::new(0.5, 400.0, 0.5),
+            &world,
+            &registries
+        ));
+    }
+
+    #[test]
+    fn test_is_valid_move() {
+        let (world, registries) = floor();
+        let from = Position::new(0.5, 0.0, 0.5);
+        let is_valid = |to: Position| {
+            let mut budget = MoveBudget::new();
+            player_movement::is_valid_move(from, to, &mut budget, &world, &registries)
+        };
+
+        assert!(is_valid(Position::new(1.5, 0.0, 0.5)));
+        assert!(is_valid(Position::new(0.5, 1.0, 0.5)));
+        assert!(!is_valid(Position::new(f32::NAN, 0.0, 0.5)));
+        assert!(!is_valid(Position::new(0.5, f32::INFINITY, 0.5)));
+        // into the floor
+        assert!(!is_valid(Position::new(1.5, -0.5, 0.5)));
+        // too far in one move
+        assert!(!is_valid(Position::new(11.0, 0.0, 0.5)));
+        // into a column that is not loaded
+        let mut budget = MoveBudget::new();
+        assert!(!player_movement::is_valid_move(
+            Position::new(175.5, 0.0, 0.5),
+            Position::new(176.5, 0.0, 0.5),
+            &mut budget,
+            &world,
+            &registries,
+        ));
+    }
+
+    #[test]
+    fn test_stuck_player_may_move() {
+        let (world, registries) = floor();
+        let mut budget = MoveBudget::new();
+        assert!(player_movement::is_valid_move(
+            Position::new(0.5, -2.0, 0.5),
+            Position::new(0.5, -1.5, 0.5),
+            &mut budget,
+            &world,
+            &registries,
+        ));
+    }
+
+    #[test]
+    fn test_move_budget() {
+        let (world, registries) = floor();
+        let mut budget = MoveBudget::new();
+        let mut position = Position::new(0.5, 0.0, 0.5);
+        let mut move_by = |budget: &mut MoveBudget, distance: f32| {
+            let to = position + Vector3f::new(distance, 0.0, 0.0);
+            let is_valid =
+                player_movement::is_valid_move(position, to, budget, &world, &registries);
+            if is_valid {
+                position = to;
+            }
+            is_valid
+        };
+
+        // many small moves in one tick are not faster than a single large one
+        assert!(move_by(&mut budget, 4.0));
+        assert!(move_by(&mut budget, 4.0));
+        assert!(!move_by(&mut budget, 4.0));
+        assert!((budget.remaining() - 2.0).abs() < 1e-5);
+        assert!(move_by(&mut budget, 2.0));
+
+        // the budget of the next tick
+        budget.tick();
+        assert!(move_by(&mut budget, 4.0));
+        assert!(!move_by(&mut budget, 8.0));
+
+        // rejected moves do not use up the budget
+        budget.tick();
+        assert!(!move_by(&mut budget, f32::NAN));
+        assert!((budget.remaining() - 16.0).abs() < 1e-5);
+
+        // idle ticks only save up for a few ticks
+        for _ in 0..100 {
+            budget.tick();
+        }
+        assert!((budget.remaining() - 30.0).abs() < 1e-5);
+    }
+}
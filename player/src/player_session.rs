@@ -0,0 +1,264 @@
+// The state of the game loop for one connected player. A session is added to the game loop when
+// its client joined, and removed when the client disconnects; the world stays.
+
+use crate::inventory::{CARRIED_ITEM_SLOT, CARRIED_ITEM_WINDOW, PLAYER_INVENTORY_WINDOW};
+use crate::keep_alive::KeepAlive;
+use crate::minecraft_connection::client_connection::ClientSendCommand;
+use crate::minecraft_connection::coordinates::{self, MINECRAFT_MIN_Y};
+use crate::minecraft_connection::entity_tracker::EntityTracker;
+use crate::minecraft_connection::player_character::PlayerCharacter;
+use crate::player_handler::PlayerHandler;
+use crate::player_movement::{self, MoveBudget};
+use crate::voxels::chunk_streamer::ChunkStreamer;
+use minecraft_protocol::packets::play_clientbound::ClientboundPacket;
+use minecraft_protocol::packets::{Array, VarInt};
+use minecraft_protocol::MinecraftPacketPart;
+use sol_log_server::logger_mt::LoggerMt;
+use sol_log_server::Severity;
+use sol_network_lib::Tick;
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate, Position};
+use std::sync::mpsc;
+use std::time::Instant;
+
+/// Identifies a session for as long as the player server runs.
+/// It is also the entity id of the player character
+pub type SessionId = u32;
+
+// any stage outside of 0..=9 removes the animation
+pub const NO_DESTROY_STAGE: i8 = -1;
+
+pub struct DigProgress {
+    pub location: Coordinate,
+    pub start_tick: Tick,
+    pub required_ticks: u32,
+    pub harvestable: bool,
+    // the destroy stage that was last sent to the client
+    pub stage: i8,
+}
+
+pub struct PlayerSession {
+    pub id: SessionId,
+    logger: LoggerMt,
+    client_comm_channel: mpsc::Sender<ClientSendCommand>,
+    pub player: PlayerHandler,
+    pub character: PlayerCharacter,
+    pub chunk_streamer: ChunkStreamer,
+    pub entity_tracker: EntityTracker,
+    // moves are ignored until the client confirms our last correction of its position
+    pub pending_teleport: Option<i32>,
+    pub next_teleport_id: i32,
+    pub move_budget: MoveBudget,
+    // the highest block change sequence of the client that was handled this tick
+    pub block_change_sequence: Option<i32>,
+    // the block that the player is digging in survival mode
+    pub digging: Option<DigProgress>,
+    // receives the chat of the column that the player is in
+    pub chat_subscriber_socket: zmq::Socket,
+    // the column whose chat topic is subscribed
+    pub chat_column: Option<ChunkColumnCoordinate>,
+    pub keep_alive: KeepAlive,
+    // the client is in the configuration phase again, where play packets are not allowed, or it is
+    // being disconnected
+    left_play: bool,
+}
+
+impl PlayerSession {
+    pub fn new(
+        id: SessionId,
+        logger: LoggerMt,
+        client_comm_channel: mpsc::Sender<ClientSendCommand>,
+        player: PlayerHandler,
+        character: PlayerCharacter,
+        chunk_streamer: ChunkStreamer,
+        chat_subscriber_socket: zmq::Socket,
+    ) -> PlayerSession {
+        PlayerSession {
+            id,
+            logger,
+            client_comm_channel,
+            player,
+            character,
+            chunk_streamer,
+            entity_tracker: EntityTracker::new(),
+            pending_teleport: Some(player_movement::INITIAL_TELEPORT_ID),
+            next_teleport_id: player_movement::INITIAL_TELEPORT_ID + 1,
+            move_budget: MoveBudget::new(),
+            block_change_sequence: None,
+            digging: None,
+            chat_subscriber_socket,
+            chat_column: None,
+            keep_alive: KeepAlive::new(Instant::now()),
+            left_play: false,
+        }
+    }
+
+    /// Makes the client enter the configuration phase again, e.g. before a transfer to another
+    /// server. The session ends when the client acknowledges it; nothing is sent to it meanwhile
+    pub fn start_configuration(&mut self) {
+        self.send_to_client(ClientboundPacket::StartConfiguration);
+        self.left_play = true;
+    }
+
+    /// Shows the reason to the player, and closes the connection.
+    /// The session ends when the receiver notices; nothing is sent to the client meanwhile
+    pub fn disconnect(&mut self, reason: &str) {
+        if self.left_play {
+            return;
+        }
+        self.left_play = true;
+
+        let result = ClientboundPacket::Disconnect { reason }
+            .serialize_minecraft_packet()
+            .map_err(|error| format!("{error:?}"))
+            .and_then(|packet| {
+                self.client_comm_channel
+                    .send(ClientSendCommand::Disconnect(packet))
+                    .map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not disconnect client: {error}"),
+            );
+        }
+    }
+
+    // the position of the player character in the coordinates of our voxels
+    pub fn player_position(&self) -> Position {
+        let position = self.character.position;
+        Position::new(position.x, position.y - MINECRAFT_MIN_Y as f32, position.z)
+    }
+
+    // moves the client back to where we think the player is
+    pub fn synchronize_player_position(&mut self) {
+        let teleport_id = self.next_teleport_id;
+        self.next_teleport_id = self.next_teleport_id.wrapping_add(1);
+        self.pending_teleport = Some(teleport_id);
+
+        let position = self.character.position;
+        let (yaw, pitch) = player_movement::yaw_and_pitch(&self.character.head_rotation);
+        self.send_to_client(ClientboundPacket::PlayerPositionAndLook {
+            x: position.x as f64,
+            y: position.y as f64,
+            z: position.z as f64,
+            yaw,
+            pitch,
+            flags: 0x00,
+            teleport_id: VarInt(teleport_id),
+        });
+    }
+
+    pub fn send_slot(&mut self, slot_idx: usize) {
+        let player_state = self.player.player_state_mut();
+        let state_id = player_state.next_state_id();
+        let slot_data = player_state.slots[slot_idx].to_slot();
+        self.send_to_client(ClientboundPacket::SetContainerSlot {
+            window_id: PLAYER_INVENTORY_WINDOW,
+            state_id: VarInt(state_id),
+            slot: slot_idx as i16,
+            slot_data,
+        });
+    }
+
+    pub fn send_window_slot(&mut self, slot_idx: usize) {
+        let player_state = self.player.player_state_mut();
+        let state_id = player_state.next_state_id();
+        let window_id = player_state.open_window_id();
+        let slot_data = player_state.window_stack(slot_idx).to_slot();
+        self.send_to_client(ClientboundPacket::SetContainerSlot {
+            window_id,
+            state_id: VarInt(state_id),
+            slot: slot_idx as i16,
+            slot_data,
+        });
+    }
+
+    pub fn send_carried(&mut self) {
+        let player_state = self.player.player_state_mut();
+        let state_id = player_state.next_state_id();
+        let slot_data = player_state.carried.to_slot();
+        self.send_to_client(ClientboundPacket::SetContainerSlot {
+            window_id: CARRIED_ITEM_WINDOW,
+            state_id: VarInt(state_id),
+            slot: CARRIED_ITEM_SLOT,
+            slot_data,
+        });
+    }
+
+    // replaces all stacks of the open window on the client
+    pub fn send_window(&mut self) {
+        let player_state = self.player.player_state_mut();
+        let state_id = player_state.next_state_id();
+        let window_id = player_state.open_window_id();
+        let slot_data: Vec<_> = (0..player_state.open_window_layout().slot_count())
+            .map(|slot_idx| player_state.window_stack(slot_idx).to_slot())
+            .collect();
+        let carried_item = player_state.carried.to_slot();
+        self.send_to_client(ClientboundPacket::SetContainerContent {
+            window_id,
+            state_id: VarInt(state_id),
+            slot_data: Array::from(slot_data),
+            carried_item,
+        });
+    }
+
+    pub fn cancel_digging(&mut self) {
+        if let Some(progress) = self.digging.take() {
+            self.send_destroy_stage(progress.location, NO_DESTROY_STAGE);
+        }
+    }
+
+    pub fn send_destroy_stage(&self, location: Coordinate, stage: i8) {
+        self.send_to_client(ClientboundPacket::SetBlockDestroyStage {
+            entity_id: VarInt(self.character.entity_id as i32),
+            location: coordinates::coordinate_to_minecraft(location),
+            destroy_stage: stage,
+        });
+    }
+
+    pub fn send_system_message(&self, content: &str) {
+        self.send_to_client(ClientboundPacket::SystemChatMessage {
+            content,
+            overlay: false,
+        });
+    }
+
+    // for packets that we serialize ourselves
+    pub fn send_raw_to_client(&self, packet: Vec<u8>) {
+        if self.left_play {
+            return;
+        }
+
+        if let Err(error) = self
+            .client_comm_channel
+            .send(ClientSendCommand::Message(packet))
+        {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not send packet to client: {error}"),
+            );
+        }
+    }
+
+    pub fn send_to_client<'a>(&self, packet: impl MinecraftPacketPart<'a>) {
+        if self.left_play {
+            return;
+        }
+
+        let result = ClientSendCommand::try_from(packet)
+            .map_err(|error| format!("{error:?}"))
+            .and_then(|command| {
+                self.client_comm_channel
+                    .send(command)
+                    .map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            self.logger.log(
+                Severity::RecoverableError,
+                &format!("Could not send packet to client: {error}"),
+            );
+        }
+    }
+}
@@ -1,4 +1,7 @@
+use crate::containers::OpenContainer;
+use crate::inventory::{self, Drag, WindowLayout, PLAYER_INVENTORY_WINDOW};
 use crate::item_stack::{ItemStack, NbtItem};
+use minecraft_protocol::components::gamemode::Gamemode;
 use minecraft_protocol::data::items::Item;
 use minecraft_protocol::nbt::NbtTag;
 use minecraft_registries::item_click_registry::ItemChange;
@@ -6,26 +9,52 @@ use sol_voxel_lib::vector_alias::{AxisDirection, Direction};
 use std::array::from_fn;
 use std::ops::RangeInclusive;
 
-pub const PLAYER_OFF_HAND_SLOT: usize = 45;
+pub const PLAYER_CRAFTING_RESULT_SLOT: usize = 0;
+pub const PLAYER_CRAFTING_SLOTS: RangeInclusive<usize> = 1..=4;
+pub const PLAYER_ARMOR_SLOTS: RangeInclusive<usize> = 5..=8;
+pub const PLAYER_MAIN_SLOTS: RangeInclusive<usize> = 9..=35;
 pub const PLAYER_HOTBAR_SLOTS: RangeInclusive<usize> = 36..=44;
+pub const PLAYER_OFF_HAND_SLOT: usize = 45;
+pub const PLAYER_SLOT_COUNT: usize = 46;
+// like vanilla: state ids wrap around at 15 bits
+const STATE_ID_MASK: i32 = 0x7FFF;
+// like vanilla: the ids of container windows go from 1 to 100
+const MAX_WINDOW_ID: i8 = 100;
+
 pub struct PlayerState {
-    // 0 if the inventory window is open
+    // None if the inventory window is open
     // We do not get a message when the inventory is opened,
-    // so 0 may also mean no window is open at all.
-    pub opened_window: u32,
+    // so None may also mean no window is open at all.
+    pub open_container: Option<OpenContainer>,
+    // the id of the container window that was opened last
+    window_counter: i8,
     // see https://minecraft.wiki/w/File:Inventory-slots.png
-    pub slots: [ItemStack; 45],
+    pub slots: [ItemStack; PLAYER_SLOT_COUNT],
+    // the stack that the mouse holds in an open window
+    pub carried: ItemStack,
+    // the drag with the mouse that is in progress
+    pub drag: Option<Drag>,
+    // the state id of the window that we sent last; clicks with an older state id are resynced
+    pub state_id: i32,
     pub selected_slot: usize,
     pub look_direction: Direction,
+    pub game_mode: Gamemode,
 }
 
 impl PlayerState {
-    pub fn handle_item_change(&mut self, slot_idx: usize, change: ItemChange) {
+    /// Returns the transformed item if it did not fit into the inventory
+    pub fn handle_item_change(&mut self, slot_idx: usize, change: ItemChange) -> ItemStack {
         match change {
             ItemChange::Consumed => self.slots[slot_idx].remove_one(),
             ItemChange::Transformed { into } => {
-                self.slots[slot_idx].remove_one();
-                self.add_item(into, 1);
+                let stack = &mut self.slots[slot_idx];
+                stack.remove_one();
+                // like vanilla: the last item is transformed in place
+                if stack.is_empty() {
+                    *stack = ItemStack::one_of(into);
+                } else {
+                    return self.add_item(into, 1);
+                }
             }
             ItemChange::Damaged { quantity } => {
                 match &mut self.slots[slot_idx] {
@@ -36,18 +65,44 @@ impl PlayerState {
                 }
             }
         }
+        ItemStack::Empty
+    }
+
+    /// Returns the items that did not fit into the inventory
+    pub fn add_item(&mut self, item: Item, count: usize) -> ItemStack {
+        let (_, leftover) = self.insert(ItemStack::new(item, count));
+        leftover
+    }
+
+    /// Puts the stack into the inventory, like vanilla: first onto the stacks of the same item in
+    /// the hands, then onto the other stacks of the same item, then into empty slots.
+    /// The hotbar comes before the rest of the inventory.
+    /// Returns the indices of the changed slots, and the part of the stack that did not fit
+    pub fn insert(&mut self, mut stack: ItemStack) -> (Vec<usize>, ItemStack) {
+        let hands = [self.selected_slot, PLAYER_OFF_HAND_SLOT];
+        let mut changed_slots =
+            inventory::merge_into(&mut self.slots, &mut stack, hands.into_iter());
+
+        let (more_changed_slots, leftover) = inventory::insert_into(
+            &mut self.slots,
+            stack,
+            PLAYER_HOTBAR_SLOTS.chain(PLAYER_MAIN_SLOTS),
+        );
+        for slot_idx in more_changed_slots {
+            if !changed_slots.contains(&slot_idx) {
+                changed_slots.push(slot_idx);
+            }
+        }
+        (changed_slots, leftover)
     }
 
-    pub fn add_item(&mut self, item: Item, count: usize) {
+    /// Removes one item, or the whole stack, from the selected slot
+    pub fn drop_selected(&mut self, whole_stack: bool) -> ItemStack {
         let stack = &mut self.slots[self.selected_slot];
-        if stack.is_empty() {
-            *stack = ItemStack::new(item, count)
+        if whole_stack {
+            std::mem::take(stack)
         } else {
-            for stack in &mut self.slots {
-                if stack.is_empty() {
-                    *stack = ItemStack::new(item, count)
-                }
-            }
+            stack.take_one()
         }
     }
 
@@ -75,13 +130,60 @@ impl PlayerState {
 impl PlayerState {
     pub fn new() -> Self {
         Self {
-            opened_window: 0,
+            open_container: None,
+            window_counter: 0,
             slots: from_fn(|_| ItemStack::default()),
+            carried: ItemStack::default(),
+            drag: None,
+            state_id: 0,
             selected_slot: *PLAYER_HOTBAR_SLOTS.start(),
             look_direction: AxisDirection::PosX.get_unit(),
+            // must match the game mode that we send at login
+            game_mode: Gamemode::Creative,
+        }
+    }
+
+    /// Every change of the window that we send to the client gets a new state id
+    pub fn next_state_id(&mut self) -> i32 {
+        self.state_id = (self.state_id + 1) & STATE_ID_MASK;
+        self.state_id
+    }
+
+    /// True if the client clicked before it got our last change of the window
+    pub fn is_outdated(&self, state_id: i32) -> bool {
+        state_id != self.state_id
+    }
+
+    /// The id of the window that is open: the container, or else the inventory
+    pub fn open_window_id(&self) -> i8 {
+        self.open_container
+            .as_ref()
+            .map_or(PLAYER_INVENTORY_WINDOW, |container| container.window_id)
+    }
+
+    pub fn open_window_layout(&self) -> WindowLayout {
+        self.open_container
+            .as_ref()
+            .map_or(WindowLayout::PlayerInventory, |container| {
+                WindowLayout::Container(container.kind)
+            })
+    }
+
+    /// The stack in a slot of the open window
+    pub fn window_stack(&self, slot: usize) -> &ItemStack {
+        let player_slot = self.open_window_layout().player_slot(slot);
+        match (&self.open_container, player_slot) {
+            (_, Some(player_slot)) => &self.slots[player_slot],
+            (Some(container), None) => &container.stacks[slot],
+            (None, None) => unreachable!("every slot of the inventory window is a player slot"),
         }
     }
 
+    pub fn next_window_id(&mut self) -> i8 {
+        self.window_counter = self.window_counter % MAX_WINDOW_ID + 1;
+        self.window_counter
+    }
+
     pub fn select_slot(&mut self, slot: usize) {
         assert!(PLAYER_HOTBAR_SLOTS.contains(&slot));
         self.selected_slot = slot;
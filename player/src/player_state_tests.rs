@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::items::Item;
+    use minecraft_registries::item_click_registry::ItemChange;
+
+    use crate::item_stack::ItemStack;
+    use crate::player_state::{
+        PlayerState, PLAYER_HOTBAR_SLOTS, PLAYER_MAIN_SLOTS, PLAYER_OFF_HAND_SLOT,
+    };
+
+    fn item(name: &str) -> Item {
+        Item::from_text_id(name).unwrap()
+    }
+
+    fn stack(name: &str, count: usize) -> ItemStack {
+        ItemStack::new(item(name), count)
+    }
+
+    fn is_stack_of(stack: &ItemStack, name: &str, count: usize) -> bool {
+        stack.item_type().map(|item| item.id()) == Some(item(name).id()) && stack.count() == count
+    }
+
+    // every slot of the hotbar and the main inventory holds a full stack
+    fn full_player() -> PlayerState {
+        let mut player = PlayerState::new();
+        for slot_idx in PLAYER_HOTBAR_SLOTS.chain(PLAYER_MAIN_SLOTS) {
+            player.slots[slot_idx] = stack("dirt", 64);
+        }
+        player
+    }
+
+    #[test]
+    fn test_insert_into_empty_inventory() {
+        let mut player = PlayerState::new();
+        let (changed_slots, leftover) = player.insert(stack("stone", 10));
+        assert_eq!(changed_slots, vec![36]);
+        assert!(leftover.is_empty());
+        assert!(is_stack_of(&player.slots[36], "stone", 10));
+
+        // more than a stack takes several slots
+        let (changed_slots, leftover) = player.insert(stack("dirt", 100));
+        assert_eq!(changed_slots, vec![37, 38]);
+        assert!(leftover.is_empty());
+        assert!(is_stack_of(&player.slots[37], "dirt", 64));
+        assert!(is_stack_of(&player.slots[38], "dirt", 36));
+
+        let (changed_slots, leftover) = player.insert(ItemStack::Empty);
+        assert!(changed_slots.is_empty());
+        assert!(leftover.is_empty());
+    }
+
+    #[test]
+    fn test_insert_into_hands_first() {
+        let mut player = PlayerState::new();
+        player.select_slot(40);
+        player.slots[9] = stack("stone", 10);
+        player.slots[36] = stack("stone", 10);
+        player.slots[40] = stack("stone", 60);
+        player.slots[PLAYER_OFF_HAND_SLOT] = stack("stone", 60);
+
+        let (changed_slots, leftover) = player.insert(stack("stone", 20));
+        assert!(leftover.is_empty());
+        assert_eq!(changed_slots, vec![40, PLAYER_OFF_HAND_SLOT, 36]);
+        assert!(is_stack_of(&player.slots[40], "stone", 64));
+        assert!(is_stack_of(
+            &player.slots[PLAYER_OFF_HAND_SLOT],
+            "stone",
+            64
+        ));
+        assert!(is_stack_of(&player.slots[36], "stone", 22));
+        assert!(is_stack_of(&player.slots[9], "stone", 10));
+    }
+
+    #[test]
+    fn test_insert_onto_stacks_before_empty_slots() {
+        let mut player = PlayerState::new();
+        player.slots[20] = stack("stone", 60);
+
+        // the stack in the main inventory comes before the empty hotbar
+        let (changed_slots, leftover) = player.insert(stack("stone", 10));
+        assert!(leftover.is_empty());
+        assert_eq!(changed_slots, vec![20, 36]);
+        assert!(is_stack_of(&player.slots[20], "stone", 64));
+        assert!(is_stack_of(&player.slots[36], "stone", 6));
+    }
+
+    #[test]
+    fn test_insert_into_full_inventory() {
+        let mut player = full_player();
+        player.slots[30] = stack("stone", 62);
+
+        let (changed_slots, leftover) = player.insert(stack("stone", 5));
+        assert_eq!(changed_slots, vec![30]);
+        assert!(is_stack_of(&leftover, "stone", 3));
+        // the off hand only takes the same items
+        assert!(player.slots[PLAYER_OFF_HAND_SLOT].is_empty());
+
+        let leftover = player.add_item(item("stone"), 4);
+        assert!(is_stack_of(&leftover, "stone", 4));
+    }
+
+    #[test]
+    fn test_consumed() {
+        let mut player = PlayerState::new();
+        player.slots[36] = stack("stone", 2);
+        let leftover = player.handle_item_change(36, ItemChange::Consumed);
+        assert!(leftover.is_empty());
+        assert!(is_stack_of(&player.slots[36], "stone", 1));
+    }
+
+    #[test]
+    fn test_transformed() {
+        let mut player = PlayerState::new();
+        let into_bottle = || ItemChange::Transformed {
+            into: item("glass_bottle"),
+        };
+
+        // the last item is transformed in place
+        player.slots[36] = stack("honey_bottle", 1);
+        let leftover = player.handle_item_change(36, into_bottle());
+        assert!(leftover.is_empty());
+        assert!(is_stack_of(&player.slots[36], "glass_bottle", 1));
+
+        // otherwise the transformed item goes into the inventory
+        player.slots[37] = stack("honey_bottle", 3);
+        let leftover = player.handle_item_change(37, into_bottle());
+        assert!(leftover.is_empty());
+        assert!(is_stack_of(&player.slots[37], "honey_bottle", 2));
+        assert!(is_stack_of(&player.slots[36], "glass_bottle", 2));
+    }
+
+    #[test]
+    fn test_transformed_into_full_inventory() {
+        let mut player = full_player();
+        player.slots[36] = stack("honey_bottle", 3);
+        let leftover = player.handle_item_change(
+            36,
+            ItemChange::Transformed {
+                into: item("glass_bottle"),
+            },
+        );
+
+        // the caller drops what did not fit
+        assert!(is_stack_of(&leftover, "glass_bottle", 1));
+        assert!(is_stack_of(&player.slots[36], "honey_bottle", 2));
+    }
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use minecraft_protocol::packets::{self as mc_packets, play_clientbound, play_serverbound};
+    use sol_log_server::logger_mt::LoggerMt;
+
+    use crate::game_loop::GameCommand;
+    use crate::minecraft_connection::client_connection::ReceiveEnd;
+    use crate::minecraft_connection::network::{self, McStream};
+    use crate::minecraft_connection::player_connect_handler::PLayerConnectHandler;
+    use crate::player_session::{PlayerSession, SessionId};
+    use crate::{play, spawn_character};
+
+    const UUID: u128 = 0x069a79f444e94726a5befca90e38aaf5;
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    // the server side and the client side of a connection
+    fn socket_pair() -> (McStream, McStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        (McStream::new(server), McStream::new(client))
+    }
+
+    fn logger() -> LoggerMt {
+        LoggerMt::new("test", zmq::Context::new(), String::from("inproc://logger")).unwrap()
+    }
+
+    // plays a session on its own thread, like `run_session` does
+    fn start_session(
+        session_id: SessionId,
+        server: McStream,
+    ) -> (mpsc::Receiver<GameCommand>, thread::JoinHandle<ReceiveEnd>) {
+        let (game_command_sender, game_command_receiver) = mpsc::channel();
+        let session = thread::spawn(move || {
+            let character = spawn_character(session_id, "Notch", UUID);
+            play(
+                session_id,
+                character,
+                server,
+                8,
+                &zmq::Context::new(),
+                &logger(),
+                &game_command_sender,
+            )
+            .unwrap()
+        });
+        (game_command_receiver, session)
+    }
+
+    fn added_session(game_commands: &mpsc::Receiver<GameCommand>) -> Box<PlayerSession> {
+        match game_commands.recv_timeout(TIMEOUT).unwrap() {
+            GameCommand::AddPlayer(session) => session,
+            _ => panic!("Expected the session to be added"),
+        }
+    }
+
+    fn is_removed(game_commands: &mpsc::Receiver<GameCommand>, session_id: SessionId) -> bool {
+        matches!(
+            game_commands.recv_timeout(TIMEOUT).unwrap(),
+            GameCommand::RemovePlayer(removed) if removed == session_id
+        )
+    }
+
+    #[test]
+    fn test_spawn_character() {
+        let character = spawn_character(7, "Notch", UUID);
+        assert_eq!(character.entity_id, 7);
+        assert_eq!(character.uuid, UUID);
+        assert_eq!(character.name, "Notch");
+    }
+
+    #[test]
+    fn test_sessions_are_added_and_removed() {
+        let (first_server, first_client) = socket_pair();
+        let (second_server, second_client) = socket_pair();
+        let (first_commands, first) = start_session(1, first_server);
+        let (second_commands, second) = start_session(2, second_server);
+
+        let first_session = added_session(&first_commands);
+        let second_session = added_session(&second_commands);
+        assert_eq!(first_session.id, 1);
+        assert_eq!(first_session.character.uuid, UUID);
+        assert_eq!(second_session.id, 2);
+
+        // a client that disconnects only ends its own session
+        drop(first_client);
+        assert!(is_removed(&first_commands, 1));
+        // the game loop drops the session, which stops its sender
+        drop(first_session);
+        assert!(matches!(first.join().unwrap(), ReceiveEnd::Disconnected));
+        assert!(second_commands.try_recv().is_err());
+
+        drop(second_client);
+        assert!(is_removed(&second_commands, 2));
+        drop(second_session);
+        assert!(matches!(second.join().unwrap(), ReceiveEnd::Disconnected));
+    }
+
+    #[test]
+    fn test_session_reenters_configuration() {
+        let (mut server, mut client) = socket_pair();
+        let (game_commands, session) = start_session(3, server.try_clone().unwrap());
+        let added = added_session(&game_commands);
+
+        // the client acknowledges `StartConfiguration`, which ends the session
+        network::send_packet(
+            &mut client,
+            play_serverbound::ServerboundPacket::AcknowledgeConfiguration,
+        )
+        .unwrap();
+        assert!(is_removed(&game_commands, 3));
+        drop(added);
+        assert!(matches!(session.join().unwrap(), ReceiveEnd::Configuration));
+
+        // the client is configured again on the same connection, and joins with its character
+        let client = thread::spawn(move || {
+            loop {
+                let packet = network::receive_packet_raw(&mut client).unwrap();
+                // the id of `FinishConfiguration`
+                if packet[0] == 0x02 {
+                    break;
+                }
+            }
+            network::send_packet(
+                &mut client,
+                mc_packets::config::ServerboundPacket::FinishConfiguration,
+            )
+            .unwrap();
+
+            let mut buffer = Vec::new();
+            let packet: play_clientbound::ClientboundPacket =
+                network::receive_packet(&mut client, &mut buffer).unwrap();
+            match packet {
+                play_clientbound::ClientboundPacket::JoinGame { player_id, .. } => player_id,
+                _ => panic!("Expected the client to join the game"),
+            }
+        });
+
+        let character = spawn_character(3, "Notch", UUID);
+        let client_information =
+            PLayerConnectHandler::send_player_rejoin(&character, &mut server, &logger()).unwrap();
+        assert!(client_information.is_none());
+        assert_eq!(client.join().unwrap(), 3);
+
+        // and plays a new session, until the client disconnected
+        let (game_commands, session) = start_session(3, server);
+        let added = added_session(&game_commands);
+        assert_eq!(added.id, 3);
+        assert!(is_removed(&game_commands, 3));
+        drop(added);
+        assert!(matches!(session.join().unwrap(), ReceiveEnd::Disconnected));
+    }
+}
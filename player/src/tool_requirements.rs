@@ -0,0 +1,332 @@
+// Like vanilla, some blocks only drop an item when they are dug with the right tool, while the
+// others drop one by hand even when a tool digs them faster. The registries do not tell which
+// blocks need one, so the names below are those of the blocks that have
+// `requiresCorrectToolForDrops` in vanilla 1.20.2.
+
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_vanilla::registries::Registries;
+use std::collections::HashSet;
+
+const TOOL_REQUIRED_BLOCKS: [&str; 230] = [
+    "stone",
+    "granite",
+    "polished_granite",
+    "diorite",
+    "polished_diorite",
+    "andesite",
+    "polished_andesite",
+    "cobblestone",
+    "mossy_cobblestone",
+    "smooth_stone",
+    "stone_bricks",
+    "mossy_stone_bricks",
+    "cracked_stone_bricks",
+    "chiseled_stone_bricks",
+    "bricks",
+    "sandstone",
+    "chiseled_sandstone",
+    "cut_sandstone",
+    "smooth_sandstone",
+    "red_sandstone",
+    "chiseled_red_sandstone",
+    "cut_red_sandstone",
+    "smooth_red_sandstone",
+    "terracotta",
+    "prismarine",
+    "prismarine_bricks",
+    "dark_prismarine",
+    "end_stone",
+    "end_stone_bricks",
+    "purpur_block",
+    "purpur_pillar",
+    "quartz_block",
+    "chiseled_quartz_block",
+    "quartz_pillar",
+    "quartz_bricks",
+    "smooth_quartz",
+    "netherrack",
+    "crimson_nylium",
+    "warped_nylium",
+    "nether_bricks",
+    "cracked_nether_bricks",
+    "chiseled_nether_bricks",
+    "red_nether_bricks",
+    "basalt",
+    "polished_basalt",
+    "smooth_basalt",
+    "blackstone",
+    "gilded_blackstone",
+    "polished_blackstone",
+    "chiseled_polished_blackstone",
+    "polished_blackstone_bricks",
+    "cracked_polished_blackstone_bricks",
+    "deepslate",
+    "cobbled_deepslate",
+    "polished_deepslate",
+    "chiseled_deepslate",
+    "deepslate_bricks",
+    "cracked_deepslate_bricks",
+    "deepslate_tiles",
+    "cracked_deepslate_tiles",
+    "tuff",
+    "calcite",
+    "dripstone_block",
+    "mud_bricks",
+    "obsidian",
+    "crying_obsidian",
+    "magma_block",
+    "bone_block",
+    "amethyst_block",
+    "budding_amethyst",
+    // stairs
+    "cobblestone_stairs",
+    "mossy_cobblestone_stairs",
+    "stone_stairs",
+    "granite_stairs",
+    "polished_granite_stairs",
+    "diorite_stairs",
+    "polished_diorite_stairs",
+    "andesite_stairs",
+    "polished_andesite_stairs",
+    "stone_brick_stairs",
+    "mossy_stone_brick_stairs",
+    "brick_stairs",
+    "sandstone_stairs",
+    "smooth_sandstone_stairs",
+    "red_sandstone_stairs",
+    "smooth_red_sandstone_stairs",
+    "prismarine_stairs",
+    "prismarine_brick_stairs",
+    "dark_prismarine_stairs",
+    "end_stone_brick_stairs",
+    "purpur_stairs",
+    "quartz_stairs",
+    "smooth_quartz_stairs",
+    "nether_brick_stairs",
+    "red_nether_brick_stairs",
+    "blackstone_stairs",
+    "polished_blackstone_stairs",
+    "polished_blackstone_brick_stairs",
+    "cobbled_deepslate_stairs",
+    "polished_deepslate_stairs",
+    "deepslate_brick_stairs",
+    "deepslate_tile_stairs",
+    "mud_brick_stairs",
+    // slabs
+    "stone_slab",
+    "smooth_stone_slab",
+    "cobblestone_slab",
+    "mossy_cobblestone_slab",
+    "granite_slab",
+    "polished_granite_slab",
+    "diorite_slab",
+    "polished_diorite_slab",
+    "andesite_slab",
+    "polished_andesite_slab",
+    "stone_brick_slab",
+    "mossy_stone_brick_slab",
+    "brick_slab",
+    "sandstone_slab",
+    "cut_sandstone_slab",
+    "smooth_sandstone_slab",
+    "red_sandstone_slab",
+    "cut_red_sandstone_slab",
+    "smooth_red_sandstone_slab",
+    "petrified_oak_slab",
+    "prismarine_slab",
+    "prismarine_brick_slab",
+    "dark_prismarine_slab",
+    "end_stone_brick_slab",
+    "purpur_slab",
+    "quartz_slab",
+    "smooth_quartz_slab",
+    "nether_brick_slab",
+    "red_nether_brick_slab",
+    "blackstone_slab",
+    "polished_blackstone_slab",
+    "polished_blackstone_brick_slab",
+    "cobbled_deepslate_slab",
+    "polished_deepslate_slab",
+    "deepslate_brick_slab",
+    "deepslate_tile_slab",
+    "mud_brick_slab",
+    // walls
+    "cobblestone_wall",
+    "mossy_cobblestone_wall",
+    "granite_wall",
+    "diorite_wall",
+    "andesite_wall",
+    "stone_brick_wall",
+    "mossy_stone_brick_wall",
+    "brick_wall",
+    "sandstone_wall",
+    "red_sandstone_wall",
+    "prismarine_wall",
+    "end_stone_brick_wall",
+    "nether_brick_wall",
+    "red_nether_brick_wall",
+    "blackstone_wall",
+    "polished_blackstone_wall",
+    "polished_blackstone_brick_wall",
+    "cobbled_deepslate_wall",
+    "polished_deepslate_wall",
+    "deepslate_brick_wall",
+    "deepslate_tile_wall",
+    "mud_brick_wall",
+    // ores and the blocks of their materials
+    "coal_ore",
+    "deepslate_coal_ore",
+    "iron_ore",
+    "deepslate_iron_ore",
+    "copper_ore",
+    "deepslate_copper_ore",
+    "gold_ore",
+    "deepslate_gold_ore",
+    "redstone_ore",
+    "deepslate_redstone_ore",
+    "emerald_ore",
+    "deepslate_emerald_ore",
+    "lapis_ore",
+    "deepslate_lapis_ore",
+    "diamond_ore",
+    "deepslate_diamond_ore",
+    "nether_gold_ore",
+    "nether_quartz_ore",
+    "ancient_debris",
+    "coal_block",
+    "iron_block",
+    "gold_block",
+    "redstone_block",
+    "emerald_block",
+    "lapis_block",
+    "diamond_block",
+    "netherite_block",
+    "raw_iron_block",
+    "raw_copper_block",
+    "raw_gold_block",
+    // blocks made of metal
+    "iron_bars",
+    "iron_door",
+    "iron_trapdoor",
+    "chain",
+    "lantern",
+    "soul_lantern",
+    "bell",
+    "lightning_rod",
+    "anvil",
+    "chipped_anvil",
+    "damaged_anvil",
+    "cauldron",
+    "water_cauldron",
+    "lava_cauldron",
+    "powder_snow_cauldron",
+    "hopper",
+    "light_weighted_pressure_plate",
+    "heavy_weighted_pressure_plate",
+    // blocks made of stone
+    "stone_pressure_plate",
+    "polished_blackstone_pressure_plate",
+    "nether_brick_fence",
+    "furnace",
+    "blast_furnace",
+    "smoker",
+    "dispenser",
+    "dropper",
+    "observer",
+    "stonecutter",
+    "grindstone",
+    "brewing_stand",
+    "enchanting_table",
+    "ender_chest",
+    "spawner",
+    "lodestone",
+    "respawn_anchor",
+    // blocks that need a shovel, a sword or shears
+    "snow",
+    "snow_block",
+    "cobweb",
+];
+// the families of blocks that exist in every color: `white_concrete`, ...
+const COLORS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+const COLOR_FAMILIES: [&str; 3] = ["terracotta", "glazed_terracotta", "concrete"];
+const CORALS: [&str; 5] = ["tube", "brain", "bubble", "fire", "horn"];
+// the cut copper blocks, in every stage of oxidation and waxed or not: `exposed_cut_copper`, ...
+const COPPER_OXIDATIONS: [&str; 4] = ["", "exposed_", "weathered_", "oxidized_"];
+const CUT_COPPER_FAMILY: [&str; 3] = ["cut_copper", "cut_copper_stairs", "cut_copper_slab"];
+
+/// The block states that only drop an item when they are dug with the right tool
+pub struct ToolRequirements {
+    block_states: HashSet<u32>,
+}
+
+impl ToolRequirements {
+    pub fn new(registries: &Registries) -> ToolRequirements {
+        let names = tool_required_names();
+        let mut block_states = HashSet::new();
+        for id in 0.. {
+            let block = BlockWithState::from_id(id);
+            let Some((name, _)) = registries.block_states().get_name_and_properties(block) else {
+                break;
+            };
+            if names.contains(name.trim_start_matches("minecraft:")) {
+                block_states.insert(block.id() as u32);
+            }
+        }
+        ToolRequirements { block_states }
+    }
+
+    pub fn requires_tool(&self, block: BlockWithState) -> bool {
+        self.block_states.contains(&(block.id() as u32))
+    }
+}
+
+/// The names of the blocks that require a tool, without the namespace
+pub fn tool_required_names() -> HashSet<String> {
+    let mut names: HashSet<String> = TOOL_REQUIRED_BLOCKS
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    for color in COLORS {
+        for family in COLOR_FAMILIES {
+            names.insert(format!("{color}_{family}"));
+        }
+    }
+    for coral in CORALS {
+        names.insert(format!("{coral}_coral_block"));
+        names.insert(format!("dead_{coral}_coral_block"));
+        names.insert(format!("dead_{coral}_coral"));
+        names.insert(format!("dead_{coral}_coral_fan"));
+        names.insert(format!("dead_{coral}_coral_wall_fan"));
+    }
+    for waxed in ["", "waxed_"] {
+        for oxidation in COPPER_OXIDATIONS {
+            // the block without oxidation is the only one with a suffix
+            if oxidation.is_empty() {
+                names.insert(format!("{waxed}copper_block"));
+            } else {
+                names.insert(format!("{waxed}{oxidation}copper"));
+            }
+            for cut in CUT_COPPER_FAMILY {
+                names.insert(format!("{waxed}{oxidation}{cut}"));
+            }
+        }
+    }
+    names
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::registries::{get_registries, Registries};
+
+    use crate::tool_requirements::{self, ToolRequirements};
+
+    fn block(registries: &Registries, name: &str) -> BlockWithState {
+        registries
+            .block_states()
+            .get_block_state(&format!("minecraft:{name}"), &HashMap::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_requires_tool() {
+        let registries = get_registries();
+        let requirements = ToolRequirements::new(&registries);
+        for name in [
+            "stone",
+            "cobblestone",
+            "iron_ore",
+            "deepslate_diamond_ore",
+            "lantern",
+            "soul_lantern",
+            "white_concrete",
+            "orange_glazed_terracotta",
+            "waxed_weathered_cut_copper_stairs",
+            "cobweb",
+            "snow",
+        ] {
+            assert!(
+                requirements.requires_tool(block(&registries, name)),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_does_not_require_tool() {
+        let registries = get_registries();
+        let requirements = ToolRequirements::new(&registries);
+        // their names contain the names of blocks that require a tool
+        for name in [
+            "spore_blossom",
+            "jack_o_lantern",
+            "sea_lantern",
+            "glowstone",
+            "redstone_lamp",
+            "redstone_torch",
+            "stone_button",
+            "white_concrete_powder",
+            "powder_snow",
+            "conduit",
+            "dirt",
+            "oak_log",
+        ] {
+            assert!(
+                !requirements.requires_tool(block(&registries, name)),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_state_of_a_block() {
+        let registries = get_registries();
+        let requirements = ToolRequirements::new(&registries);
+        let mut properties = HashMap::new();
+        properties.insert(String::from("facing"), String::from("east"));
+        properties.insert(String::from("lit"), String::from("true"));
+        let furnace = registries
+            .block_states()
+            .get_block_state(&String::from("minecraft:furnace"), &properties)
+            .unwrap();
+        assert!(requirements.requires_tool(furnace));
+    }
+
+    #[test]
+    fn test_names_exist() {
+        let registries = get_registries();
+        let requirements = ToolRequirements::new(&registries);
+        // a typo would leave a block that can be dug by hand
+        for name in tool_requirements::tool_required_names() {
+            assert!(
+                requirements.requires_tool(block(&registries, &name)),
+                "{name}"
+            );
+        }
+    }
+}
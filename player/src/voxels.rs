@@ -1 +1,3 @@
+pub mod chunk_streamer;
+pub mod column_loader;
 pub mod world;
@@ -0,0 +1,151 @@
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+use std::collections::HashSet;
+
+/// The view distance that we announce to the client at login; clients with a larger render
+/// distance only receive this many columns around them
+pub const MAX_VIEW_DISTANCE: usize = 12;
+/// The rate until the client asks for one with its first `ChunkBatchReceived`, like vanilla
+pub const INITIAL_CHUNKS_PER_TICK: f32 = 9.0;
+
+// like vanilla: stop sending until the client catches up with the batches it already received
+const MAX_UNACKNOWLEDGED_BATCHES: u32 = 10;
+const MAX_CHUNKS_PER_BATCH: f32 = 64.0;
+
+/// Keeps track of which columns the client has, and decides which columns to send or unload
+/// when the player moves.
+/// Columns are sent in batches, at the rate that the client asks for with `ChunkBatchReceived`.
+pub struct ChunkStreamer {
+    center: ChunkColumnCoordinate,
+    view_distance: i32,
+    chunks_per_tick: f32,
+    // fractional number of columns that may still be sent, carried over between ticks
+    budget: f32,
+    sent: HashSet<ChunkColumnCoordinate>,
+    unacknowledged_batches: u32,
+}
+
+impl ChunkStreamer {
+    pub fn new(
+        center: ChunkColumnCoordinate,
+        render_distance: usize,
+        chunks_per_tick: f32,
+        already_sent: impl IntoIterator<Item = ChunkColumnCoordinate>,
+    ) -> ChunkStreamer {
+        ChunkStreamer {
+            center,
+            view_distance: render_distance.min(MAX_VIEW_DISTANCE) as i32,
+            chunks_per_tick,
+            budget: 0.0,
+            sent: already_sent.into_iter().collect(),
+            unacknowledged_batches: 0,
+        }
+    }
+
+    pub fn center(&self) -> ChunkColumnCoordinate {
+        self.center
+    }
+
+    pub fn view_distance(&self) -> i32 {
+        self.view_distance
+    }
+
+    /// Returns true if the center changed
+    pub fn set_center(&mut self, center: ChunkColumnCoordinate) -> bool {
+        if center == self.center {
+            return false;
+        }
+
+        self.center = center;
+        true
+    }
+
+    pub fn is_in_range(&self, coord: ChunkColumnCoordinate) -> bool {
+        (coord.x - self.center.x).abs() <= self.view_distance
+            && (coord.z - self.center.z).abs() <= self.view_distance
+    }
+
+    /// Forgets and returns the sent columns that are out of range
+    pub fn take_out_of_range(&mut self) -> Vec<ChunkColumnCoordinate> {
+        let out_of_range: Vec<ChunkColumnCoordinate> = self
+            .sent
+            .iter()
+            .copied()
+            .filter(|coord| !self.is_in_range(*coord))
+            .collect();
+
+        for coord in &out_of_range {
+            self.sent.remove(coord);
+        }
+
+        out_of_range
+    }
+
+    /// Returns the columns to send in this tick, nearest first.
+    /// Call once per tick; the columns that are actually sent must be passed to `mark_sent`
+    pub fn next_batch(&mut self) -> Vec<ChunkColumnCoordinate> {
+        if self.unacknowledged_batches >= MAX_UNACKNOWLEDGED_BATCHES {
+            return Vec::new();
+        }
+
+        let mut missing = Vec::new();
+        for z in -self.view_distance..=self.view_distance {
+            for x in -self.view_distance..=self.view_distance {
+                let coord = self.center.add(x, z);
+                if !self.sent.contains(&coord) {
+                    missing.push(coord);
+                }
+            }
+        }
+
+        if missing.is_empty() {
+            // do not save up for a burst when the player moves
+            self.budget = 0.0;
+            return missing;
+        }
+
+        self.budget = (self.budget + self.chunks_per_tick).min(MAX_CHUNKS_PER_BATCH);
+        let batch_size = self.budget.floor() as usize;
+        if batch_size == 0 {
+            return Vec::new();
+        }
+
+        let center = self.center;
+        missing.sort_by_key(|coord| {
+            let dx = coord.x - center.x;
+            let dz = coord.z - center.z;
+            dx * dx + dz * dz
+        });
+        missing.truncate(batch_size);
+
+        self.budget -= missing.len() as f32;
+        missing
+    }
+
+    /// Returns true if the client has the column
+    pub fn has_sent(&self, coord: ChunkColumnCoordinate) -> bool {
+        self.sent.contains(&coord)
+    }
+
+    pub fn mark_sent(&mut self, coord: ChunkColumnCoordinate) {
+        self.sent.insert(coord);
+    }
+
+    /// The column is sent again with a later batch, if it is still in range; the client replaces
+    /// its copy then
+    pub fn forget(&mut self, coord: ChunkColumnCoordinate) {
+        self.sent.remove(&coord);
+    }
+
+    pub fn batch_sent(&mut self) {
+        self.unacknowledged_batches += 1;
+    }
+
+    /// The client received a batch, and asks for a new rate
+    pub fn batch_acknowledged(&mut self, chunks_per_tick: f32) {
+        self.unacknowledged_batches = self.unacknowledged_batches.saturating_sub(1);
+        // a misbehaving client should not stop the stream, or ask for everything at once
+        if chunks_per_tick.is_finite() {
+            self.chunks_per_tick = chunks_per_tick.clamp(0.01, MAX_CHUNKS_PER_BATCH);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use sol_address_server::static_addresses;
+use sol_network_lib::network::NetworkError;
+use sol_voxel_lib::chunk_column::ChunkColumn;
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+use sol_world_messages::{WorldServerRep, WorldServerReq};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+// the world server answers one request after the other, so this many may be waiting at once
+const MAX_PENDING_REQUESTS: usize = 32;
+// the time that the world server has to answer the oldest request, which may include generating
+// the column
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// a column that could not be loaded is requested again after this delay, which doubles with
+// every further failure
+const FIRST_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// A column that the world server sent, or could not send
+pub enum ColumnLoad {
+    Loaded(Box<ChunkColumn>),
+    Failed(ChunkColumnCoordinate, String),
+}
+
+/// Requests columns from the world server without waiting for them; the game loop polls for the
+/// replies once per tick.
+/// The world server answers in the order of the requests, which is how we tell the replies
+/// apart. A column that failed to load is not requested again until its retry delay passed
+pub struct ColumnLoader {
+    context: zmq::Context,
+    socket: zmq::Socket,
+    // the requested columns, oldest first, and when they were requested
+    pending: VecDeque<(ChunkColumnCoordinate, Instant)>,
+    // the columns that failed to load: when they may be requested again, and how often they failed
+    retries: HashMap<ChunkColumnCoordinate, (Instant, u32)>,
+}
+
+impl ColumnLoader {
+    pub fn new(context: zmq::Context) -> Result<ColumnLoader, zmq::Error> {
+        let socket = connect(&context)?;
+        Ok(ColumnLoader {
+            context,
+            socket,
+            pending: VecDeque::new(),
+            retries: HashMap::new(),
+        })
+    }
+
+    pub fn is_pending(&self, coord: ChunkColumnCoordinate) -> bool {
+        self.pending.iter().any(|(pending, _)| *pending == coord)
+    }
+
+    /// Requests the column, unless it was requested already, too many requests are waiting, or
+    /// it failed to load recently
+    pub fn request(
+        &mut self,
+        coord: ChunkColumnCoordinate,
+        now: Instant,
+    ) -> Result<(), NetworkError> {
+        if self.pending.len() >= MAX_PENDING_REQUESTS || self.is_pending(coord) {
+            return Ok(());
+        }
+        if let Some((retry_at, _)) = self.retries.get(&coord) {
+            if now < *retry_at {
+                return Ok(());
+            }
+        }
+
+        let request = bincode::serialize(&WorldServerReq::ContentChunkColumn(coord))
+            .map_err(NetworkError::SerialisationError)?;
+        // the empty frame stands in for the envelope of a REQ socket, which the world server expects
+        self.socket
+            .send_multipart([&[][..], &request[..]], zmq::DONTWAIT)
+            .map_err(NetworkError::ZmqError)?;
+        self.pending.push_back((coord, now));
+        Ok(())
+    }
+
+    /// Returns the replies that arrived since the last call, without waiting for more.
+    /// If the world server does not answer in time, every pending request fails
+    pub fn receive(&mut self, now: Instant) -> Vec<ColumnLoad> {
+        let mut loads = Vec::new();
+
+        while !self.pending.is_empty() {
+            let frames = match self.socket.recv_multipart(zmq::DONTWAIT) {
+                Ok(frames) => frames,
+                Err(zmq::Error::EAGAIN) => break,
+                Err(error) => {
+                    self.fail_all(&mut loads, now, &format!("{error:?}"));
+                    return loads;
+                },
+            };
+
+            let (coord, _) = self.pending.pop_front().unwrap();
+            let reply = frames
+                .last()
+                .ok_or(String::from("empty reply"))
+                .and_then(|payload| {
+                    bincode::deserialize::<WorldServerRep>(payload)
+                        .map_err(|error| format!("{error:?}"))
+                });
+
+            match reply {
+                Ok(WorldServerRep::ContentChunkColumn(_, column)) => {
+                    self.retries.remove(&coord);
+                    loads.push(ColumnLoad::Loaded(column));
+                },
+                Ok(_) => self.fail(&mut loads, coord, now, "the world server did not send it"),
+                Err(error) => self.fail(&mut loads, coord, now, &error),
+            }
+        }
+
+        let timed_out = self.pending.front().map_or(false, |(_, requested)| {
+            now.saturating_duration_since(*requested) >= REQUEST_TIMEOUT
+        });
+        if timed_out {
+            self.fail_all(&mut loads, now, "the world server did not answer in time");
+        }
+
+        loads
+    }
+
+    fn fail(
+        &mut self,
+        loads: &mut Vec<ColumnLoad>,
+        coord: ChunkColumnCoordinate,
+        now: Instant,
+        reason: &str,
+    ) {
+        let failures = self
+            .retries
+            .get(&coord)
+            .map_or(0, |(_, failures)| *failures);
+        let delay = FIRST_RETRY_DELAY
+            .saturating_mul(1 << failures.min(16))
+            .min(MAX_RETRY_DELAY);
+        self.retries.insert(coord, (now + delay, failures + 1));
+        loads.push(ColumnLoad::Failed(coord, String::from(reason)));
+    }
+
+    // the late replies would be taken for the answers to newer requests, so they are dropped
+    // along with the socket
+    fn fail_all(&mut self, loads: &mut Vec<ColumnLoad>, now: Instant, reason: &str) {
+        let reason = match connect(&self.context) {
+            Ok(socket) => {
+                self.socket = socket;
+                String::from(reason)
+            },
+            Err(error) => format!("{reason}, and could not reconnect: {error:?}"),
+        };
+
+        while let Some((coord, _)) = self.pending.pop_front() {
+            self.fail(loads, coord, now, &reason);
+        }
+    }
+}
+
+fn connect(context: &zmq::Context) -> Result<zmq::Socket, zmq::Error> {
+    let socket = context.socket(zmq::DEALER)?;
+    // unanswered requests are dropped with the socket
+    socket.set_linger(0)?;
+    socket.connect(static_addresses::WORLD_SERVER)?;
+    Ok(socket)
+}
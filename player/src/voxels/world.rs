@@ -1,17 +1,28 @@
 use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use sol_game_engine::physics::collision::ColumnSource;
 use sol_voxel_lib::chunk16::Chunk16;
+use sol_voxel_lib::light::ColumnSide;
+use sol_voxel_lib::voxel::{Voxel, VoxelRef};
+use sol_voxel_lib::voxel_errors::VoxelIndexError;
 use sol_voxel_lib::{chunk_column::ChunkColumn, vector_alias::*};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 pub struct World {
     chunks: HashMap<ChunkColumnCoordinate, Box<ChunkColumn>>,
+    // the columns whose light may have changed since `take_light_changes`
+    relit_columns: HashSet<ChunkColumnCoordinate>,
 }
 
 impl World {
     /**
      * Creates a world initialized with a 21x21 chunk square stone floor around y=0
      */
-    pub fn new() -> World {
+    pub fn new(
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) -> World {
         let mut chunks = HashMap::new();
 
         for z in -10..=10 {
@@ -24,9 +35,11 @@ impl World {
                         Coordinate16::new(x, y, z),
                         minecraft_vanilla::ids::blocks::BlockId::Stone.into(),
                         false
-                    ), todo!());
+                    ), block_properties, block_states);
                 }
 
+                chunk_column.compute_light(block_properties, block_states);
+
                 chunks.insert(
                     ChunkColumnCoordinate { x, z },
                     chunk_column,
@@ -34,13 +47,48 @@ impl World {
             }
         }
 
-        World { chunks }
+        let mut world = World {
+            chunks,
+            relit_columns: HashSet::new(),
+        };
+        let coordinates = world.column_coordinates().collect();
+        world.spread_light(coordinates, block_properties, block_states);
+        world
     }
 
     pub fn get_chunk(&self, coord: &ChunkColumnCoordinate) -> Option<&ChunkColumn> {
         self.chunks.get(coord).map(Box::as_ref)
     }
 
+    pub fn contains_column(&self, coord: &ChunkColumnCoordinate) -> bool {
+        self.chunks.contains_key(coord)
+    }
+
+    /// Adds a column, replacing the column at the same coordinate.
+    /// Its light and the light of its loaded neighbours spread across their borders; light that
+    /// a replaced column spread into its neighbours stays
+    pub fn insert_column(
+        &mut self,
+        chunk_column: Box<ChunkColumn>,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        let coord = chunk_column.coordinate();
+        self.chunks.insert(coord, chunk_column);
+
+        let mut queue = VecDeque::from([coord]);
+        queue.extend(ColumnSide::ALL.map(|side| side.neighbour(coord)));
+        self.spread_light(queue, block_properties, block_states);
+    }
+
+    pub fn remove_column(&mut self, coord: &ChunkColumnCoordinate) -> Option<Box<ChunkColumn>> {
+        self.chunks.remove(coord)
+    }
+
+    pub fn column_coordinates(&self) -> impl Iterator<Item = ChunkColumnCoordinate> + '_ {
+        self.chunks.keys().copied()
+    }
+
     pub fn get_area(&self, player_position: Position) -> Vec<&ChunkColumn> {
         let mut area = Vec::new();
 
@@ -60,12 +108,111 @@ impl World {
     }
 
     pub fn get_block(&self, coord: Coordinate) -> Option<BlockWithState> {
-        let center_point = ChunkColumnCoordinate::containing_coord(&coord);
-        let chunk = self.get_chunk(&center_point);
-        if let Some(chunk) = chunk {
-            chunk.
-        } else {
-            None
+        let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+        let column = self.get_chunk(&column_coord)?;
+        column.get_voxel(coord).ok().map(|voxel| voxel.get_block())
+    }
+
+    pub fn get_voxel(&self, coord: Coordinate) -> Option<VoxelRef> {
+        let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+        let column = self.get_chunk(&column_coord)?;
+        column.get_voxel(coord).ok()
+    }
+
+    /// Changes a voxel of a loaded column, and updates its heightmaps and light.
+    /// The light of the loaded neighbouring columns is updated too, when it may have crossed the
+    /// border of the column
+    pub fn set_voxel(
+        &mut self,
+        coord: Coordinate,
+        voxel: Voxel,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) -> Result<(), VoxelIndexError> {
+        let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+        let Some(column) = self.chunks.get_mut(&column_coord) else {
+            return Err(VoxelIndexError { coordinate: coord });
+        };
+        column.set_voxel(coord, voxel, block_properties, block_states)?;
+        self.relit_columns.insert(column_coord);
+
+        let x = coord.x.rem_euclid(16);
+        let z = coord.z.rem_euclid(16);
+        let is_at_border = x == 0 || x == 15 || z == 0 || z == 15;
+        if column.take_changed_light_sides().is_empty() && !is_at_border {
+            return Ok(());
+        }
+
+        // Light reaches at most 14 blocks from the changed block, so only the surrounding columns
+        // can hold light that depended on it. They are computed again without the light of their
+        // neighbours, which then spreads in again from all sides
+        let mut queue = VecDeque::new();
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                let neighbour_coord = column_coord.add(dx, dz);
+                if let Some(neighbour) = self.chunks.get_mut(&neighbour_coord) {
+                    neighbour.compute_light(block_properties, block_states);
+                    queue.push_back(neighbour_coord);
+                }
+            }
         }
+        self.spread_light(queue, block_properties, block_states);
+
+        Ok(())
+    }
+
+    /// Returns the columns whose light changed since the last call, each with a bit per changed
+    /// section like `ColumnLight::take_changed_sections`
+    pub fn take_light_changes(&mut self) -> Vec<(ChunkColumnCoordinate, u32)> {
+        let mut changes = Vec::new();
+        for coord in std::mem::take(&mut self.relit_columns) {
+            let Some(column) = self.chunks.get_mut(&coord) else {
+                continue;
+            };
+            let sections = column.take_changed_light_sections();
+            if sections != 0 {
+                changes.push((coord, sections));
+            }
+        }
+        changes
+    }
+
+    // Every queued column imports the light of its loaded neighbours. When that changes the light
+    // at one of its borders, the neighbour on that side imports it in turn. Light only increases
+    // while spreading, so this ends
+    fn spread_light(
+        &mut self,
+        mut queue: VecDeque<ChunkColumnCoordinate>,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        while let Some(coord) = queue.pop_front() {
+            // taken out of the map, to read its neighbours while changing it
+            let Some(mut column) = self.chunks.remove(&coord) else {
+                continue;
+            };
+
+            for side in ColumnSide::ALL {
+                if let Some(neighbour) = self.chunks.get(&side.neighbour(coord)) {
+                    column.import_light(side, neighbour, block_properties, block_states);
+                }
+            }
+
+            for side in column.take_changed_light_sides() {
+                let neighbour_coord = side.neighbour(coord);
+                if self.chunks.contains_key(&neighbour_coord) && !queue.contains(&neighbour_coord) {
+                    queue.push_back(neighbour_coord);
+                }
+            }
+
+            self.chunks.insert(coord, column);
+            self.relit_columns.insert(coord);
+        }
+    }
+}
+
+impl ColumnSource for World {
+    fn get_column(&self, coord: &ChunkColumnCoordinate) -> Option<&ChunkColumn> {
+        self.get_chunk(coord)
     }
 }
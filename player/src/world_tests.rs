@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::ids::blocks::BlockId;
+    use minecraft_vanilla::registries::Registries;
+    use sol_voxel_lib::chunk_column::ChunkColumn;
+    use sol_voxel_lib::light::MAX_LIGHT;
+    use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate, ICoordinate};
+    use sol_voxel_lib::voxel::Voxel;
+
+    use crate::voxels::world::World;
+
+    const ORIGIN: ChunkColumnCoordinate = ChunkColumnCoordinate { x: 0, z: 0 };
+    const EAST: ChunkColumnCoordinate = ChunkColumnCoordinate { x: 1, z: 0 };
+    const WEST: ChunkColumnCoordinate = ChunkColumnCoordinate { x: -1, z: 0 };
+
+    // at the eastern border of the column at the origin, in section 4 just above the stone floor
+    fn source() -> Coordinate {
+        Coordinate::new(15, 70, 8)
+    }
+
+    fn glowstone() -> Voxel {
+        let block: BlockWithState = BlockId::Glowstone.into();
+        Voxel::from_block(block)
+    }
+
+    fn set_voxel(world: &mut World, registries: &Registries, coord: Coordinate, voxel: Voxel) {
+        world
+            .set_voxel(
+                coord,
+                voxel,
+                registries.block_properties(),
+                registries.block_states(),
+            )
+            .unwrap();
+    }
+
+    fn block_light(world: &World, column: ChunkColumnCoordinate, coord: ICoordinate) -> u8 {
+        world
+            .get_chunk(&column)
+            .unwrap()
+            .get_light()
+            .get_block_light(coord)
+    }
+
+    fn new_world(registries: &Registries) -> World {
+        let mut world = World::new(registries.block_properties(), registries.block_states());
+        world.take_light_changes();
+        world
+    }
+
+    #[test]
+    fn test_light_crosses_column_border() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut world = new_world(&registries);
+
+        set_voxel(&mut world, &registries, source(), glowstone());
+        assert_eq!(
+            block_light(&world, EAST, ICoordinate::new(0, 70, 8)),
+            MAX_LIGHT - 1
+        );
+        assert_eq!(
+            block_light(&world, EAST, ICoordinate::new(2, 72, 8)),
+            MAX_LIGHT - 5
+        );
+
+        // both columns send the sections that their light changed in
+        let changes = world.take_light_changes();
+        let sections = |column: ChunkColumnCoordinate| {
+            changes
+                .iter()
+                .find(|(coord, _)| *coord == column)
+                .map(|(_, sections)| *sections)
+        };
+        // the light does not enter the stone below section 4
+        assert_eq!(sections(ORIGIN), Some(0b110000));
+        assert_eq!(sections(EAST), Some(0b110000));
+        assert!(world.take_light_changes().is_empty());
+
+        // removing the source darkens the neighbour again
+        let air: BlockWithState = BlockId::Air.into();
+        set_voxel(&mut world, &registries, source(), Voxel::from_block(air));
+        assert_eq!(block_light(&world, EAST, ICoordinate::new(0, 70, 8)), 0);
+        assert_eq!(block_light(&world, EAST, ICoordinate::new(2, 72, 8)), 0);
+        assert!(world
+            .take_light_changes()
+            .iter()
+            .any(|(coord, _)| *coord == EAST));
+    }
+
+    #[test]
+    fn test_inserted_column_exchanges_light() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut world = new_world(&registries);
+        world.remove_column(&EAST);
+        set_voxel(&mut world, &registries, source(), glowstone());
+
+        let mut column = Box::new(ChunkColumn::new(EAST.x, EAST.z));
+        column.compute_light(registries.block_properties(), registries.block_states());
+        world.insert_column(
+            column,
+            registries.block_properties(),
+            registries.block_states(),
+        );
+        assert_eq!(
+            block_light(&world, EAST, ICoordinate::new(0, 70, 8)),
+            MAX_LIGHT - 1
+        );
+
+        // an inserted column also spreads its own light into the loaded columns
+        let mut column = world.remove_column(&WEST).unwrap();
+        column
+            .set_voxel(
+                Coordinate::new(-1, 70, 8),
+                glowstone(),
+                registries.block_properties(),
+                registries.block_states(),
+            )
+            .unwrap();
+        world.insert_column(
+            column,
+            registries.block_properties(),
+            registries.block_states(),
+        );
+        assert_eq!(
+            block_light(&world, ORIGIN, ICoordinate::new(0, 70, 8)),
+            MAX_LIGHT - 1
+        );
+    }
+}
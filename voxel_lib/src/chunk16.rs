@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 
+use crate::biome::{self, BiomeId};
 use crate::palette::Palette;
 use crate::vector_alias::Coordinate16;
 use crate::voxel::{Voxel, VoxelRef};
 use crate::{
     vector_alias::{Coordinate, ICoordinate},
-    voxel_errors::VoxelIndexError,
+    voxel_errors::{PaletteIndexError, VoxelIndexError},
 };
 use minecraft_protocol::components::{blocks as mc_blocks, chunk as mc_chunk};
 use minecraft_protocol::data::block_states::BlockWithState;
@@ -60,17 +61,18 @@ impl ChunkB32Entry {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Chunk16 {
     // NOTE: [y][z][x] where y is height
     grid: Chunk16Grid,
     palette: Palette,
-    biomes: [[[u8; 4]; 4]; 4],
+    // one biome per 4x4x4 blocks, [y][z][x] like the grid
+    biomes: [[[BiomeId; 4]; 4]; 4],
     zero_coordinate: Coordinate,
     num_non_air_blocks: u16,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 enum Chunk16Grid {
     // 2^16 = 65_536 different element types, but there are only 4096 voxels per chunk.
     // we instead put all simple voxels directly in the grid for 32 bits per voxel, and only map the advanced voxels
@@ -94,7 +96,7 @@ impl Chunk16 {
         Chunk16 {
             grid: Chunk16Grid::B0,
             palette: Palette::fill(fill_value),
-            biomes: Default::default(),
+            biomes: [[[biome::PLAINS; 4]; 4]; 4],
             zero_coordinate: Coordinate::from(location),
             num_non_air_blocks: if is_air { 0 } else { 16 * 16 * 16 },
         }
@@ -104,11 +106,11 @@ impl Chunk16 {
         let relative_coord = coord - self.zero_coordinate;
 
         if relative_coord.x < 0
-            || relative_coord.x > 16
+            || relative_coord.x >= 16
             || relative_coord.y < 0
-            || relative_coord.y > 16
+            || relative_coord.y >= 16
             || relative_coord.z < 0
-            || relative_coord.z > 16
+            || relative_coord.z >= 16
         {
             return Err(VoxelIndexError { coordinate: coord });
         }
@@ -156,6 +158,68 @@ impl Chunk16 {
         return self.palette.get(id);
     }
 
+    /// Returns the biome of the 4x4x4 cell that contains the given coordinate
+    pub fn get_biome(&self, coord: Coordinate) -> Result<BiomeId, VoxelIndexError> {
+        let internal_coord = self.to_internal(coord)?;
+        Ok(self.biomes[internal_coord.y / 4][internal_coord.z / 4][internal_coord.x / 4])
+    }
+
+    /// Sets the biome of the 4x4x4 cell that contains the given coordinate
+    pub fn set_biome(&mut self, coord: Coordinate, biome: BiomeId) -> Result<(), VoxelIndexError> {
+        let internal_coord = self.to_internal(coord)?;
+        self.biomes[internal_coord.y / 4][internal_coord.z / 4][internal_coord.x / 4] = biome;
+        Ok(())
+    }
+
+    pub fn fill_biome(&mut self, biome: BiomeId) {
+        self.biomes = [[[biome; 4]; 4]; 4];
+    }
+
+    fn biomes_from_minecraft(biomes: &mc_chunk::PalettedData) -> [[[BiomeId; 4]; 4]; 4] {
+        let mut result = [[[0; 4]; 4]; 4];
+
+        // biomes are stored per 4x4x4 blocks, indexed [y][z][x] like the blocks
+        for i in 0..64usize {
+            let biome = match biomes {
+                mc_chunk::PalettedData::Single { value } => *value,
+                mc_chunk::PalettedData::Paletted { palette, indexed } => {
+                    palette[indexed[i] as usize]
+                },
+                mc_chunk::PalettedData::Raw { values } => values[i],
+            };
+            result[i / 16][(i / 4) % 4][i % 4] = biome as BiomeId;
+        }
+
+        return result;
+    }
+
+    fn biomes_to_minecraft(&self) -> mc_chunk::PalettedData {
+        let mut palette: Vec<u32> = Vec::new();
+        let mut indexed = Vec::with_capacity(64);
+
+        for y in 0..4usize {
+            for z in 0..4usize {
+                for x in 0..4usize {
+                    let biome = self.biomes[y][z][x] as u32;
+                    let index = match palette.iter().position(|b| *b == biome) {
+                        Some(index) => index,
+                        None => {
+                            palette.push(biome);
+                            palette.len() - 1
+                        },
+                    };
+                    indexed.push(index as u8);
+                }
+            }
+        }
+
+        if palette.len() == 1 {
+            return mc_chunk::PalettedData::Single { value: palette[0] };
+        }
+
+        return mc_chunk::PalettedData::Paletted { palette, indexed };
+    }
+
     pub fn set_voxel(
         &mut self,
         coord: Coordinate,
@@ -268,10 +332,12 @@ impl Chunk16 {
         block_entities: Vec<mc_blocks::BlockEntity>,
         block_properties: &BlockPropertyRegistry,
         block_states: &BlockStateRegistry,
-    ) -> Chunk16 {
+    ) -> Result<Chunk16, PaletteIndexError> {
         let mut chunk = match &mc_chunk.blocks {
             mc_chunk::PalettedData::Paletted { palette, indexed } => {
-                Chunk16::from_raw_palette(indexed, palette, position)
+                let mut chunk = Chunk16::from_raw_palette(indexed, palette, position)?;
+                chunk.num_non_air_blocks = mc_chunk.block_count as u16;
+                chunk
             },
             mc_chunk::PalettedData::Single { value } => {
                 // TODO from_id or from_state_id?
@@ -284,7 +350,11 @@ impl Chunk16 {
                         .is_air,
                 )
             },
-            mc_chunk::PalettedData::Raw { values } => Chunk16::from_direct(values, position),
+            mc_chunk::PalettedData::Raw { values } => {
+                let mut chunk = Chunk16::from_direct(values, position);
+                chunk.num_non_air_blocks = mc_chunk.block_count as u16;
+                chunk
+            },
         };
 
         for block_entity in block_entities {
@@ -299,9 +369,9 @@ impl Chunk16 {
                 .set_block_entity(block_entity, internal_coordinate);
         }
 
-        // TODO biomes?
+        chunk.biomes = Chunk16::biomes_from_minecraft(&mc_chunk.biomes);
 
-        return chunk;
+        return Ok(chunk);
     }
 
     pub fn to_minecraft(&self) -> (mc_chunk::Chunk, Coordinate16, Vec<mc_blocks::BlockEntity>) {
@@ -421,19 +491,37 @@ impl Chunk16 {
             mc_chunk::Chunk {
                 block_count: self.num_non_air_blocks as i16,
                 blocks,
-                biomes: mc_chunk::PalettedData::Single { value: 0 },
+                biomes: self.biomes_to_minecraft(),
             },
             Coordinate16::containing_coord(&self.zero_coordinate()),
             block_entities,
         );
     }
 
-    fn from_raw_palette(grid: &[u8], palette: &[u32], position: Coordinate16) -> Chunk16 {
-        let mut sol_palette = Palette::new();
-        for id in palette {
-            sol_palette.add_simple(BlockWithState::from_id(*id));
+    fn from_raw_palette(
+        grid: &[u8],
+        palette: &[u32],
+        position: Coordinate16,
+    ) -> Result<Chunk16, PaletteIndexError> {
+        let mut counts = vec![0u16; palette.len()];
+        for index in grid {
+            let Some(count) = counts.get_mut(*index as usize) else {
+                return Err(PaletteIndexError {
+                    index: *index as u32,
+                    palette_length: palette.len(),
+                });
+            };
+            *count += 1;
         }
 
+        let sol_palette = Palette::from_counted(
+            palette
+                .iter()
+                .zip(counts)
+                .map(|(id, count)| (BlockWithState::from_id(*id), count))
+                .collect(),
+        );
+
         let grid = if palette.len() < (1 << 2) {
             Chunk16::from_slice_b2(grid)
         } else if palette.len() < (1 << 4) {
@@ -443,13 +531,13 @@ impl Chunk16 {
             Chunk16::from_slice_b8(grid)
         };
 
-        return Chunk16 {
+        return Ok(Chunk16 {
             grid,
             palette: sol_palette,
-            biomes: [[[0; 4]; 4]; 4],
+            biomes: [[[biome::PLAINS; 4]; 4]; 4],
             zero_coordinate: Coordinate::from(position),
             num_non_air_blocks: 0,
-        };
+        });
     }
 
     fn from_slice_b2(slice: &[u8]) -> Chunk16Grid {
@@ -559,7 +647,7 @@ impl Chunk16 {
         return Chunk16 {
             grid: Chunk16Grid::B32(grid),
             palette: Palette::new(),
-            biomes: [[[0; 4]; 4]; 4],
+            biomes: [[[biome::PLAINS; 4]; 4]; 4],
             zero_coordinate: Coordinate::from(position),
             num_non_air_blocks: 0,
         };
@@ -738,6 +826,11 @@ impl Chunk16 {
         }
     }
 
+    /// true if this chunk only contains air
+    pub fn is_empty(&self) -> bool {
+        self.num_non_air_blocks == 0
+    }
+
     pub fn zero_coordinate(&self) -> Coordinate {
         self.zero_coordinate
     }
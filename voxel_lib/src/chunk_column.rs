@@ -1,5 +1,7 @@
+use crate::biome::BiomeId;
 use crate::chunk16::Chunk16;
-use crate::vector_alias::{Coordinate, Coordinate16, ICoordinate};
+use crate::light::{ColumnLight, ColumnSide, LightData};
+use crate::vector_alias::{ChunkColumnCoordinate, Coordinate, Coordinate16, ICoordinate};
 use crate::voxel::{Voxel, VoxelRef};
 use minecraft_protocol::components::blocks::BlockEntity;
 use std::array::from_fn;
@@ -8,6 +10,7 @@ use crate::voxel_errors::VoxelIndexError;
 use minecraft_protocol::components::chunk as mc_chunk;
 use minecraft_protocol::data::blocks::Block;
 use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
 use serde::{Deserialize, Serialize};
 
 // the protocol enforces 24 chunks per column.
@@ -18,13 +21,16 @@ type Heightmap = [[u16; 16]; 16];
 
 pub enum WorldCommand {}
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ChunkColumn {
     chunk_x_16: i32,
     chunk_z_16: i32,
     chunk_sections: [Chunk16; NUM_CHUNK_SECTIONS_PER_COLUMN],
     heightmap_motion_blocking: Heightmap,
     heightmap_world_surface: Heightmap,
+    // recomputed after loading, see `compute_light`
+    #[serde(skip)]
+    light: ColumnLight,
 }
 
 pub struct ChunkColumnSerialized {
@@ -34,6 +40,7 @@ pub struct ChunkColumnSerialized {
     pub block_entities: Vec<BlockEntity>,
     pub heightmap_motion_blocking: Vec<i64>,
     pub heightmap_world_surface: Vec<i64>,
+    pub light: LightData,
 }
 
 impl ChunkColumn {
@@ -51,76 +58,221 @@ impl ChunkColumn {
             }),
             heightmap_motion_blocking: [[0; 16]; 16],
             heightmap_world_surface: [[0; 16]; 16],
+            light: ColumnLight::default(),
         }
     }
 
-    pub fn set_chunk(&mut self, y_16: i32, chunk: Chunk16, registry: &BlockPropertyRegistry) {
+    pub fn coordinate(&self) -> ChunkColumnCoordinate {
+        ChunkColumnCoordinate {
+            x: self.chunk_x_16,
+            z: self.chunk_z_16,
+        }
+    }
+
+    pub fn get_chunk(&self, y_16: i32) -> Option<&Chunk16> {
+        if y_16 < 0 {
+            return None;
+        }
+        self.chunk_sections.get(y_16 as usize)
+    }
+
+    pub fn set_chunk(
+        &mut self,
+        y_16: i32,
+        chunk: Chunk16,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
         self.chunk_sections[y_16 as usize] = chunk;
-        self.update_heightmap(y_16, registry);
+        self.update_heightmap(y_16, block_properties, block_states);
+        // a whole section is too much to update incrementally
+        self.light.invalidate();
     }
 
-    pub fn set_voxel(&mut self, coord: Coordinate, voxel: Voxel, registry: &BlockPropertyRegistry) -> Result<(), VoxelIndexError> {
-        let y_16 = coord.y / 16;
-        let chunk = &mut self.chunk_sections[y_16 as usize];
+    /// Computes the sky light and block light of the whole column, without the light of the
+    /// neighbouring columns. Afterwards, `set_voxel` keeps the light up to date within the column.
+    pub fn compute_light(
+        &mut self,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        self.light
+            .compute(&self.chunk_sections, block_properties, block_states);
+    }
 
-        chunk.set_voxel(coord, voxel, registry)
+    pub fn get_light(&self) -> &ColumnLight {
+        &self.light
     }
 
-    pub fn get_voxel(&mut self, coord: Coordinate) -> Result<VoxelRef, VoxelIndexError> {
-        let y_16 = coord.y / 16;
-        let chunk = &self.chunk_sections[y_16 as usize];
+    /// Spreads the light at the border of the neighbouring column on the given side into this column
+    pub fn import_light(
+        &mut self,
+        side: ColumnSide,
+        neighbour: &ChunkColumn,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        self.light.import_side(
+            side,
+            &neighbour.light,
+            &self.chunk_sections,
+            block_properties,
+            block_states,
+        );
+    }
+
+    /// See `ColumnLight::take_changed_sections`
+    pub fn take_changed_light_sections(&mut self) -> u32 {
+        self.light.take_changed_sections()
+    }
+
+    /// See `ColumnLight::take_changed_sides`
+    pub fn take_changed_light_sides(&mut self) -> Vec<ColumnSide> {
+        self.light.take_changed_sides()
+    }
+
+    pub fn set_voxel(
+        &mut self,
+        coord: Coordinate,
+        voxel: Voxel,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) -> Result<(), VoxelIndexError> {
+        let y_16 = Self::section_index(coord)?;
+        let chunk = &mut self.chunk_sections[y_16];
+
+        chunk.set_voxel(coord, voxel, block_properties, block_states)?;
+
+        let x = coord.x.rem_euclid(16) as usize;
+        let z = coord.z.rem_euclid(16) as usize;
+        // only a change at or above the current height can change the heightmap
+        if coord.y + 1 >= self.heightmap_world_surface[z][x] as i32
+            || coord.y + 1 >= self.heightmap_motion_blocking[z][x] as i32
+        {
+            let world_top = (NUM_CHUNK_SECTIONS_PER_COLUMN * 16) as u16;
+            self.update_pillar(x, z, world_top, block_properties, block_states);
+        }
+
+        self.light.update(
+            ICoordinate::new(x, coord.y as usize, z),
+            &self.chunk_sections,
+            block_properties,
+            block_states,
+        );
+
+        Ok(())
+    }
+
+    pub fn get_voxel(&self, coord: Coordinate) -> Result<VoxelRef, VoxelIndexError> {
+        let y_16 = Self::section_index(coord)?;
+        let chunk = &self.chunk_sections[y_16];
         chunk.get_voxel(coord)
     }
 
-    fn update_heightmap(&mut self, y_16: i32, registry: &BlockPropertyRegistry) {
-        let chunk = &self.chunk_sections[y_16 as usize];
+    pub fn get_biome(&self, coord: Coordinate) -> Result<BiomeId, VoxelIndexError> {
+        let y_16 = Self::section_index(coord)?;
+        self.chunk_sections[y_16].get_biome(coord)
+    }
 
-        let highest_chunk_value = (y_16 + 1) * 16;
+    /// Sets the biome of the 4x4x4 cell that contains the given coordinate
+    pub fn set_biome(&mut self, coord: Coordinate, biome: BiomeId) -> Result<(), VoxelIndexError> {
+        let y_16 = Self::section_index(coord)?;
+        self.chunk_sections[y_16].set_biome(coord, biome)
+    }
 
-        for z in 0..16usize {
-            for x in 0..16usize {
-                // "WORLD_SURFACE = All blocks other than air, cave air, and void air"
-                let mut blocking_found = false;
-                let mut surface_found = false;
+    pub fn fill_biome(&mut self, biome: BiomeId) {
+        for chunk in &mut self.chunk_sections {
+            chunk.fill_biome(biome);
+        }
+    }
 
-                if self.heightmap_motion_blocking[z][x] >= highest_chunk_value as u16 {
-                    blocking_found = true;
-                }
+    fn section_index(coord: Coordinate) -> Result<usize, VoxelIndexError> {
+        let y_16 = coord.y.div_euclid(16);
+        if y_16 < 0 || y_16 >= NUM_CHUNK_SECTIONS_PER_COLUMN as i32 {
+            return Err(VoxelIndexError { coordinate: coord });
+        }
+        Ok(y_16 as usize)
+    }
 
-                if self.heightmap_world_surface[z][x] >= highest_chunk_value as u16 {
-                    surface_found = true;
-                }
+    /// Replaces the heightmaps with the long arrays as used in the protocol and in anvil files
+    pub fn set_heightmaps_from_minecraft(
+        &mut self,
+        motion_blocking: &[i64],
+        world_surface: &[i64],
+    ) {
+        self.heightmap_motion_blocking = self.heightmap_from_minecraft(motion_blocking);
+        self.heightmap_world_surface = self.heightmap_from_minecraft(world_surface);
+    }
 
-                if blocking_found && surface_found {
+    // Heightmap values follow the minecraft definition: the number of blocks between the bottom of
+    // the world and the first free space above the highest matching block. 0 means no such block.
+    fn update_heightmap(
+        &mut self,
+        y_16: i32,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        let section_top = ((y_16 + 1) * 16) as u16;
+
+        for z in 0..16usize {
+            for x in 0..16usize {
+                // a height above this chunk is not affected by the contents of this chunk
+                if self.heightmap_motion_blocking[z][x] > section_top
+                    && self.heightmap_world_surface[z][x] > section_top
+                {
                     continue;
                 }
 
-                for y in (0..16usize).rev() {
-                    let block = chunk
-                        .get_voxel_internal(ICoordinate::new(x, y, z))
-                        .get_block();
-                    let block_properties = registry.get_block_properties(block);
-
-                    if !blocking_found {
-                        if !block_properties.is_solid {
-                            continue;
-                        }
-                        // first blocking block of the pillar
-                        self.heightmap_motion_blocking[z][x] = ((y_16 * 16) as u16) + (y as u16);
-
-                        if surface_found {
-                            break;
-                        }
-                        blocking_found = true;
-                    } else if block_properties.is_air {
-                        self.heightmap_world_surface[z][x] = ((y_16 * 16) as u16) + (y as u16);
-                        break;
-                    }
-                }
+                self.update_pillar(x, z, section_top, block_properties, block_states);
+            }
+        }
+    }
+
+    // scans the pillar at (x, z) downward from `start_y` (exclusive) for heightmaps at or below start_y
+    fn update_pillar(
+        &mut self,
+        x: usize,
+        z: usize,
+        start_y: u16,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        let mut blocking_found = self.heightmap_motion_blocking[z][x] > start_y;
+        let mut surface_found = self.heightmap_world_surface[z][x] > start_y;
+
+        for y in (0..start_y as usize).rev() {
+            if blocking_found && surface_found {
+                return;
+            }
+
+            let block = self.chunk_sections[y / 16]
+                .get_voxel_internal(ICoordinate::new(x, y % 16, z))
+                .get_block();
+            let block = block_states.block_state_to_block(block);
+            let properties = block_properties.get_block_properties(block);
+
+            // "WORLD_SURFACE = All blocks other than air, cave air, and void air"
+            if !surface_found && !properties.is_air {
+                self.heightmap_world_surface[z][x] = (y + 1) as u16;
+                surface_found = true;
+            }
+
+            // "MOTION_BLOCKING = The highest block that blocks motion or contains a fluid"
+            if !blocking_found && (properties.is_solid || properties.is_liquid) {
+                self.heightmap_motion_blocking[z][x] = (y + 1) as u16;
+                blocking_found = true;
             }
         }
+
+        if !surface_found {
+            self.heightmap_world_surface[z][x] = 0;
+        }
+        if !blocking_found {
+            self.heightmap_motion_blocking[z][x] = 0;
+        }
     }
 
+    /// The light is only included after calling `compute_light`
     pub fn to_minecraft(&self) -> Result<ChunkColumnSerialized, &'static str> {
         let results = self
             .chunk_sections
@@ -136,8 +288,7 @@ impl ChunkColumn {
 
         let block_entities: Vec<BlockEntity> = block_entities.into_iter().flatten().collect();
 
-        let world_surface = self.heightmap_to_minecraft(&self.heightmap_world_surface);
-        let motion_blocking = self.heightmap_to_minecraft(&self.heightmap_motion_blocking);
+        let (motion_blocking, world_surface) = self.heightmaps_to_minecraft();
 
         Ok(ChunkColumnSerialized {
             chunk_x_16: self.chunk_x_16,
@@ -146,63 +297,76 @@ impl ChunkColumn {
             block_entities,
             heightmap_motion_blocking: motion_blocking,
             heightmap_world_surface: world_surface,
+            light: self.light.to_minecraft(),
         })
     }
 
+    /// Returns the motion blocking and world surface heightmaps as long arrays, in that order
+    pub fn heightmaps_to_minecraft(&self) -> (Vec<i64>, Vec<i64>) {
+        (
+            self.heightmap_to_minecraft(&self.heightmap_motion_blocking),
+            self.heightmap_to_minecraft(&self.heightmap_world_surface),
+        )
+    }
+
+    // Values are packed into longs from the least significant bit up, in z-major order.
+    // A value never spans two longs; the remaining bits of each long are left zero.
     fn heightmap_to_minecraft(&self, heightmap: &Heightmap) -> Vec<i64> {
-        let world_height = NUM_CHUNK_SECTIONS_PER_COLUMN * 16;
-        let bits_per_element = usize::BITS - world_height.leading_zeros();
+        let bits_per_element = Self::heightmap_bits_per_element();
+        let elements_per_long = u64::BITS / bits_per_element;
 
         let mut result = Vec::new();
         let mut accumulator: u64 = 0;
-        let mut start_bit_index = 0;
-        for x in 0..16 {
-            for z in 0..16 {
+        let mut num_in_accumulator = 0;
+        for z in 0..16 {
+            for x in 0..16 {
                 debug_assert!(heightmap[z][x] < (1 << bits_per_element));
 
-                accumulator |= (heightmap[z][x] as u64) << start_bit_index;
-                start_bit_index += bits_per_element;
+                accumulator |= (heightmap[z][x] as u64) << (num_in_accumulator * bits_per_element);
+                num_in_accumulator += 1;
 
-                if start_bit_index > u64::BITS {
+                if num_in_accumulator == elements_per_long {
                     result.push(accumulator as i64);
                     accumulator = 0;
-                    start_bit_index = 0;
+                    num_in_accumulator = 0;
                 }
             }
         }
 
+        if num_in_accumulator > 0 {
+            result.push(accumulator as i64);
+        }
+
         return result;
     }
 
-    fn heightmap_from_minecraft(&self, byte_array: &Vec<i64>) -> Heightmap {
-        // could be a constant
-        let world_height = self.chunk_sections.len() * 16;
-        let bits_per_element = usize::BITS - world_height.leading_zeros();
+    fn heightmap_from_minecraft(&self, byte_array: &[i64]) -> Heightmap {
+        let bits_per_element = Self::heightmap_bits_per_element();
+        let elements_per_long = u64::BITS / bits_per_element;
 
         let mut heightmap = [[0; 16]; 16];
         let mask: u64 = (1 << bits_per_element) - 1;
-        let mut byte_index = 0;
-        let mut start_bit_index = 0;
-        for x in 0..16 {
-            for z in 0..16 {
-                // check first for the sake of the trailing assert.
-                // note that the unnecessary extra check replaces a possibly unnecessary increment
-                if start_bit_index > u64::BITS {
-                    byte_index += 1;
-                    start_bit_index = 0;
-                }
-
-                let byte = byte_array[byte_index] as u64;
+        for z in 0..16 {
+            for x in 0..16 {
+                let index = (z * 16 + x) as u32;
+                let byte_index = (index / elements_per_long) as usize;
+                let start_bit_index = (index % elements_per_long) * bits_per_element;
+
+                // a missing long is read as an empty heightmap
+                let byte = byte_array.get(byte_index).copied().unwrap_or(0) as u64;
                 heightmap[z][x] = ((byte >> start_bit_index) & mask) as u16;
-                start_bit_index += bits_per_element;
             }
         }
 
-        assert_eq!(byte_array.len(), byte_index);
-
         return heightmap;
     }
 
+    fn heightmap_bits_per_element() -> u32 {
+        // heights range from 0 up to and including the world height
+        let world_height = NUM_CHUNK_SECTIONS_PER_COLUMN * 16;
+        usize::BITS - world_height.leading_zeros()
+    }
+
     pub fn for_each<Action: FnMut(&ICoordinate, VoxelRef)>(&self, mut action: Action) {
         // I regret nothing
         for y16 in 0..24usize {
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::ids::blocks::BlockId;
+    use minecraft_vanilla::registries::{get_registries, Registries};
+
+    use crate::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+    use crate::vector_alias::Coordinate;
+    use crate::voxel::Voxel;
+    use crate::voxel_errors::VoxelIndexError;
+
+    const WORLD_HEIGHT: i32 = NUM_CHUNK_SECTIONS_PER_COLUMN as i32 * 16;
+
+    fn set_stone(
+        column: &mut ChunkColumn,
+        registries: &Registries,
+        coord: Coordinate,
+    ) -> Result<(), VoxelIndexError> {
+        let block: BlockWithState = BlockId::Stone.into();
+        column.set_voxel(
+            coord,
+            Voxel::from_block(block),
+            registries.block_properties(),
+            registries.block_states(),
+        )
+    }
+
+    fn is_stone(column: &ChunkColumn, coord: Coordinate) -> bool {
+        let stone: BlockWithState = BlockId::Stone.into();
+        column.get_voxel(coord).unwrap().get_block_id() == stone.id()
+    }
+
+    #[test]
+    fn test_set_voxel_negative_coordinates() {
+        let registries = get_registries();
+        let mut column = ChunkColumn::new(-1, -2);
+
+        // the corners of the column, in its lowest and highest section
+        for coord in [
+            Coordinate::new(-16, 0, -32),
+            Coordinate::new(-1, 17, -17),
+            Coordinate::new(-1, WORLD_HEIGHT - 1, -32),
+        ] {
+            set_stone(&mut column, &registries, coord).unwrap();
+            assert!(is_stone(&column, coord), "{coord:?}");
+        }
+        assert!(!is_stone(&column, Coordinate::new(-2, 17, -17)));
+    }
+
+    #[test]
+    fn test_set_voxel_out_of_the_world() {
+        let registries = get_registries();
+        let mut column = ChunkColumn::new(0, 0);
+
+        for coord in [
+            Coordinate::new(0, -1, 0),
+            Coordinate::new(0, -16, 0),
+            Coordinate::new(0, WORLD_HEIGHT, 0),
+        ] {
+            let error = set_stone(&mut column, &registries, coord).unwrap_err();
+            assert_eq!(error.coordinate, coord);
+            assert!(column.get_voxel(coord).is_err());
+        }
+    }
+
+    #[test]
+    fn test_set_voxel_outside_of_the_column() {
+        let registries = get_registries();
+        let mut column = ChunkColumn::new(0, 0);
+
+        for coord in [
+            Coordinate::new(16, 0, 0),
+            Coordinate::new(0, 0, 16),
+            Coordinate::new(-1, 0, 0),
+        ] {
+            assert!(set_stone(&mut column, &registries, coord).is_err());
+        }
+    }
+}
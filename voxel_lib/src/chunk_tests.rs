@@ -3,10 +3,12 @@ use minecraft_protocol::data::blocks as mc_ids;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::biome;
     use crate::chunk16::Chunk16;
     use crate::vector_alias::*;
     use crate::voxel::Voxel;
     use crate::voxel_errors::VoxelIndexError;
+    use minecraft_protocol::components::chunk as mc_chunk;
     use minecraft_registries::block_property_registry::{BlockDataEntry, BlockPropertyRegistry, FireBehaviour, PistonBehaviour};
 
     fn get_registry() -> BlockPropertyRegistry {
@@ -110,7 +112,7 @@ mod tests {
             }
 
             let (mc_chunk, coord, block_entities) = original_chunk.to_minecraft();
-            let new_chunk = Chunk16::from_minecraft(&mc_chunk, coord, block_entities, &registry);
+            let new_chunk = Chunk16::from_minecraft(&mc_chunk, coord, block_entities, &registry).unwrap();
 
             check_voxels(&new_chunk, max);
         }
@@ -141,6 +143,167 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_set_voxel_upper_bounds() {
+        let registry = get_registry();
+        let location = Coordinate16::new(-1, 0, 0);
+        let mut chunk = Chunk16::new(location, mc_ids::Block::from_id(1), true);
+        let new_voxel = Voxel::from_block(mc_ids::Block::from_id(2));
+
+        // the last voxel of the chunk, and the first one of its neighbours
+        let last = Coordinate::new(-1, 15, 15);
+        assert!(chunk.set_voxel(last, new_voxel.clone(), &registry).is_ok());
+        for coord in [
+            Coordinate::new(0, 0, 0),
+            Coordinate::new(-16, 16, 0),
+            Coordinate::new(-16, 0, 16),
+        ] {
+            let result = chunk.set_voxel(coord, new_voxel.clone(), &registry);
+            assert!(result.is_err());
+            assert!(chunk.get_voxel(coord).is_err());
+        }
+    }
+
+    #[test]
+    fn test_biomes() {
+        let location = Coordinate16::new(1, 2, 3);
+        let mut chunk = Chunk16::new(location, mc_ids::Block::from_id(0), true);
+        let zero = chunk.zero_coordinate();
+
+        assert_eq!(chunk.get_biome(zero).unwrap(), biome::PLAINS);
+
+        // the whole 4x4x4 cell changes
+        chunk.set_biome(zero + Coordinate::new(5, 6, 7), biome::OCEAN).unwrap();
+        assert_eq!(chunk.get_biome(zero + Coordinate::new(4, 4, 4)).unwrap(), biome::OCEAN);
+        assert_eq!(chunk.get_biome(zero + Coordinate::new(7, 7, 7)).unwrap(), biome::OCEAN);
+        assert_eq!(chunk.get_biome(zero + Coordinate::new(8, 4, 4)).unwrap(), biome::PLAINS);
+
+        assert!(chunk.set_biome(zero + Coordinate::new(16, 0, 0), biome::OCEAN).is_err());
+    }
+
+    #[test]
+    fn test_biomes_to_minecraft() {
+        let location = Coordinate16::new(0, 0, 0);
+        let mut chunk = Chunk16::new(location, mc_ids::Block::from_id(0), true);
+
+        let (mc_chunk, _, _) = chunk.to_minecraft();
+        assert!(matches!(
+            mc_chunk.biomes,
+            mc_chunk::PalettedData::Single { value } if value == biome::PLAINS as u32
+        ));
+
+        // cell (x = 1, y = 2, z = 3) is index (2 * 4 + 3) * 4 + 1
+        chunk.set_biome(Coordinate::new(4, 8, 12), biome::OCEAN).unwrap();
+        let (mc_chunk, _, _) = chunk.to_minecraft();
+        match mc_chunk.biomes {
+            mc_chunk::PalettedData::Paletted { palette, indexed } => {
+                assert_eq!(indexed.len(), 64);
+                for (i, index) in indexed.iter().enumerate() {
+                    let expected = if i == 45 { biome::OCEAN } else { biome::PLAINS };
+                    assert_eq!(palette[*index as usize], expected as u32);
+                }
+            },
+            _ => panic!("expected paletted biomes"),
+        }
+    }
+
+    #[test]
+    fn test_biomes_from_to_minecraft() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let location = Coordinate16::new(-2, 5, 1);
+        let mut chunk = Chunk16::new(location, mc_ids::Block::from_id(0), true);
+        let zero = chunk.zero_coordinate();
+        let desert = biome::biome_from_name("minecraft:desert").unwrap();
+        chunk.set_biome(zero + Coordinate::new(0, 12, 4), biome::OCEAN).unwrap();
+        chunk.set_biome(zero + Coordinate::new(15, 0, 15), desert).unwrap();
+
+        let (mc_chunk, coord, block_entities) = chunk.to_minecraft();
+        let new_chunk = Chunk16::from_minecraft(
+            &mc_chunk,
+            coord,
+            block_entities,
+            registries.block_properties(),
+            registries.block_states(),
+        )
+        .unwrap();
+
+        for y in (0..16).step_by(4) {
+            for z in (0..16).step_by(4) {
+                for x in (0..16).step_by(4) {
+                    let cell = zero + Coordinate::new(x, y, z);
+                    assert_eq!(new_chunk.get_biome(cell).unwrap(), chunk.get_biome(cell).unwrap());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_biomes_from_raw_minecraft() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let location = Coordinate16::new(0, 0, 0);
+        let chunk = Chunk16::new(location, mc_ids::Block::from_id(0), true);
+        let (mut mc_chunk, coord, block_entities) = chunk.to_minecraft();
+
+        // the cell index is (y * 4 + z) * 4 + x
+        let values = (0..64)
+            .map(|i| if i == 45 { biome::OCEAN } else { biome::PLAINS })
+            .map(|biome| biome as u32)
+            .collect();
+        mc_chunk.biomes = mc_chunk::PalettedData::Raw { values };
+        let chunk = Chunk16::from_minecraft(
+            &mc_chunk,
+            coord,
+            block_entities,
+            registries.block_properties(),
+            registries.block_states(),
+        )
+        .unwrap();
+
+        assert_eq!(chunk.get_biome(Coordinate::new(4, 8, 12)).unwrap(), biome::OCEAN);
+        assert_eq!(chunk.get_biome(Coordinate::new(8, 8, 12)).unwrap(), biome::PLAINS);
+    }
+
+    #[test]
+    fn test_from_minecraft_invalid_palette_index() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let location = Coordinate16::new(0, 0, 0);
+        let chunk = Chunk16::new(location, mc_ids::Block::from_id(0), true);
+        let (mut mc_chunk, coord, _) = chunk.to_minecraft();
+
+        let mut indexed = vec![0; 16 * 16 * 16];
+        indexed[100] = 1;
+        mc_chunk.blocks = mc_chunk::PalettedData::Paletted {
+            palette: vec![0, 1],
+            indexed: indexed.clone(),
+        };
+        assert!(Chunk16::from_minecraft(
+            &mc_chunk,
+            coord,
+            Vec::new(),
+            registries.block_properties(),
+            registries.block_states(),
+        )
+        .is_ok());
+
+        // the palette only has 2 entries
+        indexed[200] = 2;
+        mc_chunk.blocks = mc_chunk::PalettedData::Paletted {
+            palette: vec![0, 1],
+            indexed,
+        };
+        let error = Chunk16::from_minecraft(
+            &mc_chunk,
+            coord,
+            Vec::new(),
+            registries.block_properties(),
+            registries.block_states(),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.index, 2);
+        assert_eq!(error.palette_length, 2);
+    }
+
     fn add_voxel(chunk: &mut Chunk16, i: i32) {
         let registry = get_registry();
         let new_voxel = Voxel::from_block(mc_ids::Block::from_id(i as u32));
@@ -1,12 +1,20 @@
 #![allow(dead_code)]
 
+#[cfg(test)]
+mod chunk_column_tests;
 #[cfg(test)]
 mod chunk_tests;
+#[cfg(test)]
+mod light_tests;
 mod palette_tests;
+#[cfg(test)]
+mod vector_alias_tests;
 
+pub mod biome;
 pub mod block;
 pub mod chunk16;
 pub mod chunk_column;
+pub mod light;
 mod palette;
 pub mod vector_alias;
 pub mod voxel;
@@ -0,0 +1,667 @@
+use std::collections::VecDeque;
+
+use minecraft_protocol::data::blocks::Block;
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+
+use crate::chunk16::Chunk16;
+use crate::chunk_column::NUM_CHUNK_SECTIONS_PER_COLUMN;
+use crate::vector_alias::{ChunkColumnCoordinate, ICoordinate};
+
+// Sky light and block light, following the minecraft rules:
+// - light decreases by the `filter_light` of every block it enters, and at least by 1.
+// - sky light of level 15 travels straight down through blocks that do not filter light.
+// - blocks emit their `emit_light` as block light.
+//
+// Light is computed per chunk column. Light crosses a column border when the column imports the
+// light of its neighbour, see `ColumnLight::import_side`; the world decides which columns do so.
+
+pub const MAX_LIGHT: u8 = 15;
+
+const WORLD_HEIGHT: usize = NUM_CHUNK_SECTIONS_PER_COLUMN * 16;
+// two light levels per byte
+const NIBBLE_ARRAY_LEN: usize = 16 * 16 * 16 / 2;
+// the protocol sends one section below, and one section above the world
+const NUM_LIGHT_SECTIONS: usize = NUM_CHUNK_SECTIONS_PER_COLUMN + 2;
+
+/// The four sides of a chunk column, where it borders its neighbouring columns
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColumnSide {
+    NegX,
+    PosX,
+    NegZ,
+    PosZ,
+}
+
+impl ColumnSide {
+    pub const ALL: [ColumnSide; 4] = [
+        ColumnSide::NegX,
+        ColumnSide::PosX,
+        ColumnSide::NegZ,
+        ColumnSide::PosZ,
+    ];
+
+    pub fn opposite(self) -> ColumnSide {
+        match self {
+            ColumnSide::NegX => ColumnSide::PosX,
+            ColumnSide::PosX => ColumnSide::NegX,
+            ColumnSide::NegZ => ColumnSide::PosZ,
+            ColumnSide::PosZ => ColumnSide::NegZ,
+        }
+    }
+
+    /// The coordinate of the column on this side of the given column
+    pub fn neighbour(self, coord: ChunkColumnCoordinate) -> ChunkColumnCoordinate {
+        match self {
+            ColumnSide::NegX => coord.add(-1, 0),
+            ColumnSide::PosX => coord.add(1, 0),
+            ColumnSide::NegZ => coord.add(0, -1),
+            ColumnSide::PosZ => coord.add(0, 1),
+        }
+    }
+
+    // the column relative coordinate of the i-th block on this side at height y
+    fn border_block(self, i: usize, y: usize) -> ICoordinate {
+        match self {
+            ColumnSide::NegX => ICoordinate::new(0, y, i),
+            ColumnSide::PosX => ICoordinate::new(15, y, i),
+            ColumnSide::NegZ => ICoordinate::new(i, y, 0),
+            ColumnSide::PosZ => ICoordinate::new(i, y, 15),
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LightType {
+    Sky,
+    Block,
+}
+
+#[derive(Clone)]
+enum LightSection {
+    // every block has the same light level; saves memory for the many dark and fully lit sections
+    Uniform(u8),
+    // indexed [y][z][x], the even index in the lower 4 bits, like the protocol
+    Nibbles(Box<[u8; NIBBLE_ARRAY_LEN]>),
+}
+
+impl LightSection {
+    fn get(&self, index: usize) -> u8 {
+        match self {
+            LightSection::Uniform(level) => *level,
+            LightSection::Nibbles(data) => {
+                let byte = data[index / 2];
+                if index % 2 == 0 {
+                    byte & 0x0F
+                } else {
+                    byte >> 4
+                }
+            },
+        }
+    }
+
+    // returns whether the level changed
+    fn set(&mut self, index: usize, level: u8) -> bool {
+        if let LightSection::Uniform(uniform) = *self {
+            if uniform == level {
+                return false;
+            }
+            *self = LightSection::Nibbles(Box::new([uniform | (uniform << 4); NIBBLE_ARRAY_LEN]));
+        }
+
+        if let LightSection::Nibbles(data) = self {
+            let byte = &mut data[index / 2];
+            let old_byte = *byte;
+            if index % 2 == 0 {
+                *byte = (*byte & 0xF0) | level;
+            } else {
+                *byte = (*byte & 0x0F) | (level << 4);
+            }
+            return *byte != old_byte;
+        }
+        false
+    }
+
+    fn same_levels(&self, other: &LightSection) -> bool {
+        match (self, other) {
+            (LightSection::Uniform(level), LightSection::Uniform(other_level)) => {
+                level == other_level
+            },
+            _ => self.to_minecraft() == other.to_minecraft(),
+        }
+    }
+
+    fn is_dark(&self) -> bool {
+        match self {
+            LightSection::Uniform(level) => *level == 0,
+            LightSection::Nibbles(data) => data.iter().all(|byte| *byte == 0),
+        }
+    }
+
+    fn to_minecraft(&self) -> Vec<u8> {
+        match self {
+            LightSection::Uniform(level) => vec![level | (level << 4); NIBBLE_ARRAY_LEN],
+            LightSection::Nibbles(data) => data.to_vec(),
+        }
+    }
+}
+
+/// The light arrays of the `ChunkData` and `UpdateLight` packets.
+/// The masks are bit sets over the sections, starting with the section below the world.
+pub struct LightData {
+    pub sky_light_mask: Vec<i64>,
+    pub block_light_mask: Vec<i64>,
+    pub empty_sky_light_mask: Vec<i64>,
+    pub empty_block_light_mask: Vec<i64>,
+    /// one nibble array for every bit in `sky_light_mask`
+    pub sky_light: Vec<Vec<u8>>,
+    /// one nibble array for every bit in `block_light_mask`
+    pub block_light: Vec<Vec<u8>>,
+}
+
+/// The sky light and block light of every block in a chunk column.
+/// Coordinates are relative to the column, like `ChunkColumn::for_each`.
+#[derive(Clone)]
+pub struct ColumnLight {
+    sky: Vec<LightSection>,
+    block: Vec<LightSection>,
+    is_computed: bool,
+    // a bit per section whose light changed, see `take_changed_sections`
+    changed_sections: u32,
+    // a bit per `ColumnSide` whose border blocks changed light, see `take_changed_sides`
+    changed_sides: u8,
+}
+
+// looks up the light properties of the blocks in a column
+struct BlockLightProperties<'a> {
+    sections: &'a [Chunk16],
+    block_properties: &'a BlockPropertyRegistry,
+    block_states: &'a BlockStateRegistry,
+}
+
+impl<'a> BlockLightProperties<'a> {
+    fn filter(&self, coord: ICoordinate) -> u8 {
+        let properties = self.block_properties.get_block_properties(self.block(coord));
+        properties.filter_light.min(MAX_LIGHT)
+    }
+
+    fn emission(&self, coord: ICoordinate) -> u8 {
+        let properties = self.block_properties.get_block_properties(self.block(coord));
+        properties.emit_light.min(MAX_LIGHT)
+    }
+
+    fn block(&self, coord: ICoordinate) -> Block {
+        let block = self.sections[coord.y / 16]
+            .get_voxel_internal(ICoordinate::new(coord.x, coord.y % 16, coord.z))
+            .get_block();
+        self.block_states.block_state_to_block(block)
+    }
+}
+
+impl Default for ColumnLight {
+    fn default() -> Self {
+        ColumnLight {
+            sky: vec![LightSection::Uniform(0); NUM_CHUNK_SECTIONS_PER_COLUMN],
+            block: vec![LightSection::Uniform(0); NUM_CHUNK_SECTIONS_PER_COLUMN],
+            is_computed: false,
+            changed_sections: 0,
+            changed_sides: 0,
+        }
+    }
+}
+
+impl ColumnLight {
+    /// false until the light is computed; light is not kept when a column is serialized
+    pub fn is_computed(&self) -> bool {
+        self.is_computed
+    }
+
+    pub fn get_sky_light(&self, coord: ICoordinate) -> u8 {
+        self.get(LightType::Sky, coord)
+    }
+
+    pub fn get_block_light(&self, coord: ICoordinate) -> u8 {
+        self.get(LightType::Block, coord)
+    }
+
+    /// Marks the light as outdated, for example after replacing a whole section
+    pub fn invalidate(&mut self) {
+        self.is_computed = false;
+    }
+
+    /// Returns a bit per section whose light changed since the last call, with the lowest section
+    /// in the lowest bit, and forgets the changes
+    pub fn take_changed_sections(&mut self) -> u32 {
+        std::mem::take(&mut self.changed_sections)
+    }
+
+    /// Returns the sides whose border blocks changed light since the last call, and forgets the
+    /// changes. The neighbouring columns on these sides may need to import the light again
+    pub fn take_changed_sides(&mut self) -> Vec<ColumnSide> {
+        let changed_sides = std::mem::take(&mut self.changed_sides);
+        ColumnSide::ALL
+            .into_iter()
+            .filter(|side| changed_sides & side.bit() != 0)
+            .collect()
+    }
+
+    /// The light of the whole column, including the sections below and above the world
+    pub fn to_minecraft(&self) -> LightData {
+        let all_sections = (1 << NUM_CHUNK_SECTIONS_PER_COLUMN) - 1;
+        let mut data = self.to_minecraft_sections(all_sections);
+
+        // below the world is dark, above the world is the open sky
+        let below_world = 1;
+        let above_world = 1 << (NUM_LIGHT_SECTIONS - 1);
+        data.sky_light_mask[0] |= above_world;
+        data.empty_sky_light_mask[0] |= below_world;
+        data.empty_block_light_mask[0] |= below_world | above_world;
+        data.sky_light
+            .push(LightSection::Uniform(MAX_LIGHT).to_minecraft());
+        data
+    }
+
+    /// The light of the given sections only, as a bit per section like `take_changed_sections`.
+    /// The masks leave out the other sections, so that the client keeps their light
+    pub fn to_minecraft_sections(&self, sections: u32) -> LightData {
+        let mut data = LightData {
+            sky_light_mask: Vec::new(),
+            block_light_mask: Vec::new(),
+            empty_sky_light_mask: Vec::new(),
+            empty_block_light_mask: Vec::new(),
+            sky_light: Vec::new(),
+            block_light: Vec::new(),
+        };
+
+        let mut sky_mask: u64 = 0;
+        let mut block_mask: u64 = 0;
+        let mut empty_sky_mask: u64 = 0;
+        let mut empty_block_mask: u64 = 0;
+
+        for y_16 in 0..NUM_CHUNK_SECTIONS_PER_COLUMN {
+            if sections & (1 << y_16) == 0 {
+                continue;
+            }
+            // the first bit is the section below the world
+            let bit = 1 << (y_16 + 1);
+
+            if self.sky[y_16].is_dark() {
+                empty_sky_mask |= bit;
+            } else {
+                sky_mask |= bit;
+                data.sky_light.push(self.sky[y_16].to_minecraft());
+            }
+
+            if self.block[y_16].is_dark() {
+                empty_block_mask |= bit;
+            } else {
+                block_mask |= bit;
+                data.block_light.push(self.block[y_16].to_minecraft());
+            }
+        }
+
+        data.sky_light_mask.push(sky_mask as i64);
+        data.block_light_mask.push(block_mask as i64);
+        data.empty_sky_light_mask.push(empty_sky_mask as i64);
+        data.empty_block_light_mask.push(empty_block_mask as i64);
+        data
+    }
+
+    /// Computes all light of the column from scratch
+    pub(crate) fn compute(
+        &mut self,
+        sections: &[Chunk16],
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        let blocks = BlockLightProperties {
+            sections,
+            block_properties,
+            block_states,
+        };
+
+        let old_sky = std::mem::replace(
+            &mut self.sky,
+            vec![LightSection::Uniform(0); NUM_CHUNK_SECTIONS_PER_COLUMN],
+        );
+        let old_block = std::mem::replace(
+            &mut self.block,
+            vec![LightSection::Uniform(0); NUM_CHUNK_SECTIONS_PER_COLUMN],
+        );
+        let (changed_sections, changed_sides) = (self.changed_sections, self.changed_sides);
+
+        // direct sunlight: every block above the first block that filters light
+        let mut direct_bottom = [[WORLD_HEIGHT; 16]; 16];
+        for z in 0..16usize {
+            for x in 0..16usize {
+                for y in (0..WORLD_HEIGHT).rev() {
+                    let coord = ICoordinate::new(x, y, z);
+                    if blocks.filter(coord) != 0 {
+                        break;
+                    }
+                    self.set(LightType::Sky, coord, MAX_LIGHT);
+                    direct_bottom[z][x] = y;
+                }
+            }
+        }
+
+        // the sunlight spreads from where it meets less lit neighbours
+        let mut queue = VecDeque::new();
+        for z in 0..16usize {
+            for x in 0..16usize {
+                let bottom = direct_bottom[z][x];
+
+                if bottom == WORLD_HEIGHT {
+                    // the top block filters light, but may still let some through
+                    let coord = ICoordinate::new(x, WORLD_HEIGHT - 1, z);
+                    let level = Self::spread(LightType::Sky, MAX_LIGHT, true, blocks.filter(coord));
+                    if level > 0 {
+                        self.set(LightType::Sky, coord, level);
+                        queue.push_back(coord);
+                    }
+                    continue;
+                }
+
+                let mut top = bottom + 1;
+                for (nx, nz) in Self::horizontal_neighbours(x, z) {
+                    top = top.max(direct_bottom[nz][nx]);
+                }
+
+                for y in bottom..top.min(WORLD_HEIGHT) {
+                    queue.push_back(ICoordinate::new(x, y, z));
+                }
+            }
+        }
+        self.propagate(LightType::Sky, &blocks, queue);
+
+        let mut queue = VecDeque::new();
+        for (y_16, section) in sections.iter().enumerate() {
+            // empty sections contain no light sources
+            if section.is_empty() {
+                continue;
+            }
+
+            for y in y_16 * 16..(y_16 + 1) * 16 {
+                for z in 0..16usize {
+                    for x in 0..16usize {
+                        let coord = ICoordinate::new(x, y, z);
+                        let emission = blocks.emission(coord);
+                        if emission > 0 {
+                            self.set(LightType::Block, coord, emission);
+                            queue.push_back(coord);
+                        }
+                    }
+                }
+            }
+        }
+        self.propagate(LightType::Block, &blocks, queue);
+
+        // only the sections that differ from the previous light count as changed.
+        // Any side may have changed, and the light that the neighbours spread in is gone
+        let mut differing_sections = 0;
+        for y_16 in 0..NUM_CHUNK_SECTIONS_PER_COLUMN {
+            if !self.sky[y_16].same_levels(&old_sky[y_16])
+                || !self.block[y_16].same_levels(&old_block[y_16])
+            {
+                differing_sections |= 1 << y_16;
+            }
+        }
+        self.changed_sections = changed_sections | differing_sections;
+        self.changed_sides = changed_sides;
+        if differing_sections != 0 || !self.is_computed {
+            self.changed_sides = (1 << ColumnSide::ALL.len()) - 1;
+        }
+
+        self.is_computed = true;
+    }
+
+    /// Spreads the light at the border of the neighbouring column on the given side into this
+    /// column. Light only increases; to remove light that came from a neighbour, `compute` the
+    /// column again first
+    pub(crate) fn import_side(
+        &mut self,
+        side: ColumnSide,
+        neighbour: &ColumnLight,
+        sections: &[Chunk16],
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        if !self.is_computed || !neighbour.is_computed {
+            return;
+        }
+
+        let blocks = BlockLightProperties {
+            sections,
+            block_properties,
+            block_states,
+        };
+
+        for light_type in [LightType::Sky, LightType::Block] {
+            let mut queue = VecDeque::new();
+
+            for y in 0..WORLD_HEIGHT {
+                for i in 0..16 {
+                    let coord = side.border_block(i, y);
+                    let level = neighbour.get(light_type, side.opposite().border_block(i, y));
+                    let new_level = Self::spread(light_type, level, false, blocks.filter(coord));
+                    if new_level > self.get(light_type, coord) {
+                        self.set(light_type, coord, new_level);
+                        queue.push_back(coord);
+                    }
+                }
+            }
+
+            self.propagate(light_type, &blocks, queue);
+        }
+    }
+
+    /// Updates the light after the block at `coord` changed.
+    /// Light that depended on the old block is removed, and then filled in again from the
+    /// surrounding light and the new block.
+    pub(crate) fn update(
+        &mut self,
+        coord: ICoordinate,
+        sections: &[Chunk16],
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) {
+        if !self.is_computed {
+            return;
+        }
+
+        // light is removed before it is filled in again, often to the same levels
+        let before = self.clone();
+        let blocks = BlockLightProperties {
+            sections,
+            block_properties,
+            block_states,
+        };
+
+        for light_type in [LightType::Sky, LightType::Block] {
+            let mut relight = VecDeque::new();
+            let mut darkened = VecDeque::new();
+
+            darkened.push_back((coord, self.get(light_type, coord)));
+            self.set(light_type, coord, 0);
+
+            while let Some((current, level)) = darkened.pop_front() {
+                for (neighbour, is_down) in Self::neighbours(current) {
+                    let neighbour_level = self.get(light_type, neighbour);
+                    if neighbour_level == 0 {
+                        continue;
+                    }
+
+                    let is_direct_sunlight = light_type == LightType::Sky
+                        && is_down
+                        && level == MAX_LIGHT
+                        && neighbour_level == MAX_LIGHT;
+
+                    if neighbour_level < level || is_direct_sunlight {
+                        // this light may have come from the darkened block
+                        self.set(light_type, neighbour, 0);
+                        darkened.push_back((neighbour, neighbour_level));
+                    } else {
+                        relight.push_back(neighbour);
+                    }
+                }
+
+                // the top of the world is lit by the sky above it
+                if light_type == LightType::Sky && current.y == WORLD_HEIGHT - 1 {
+                    let level =
+                        Self::spread(LightType::Sky, MAX_LIGHT, true, blocks.filter(current));
+                    if level > 0 {
+                        self.set(LightType::Sky, current, level);
+                        relight.push_back(current);
+                    }
+                }
+            }
+
+            if light_type == LightType::Block {
+                let emission = blocks.emission(coord);
+                if emission > self.get(LightType::Block, coord) {
+                    self.set(LightType::Block, coord, emission);
+                    relight.push_back(coord);
+                }
+            }
+
+            self.propagate(light_type, &blocks, relight);
+        }
+
+        self.forget_unchanged(&before);
+    }
+
+    // keeps only the changes since `before` of the sections and sides whose light differs from it
+    fn forget_unchanged(&mut self, before: &ColumnLight) {
+        for y_16 in 0..NUM_CHUNK_SECTIONS_PER_COLUMN {
+            let bit = 1 << y_16;
+            if self.changed_sections & !before.changed_sections & bit != 0
+                && self.sky[y_16].same_levels(&before.sky[y_16])
+                && self.block[y_16].same_levels(&before.block[y_16])
+            {
+                self.changed_sections &= !bit;
+            }
+        }
+
+        for side in ColumnSide::ALL {
+            if self.changed_sides & !before.changed_sides & side.bit() == 0 {
+                continue;
+            }
+            let is_unchanged = (0..WORLD_HEIGHT).all(|y| {
+                (0..16).all(|i| {
+                    let coord = side.border_block(i, y);
+                    self.get_sky_light(coord) == before.get_sky_light(coord)
+                        && self.get_block_light(coord) == before.get_block_light(coord)
+                })
+            });
+            if is_unchanged {
+                self.changed_sides &= !side.bit();
+            }
+        }
+    }
+
+    fn propagate(
+        &mut self,
+        light_type: LightType,
+        blocks: &BlockLightProperties,
+        mut queue: VecDeque<ICoordinate>,
+    ) {
+        while let Some(current) = queue.pop_front() {
+            let level = self.get(light_type, current);
+            if level <= 1 {
+                continue;
+            }
+
+            for (neighbour, is_down) in Self::neighbours(current) {
+                let new_level = Self::spread(light_type, level, is_down, blocks.filter(neighbour));
+                if new_level > self.get(light_type, neighbour) {
+                    self.set(light_type, neighbour, new_level);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    // the light level of a block with the given filter, next to a block of the given level
+    fn spread(light_type: LightType, level: u8, is_down: bool, filter: u8) -> u8 {
+        if light_type == LightType::Sky && is_down && level == MAX_LIGHT && filter == 0 {
+            return MAX_LIGHT;
+        }
+        level.saturating_sub(filter.max(1))
+    }
+
+    // all neighbours within the column, and whether the neighbour is below the given coordinate
+    fn neighbours(coord: ICoordinate) -> impl Iterator<Item = (ICoordinate, bool)> {
+        let mut neighbours = Vec::with_capacity(6);
+
+        for (x, z) in Self::horizontal_neighbours(coord.x, coord.z) {
+            neighbours.push((ICoordinate::new(x, coord.y, z), false));
+        }
+        if coord.y > 0 {
+            neighbours.push((ICoordinate::new(coord.x, coord.y - 1, coord.z), true));
+        }
+        if coord.y + 1 < WORLD_HEIGHT {
+            neighbours.push((ICoordinate::new(coord.x, coord.y + 1, coord.z), false));
+        }
+
+        neighbours.into_iter()
+    }
+
+    fn horizontal_neighbours(x: usize, z: usize) -> impl Iterator<Item = (usize, usize)> {
+        let mut neighbours = Vec::with_capacity(4);
+
+        if x > 0 {
+            neighbours.push((x - 1, z));
+        }
+        if x < 15 {
+            neighbours.push((x + 1, z));
+        }
+        if z > 0 {
+            neighbours.push((x, z - 1));
+        }
+        if z < 15 {
+            neighbours.push((x, z + 1));
+        }
+
+        neighbours.into_iter()
+    }
+
+    fn get(&self, light_type: LightType, coord: ICoordinate) -> u8 {
+        let sections = match light_type {
+            LightType::Sky => &self.sky,
+            LightType::Block => &self.block,
+        };
+        sections[coord.y / 16].get(Self::index(coord))
+    }
+
+    fn set(&mut self, light_type: LightType, coord: ICoordinate, level: u8) {
+        let sections = match light_type {
+            LightType::Sky => &mut self.sky,
+            LightType::Block => &mut self.block,
+        };
+        if !sections[coord.y / 16].set(Self::index(coord), level) {
+            return;
+        }
+
+        self.changed_sections |= 1 << (coord.y / 16);
+        if coord.x == 0 {
+            self.changed_sides |= ColumnSide::NegX.bit();
+        }
+        if coord.x == 15 {
+            self.changed_sides |= ColumnSide::PosX.bit();
+        }
+        if coord.z == 0 {
+            self.changed_sides |= ColumnSide::NegZ.bit();
+        }
+        if coord.z == 15 {
+            self.changed_sides |= ColumnSide::PosZ.bit();
+        }
+    }
+
+    fn index(coord: ICoordinate) -> usize {
+        ((coord.y % 16) * 16 + coord.z) * 16 + coord.x
+    }
+}
@@ -0,0 +1,318 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::ids::blocks::BlockId;
+    use minecraft_vanilla::registries::Registries;
+
+    use crate::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+    use crate::light::{ColumnLight, ColumnSide, MAX_LIGHT};
+    use crate::vector_alias::{Coordinate, ICoordinate};
+    use crate::voxel::Voxel;
+
+    const WORLD_HEIGHT: usize = NUM_CHUNK_SECTIONS_PER_COLUMN * 16;
+    const ROOF_Y: usize = 100;
+
+    fn set_block(
+        column: &mut ChunkColumn,
+        registries: &Registries,
+        coord: ICoordinate,
+        block: BlockId,
+    ) {
+        let coord = Coordinate::new(coord.x as i32, coord.y as i32, coord.z as i32);
+        let block: BlockWithState = block.into();
+        column
+            .set_voxel(
+                coord,
+                Voxel::from_block(block),
+                registries.block_properties(),
+                registries.block_states(),
+            )
+            .unwrap();
+    }
+
+    // a 3x3 stone roof at y = 100, centered on x = 6, z = 6
+    fn add_roof(column: &mut ChunkColumn, registries: &Registries) {
+        for z in 5..=7 {
+            for x in 5..=7 {
+                set_block(
+                    column,
+                    registries,
+                    ICoordinate::new(x, ROOF_Y, z),
+                    BlockId::Stone,
+                );
+            }
+        }
+    }
+
+    fn computed_column(registries: &Registries) -> ChunkColumn {
+        let mut column = ChunkColumn::new(0, 0);
+        column.compute_light(registries.block_properties(), registries.block_states());
+        column
+    }
+
+    fn assert_same_light(actual: &ColumnLight, expected: &ColumnLight) {
+        for y in 0..WORLD_HEIGHT {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let coord = ICoordinate::new(x, y, z);
+                    assert_eq!(
+                        actual.get_sky_light(coord),
+                        expected.get_sky_light(coord),
+                        "sky light at {coord:?}"
+                    );
+                    assert_eq!(
+                        actual.get_block_light(coord),
+                        expected.get_block_light(coord),
+                        "block light at {coord:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uncomputed_light_is_dark() {
+        let light = ColumnLight::default();
+        assert!(!light.is_computed());
+        assert_eq!(light.get_sky_light(ICoordinate::new(3, 200, 7)), 0);
+        assert_eq!(light.get_block_light(ICoordinate::new(3, 200, 7)), 0);
+    }
+
+    #[test]
+    fn test_light_masks() {
+        let data = ColumnLight::default().to_minecraft();
+
+        // 24 sections, plus one below and one above the world
+        let all_sections: i64 = (1 << 26) - 1;
+        let above_world: i64 = 1 << 25;
+
+        // only the section above the world is lit, by the open sky
+        assert_eq!(data.sky_light_mask, vec![above_world]);
+        assert_eq!(data.empty_sky_light_mask, vec![all_sections & !above_world]);
+        assert_eq!(data.sky_light.len(), 1);
+        assert!(data.sky_light[0]
+            .iter()
+            .all(|byte| *byte == MAX_LIGHT | (MAX_LIGHT << 4)));
+
+        assert_eq!(data.block_light_mask, vec![0]);
+        assert_eq!(data.empty_block_light_mask, vec![all_sections]);
+        assert!(data.block_light.is_empty());
+    }
+
+    #[test]
+    fn test_sky_light_under_overhang() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut column = ChunkColumn::new(0, 0);
+        add_roof(&mut column, &registries);
+        column.compute_light(registries.block_properties(), registries.block_states());
+        let light = column.get_light();
+
+        assert!(light.is_computed());
+        assert_eq!(
+            light.get_sky_light(ICoordinate::new(6, ROOF_Y + 1, 6)),
+            MAX_LIGHT
+        );
+        assert_eq!(light.get_sky_light(ICoordinate::new(6, ROOF_Y, 6)), 0);
+        // the sunlight spreads in from the sides, and loses one level per block
+        for y in [0, 50, ROOF_Y - 1] {
+            assert_eq!(light.get_sky_light(ICoordinate::new(4, y, 6)), MAX_LIGHT);
+            assert_eq!(
+                light.get_sky_light(ICoordinate::new(5, y, 6)),
+                MAX_LIGHT - 1
+            );
+            assert_eq!(
+                light.get_sky_light(ICoordinate::new(6, y, 6)),
+                MAX_LIGHT - 2
+            );
+        }
+    }
+
+    #[test]
+    fn test_sky_light_update_matches_compute() {
+        let registries = minecraft_vanilla::registries::get_registries();
+
+        let mut expected = ChunkColumn::new(0, 0);
+        add_roof(&mut expected, &registries);
+        expected.compute_light(registries.block_properties(), registries.block_states());
+
+        // building the roof on a lit column
+        let mut column = computed_column(&registries);
+        add_roof(&mut column, &registries);
+        assert_same_light(column.get_light(), expected.get_light());
+
+        // removing it again lets the sunlight straight through
+        for z in 5..=7 {
+            for x in 5..=7 {
+                set_block(
+                    &mut column,
+                    &registries,
+                    ICoordinate::new(x, ROOF_Y, z),
+                    BlockId::Air,
+                );
+            }
+        }
+        assert_same_light(column.get_light(), computed_column(&registries).get_light());
+    }
+
+    #[test]
+    fn test_block_light_falloff() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut column = computed_column(&registries);
+        let source = ICoordinate::new(8, 50, 8);
+        set_block(&mut column, &registries, source, BlockId::Glowstone);
+        let light = column.get_light();
+
+        assert_eq!(light.get_block_light(source), MAX_LIGHT);
+        // one level less per block, by the manhattan distance
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(9, 50, 8)),
+            MAX_LIGHT - 1
+        );
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(8, 53, 8)),
+            MAX_LIGHT - 3
+        );
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(10, 49, 9)),
+            MAX_LIGHT - 4
+        );
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(15, 50, 8)),
+            MAX_LIGHT - 7
+        );
+        assert_eq!(light.get_block_light(ICoordinate::new(8, 50 + 15, 8)), 0);
+
+        let mut expected = ChunkColumn::new(0, 0);
+        set_block(&mut expected, &registries, source, BlockId::Glowstone);
+        expected.compute_light(registries.block_properties(), registries.block_states());
+        assert_same_light(column.get_light(), expected.get_light());
+    }
+
+    #[test]
+    fn test_remove_light_source() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut column = computed_column(&registries);
+        let source = ICoordinate::new(8, 50, 8);
+        set_block(&mut column, &registries, source, BlockId::Glowstone);
+        set_block(&mut column, &registries, source, BlockId::Air);
+        let light = column.get_light();
+
+        for coord in [
+            source,
+            ICoordinate::new(9, 50, 8),
+            ICoordinate::new(8, 53, 8),
+            ICoordinate::new(15, 50, 8),
+        ] {
+            assert_eq!(light.get_block_light(coord), 0);
+        }
+        assert_same_light(light, computed_column(&registries).get_light());
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut column = computed_column(&registries);
+        column.take_changed_light_sections();
+        assert_eq!(column.take_changed_light_sections(), 0);
+
+        // the glowstone shades the sections below it from the sky,
+        // and its light reaches 14 blocks up and down, from section 2 to section 4
+        set_block(
+            &mut column,
+            &registries,
+            ICoordinate::new(8, 50, 8),
+            BlockId::Glowstone,
+        );
+        let sections = column.take_changed_light_sections();
+        assert_eq!(sections, 0b11111);
+        assert_eq!(column.take_changed_light_sections(), 0);
+
+        // computing the same light again changes nothing
+        column.compute_light(registries.block_properties(), registries.block_states());
+        assert_eq!(column.take_changed_light_sections(), 0);
+
+        // only the changed sections are sent, shifted by the section below the world
+        let data = column.get_light().to_minecraft_sections(sections);
+        assert_eq!(data.sky_light_mask, vec![0b111110]);
+        assert_eq!(data.empty_sky_light_mask, vec![0]);
+        assert_eq!(data.block_light_mask, vec![0b111000]);
+        assert_eq!(data.empty_block_light_mask, vec![0b000110]);
+        assert_eq!(data.sky_light.len(), 5);
+        assert_eq!(data.block_light.len(), 3);
+    }
+
+    #[test]
+    fn test_changed_sides() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut column = computed_column(&registries);
+        column.take_changed_light_sides();
+
+        // the light fades before it reaches the other side
+        set_block(
+            &mut column,
+            &registries,
+            ICoordinate::new(15, 50, 8),
+            BlockId::Glowstone,
+        );
+        let sides = column.take_changed_light_sides();
+        assert!(sides.contains(&ColumnSide::PosX));
+        assert!(!sides.contains(&ColumnSide::NegX));
+        assert!(column.take_changed_light_sides().is_empty());
+
+        // the light at the borders stays the same
+        set_block(
+            &mut column,
+            &registries,
+            ICoordinate::new(8, 50, 8),
+            BlockId::Stone,
+        );
+        assert!(column.take_changed_light_sides().is_empty());
+    }
+
+    #[test]
+    fn test_import_light() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let mut source = computed_column(&registries);
+        set_block(
+            &mut source,
+            &registries,
+            ICoordinate::new(15, 50, 8),
+            BlockId::Glowstone,
+        );
+
+        let mut column = ChunkColumn::new(1, 0);
+        column.compute_light(registries.block_properties(), registries.block_states());
+        column.take_changed_light_sections();
+        column.import_light(
+            ColumnSide::NegX,
+            &source,
+            registries.block_properties(),
+            registries.block_states(),
+        );
+
+        // the light continues across the border
+        let light = column.get_light();
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(0, 50, 8)),
+            MAX_LIGHT - 1
+        );
+        assert_eq!(
+            light.get_block_light(ICoordinate::new(3, 51, 8)),
+            MAX_LIGHT - 5
+        );
+        assert_eq!(light.get_block_light(ICoordinate::new(14, 50, 8)), 0);
+        assert_eq!(light.get_sky_light(ICoordinate::new(0, 50, 8)), MAX_LIGHT);
+        assert_eq!(column.take_changed_light_sections(), 0b1100);
+
+        // the column on the other side is not affected
+        let mut other = ChunkColumn::new(1, 0);
+        other.compute_light(registries.block_properties(), registries.block_states());
+        other.import_light(
+            ColumnSide::PosX,
+            &source,
+            registries.block_properties(),
+            registries.block_states(),
+        );
+        assert_same_light(other.get_light(), computed_column(&registries).get_light());
+    }
+}
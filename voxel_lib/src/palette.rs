@@ -8,7 +8,7 @@ use crate::{
     voxel::{Voxel, VoxelRef},
 };
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Palette {
     base: Vec<BlockMapping>,
     // every nbt_voxel also exists as a simple voxel
@@ -60,6 +60,30 @@ impl Palette {
         }
     }
 
+    /// Creates a palette of simple voxels, where the index of every mapping is its index in
+    /// `entries`. Entries with a count of 0 become empty mappings.
+    pub fn from_counted(entries: Vec<(BlockWithState, u16)>) -> Palette {
+        let mut size = 0;
+        let base = entries
+            .into_iter()
+            .map(|(block_id, num_elements)| {
+                let data = if num_elements == 0 {
+                    MappingData::Empty
+                } else {
+                    size += 1;
+                    MappingData::Simple { num_elements }
+                };
+                BlockMapping { block_id, data }
+            })
+            .collect();
+
+        Palette {
+            base,
+            nbt_voxels: Vec::new(),
+            size,
+        }
+    }
+
     pub fn add_simple(&mut self, block_id: BlockWithState) -> u16 {
         // first see if it is already in here
         for idx in 0..self.base.len() {
@@ -21,7 +21,7 @@ pub type Direction = UnitVector3<f32>;
 pub type Rotation = UnitQuaternion<f32>;
 
 pub fn coordinate_containing_position(pos: &Position) -> Coordinate {
-    Coordinate::new(pos.x as i32, pos.y as i32, pos.z as i32)
+    Coordinate::new(pos.x.floor() as i32, pos.y.floor() as i32, pos.z.floor() as i32)
 }
 
 impl Coordinate16 {
@@ -39,14 +39,19 @@ impl Coordinate16 {
 
     pub fn containing_position(pos: &Position) -> Coordinate16 {
         Self::new(
-            (pos.x / 16.0) as i32,
-            (pos.y / 16.0) as i32,
-            (pos.z / 16.0) as i32,
+            (pos.x / 16.0).floor() as i32,
+            (pos.y / 16.0).floor() as i32,
+            (pos.z / 16.0).floor() as i32,
         )
     }
 
+    // note: div_euclid rounds toward negative infinity, where `/` would round toward zero
     pub fn containing_coord(coord: &Coordinate) -> Coordinate16 {
-        Self::new(coord.x / 16, coord.y / 16, coord.z / 16)
+        Self::new(
+            coord.x.div_euclid(16),
+            coord.y.div_euclid(16),
+            coord.z.div_euclid(16),
+        )
     }
 }
 
@@ -57,15 +62,15 @@ impl ChunkColumnCoordinate {
 
     pub fn containing_position(pos: &Position) -> ChunkColumnCoordinate {
         Self {
-            x: (pos.x / 16.0) as i32,
-            z: (pos.z / 16.0) as i32,
+            x: (pos.x / 16.0).floor() as i32,
+            z: (pos.z / 16.0).floor() as i32,
         }
     }
 
     pub fn containing_coord(coord: &Coordinate) -> ChunkColumnCoordinate {
         Self {
-            x: coord.x / 16,
-            z: coord.z / 16,
+            x: coord.x.div_euclid(16),
+            z: coord.z.div_euclid(16),
         }
     }
 }
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::vector_alias::{
+        coordinate_containing_position, ChunkColumnCoordinate, Coordinate, Coordinate16, Position,
+    };
+
+    #[test]
+    fn test_coordinate_containing_position() {
+        let position = Position::new(0.5, 70.0, 15.9);
+        assert_eq!(
+            coordinate_containing_position(&position),
+            Coordinate::new(0, 70, 15)
+        );
+
+        // rounded toward negative infinity, not toward zero
+        let position = Position::new(-0.5, -0.0, -16.1);
+        assert_eq!(
+            coordinate_containing_position(&position),
+            Coordinate::new(-1, 0, -17)
+        );
+    }
+
+    #[test]
+    fn test_coordinate16_containing() {
+        assert_eq!(
+            Coordinate16::containing_coord(&Coordinate::new(15, 16, 31)),
+            Coordinate16::new(0, 1, 1)
+        );
+        assert_eq!(
+            Coordinate16::containing_coord(&Coordinate::new(-1, -16, -17)),
+            Coordinate16::new(-1, -1, -2)
+        );
+
+        assert_eq!(
+            Coordinate16::containing_position(&Position::new(-0.1, 15.9, -16.0)),
+            Coordinate16::new(-1, 0, -1)
+        );
+    }
+
+    #[test]
+    fn test_chunk_column_containing() {
+        let coord = ChunkColumnCoordinate::containing_coord(&Coordinate::new(-1, 100, 16));
+        assert_eq!(coord, ChunkColumnCoordinate { x: -1, z: 1 });
+        let coord = ChunkColumnCoordinate::containing_coord(&Coordinate::new(-16, 0, -17));
+        assert_eq!(coord, ChunkColumnCoordinate { x: -1, z: -2 });
+
+        let coord = ChunkColumnCoordinate::containing_position(&Position::new(-0.5, 0.0, 0.5));
+        assert_eq!(coord, ChunkColumnCoordinate { x: -1, z: 0 });
+    }
+}
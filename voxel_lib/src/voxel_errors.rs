@@ -6,6 +6,13 @@ pub struct VoxelIndexError {
     pub coordinate: Coordinate,
 }
 
+/// A block of a paletted section refers to an entry past the end of the palette
+#[derive(Debug)]
+pub struct PaletteIndexError {
+    pub index: u32,
+    pub palette_length: usize,
+}
+
 #[derive(Debug)]
 pub struct UnknownBlockTypeError {
     pub value: block::BaseVoxel,
@@ -47,3 +54,21 @@ impl From<UnknownBlockTypeError> for SimpleError {
         SimpleError::new(err.to_string())
     }
 }
+
+impl std::error::Error for PaletteIndexError {}
+
+impl std::fmt::Display for PaletteIndexError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "Palette index {} is out of a palette of {} entries",
+            self.index, self.palette_length
+        )
+    }
+}
+
+impl From<PaletteIndexError> for SimpleError {
+    fn from(err: PaletteIndexError) -> Self {
+        SimpleError::new(err.to_string())
+    }
+}
@@ -0,0 +1,7 @@
+#[cfg(test)]
+mod noise_tests;
+#[cfg(test)]
+mod terrain_tests;
+
+pub mod noise;
+pub mod terrain;
@@ -0,0 +1,124 @@
+// Value noise: every point of an integer lattice gets a pseudo-random value derived from the seed,
+// and values in between are smoothly interpolated. The same seed always gives the same noise,
+// independent of platform or of the order in which it is sampled.
+
+const PRIME_X: u64 = 0x9E3779B97F4A7C15;
+const PRIME_Y: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME_Z: u64 = 0x165667B19E3779F9;
+
+#[derive(Clone, Copy)]
+pub struct ValueNoise {
+    seed: u64,
+}
+
+impl ValueNoise {
+    pub fn new(seed: u64) -> ValueNoise {
+        ValueNoise { seed: hash(seed) }
+    }
+
+    /// Returns a value in [-1, 1]
+    pub fn sample_2d(&self, x: f64, z: f64) -> f64 {
+        let x0 = x.floor();
+        let z0 = z.floor();
+        let tx = fade(x - x0);
+        let tz = fade(z - z0);
+        let (x0, z0) = (x0 as i64, z0 as i64);
+
+        let v00 = self.lattice_value(x0, 0, z0);
+        let v10 = self.lattice_value(x0 + 1, 0, z0);
+        let v01 = self.lattice_value(x0, 0, z0 + 1);
+        let v11 = self.lattice_value(x0 + 1, 0, z0 + 1);
+
+        lerp(lerp(v00, v10, tx), lerp(v01, v11, tx), tz)
+    }
+
+    /// Returns a value in [-1, 1]
+    pub fn sample_3d(&self, x: f64, y: f64, z: f64) -> f64 {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let z0 = z.floor();
+        let tx = fade(x - x0);
+        let ty = fade(y - y0);
+        let tz = fade(z - z0);
+        let (x0, y0, z0) = (x0 as i64, y0 as i64, z0 as i64);
+
+        let plane = |y: i64| {
+            let v00 = self.lattice_value(x0, y, z0);
+            let v10 = self.lattice_value(x0 + 1, y, z0);
+            let v01 = self.lattice_value(x0, y, z0 + 1);
+            let v11 = self.lattice_value(x0 + 1, y, z0 + 1);
+            lerp(lerp(v00, v10, tx), lerp(v01, v11, tx), tz)
+        };
+
+        lerp(plane(y0), plane(y0 + 1), ty)
+    }
+
+    /// Sums `octaves` layers of noise, each with double the frequency and half the amplitude of
+    /// the previous. Returns a value in [-1, 1]
+    pub fn fractal_2d(&self, x: f64, z: f64, octaves: u32) -> f64 {
+        self.fractal(octaves, |frequency, offset| {
+            self.sample_2d(x * frequency + offset, z * frequency + offset)
+        })
+    }
+
+    /// Like `fractal_2d`, in three dimensions
+    pub fn fractal_3d(&self, x: f64, y: f64, z: f64, octaves: u32) -> f64 {
+        self.fractal(octaves, |frequency, offset| {
+            self.sample_3d(
+                x * frequency + offset,
+                y * frequency + offset,
+                z * frequency + offset,
+            )
+        })
+    }
+
+    fn fractal<Sample: Fn(f64, f64) -> f64>(&self, octaves: u32, sample: Sample) -> f64 {
+        let mut total = 0.0;
+        let mut total_amplitude = 0.0;
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+
+        for octave in 0..octaves {
+            // shift every octave, so that the lattice points of the octaves do not line up
+            let offset = octave as f64 * 31.7;
+            total += sample(frequency, offset) * amplitude;
+            total_amplitude += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+
+        if total_amplitude == 0.0 {
+            return 0.0;
+        }
+
+        total / total_amplitude
+    }
+
+    fn lattice_value(&self, x: i64, y: i64, z: i64) -> f64 {
+        let position = (x as u64).wrapping_mul(PRIME_X)
+            ^ (y as u64).wrapping_mul(PRIME_Y)
+            ^ (z as u64).wrapping_mul(PRIME_Z);
+        let value = hash(self.seed ^ position);
+
+        // the upper 53 bits fit exactly in the mantissa of a f64
+        let unit = (value >> 11) as f64 / (1u64 << 53) as f64;
+        unit * 2.0 - 1.0
+    }
+}
+
+/// The finalizer of splitmix64; spreads every input bit over all output bits
+pub fn hash(value: u64) -> u64 {
+    let mut value = value;
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D049BB133111EB);
+    value ^ (value >> 31)
+}
+
+// smootherstep: the first and second derivative are zero at 0 and 1
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
@@ -0,0 +1,49 @@
+#[cfg(test)]
+mod tests {
+    use crate::noise::ValueNoise;
+
+    #[test]
+    fn test_deterministic() {
+        let noise_a = ValueNoise::new(42);
+        let noise_b = ValueNoise::new(42);
+
+        for i in 0..100 {
+            let x = i as f64 * 0.37 - 20.0;
+            let z = i as f64 * -1.13 + 5.0;
+            assert_eq!(noise_a.fractal_2d(x, z, 4), noise_b.fractal_2d(x, z, 4));
+            assert_eq!(
+                noise_a.fractal_3d(x, z, x, 3),
+                noise_b.fractal_3d(x, z, x, 3)
+            );
+        }
+    }
+
+    #[test]
+    fn test_seed_changes_noise() {
+        let noise_a = ValueNoise::new(1);
+        let noise_b = ValueNoise::new(2);
+
+        let num_different = (0..100)
+            .filter(|i| {
+                let x = *i as f64 * 0.5;
+                noise_a.sample_2d(x, x) != noise_b.sample_2d(x, x)
+            })
+            .count();
+        assert!(num_different > 90);
+    }
+
+    #[test]
+    fn test_range_and_continuity() {
+        let noise = ValueNoise::new(7);
+
+        let mut previous = noise.sample_2d(0.0, 3.5);
+        for i in 1..1000 {
+            let x = i as f64 * 0.01;
+            let value = noise.sample_2d(x, 3.5);
+            assert!((-1.0..=1.0).contains(&value));
+            // a small step gives a small change
+            assert!((value - previous).abs() < 0.1);
+            previous = value;
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use minecraft_protocol::components::chunk as mc_chunk;
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use minecraft_vanilla::ids::blocks::BlockId;
+use sol_voxel_lib::biome;
+use sol_voxel_lib::chunk16::Chunk16;
+use sol_voxel_lib::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate16};
+
+use crate::noise::{hash, ValueNoise};
+
+// indices into the palette of every generated section
+const AIR: u8 = 0;
+const STONE: u8 = 1;
+const DIRT: u8 = 2;
+const GRASS: u8 = 3;
+const WATER: u8 = 4;
+const BEDROCK: u8 = 5;
+const SAND: u8 = 6;
+
+const HEIGHT_OCTAVES: u32 = 5;
+const CAVE_OCTAVES: u32 = 2;
+
+/// All heights are in column coordinates, where y = 0 is the bottom of the world
+#[derive(Clone, Debug)]
+pub struct TerrainSettings {
+    /// the highest y that is filled with water
+    pub sea_level: i32,
+    /// the average surface height
+    pub base_height: i32,
+    /// the maximum distance of the surface from `base_height`
+    pub height_variation: f64,
+    /// horizontal size of the largest hills, in blocks
+    pub horizontal_scale: f64,
+    /// number of dirt (or sand) blocks below the surface block
+    pub dirt_depth: i32,
+    pub generate_caves: bool,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        // like vanilla: sea level at y = 62, and the surface around y = 64
+        TerrainSettings {
+            sea_level: 62 + 64,
+            base_height: 64 + 64,
+            height_variation: 24.0,
+            horizontal_scale: 256.0,
+            dirt_depth: 3,
+            generate_caves: true,
+        }
+    }
+}
+
+/// Generates chunk columns from a seed.
+/// Every column only depends on the seed and its own coordinate, so columns can be generated in
+/// any order, and neighbouring columns always line up.
+pub struct TerrainGenerator {
+    settings: TerrainSettings,
+    height_noise: ValueNoise,
+    cave_noise: ValueNoise,
+    palette: [BlockWithState; 7],
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u64, settings: TerrainSettings) -> TerrainGenerator {
+        TerrainGenerator {
+            settings,
+            height_noise: ValueNoise::new(seed),
+            cave_noise: ValueNoise::new(hash(seed.wrapping_add(1))),
+            palette: [
+                BlockId::Air.into(),
+                BlockId::Stone.into(),
+                BlockId::Dirt.into(),
+                BlockId::GrassBlock.into(),
+                BlockId::Water.into(),
+                BlockId::Bedrock.into(),
+                BlockId::Sand.into(),
+            ],
+        }
+    }
+
+    /// The y of the highest terrain block at the given block coordinates
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        let scale = self.settings.horizontal_scale;
+        let noise = self
+            .height_noise
+            .fractal_2d(x as f64 / scale, z as f64 / scale, HEIGHT_OCTAVES);
+
+        let height = self.settings.base_height + (noise * self.settings.height_variation) as i32;
+        let world_top = (NUM_CHUNK_SECTIONS_PER_COLUMN * 16) as i32 - 1;
+        height.clamp(1, world_top)
+    }
+
+    pub fn generate_column(
+        &self,
+        coord: ChunkColumnCoordinate,
+        block_properties: &BlockPropertyRegistry,
+        block_states: &BlockStateRegistry,
+    ) -> ChunkColumn {
+        let mut heights = [[0; 16]; 16];
+        for (z, row) in heights.iter_mut().enumerate() {
+            for (x, height) in row.iter_mut().enumerate() {
+                *height = self.surface_height(coord.x * 16 + x as i32, coord.z * 16 + z as i32);
+            }
+        }
+
+        let biomes = self.biomes(&heights);
+
+        let mut column = ChunkColumn::new(coord.x, coord.z);
+        for y_16 in 0..NUM_CHUNK_SECTIONS_PER_COLUMN as i32 {
+            let mc_chunk = self.generate_section(coord, y_16, &heights, biomes.clone());
+            let chunk = Chunk16::from_minecraft(
+                &mc_chunk,
+                Coordinate16::new(coord.x, y_16, coord.z),
+                Vec::new(),
+                block_properties,
+                block_states,
+            )
+            .expect("the generated palette holds every block of the section");
+            column.set_chunk(y_16, chunk, block_properties, block_states);
+        }
+
+        column
+    }
+
+    fn generate_section(
+        &self,
+        coord: ChunkColumnCoordinate,
+        y_16: i32,
+        heights: &[[i32; 16]; 16],
+        biomes: mc_chunk::PalettedData,
+    ) -> mc_chunk::Chunk {
+        let section_bottom = y_16 * 16;
+        let highest_block = heights
+            .iter()
+            .flatten()
+            .copied()
+            .fold(self.settings.sea_level, i32::max);
+
+        if section_bottom > highest_block {
+            return mc_chunk::Chunk {
+                block_count: 0,
+                blocks: mc_chunk::PalettedData::Single {
+                    value: self.palette[AIR as usize].id(),
+                },
+                biomes,
+            };
+        }
+
+        let mut indices = Vec::with_capacity(16 * 16 * 16);
+        for y in section_bottom..section_bottom + 16 {
+            for (z, row) in heights.iter().enumerate() {
+                for (x, height) in row.iter().enumerate() {
+                    let block_x = coord.x * 16 + x as i32;
+                    let block_z = coord.z * 16 + z as i32;
+                    indices.push(self.block_at(block_x, y, block_z, *height));
+                }
+            }
+        }
+
+        let block_count = indices.iter().filter(|index| **index != AIR).count();
+
+        let blocks = if indices.iter().all(|index| *index == indices[0]) {
+            mc_chunk::PalettedData::Single {
+                value: self.palette[indices[0] as usize].id(),
+            }
+        } else {
+            mc_chunk::PalettedData::Paletted {
+                palette: self.palette.iter().map(|block| block.id()).collect(),
+                indexed: indices,
+            }
+        };
+
+        mc_chunk::Chunk {
+            block_count: block_count as i16,
+            blocks,
+            biomes,
+        }
+    }
+
+    fn block_at(&self, x: i32, y: i32, z: i32, height: i32) -> u8 {
+        if y == 0 {
+            return BEDROCK;
+        }
+
+        if y > height {
+            return if y <= self.settings.sea_level {
+                WATER
+            } else {
+                AIR
+            };
+        }
+
+        // keep a few blocks below the surface, so that caves do not open up under water
+        if self.settings.generate_caves && y < height - 4 && self.is_cave(x, y, z) {
+            return AIR;
+        }
+
+        let is_beach = height <= self.settings.sea_level + 1;
+        if y == height {
+            return if is_beach { SAND } else { GRASS };
+        }
+        if y >= height - self.settings.dirt_depth {
+            return if is_beach { SAND } else { DIRT };
+        }
+
+        STONE
+    }
+
+    fn is_cave(&self, x: i32, y: i32, z: i32) -> bool {
+        // caves are stretched horizontally
+        let noise = self.cave_noise.fractal_3d(
+            x as f64 / 32.0,
+            y as f64 / 16.0,
+            z as f64 / 32.0,
+            CAVE_OCTAVES,
+        );
+
+        // a thin band around 0 gives winding tunnels
+        noise.abs() < 0.04
+    }
+
+    // biomes are stored per 4x4x4 blocks; we decide per 4x4 pillar, using the height at its center
+    fn biomes(&self, heights: &[[i32; 16]; 16]) -> mc_chunk::PalettedData {
+        let mut pillars = [[0u8; 4]; 4];
+        for (z4, row) in pillars.iter_mut().enumerate() {
+            for (x4, pillar) in row.iter_mut().enumerate() {
+                let height = heights[z4 * 4 + 2][x4 * 4 + 2];
+                // palette index 1 is ocean, see below
+                *pillar = if height < self.settings.sea_level { 1 } else { 0 };
+            }
+        }
+
+        let flat: Vec<u8> = pillars.iter().flatten().copied().collect();
+        if flat.iter().all(|index| *index == flat[0]) {
+            let biome = if flat[0] == 1 { biome::OCEAN } else { biome::PLAINS };
+            return mc_chunk::PalettedData::Single {
+                value: biome as u32,
+            };
+        }
+
+        // the same 4x4 layer for each of the 4 layers in the section, indexed [y][z][x]
+        let indexed = (0..4).flat_map(|_| flat.iter().copied()).collect();
+        mc_chunk::PalettedData::Paletted {
+            palette: vec![biome::PLAINS as u32, biome::OCEAN as u32],
+            indexed,
+        }
+    }
+}
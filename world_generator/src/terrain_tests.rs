@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::ids::blocks::BlockId;
+    use minecraft_vanilla::registries::Registries;
+    use sol_voxel_lib::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+    use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate};
+
+    use crate::terrain::{TerrainGenerator, TerrainSettings};
+
+    const WORLD_HEIGHT: i32 = NUM_CHUNK_SECTIONS_PER_COLUMN as i32 * 16;
+
+    // a flat surface at y = 20, so that every pillar has the same layers
+    fn flat_settings(sea_level: i32) -> TerrainSettings {
+        TerrainSettings {
+            sea_level,
+            base_height: 20,
+            height_variation: 0.0,
+            horizontal_scale: 64.0,
+            dirt_depth: 3,
+            generate_caves: false,
+        }
+    }
+
+    fn generate(
+        generator: &TerrainGenerator,
+        coord: ChunkColumnCoordinate,
+        registries: &Registries,
+    ) -> ChunkColumn {
+        generator.generate_column(
+            coord,
+            registries.block_properties(),
+            registries.block_states(),
+        )
+    }
+
+    fn block_at(column: &ChunkColumn, x: i32, y: i32, z: i32) -> u32 {
+        let coord = column.coordinate();
+        column
+            .get_voxel(Coordinate::new(coord.x * 16 + x, y, coord.z * 16 + z))
+            .unwrap()
+            .get_block()
+            .id()
+    }
+
+    fn id(block: BlockId) -> u32 {
+        BlockWithState::from(block).id()
+    }
+
+    // the block of every y in the pillar at (x, z), bottom to top
+    fn pillar(column: &ChunkColumn, x: i32, z: i32) -> Vec<u32> {
+        (0..WORLD_HEIGHT)
+            .map(|y| block_at(column, x, y, z))
+            .collect()
+    }
+
+    #[test]
+    fn test_deterministic_per_seed() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let coord = ChunkColumnCoordinate { x: -3, z: 5 };
+
+        let column_a = generate(
+            &TerrainGenerator::new(42, TerrainSettings::default()),
+            coord,
+            &registries,
+        );
+        let column_b = generate(
+            &TerrainGenerator::new(42, TerrainSettings::default()),
+            coord,
+            &registries,
+        );
+
+        for z in 0..16 {
+            for x in 0..16 {
+                assert_eq!(pillar(&column_a, x, z), pillar(&column_b, x, z));
+            }
+        }
+    }
+
+    #[test]
+    fn test_seed_changes_terrain() {
+        let generator_a = TerrainGenerator::new(1, TerrainSettings::default());
+        let generator_b = TerrainGenerator::new(2, TerrainSettings::default());
+
+        let num_different = (0..100)
+            .filter(|i| {
+                generator_a.surface_height(i * 7, i * 3) != generator_b.surface_height(i * 7, i * 3)
+            })
+            .count();
+        assert!(num_different > 50);
+    }
+
+    #[test]
+    fn test_layers_above_sea_level() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let generator = TerrainGenerator::new(7, flat_settings(10));
+        let column = generate(
+            &generator,
+            ChunkColumnCoordinate { x: 1, z: -2 },
+            &registries,
+        );
+
+        for (x, z) in [(0, 0), (7, 3), (15, 15)] {
+            let pillar = pillar(&column, x, z);
+            assert_eq!(pillar[0], id(BlockId::Bedrock));
+            assert!(pillar[1..=16]
+                .iter()
+                .all(|block| *block == id(BlockId::Stone)));
+            assert!(pillar[17..=19]
+                .iter()
+                .all(|block| *block == id(BlockId::Dirt)));
+            assert_eq!(pillar[20], id(BlockId::GrassBlock));
+            assert!(pillar[21..].iter().all(|block| *block == id(BlockId::Air)));
+        }
+    }
+
+    #[test]
+    fn test_layers_below_sea_level() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let generator = TerrainGenerator::new(7, flat_settings(30));
+        let column = generate(
+            &generator,
+            ChunkColumnCoordinate { x: 0, z: 0 },
+            &registries,
+        );
+
+        let pillar = pillar(&column, 4, 9);
+        assert_eq!(pillar[0], id(BlockId::Bedrock));
+        assert!(pillar[1..=16]
+            .iter()
+            .all(|block| *block == id(BlockId::Stone)));
+        // the surface under water is sand instead of grass and dirt
+        assert!(pillar[17..=20]
+            .iter()
+            .all(|block| *block == id(BlockId::Sand)));
+        assert!(pillar[21..=30]
+            .iter()
+            .all(|block| *block == id(BlockId::Water)));
+        assert!(pillar[31..].iter().all(|block| *block == id(BlockId::Air)));
+    }
+}
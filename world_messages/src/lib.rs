@@ -15,6 +15,8 @@ pub enum WorldServerReq {
     ContentChunk16(Coordinate16),
     ContentChunkColumn(ChunkColumnCoordinate),
     SetVoxel(Coordinate, Voxel),
+    /// Changes that are applied in order, as if sent one by one with `SetVoxel`
+    SetVoxels(Vec<(Coordinate, Voxel)>),
 }
 
 pub const CONNECTION_NAME_WORLD_SERVER_REP: &str = "WorldServerReply";
@@ -26,5 +28,7 @@ pub enum WorldServerRep {
     ContentChunkColumn(ChunkColumnCoordinate, Box<ChunkColumn>),
     SetVoxelAcknowledged(Coordinate),
     SetVoxelDenied(Coordinate),
+    /// The reply to `SetVoxels`: the changes that were denied; all others were applied
+    SetVoxelsDenied(Vec<Coordinate>),
     Empty,
 }
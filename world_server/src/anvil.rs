@@ -0,0 +1,237 @@
+// Conversion between vanilla anvil (.mca) region files and our own chunk columns.
+// See https://minecraft.wiki/w/Region_file_format and https://minecraft.wiki/w/Chunk_format
+
+pub mod export;
+pub mod import;
+pub mod region;
+
+use std::collections::HashMap;
+use std::io;
+
+use minecraft_protocol::nbt::{self, NbtList, NbtTag};
+use sol_voxel_lib::voxel_errors::PaletteIndexError;
+
+use crate::region_file::StorageError;
+
+/// the `Y` of the lowest section in an overworld anvil chunk.
+/// Our chunk columns start counting sections at 0.
+pub const MIN_SECTION_Y: i32 = -4;
+
+/// the data version of minecraft 1.20.2, the version that our protocol implements
+pub const DATA_VERSION: i32 = 3578;
+
+/// block palettes always use at least 4 bits per entry
+pub const MIN_BITS_PER_BLOCK: u32 = 4;
+
+const NBT_COMPOUND_ID: u8 = 0x0A;
+
+#[derive(Debug)]
+pub enum AnvilError {
+    IoError(io::Error),
+    StorageError(StorageError),
+    UnknownCompression(u8),
+    MalformedNbt(String),
+    UnknownBlockState(String),
+    UnknownBiome(String),
+    UnknownBlockStateId(u32),
+    PaletteIndexError(PaletteIndexError),
+    ChunkTooLarge,
+}
+
+impl From<io::Error> for AnvilError {
+    fn from(value: io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+impl From<PaletteIndexError> for AnvilError {
+    fn from(value: PaletteIndexError) -> Self {
+        Self::PaletteIndexError(value)
+    }
+}
+
+impl From<StorageError> for AnvilError {
+    fn from(value: StorageError) -> Self {
+        Self::StorageError(value)
+    }
+}
+
+impl std::fmt::Display for AnvilError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            AnvilError::IoError(error) => write!(formatter, "IO error: {error}"),
+            AnvilError::StorageError(error) => write!(formatter, "{error}"),
+            AnvilError::UnknownCompression(id) => {
+                write!(formatter, "Unknown compression type {id}")
+            },
+            AnvilError::MalformedNbt(reason) => write!(formatter, "Malformed NBT: {reason}"),
+            AnvilError::UnknownBlockState(name) => write!(formatter, "Unknown block {name}"),
+            AnvilError::UnknownBiome(name) => write!(formatter, "Unknown biome {name}"),
+            AnvilError::UnknownBlockStateId(id) => write!(formatter, "Unknown block state {id}"),
+            AnvilError::PaletteIndexError(error) => write!(formatter, "{error}"),
+            AnvilError::ChunkTooLarge => {
+                write!(formatter, "Chunk does not fit in a region file")
+            },
+        }
+    }
+}
+
+/// Anvil files store the root compound with a (usually empty) name, which the network format
+/// omits. This strips the name, and parses the remainder like the network format.
+pub fn parse_named_nbt(bytes: &[u8]) -> Result<NbtTag, AnvilError> {
+    if bytes.len() < 3 || bytes[0] != NBT_COMPOUND_ID {
+        return Err(AnvilError::MalformedNbt(String::from(
+            "root tag is not a compound",
+        )));
+    }
+
+    let name_length = u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+    if 3 + name_length > bytes.len() {
+        return Err(AnvilError::MalformedNbt(String::from(
+            "root name is longer than the data",
+        )));
+    }
+    let mut unnamed = Vec::with_capacity(bytes.len() - 2 - name_length);
+    unnamed.push(NBT_COMPOUND_ID);
+    unnamed.extend_from_slice(&bytes[3 + name_length..]);
+
+    nbt::parse_nbt(&unnamed)
+        .map(|(tag, _)| tag)
+        .map_err(|error| AnvilError::MalformedNbt(error.to_string()))
+}
+
+/// Writes the tag with an empty root name, as anvil files expect
+pub fn serialize_named_nbt(tag: &NbtTag) -> Vec<u8> {
+    let mut unnamed = Vec::new();
+    tag.serialize(&mut unnamed);
+
+    let mut named = Vec::with_capacity(unnamed.len() + 2);
+    named.push(unnamed[0]);
+    named.extend_from_slice(&0u16.to_be_bytes());
+    named.extend_from_slice(&unnamed[1..]);
+    named
+}
+
+/// number of bits required to index a palette of the given length
+pub fn bits_for_palette(palette_length: usize) -> u32 {
+    if palette_length <= 1 {
+        return 0;
+    }
+    usize::BITS - (palette_length - 1).leading_zeros()
+}
+
+/// Reads `num_values` values of `bits_per_value` bits each.
+/// Since 1.16, a value never spans two longs.
+pub fn unpack_long_array(data: &[i64], bits_per_value: u32, num_values: usize) -> Vec<u32> {
+    if bits_per_value == 0 {
+        return vec![0; num_values];
+    }
+
+    let values_per_long = (u64::BITS / bits_per_value) as usize;
+    let mask: u64 = (1 << bits_per_value) - 1;
+
+    (0..num_values)
+        .map(|i| {
+            let long = data.get(i / values_per_long).copied().unwrap_or(0) as u64;
+            let shift = (i % values_per_long) as u32 * bits_per_value;
+            ((long >> shift) & mask) as u32
+        })
+        .collect()
+}
+
+/// The inverse of `unpack_long_array`
+pub fn pack_long_array(values: &[u32], bits_per_value: u32) -> Vec<i64> {
+    if bits_per_value == 0 {
+        return Vec::new();
+    }
+
+    let values_per_long = (u64::BITS / bits_per_value) as usize;
+
+    values
+        .chunks(values_per_long)
+        .map(|chunk| {
+            let mut long: u64 = 0;
+            for (i, value) in chunk.iter().enumerate() {
+                long |= (*value as u64) << (i as u32 * bits_per_value);
+            }
+            long as i64
+        })
+        .collect()
+}
+
+pub(crate) fn get_tag<'a>(
+    compound: &'a HashMap<String, NbtTag>,
+    key: &str,
+) -> Result<&'a NbtTag, AnvilError> {
+    compound
+        .get(key)
+        .ok_or_else(|| AnvilError::MalformedNbt(format!("missing tag '{key}'")))
+}
+
+pub(crate) fn as_compound(tag: &NbtTag) -> Result<&HashMap<String, NbtTag>, AnvilError> {
+    match tag {
+        NbtTag::Compound(compound) => Ok(compound),
+        _ => Err(AnvilError::MalformedNbt(String::from("expected a compound"))),
+    }
+}
+
+pub(crate) fn get_compound<'a>(
+    compound: &'a HashMap<String, NbtTag>,
+    key: &str,
+) -> Result<&'a HashMap<String, NbtTag>, AnvilError> {
+    as_compound(get_tag(compound, key)?)
+}
+
+pub(crate) fn get_int(compound: &HashMap<String, NbtTag>, key: &str) -> Result<i32, AnvilError> {
+    match get_tag(compound, key)? {
+        NbtTag::Byte(value) => Ok(*value as i32),
+        NbtTag::Short(value) => Ok(*value as i32),
+        NbtTag::Int(value) => Ok(*value),
+        _ => Err(AnvilError::MalformedNbt(format!("'{key}' is not an integer"))),
+    }
+}
+
+pub(crate) fn get_string<'a>(
+    compound: &'a HashMap<String, NbtTag>,
+    key: &str,
+) -> Result<&'a String, AnvilError> {
+    match get_tag(compound, key)? {
+        NbtTag::String(value) => Ok(value),
+        _ => Err(AnvilError::MalformedNbt(format!("'{key}' is not a string"))),
+    }
+}
+
+/// a missing long array is returned as empty; palettes of one element have no data array
+pub(crate) fn get_long_array<'a>(compound: &'a HashMap<String, NbtTag>, key: &str) -> &'a [i64] {
+    match compound.get(key) {
+        Some(NbtTag::LongArray(values)) => values,
+        _ => &[],
+    }
+}
+
+/// a missing or empty list is returned as empty
+pub(crate) fn get_compound_list<'a>(
+    compound: &'a HashMap<String, NbtTag>,
+    key: &str,
+) -> Result<&'a [HashMap<String, NbtTag>], AnvilError> {
+    match compound.get(key) {
+        None | Some(NbtTag::List(NbtList::None)) => Ok(&[]),
+        Some(NbtTag::List(NbtList::Compound(list))) => Ok(list),
+        _ => Err(AnvilError::MalformedNbt(format!(
+            "'{key}' is not a list of compounds"
+        ))),
+    }
+}
+
+pub(crate) fn get_string_list<'a>(
+    compound: &'a HashMap<String, NbtTag>,
+    key: &str,
+) -> Result<&'a [String], AnvilError> {
+    match compound.get(key) {
+        None | Some(NbtTag::List(NbtList::None)) => Ok(&[]),
+        Some(NbtTag::List(NbtList::String(list))) => Ok(list),
+        _ => Err(AnvilError::MalformedNbt(format!(
+            "'{key}' is not a list of strings"
+        ))),
+    }
+}
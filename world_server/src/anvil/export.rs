@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use minecraft_protocol::components::{blocks as mc_blocks, chunk as mc_chunk};
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_protocol::nbt::{NbtList, NbtTag};
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use sol_voxel_lib::biome;
+use sol_voxel_lib::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+
+use super::region::{AnvilRegionWriter, ANVIL_REGION_SIZE};
+use super::{
+    bits_for_palette, pack_long_array, AnvilError, DATA_VERSION, MIN_BITS_PER_BLOCK, MIN_SECTION_Y,
+};
+use crate::chunk_storage::ChunkStorage;
+use crate::region_file::{RegionCoordinate, REGION_SIZE};
+
+/// Writes every stored column of the given region to an anvil region file.
+/// Returns the number of exported chunks; no file is written if the region has no columns.
+pub fn export_region(
+    region: RegionCoordinate,
+    storage: &mut ChunkStorage,
+    block_states: &BlockStateRegistry,
+    path: &Path,
+) -> Result<usize, AnvilError> {
+    // both formats group the same number of chunks per region
+    debug_assert_eq!(REGION_SIZE as usize, ANVIL_REGION_SIZE);
+
+    let mut writer = AnvilRegionWriter::new();
+    let mut num_exported = 0;
+
+    for local_z in 0..ANVIL_REGION_SIZE {
+        for local_x in 0..ANVIL_REGION_SIZE {
+            let coord = ChunkColumnCoordinate {
+                x: region.x * REGION_SIZE + local_x as i32,
+                z: region.z * REGION_SIZE + local_z as i32,
+            };
+
+            let was_loaded = storage.is_loaded(&coord);
+            let Some(column) = storage.get_column(&coord)? else {
+                continue;
+            };
+
+            let chunk_nbt = chunk_column_to_anvil(column, block_states)?;
+            writer.set_chunk(local_x, local_z, &chunk_nbt)?;
+            num_exported += 1;
+
+            // do not keep the whole world in memory
+            if !was_loaded {
+                storage.unload_column(&coord);
+            }
+        }
+    }
+
+    if !writer.is_empty() {
+        writer.write(path)?;
+    }
+
+    Ok(num_exported)
+}
+
+/// Converts a column into the nbt of a fully generated anvil chunk.
+/// Light is not exported; the chunk is marked such that the vanilla server recomputes it.
+pub fn chunk_column_to_anvil(
+    column: &ChunkColumn,
+    block_states: &BlockStateRegistry,
+) -> Result<NbtTag, AnvilError> {
+    let coord = column.coordinate();
+
+    let mut sections = Vec::with_capacity(NUM_CHUNK_SECTIONS_PER_COLUMN);
+    let mut block_entities = Vec::new();
+    for y_16 in 0..NUM_CHUNK_SECTIONS_PER_COLUMN as i32 {
+        let chunk = column
+            .get_chunk(y_16)
+            .expect("every column has all of its sections");
+        let (mc_chunk, _, section_block_entities) = chunk.to_minecraft();
+
+        sections.push(section_to_anvil(
+            &mc_chunk,
+            y_16 + MIN_SECTION_Y,
+            block_states,
+        )?);
+
+        block_entities.extend(
+            section_block_entities
+                .iter()
+                .filter_map(|block_entity| block_entity_to_anvil(block_entity, coord)),
+        );
+    }
+
+    let (motion_blocking, world_surface) = column.heightmaps_to_minecraft();
+    let mut heightmaps = HashMap::new();
+    heightmaps.insert(
+        String::from("MOTION_BLOCKING"),
+        NbtTag::LongArray(motion_blocking),
+    );
+    heightmaps.insert(
+        String::from("WORLD_SURFACE"),
+        NbtTag::LongArray(world_surface),
+    );
+
+    let mut root = HashMap::new();
+    root.insert(String::from("DataVersion"), NbtTag::Int(DATA_VERSION));
+    root.insert(String::from("xPos"), NbtTag::Int(coord.x));
+    root.insert(String::from("zPos"), NbtTag::Int(coord.z));
+    root.insert(String::from("yPos"), NbtTag::Int(MIN_SECTION_Y));
+    root.insert(
+        String::from("Status"),
+        NbtTag::String(String::from("minecraft:full")),
+    );
+    root.insert(String::from("LastUpdate"), NbtTag::Long(0));
+    root.insert(String::from("InhabitedTime"), NbtTag::Long(0));
+    root.insert(String::from("isLightOn"), NbtTag::Byte(0));
+    root.insert(
+        String::from("sections"),
+        NbtTag::List(NbtList::Compound(sections)),
+    );
+    root.insert(
+        String::from("block_entities"),
+        NbtTag::List(NbtList::Compound(block_entities)),
+    );
+    root.insert(String::from("Heightmaps"), NbtTag::Compound(heightmaps));
+
+    Ok(NbtTag::Compound(root))
+}
+
+fn section_to_anvil(
+    mc_chunk: &mc_chunk::Chunk,
+    section_y: i32,
+    block_states: &BlockStateRegistry,
+) -> Result<HashMap<String, NbtTag>, AnvilError> {
+    let (block_palette, block_indices) = to_palette(&mc_chunk.blocks);
+    let block_palette_nbt = block_palette
+        .iter()
+        .map(|id| block_state_to_anvil(*id, block_states))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut block_states_tag = HashMap::new();
+    block_states_tag.insert(
+        String::from("palette"),
+        NbtTag::List(NbtList::Compound(block_palette_nbt)),
+    );
+    if block_palette.len() > 1 {
+        let bits_per_block = u32::max(MIN_BITS_PER_BLOCK, bits_for_palette(block_palette.len()));
+        block_states_tag.insert(
+            String::from("data"),
+            NbtTag::LongArray(pack_long_array(&block_indices, bits_per_block)),
+        );
+    }
+
+    let (biome_palette, biome_indices) = to_palette(&mc_chunk.biomes);
+    let biome_palette_nbt = biome_palette
+        .iter()
+        .map(|id| {
+            biome::biome_name(*id as biome::BiomeId)
+                .map(String::from)
+                .ok_or_else(|| AnvilError::UnknownBiome(id.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut biomes_tag = HashMap::new();
+    biomes_tag.insert(
+        String::from("palette"),
+        NbtTag::List(NbtList::String(biome_palette_nbt)),
+    );
+    if biome_palette.len() > 1 {
+        biomes_tag.insert(
+            String::from("data"),
+            NbtTag::LongArray(pack_long_array(
+                &biome_indices,
+                bits_for_palette(biome_palette.len()),
+            )),
+        );
+    }
+
+    let mut section = HashMap::new();
+    section.insert(String::from("Y"), NbtTag::Byte(section_y as i8));
+    section.insert(
+        String::from("block_states"),
+        NbtTag::Compound(block_states_tag),
+    );
+    section.insert(String::from("biomes"), NbtTag::Compound(biomes_tag));
+
+    Ok(section)
+}
+
+// returns the palette, and the palette index of every element.
+// For single values, the index list is empty
+fn to_palette(data: &mc_chunk::PalettedData) -> (Vec<u32>, Vec<u32>) {
+    match data {
+        mc_chunk::PalettedData::Single { value } => (vec![*value], Vec::new()),
+        mc_chunk::PalettedData::Paletted { palette, indexed } => (
+            palette.clone(),
+            indexed.iter().map(|index| *index as u32).collect(),
+        ),
+        mc_chunk::PalettedData::Raw { values } => {
+            let mut palette: Vec<u32> = Vec::new();
+            let mut palette_indices: HashMap<u32, u32> = HashMap::new();
+
+            let indices = values
+                .iter()
+                .map(|value| {
+                    *palette_indices.entry(*value).or_insert_with(|| {
+                        palette.push(*value);
+                        (palette.len() - 1) as u32
+                    })
+                })
+                .collect();
+
+            (palette, indices)
+        },
+    }
+}
+
+fn block_state_to_anvil(
+    id: u32,
+    block_states: &BlockStateRegistry,
+) -> Result<HashMap<String, NbtTag>, AnvilError> {
+    let (name, properties) = block_states
+        .get_name_and_properties(BlockWithState::from_id(id))
+        .ok_or(AnvilError::UnknownBlockStateId(id))?;
+
+    let mut entry = HashMap::new();
+    entry.insert(String::from("Name"), NbtTag::String(name));
+
+    if !properties.is_empty() {
+        let properties = properties
+            .into_iter()
+            .map(|(key, value)| (key, NbtTag::String(value)))
+            .collect();
+        entry.insert(String::from("Properties"), NbtTag::Compound(properties));
+    }
+
+    Ok(entry)
+}
+
+// block entities without compound data cannot be represented, as they lack an id
+fn block_entity_to_anvil(
+    block_entity: &mc_blocks::BlockEntity,
+    column: ChunkColumnCoordinate,
+) -> Option<HashMap<String, NbtTag>> {
+    let NbtTag::Compound(data) = block_entity.data() else {
+        return None;
+    };
+
+    let mut tag = data.clone();
+    tag.insert(
+        String::from("x"),
+        NbtTag::Int(column.x * 16 + block_entity.x() as i32),
+    );
+    // our y coordinates start at 0 at the bottom of the column
+    tag.insert(
+        String::from("y"),
+        NbtTag::Int(block_entity.y() + MIN_SECTION_Y * 16),
+    );
+    tag.insert(
+        String::from("z"),
+        NbtTag::Int(column.z * 16 + block_entity.z() as i32),
+    );
+    tag.insert(String::from("keepPacked"), NbtTag::Byte(0));
+
+    Some(tag)
+}
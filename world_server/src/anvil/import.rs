@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use minecraft_protocol::components::{blocks as mc_blocks, chunk as mc_chunk};
+use minecraft_protocol::data::block_states::BlockWithState;
+use minecraft_protocol::nbt::NbtTag;
+use minecraft_registries::block_property_registry::BlockPropertyRegistry;
+use minecraft_registries::block_state_registry::BlockStateRegistry;
+use sol_voxel_lib::biome;
+use sol_voxel_lib::chunk16::Chunk16;
+use sol_voxel_lib::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+use sol_voxel_lib::vector_alias::Coordinate16;
+
+use super::region::{AnvilRegion, ANVIL_REGION_SIZE};
+use super::{
+    as_compound, bits_for_palette, get_compound, get_compound_list, get_int, get_long_array,
+    get_string, get_string_list, unpack_long_array, AnvilError, MIN_BITS_PER_BLOCK, MIN_SECTION_Y,
+};
+use crate::chunk_storage::ChunkStorage;
+
+const BLOCKS_PER_SECTION: usize = 16 * 16 * 16;
+const BIOMES_PER_SECTION: usize = 4 * 4 * 4;
+
+#[derive(Default)]
+pub struct ImportSummary {
+    pub num_imported: usize,
+    // chunks that are not fully generated
+    pub num_skipped: usize,
+    pub failed: Vec<(i32, i32, AnvilError)>,
+}
+
+/// Converts every fully generated chunk in the given anvil region file, and stores it.
+/// Chunks that cannot be converted are reported in the summary, and do not stop the import.
+pub fn import_region(
+    path: &Path,
+    storage: &mut ChunkStorage,
+    block_properties: &BlockPropertyRegistry,
+    block_states: &BlockStateRegistry,
+) -> Result<ImportSummary, AnvilError> {
+    let mut region = AnvilRegion::open(path)?;
+    let mut summary = ImportSummary::default();
+
+    for local_z in 0..ANVIL_REGION_SIZE {
+        for local_x in 0..ANVIL_REGION_SIZE {
+            let chunk_nbt = match region.read_chunk(local_x, local_z) {
+                Ok(Some(chunk_nbt)) => chunk_nbt,
+                Ok(None) => continue,
+                Err(error) => {
+                    summary
+                        .failed
+                        .push((local_x as i32, local_z as i32, error));
+                    continue;
+                },
+            };
+
+            match chunk_column_from_anvil(&chunk_nbt, block_properties, block_states) {
+                Ok(Some(column)) => {
+                    let coord = column.coordinate();
+                    storage.store_column(column)?;
+                    // do not keep the whole world in memory
+                    storage.unload_column(&coord);
+                    summary.num_imported += 1;
+                },
+                Ok(None) => summary.num_skipped += 1,
+                Err(error) => {
+                    summary
+                        .failed
+                        .push((local_x as i32, local_z as i32, error));
+                },
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Converts the nbt of one anvil chunk. Returns `None` if the chunk is not fully generated.
+pub fn chunk_column_from_anvil(
+    chunk_nbt: &NbtTag,
+    block_properties: &BlockPropertyRegistry,
+    block_states: &BlockStateRegistry,
+) -> Result<Option<ChunkColumn>, AnvilError> {
+    let root = as_compound(chunk_nbt)?;
+
+    // proto-chunks only have part of their terrain
+    let status = get_string(root, "Status")?;
+    if status != "minecraft:full" && status != "full" {
+        return Ok(None);
+    }
+
+    let chunk_x = get_int(root, "xPos")?;
+    let chunk_z = get_int(root, "zPos")?;
+    let mut column = ChunkColumn::new(chunk_x, chunk_z);
+
+    // block entities are stored per chunk, but we store them per section
+    let mut block_entity_tags: HashMap<i32, Vec<&HashMap<String, NbtTag>>> = HashMap::new();
+    for tag in get_compound_list(root, "block_entities")? {
+        let section_y = get_int(tag, "y")?.div_euclid(16);
+        block_entity_tags.entry(section_y).or_default().push(tag);
+    }
+
+    for section in get_compound_list(root, "sections")? {
+        let section_y = get_int(section, "Y")?;
+        let y_16 = section_y - MIN_SECTION_Y;
+        if y_16 < 0 || y_16 >= NUM_CHUNK_SECTIONS_PER_COLUMN as i32 {
+            // sections outside the world only carry light data
+            continue;
+        }
+
+        if !section.contains_key("block_states") {
+            continue;
+        }
+
+        let (mc_chunk, blocks) = section_from_anvil(section, block_properties, block_states)?;
+
+        let block_entities = block_entity_tags
+            .get(&section_y)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|tag| block_entity_from_anvil(tag, &blocks))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let chunk = Chunk16::from_minecraft(
+            &mc_chunk,
+            Coordinate16::new(chunk_x, y_16, chunk_z),
+            block_entities,
+            block_properties,
+            block_states,
+        )?;
+        column.set_chunk(y_16, chunk, block_properties, block_states);
+    }
+
+    if let Ok(heightmaps) = get_compound(root, "Heightmaps") {
+        let motion_blocking = get_long_array(heightmaps, "MOTION_BLOCKING");
+        let world_surface = get_long_array(heightmaps, "WORLD_SURFACE");
+
+        // older chunks may lack these; then we keep the heightmaps computed by set_chunk
+        if !motion_blocking.is_empty() && !world_surface.is_empty() {
+            column.set_heightmaps_from_minecraft(motion_blocking, world_surface);
+        }
+    }
+
+    Ok(Some(column))
+}
+
+// returns the section in protocol format, and the block state of every block in [y][z][x] order
+fn section_from_anvil(
+    section: &HashMap<String, NbtTag>,
+    block_properties: &BlockPropertyRegistry,
+    block_states: &BlockStateRegistry,
+) -> Result<(mc_chunk::Chunk, Vec<BlockWithState>), AnvilError> {
+    let block_states_tag = get_compound(section, "block_states")?;
+
+    let palette = get_compound_list(block_states_tag, "palette")?
+        .iter()
+        .map(|entry| block_state_from_anvil(entry, block_states))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if palette.is_empty() {
+        return Err(AnvilError::MalformedNbt(String::from(
+            "empty block palette",
+        )));
+    }
+
+    let bits_per_block = u32::max(MIN_BITS_PER_BLOCK, bits_for_palette(palette.len()));
+    let indices = if palette.len() == 1 {
+        vec![0; BLOCKS_PER_SECTION]
+    } else {
+        unpack_long_array(
+            get_long_array(block_states_tag, "data"),
+            bits_per_block,
+            BLOCKS_PER_SECTION,
+        )
+    };
+
+    if indices.iter().any(|index| *index as usize >= palette.len()) {
+        return Err(AnvilError::MalformedNbt(String::from(
+            "block index outside of the palette",
+        )));
+    }
+
+    let blocks: Vec<BlockWithState> = indices.iter().map(|i| palette[*i as usize]).collect();
+
+    let block_count = blocks
+        .iter()
+        .filter(|block| {
+            let block = block_states.block_state_to_block(**block);
+            !block_properties.get_block_properties(block).is_air
+        })
+        .count();
+
+    let block_data = if palette.len() == 1 {
+        mc_chunk::PalettedData::Single {
+            value: palette[0].id(),
+        }
+    } else if palette.len() <= (u8::MAX as usize) + 1 {
+        mc_chunk::PalettedData::Paletted {
+            palette: palette.iter().map(|block| block.id()).collect(),
+            indexed: indices.iter().map(|index| *index as u8).collect(),
+        }
+    } else {
+        mc_chunk::PalettedData::Raw {
+            values: blocks.iter().map(|block| block.id()).collect(),
+        }
+    };
+
+    let biome_data = match get_compound(section, "biomes") {
+        Ok(biomes) => biomes_from_anvil(biomes)?,
+        Err(_) => mc_chunk::PalettedData::Single {
+            value: biome::PLAINS as u32,
+        },
+    };
+
+    let mc_chunk = mc_chunk::Chunk {
+        block_count: block_count as i16,
+        blocks: block_data,
+        biomes: biome_data,
+    };
+
+    Ok((mc_chunk, blocks))
+}
+
+fn biomes_from_anvil(biomes: &HashMap<String, NbtTag>) -> Result<mc_chunk::PalettedData, AnvilError> {
+    let palette = get_string_list(biomes, "palette")?
+        .iter()
+        .map(|name| {
+            biome::biome_from_name(name)
+                .map(|id| id as u32)
+                .ok_or_else(|| AnvilError::UnknownBiome(name.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match palette.len() {
+        0 => Err(AnvilError::MalformedNbt(String::from(
+            "empty biome palette",
+        ))),
+        1 => Ok(mc_chunk::PalettedData::Single { value: palette[0] }),
+        _ => {
+            let indices = unpack_long_array(
+                get_long_array(biomes, "data"),
+                bits_for_palette(palette.len()),
+                BIOMES_PER_SECTION,
+            );
+
+            if indices.iter().any(|index| *index as usize >= palette.len()) {
+                return Err(AnvilError::MalformedNbt(String::from(
+                    "biome index outside of the palette",
+                )));
+            }
+
+            Ok(mc_chunk::PalettedData::Paletted {
+                palette,
+                indexed: indices.iter().map(|index| *index as u8).collect(),
+            })
+        },
+    }
+}
+
+fn block_state_from_anvil(
+    entry: &HashMap<String, NbtTag>,
+    block_states: &BlockStateRegistry,
+) -> Result<BlockWithState, AnvilError> {
+    let name = get_string(entry, "Name")?;
+
+    let mut properties = HashMap::new();
+    if let Ok(property_tags) = get_compound(entry, "Properties") {
+        for (key, value) in property_tags {
+            if let NbtTag::String(value) = value {
+                properties.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    block_states
+        .get_block_state(name, &properties)
+        .ok_or_else(|| AnvilError::UnknownBlockState(name.clone()))
+}
+
+fn block_entity_from_anvil(
+    tag: &HashMap<String, NbtTag>,
+    blocks: &[BlockWithState],
+) -> Result<mc_blocks::BlockEntity, AnvilError> {
+    let x = get_int(tag, "x")?;
+    let z = get_int(tag, "z")?;
+    // our y coordinates start at 0 at the bottom of the column
+    let column_y = get_int(tag, "y")? - MIN_SECTION_Y * 16;
+
+    let relative_x = x.rem_euclid(16) as usize;
+    let relative_y = column_y.rem_euclid(16) as usize;
+    let relative_z = z.rem_euclid(16) as usize;
+    let block = blocks[(relative_y * 16 + relative_z) * 16 + relative_x];
+
+    // the position is implied by where we store it
+    let mut data = tag.clone();
+    data.remove("x");
+    data.remove("y");
+    data.remove("z");
+
+    Ok(mc_blocks::BlockEntity::new(
+        relative_x as u8,
+        column_y,
+        relative_z as u8,
+        block,
+        NbtTag::Compound(data),
+    ))
+}
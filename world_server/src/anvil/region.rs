@@ -0,0 +1,186 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use minecraft_protocol::nbt::NbtTag;
+
+use super::{parse_named_nbt, serialize_named_nbt, AnvilError};
+use crate::region_file::RegionCoordinate;
+
+/// number of chunks along one side of an anvil region
+pub const ANVIL_REGION_SIZE: usize = 32;
+const SECTOR_SIZE: u64 = 4096;
+
+const COMPRESSION_GZIP: u8 = 1;
+const COMPRESSION_ZLIB: u8 = 2;
+const COMPRESSION_NONE: u8 = 3;
+
+pub struct AnvilRegion {
+    file: File,
+    // one entry per chunk: the sector offset in the upper 3 bytes, the sector count in the lowest
+    locations: Vec<u32>,
+}
+
+impl AnvilRegion {
+    pub fn open(path: &Path) -> Result<AnvilRegion, AnvilError> {
+        let mut file = File::open(path)?;
+        let mut locations = vec![0; ANVIL_REGION_SIZE * ANVIL_REGION_SIZE];
+
+        // an empty file is a valid region without chunks
+        if file.metadata()?.len() >= SECTOR_SIZE {
+            let mut header = [0u8; SECTOR_SIZE as usize];
+            file.read_exact(&mut header)?;
+
+            for (idx, location) in locations.iter_mut().enumerate() {
+                let bytes = [
+                    header[idx * 4],
+                    header[idx * 4 + 1],
+                    header[idx * 4 + 2],
+                    header[idx * 4 + 3],
+                ];
+                *location = u32::from_be_bytes(bytes);
+            }
+        }
+
+        Ok(AnvilRegion { file, locations })
+    }
+
+    pub fn file_name(region_x: i32, region_z: i32) -> String {
+        format!("r.{region_x}.{region_z}.mca")
+    }
+
+    /// parses the region coordinates from a file name like `r.-1.2.mca`
+    pub fn coordinate_from_file_name(file_name: &str) -> Option<(i32, i32)> {
+        RegionCoordinate::parse_file_name(file_name, "mca").map(|region| (region.x, region.z))
+    }
+
+    /// Reads the chunk at the given position within this region, or `None` if it was never
+    /// generated
+    pub fn read_chunk(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+    ) -> Result<Option<NbtTag>, AnvilError> {
+        let location = self.locations[local_z * ANVIL_REGION_SIZE + local_x];
+        if location == 0 {
+            return Ok(None);
+        }
+
+        let sector_offset = (location >> 8) as u64;
+        self.file.seek(SeekFrom::Start(sector_offset * SECTOR_SIZE))?;
+
+        let mut length_bytes = [0u8; 4];
+        self.file.read_exact(&mut length_bytes)?;
+        // the length includes the compression byte
+        let length = u32::from_be_bytes(length_bytes) as usize;
+
+        let mut compression = [0u8; 1];
+        self.file.read_exact(&mut compression)?;
+
+        let mut compressed = vec![0; length.saturating_sub(1)];
+        self.file.read_exact(&mut compressed)?;
+
+        let mut data = Vec::new();
+        match compression[0] {
+            COMPRESSION_GZIP => {
+                GzDecoder::new(compressed.as_slice()).read_to_end(&mut data)?;
+            },
+            COMPRESSION_ZLIB => {
+                ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut data)?;
+            },
+            COMPRESSION_NONE => data = compressed,
+            other => return Err(AnvilError::UnknownCompression(other)),
+        }
+
+        parse_named_nbt(&data).map(Some)
+    }
+}
+
+/// Collects chunks in memory, and writes them as one region file.
+/// Anvil files are only written when exporting, so we do not support updating existing files.
+pub struct AnvilRegionWriter {
+    // the length header, compression type and compressed nbt of every chunk
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl AnvilRegionWriter {
+    pub fn new() -> AnvilRegionWriter {
+        AnvilRegionWriter {
+            chunks: vec![None; ANVIL_REGION_SIZE * ANVIL_REGION_SIZE],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().all(Option::is_none)
+    }
+
+    pub fn set_chunk(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+        chunk_nbt: &NbtTag,
+    ) -> Result<(), AnvilError> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&serialize_named_nbt(chunk_nbt))?;
+        let compressed = encoder.finish()?;
+
+        let mut data = Vec::with_capacity(compressed.len() + 5);
+        // the length includes the compression byte
+        data.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+        data.push(COMPRESSION_ZLIB);
+        data.extend_from_slice(&compressed);
+
+        // the sector count is stored in a single byte
+        if (data.len() as u64).div_ceil(SECTOR_SIZE) > u8::MAX as u64 {
+            return Err(AnvilError::ChunkTooLarge);
+        }
+
+        self.chunks[local_z * ANVIL_REGION_SIZE + local_x] = Some(data);
+        Ok(())
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), AnvilError> {
+        let mut locations = vec![0u8; SECTOR_SIZE as usize];
+        let mut timestamps = vec![0u8; SECTOR_SIZE as usize];
+        let mut body = Vec::new();
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as u32)
+            .unwrap_or(0);
+
+        // the locations and timestamps take up the first two sectors
+        let mut next_sector: u32 = 2;
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            let Some(data) = chunk else {
+                continue;
+            };
+
+            let sector_count = (data.len() as u64).div_ceil(SECTOR_SIZE) as u32;
+            let location = (next_sector << 8) | sector_count;
+            locations[idx * 4..idx * 4 + 4].copy_from_slice(&location.to_be_bytes());
+            timestamps[idx * 4..idx * 4 + 4].copy_from_slice(&timestamp.to_be_bytes());
+
+            body.extend_from_slice(data);
+            body.resize(body.len() + (sector_count as usize * SECTOR_SIZE as usize - data.len()), 0);
+            next_sector += sector_count;
+        }
+
+        let mut file = File::create(path)?;
+        file.write_all(&locations)?;
+        file.write_all(&timestamps)?;
+        file.write_all(&body)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+impl Default for AnvilRegionWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use minecraft_protocol::nbt::{NbtList, NbtTag};
+    use sol_voxel_lib::biome;
+    use sol_voxel_lib::vector_alias::Coordinate;
+
+    use crate::anvil::import::chunk_column_from_anvil;
+    use crate::anvil::region::AnvilRegion;
+    use crate::anvil::{
+        bits_for_palette, pack_long_array, parse_named_nbt, serialize_named_nbt, unpack_long_array,
+        AnvilError, MIN_BITS_PER_BLOCK, MIN_SECTION_Y,
+    };
+
+    fn palette_entry(name: &str) -> HashMap<String, NbtTag> {
+        HashMap::from([(String::from("Name"), NbtTag::String(String::from(name)))])
+    }
+
+    // a chunk at (2, -1) whose lowest section is air with one stone block, in an ocean
+    fn anvil_chunk(status: &str) -> NbtTag {
+        let mut indices = vec![0; 16 * 16 * 16];
+        // x = 3, y = 1, z = 2
+        indices[(16 + 2) * 16 + 3] = 1;
+
+        let block_states = HashMap::from([
+            (
+                String::from("palette"),
+                NbtTag::List(NbtList::Compound(vec![
+                    palette_entry("minecraft:air"),
+                    palette_entry("minecraft:stone"),
+                ])),
+            ),
+            (
+                String::from("data"),
+                NbtTag::LongArray(pack_long_array(&indices, MIN_BITS_PER_BLOCK)),
+            ),
+        ]);
+        let biomes = HashMap::from([(
+            String::from("palette"),
+            NbtTag::List(NbtList::String(vec![String::from("minecraft:ocean")])),
+        )]);
+
+        let section = HashMap::from([
+            (String::from("Y"), NbtTag::Byte(MIN_SECTION_Y as i8)),
+            (String::from("block_states"), NbtTag::Compound(block_states)),
+            (String::from("biomes"), NbtTag::Compound(biomes)),
+        ]);
+        // below the world; only carries light
+        let light_section =
+            HashMap::from([(String::from("Y"), NbtTag::Byte(MIN_SECTION_Y as i8 - 1))]);
+
+        NbtTag::Compound(HashMap::from([
+            (String::from("xPos"), NbtTag::Int(2)),
+            (String::from("zPos"), NbtTag::Int(-1)),
+            (String::from("Status"), NbtTag::String(String::from(status))),
+            (
+                String::from("sections"),
+                NbtTag::List(NbtList::Compound(vec![light_section, section])),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn test_chunk_column_from_anvil() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let block_properties = registries.block_properties();
+        let block_states = registries.block_states();
+
+        let column = chunk_column_from_anvil(
+            &anvil_chunk("minecraft:full"),
+            block_properties,
+            block_states,
+        )
+        .unwrap()
+        .expect("the chunk is fully generated");
+        assert_eq!(column.coordinate().x, 2);
+        assert_eq!(column.coordinate().z, -1);
+
+        let block_at = |x, y, z| {
+            column
+                .get_voxel(Coordinate::new(x, y, z))
+                .unwrap()
+                .get_block()
+                .id()
+        };
+        let stone = block_states
+            .get_block_state("minecraft:stone", &HashMap::new())
+            .unwrap();
+        let air = block_states
+            .get_block_state("minecraft:air", &HashMap::new())
+            .unwrap();
+        assert_eq!(block_at(32 + 3, 1, -16 + 2), stone.id());
+        assert_eq!(block_at(32 + 3, 0, -16 + 2), air.id());
+        // sections that the chunk does not contain stay empty
+        assert_eq!(block_at(32 + 3, 17, -16 + 2), air.id());
+
+        assert_eq!(
+            column.get_biome(Coordinate::new(32, 0, -16)).unwrap(),
+            biome::OCEAN
+        );
+        assert_eq!(
+            column.get_biome(Coordinate::new(32, 16, -16)).unwrap(),
+            biome::PLAINS
+        );
+    }
+
+    #[test]
+    fn test_chunk_column_from_anvil_skips_proto_chunks() {
+        let registries = minecraft_vanilla::registries::get_registries();
+        let column = chunk_column_from_anvil(
+            &anvil_chunk("minecraft:noise"),
+            registries.block_properties(),
+            registries.block_states(),
+        )
+        .unwrap();
+        assert!(column.is_none());
+    }
+
+    #[test]
+    fn test_bits_for_palette() {
+        assert_eq!(bits_for_palette(1), 0);
+        assert_eq!(bits_for_palette(2), 1);
+        assert_eq!(bits_for_palette(16), 4);
+        assert_eq!(bits_for_palette(17), 5);
+    }
+
+    #[test]
+    fn test_unpack_long_array() {
+        // 5 bits per value gives 12 values per long, with 4 unused bits
+        let values: Vec<u32> = (0..24).collect();
+        let mut data = vec![0i64; 2];
+        for (i, value) in values.iter().enumerate() {
+            data[i / 12] |= (*value as i64) << ((i % 12) * 5);
+        }
+
+        assert_eq!(unpack_long_array(&data, 5, 24), values);
+    }
+
+    #[test]
+    fn test_pack_long_array() {
+        let values: Vec<u32> = (0..4096).map(|i| i % 17).collect();
+        let data = pack_long_array(&values, 5);
+
+        assert_eq!(data.len(), 4096usize.div_ceil(12));
+        assert_eq!(unpack_long_array(&data, 5, 4096), values);
+    }
+
+    #[test]
+    fn test_region_file_name() {
+        assert_eq!(
+            AnvilRegion::coordinate_from_file_name("r.-1.2.mca"),
+            Some((-1, 2))
+        );
+        assert_eq!(AnvilRegion::coordinate_from_file_name("r.0.0.mcc"), None);
+        assert_eq!(AnvilRegion::coordinate_from_file_name("level.dat"), None);
+    }
+
+    #[test]
+    fn test_named_nbt_round_trip() {
+        let tag = anvil_chunk("minecraft:full");
+        assert!(parse_named_nbt(&serialize_named_nbt(&tag)).unwrap() == tag);
+
+        // the name of the root is skipped
+        let mut named = serialize_named_nbt(&tag);
+        named.splice(1..3, [0, 4, b'r', b'o', b'o', b't']);
+        assert!(parse_named_nbt(&named).unwrap() == tag);
+    }
+
+    #[test]
+    fn test_malformed_named_nbt() {
+        for bytes in [
+            &[][..],
+            &[0x0A, 0][..],
+            // not a compound
+            &[0x08, 0, 0, 0, 0][..],
+            // the name is longer than the data
+            &[0x0A, 0, 4, b'r', b'o'][..],
+            &[0x0A, 0xff, 0xff][..],
+        ] {
+            assert!(matches!(
+                parse_named_nbt(bytes),
+                Err(AnvilError::MalformedNbt(_))
+            ));
+        }
+    }
+}
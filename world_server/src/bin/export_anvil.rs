@@ -0,0 +1,58 @@
+// Writes the world of the world server as the region files of a vanilla world folder.
+// usage: export_anvil <world directory> <vanilla world folder>
+
+use std::fs;
+use std::path::PathBuf;
+
+use sol_world_server::anvil::export;
+use sol_world_server::anvil::region::AnvilRegion;
+use sol_world_server::chunk_storage::ChunkStorage;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        println!("usage: export_anvil <world directory> <vanilla world folder>");
+        return;
+    }
+
+    let mut storage = match ChunkStorage::new(PathBuf::from(&args[1])) {
+        Ok(storage) => storage,
+        Err(error) => {
+            println!("Could not open world directory {}: {error}", args[1]);
+            return;
+        },
+    };
+
+    let mut regions = match storage.stored_regions() {
+        Ok(regions) => regions,
+        Err(error) => {
+            println!("Could not read world directory {}: {error}", args[1]);
+            return;
+        },
+    };
+    regions.sort_by_key(|region| (region.x, region.z));
+
+    let region_directory = PathBuf::from(&args[2]).join("region");
+    if let Err(error) = fs::create_dir_all(&region_directory) {
+        println!("Could not create {}: {error}", region_directory.display());
+        return;
+    }
+
+    let registries = minecraft_vanilla::registries::get_registries();
+
+    let mut total_exported = 0;
+    for region in regions {
+        let file_name = AnvilRegion::file_name(region.x, region.z);
+        let path = region_directory.join(&file_name);
+
+        match export::export_region(region, &mut storage, registries.block_states(), &path) {
+            Ok(num_exported) => {
+                println!("{file_name}: exported {num_exported} chunks");
+                total_exported += num_exported;
+            },
+            Err(error) => println!("Could not export {file_name}: {error}"),
+        }
+    }
+
+    println!("Exported {total_exported} chunks");
+}
@@ -0,0 +1,77 @@
+// Converts the overworld of a vanilla world folder into the storage format of the world server.
+// usage: import_anvil <vanilla world folder> <world directory>
+
+use std::fs;
+use std::path::PathBuf;
+
+use sol_world_server::anvil::import;
+use sol_world_server::anvil::region::{AnvilRegion, ANVIL_REGION_SIZE};
+use sol_world_server::chunk_storage::ChunkStorage;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        println!("usage: import_anvil <vanilla world folder> <world directory>");
+        return;
+    }
+
+    let region_directory = PathBuf::from(&args[1]).join("region");
+    let mut region_files: Vec<PathBuf> = match fs::read_dir(&region_directory) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "mca"))
+            .collect(),
+        Err(error) => {
+            println!("Could not read {}: {error}", region_directory.display());
+            return;
+        },
+    };
+    region_files.sort();
+
+    let mut storage = match ChunkStorage::new(PathBuf::from(&args[2])) {
+        Ok(storage) => storage,
+        Err(error) => {
+            println!("Could not open world directory {}: {error}", args[2]);
+            return;
+        },
+    };
+
+    let registries = minecraft_vanilla::registries::get_registries();
+
+    let mut total_imported = 0;
+    for path in region_files {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let Some((region_x, region_z)) = AnvilRegion::coordinate_from_file_name(&file_name) else {
+            println!("Skipping {file_name}: not a region file");
+            continue;
+        };
+
+        let summary = match import::import_region(
+            &path,
+            &mut storage,
+            registries.block_properties(),
+            registries.block_states(),
+        ) {
+            Ok(summary) => summary,
+            Err(error) => {
+                println!("Could not import {file_name}: {error}");
+                continue;
+            },
+        };
+
+        for (local_x, local_z, error) in &summary.failed {
+            let chunk_x = region_x * ANVIL_REGION_SIZE as i32 + local_x;
+            let chunk_z = region_z * ANVIL_REGION_SIZE as i32 + local_z;
+            println!("Could not import chunk ({chunk_x}, {chunk_z}): {error}");
+        }
+
+        println!(
+            "{file_name}: imported {} chunks, skipped {} ungenerated chunks",
+            summary.num_imported, summary.num_skipped
+        );
+        total_imported += summary.num_imported;
+    }
+
+    println!("Imported {total_imported} chunks");
+}
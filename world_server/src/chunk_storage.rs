@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::Hash;
+use std::path::PathBuf;
+
+use sol_voxel_lib::chunk_column::ChunkColumn;
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+
+use crate::region_file::{RegionCoordinate, RegionFile, StorageError};
+
+/// the number of columns that are kept in memory; the least recently used are dropped first
+pub const MAX_CACHED_COLUMNS: usize = 2048;
+/// the number of region files that are kept open
+pub const MAX_OPEN_REGIONS: usize = 64;
+
+/// Disk-backed store of chunk columns.
+/// Columns are loaded from their region file on first access, and are kept in memory until the
+/// cache is full. Columns with changes that were not saved yet are never dropped
+pub struct ChunkStorage {
+    directory: PathBuf,
+    regions: HashMap<RegionCoordinate, Cached<RegionFile>>,
+    columns: HashMap<ChunkColumnCoordinate, Cached<Box<ChunkColumn>>>,
+    // columns that were handed out by `get_column_mut`, and not saved since
+    unsaved_columns: HashSet<ChunkColumnCoordinate>,
+    max_columns: usize,
+    max_regions: usize,
+    // increases with every access, to find the least recently used entries
+    access_count: u64,
+}
+
+struct Cached<T> {
+    value: T,
+    last_access: u64,
+}
+
+impl ChunkStorage {
+    pub fn new(directory: PathBuf) -> Result<ChunkStorage, StorageError> {
+        Self::with_cache_sizes(directory, MAX_CACHED_COLUMNS, MAX_OPEN_REGIONS)
+    }
+
+    pub fn with_cache_sizes(
+        directory: PathBuf,
+        max_columns: usize,
+        max_regions: usize,
+    ) -> Result<ChunkStorage, StorageError> {
+        fs::create_dir_all(&directory)?;
+
+        Ok(ChunkStorage {
+            directory,
+            regions: HashMap::new(),
+            columns: HashMap::new(),
+            unsaved_columns: HashSet::new(),
+            max_columns,
+            max_regions,
+            access_count: 0,
+        })
+    }
+
+    /// Returns the coordinates of all region files in the storage directory
+    pub fn stored_regions(&self) -> Result<Vec<RegionCoordinate>, StorageError> {
+        let mut regions = Vec::new();
+
+        for entry in fs::read_dir(&self.directory)? {
+            let file_name = entry?.file_name();
+            if let Some(coord) = RegionCoordinate::from_file_name(&file_name.to_string_lossy()) {
+                regions.push(coord);
+            }
+        }
+
+        Ok(regions)
+    }
+
+    pub fn is_loaded(&self, coord: &ChunkColumnCoordinate) -> bool {
+        self.columns.contains_key(coord)
+    }
+
+    /// Returns the column at the given coordinate, or `None` if it was never stored
+    pub fn get_column(
+        &mut self,
+        coord: &ChunkColumnCoordinate,
+    ) -> Result<Option<&ChunkColumn>, StorageError> {
+        self.load_column(coord)?;
+        Ok(self.touch_column(coord).map(|column| &**column))
+    }
+
+    /// Like `get_column`, but changes made to the returned column are only written to disk after
+    /// calling `save_column`
+    pub fn get_column_mut(
+        &mut self,
+        coord: &ChunkColumnCoordinate,
+    ) -> Result<Option<&mut ChunkColumn>, StorageError> {
+        self.load_column(coord)?;
+        if self.columns.contains_key(coord) {
+            self.unsaved_columns.insert(*coord);
+        }
+        Ok(self.touch_column(coord).map(|column| &mut **column))
+    }
+
+    /// Stores the column in memory and writes it to its region file
+    pub fn store_column(&mut self, column: ChunkColumn) -> Result<(), StorageError> {
+        let coord = column.coordinate();
+        self.get_region(&coord)?.write_column(&column)?;
+        self.unsaved_columns.remove(&coord);
+        self.insert_column(coord, Box::new(column));
+        Ok(())
+    }
+
+    /// Removes the column from memory, without saving it
+    pub fn unload_column(&mut self, coord: &ChunkColumnCoordinate) {
+        self.columns.remove(coord);
+        self.unsaved_columns.remove(coord);
+    }
+
+    /// Writes the in-memory state of a loaded column to its region file
+    pub fn save_column(&mut self, coord: &ChunkColumnCoordinate) -> Result<(), StorageError> {
+        let Some(cached) = self.columns.remove(coord) else {
+            return Ok(());
+        };
+
+        // the column is put back even if it could not be written, to keep its changes
+        let result = self
+            .get_region(coord)
+            .and_then(|region| region.write_column(&cached.value));
+        self.columns.insert(*coord, cached);
+        result?;
+
+        self.unsaved_columns.remove(coord);
+        Ok(())
+    }
+
+    fn load_column(&mut self, coord: &ChunkColumnCoordinate) -> Result<(), StorageError> {
+        if self.columns.contains_key(coord) {
+            return Ok(());
+        }
+
+        // reading never creates a region file
+        let Some(region) = self.get_existing_region(coord)? else {
+            return Ok(());
+        };
+        if let Some(column) = region.read_column(coord)? {
+            self.insert_column(*coord, Box::new(column));
+        }
+
+        Ok(())
+    }
+
+    fn touch_column(&mut self, coord: &ChunkColumnCoordinate) -> Option<&mut Box<ChunkColumn>> {
+        self.access_count += 1;
+        let cached = self.columns.get_mut(coord)?;
+        cached.last_access = self.access_count;
+        Some(&mut cached.value)
+    }
+
+    fn insert_column(&mut self, coord: ChunkColumnCoordinate, column: Box<ChunkColumn>) {
+        if !self.columns.contains_key(&coord) && self.columns.len() >= self.max_columns {
+            let unsaved_columns = &self.unsaved_columns;
+            // when every column has unsaved changes, the cache grows beyond its size
+            if let Some(evicted) =
+                least_recently_used(&self.columns, |coord| !unsaved_columns.contains(coord))
+            {
+                self.columns.remove(&evicted);
+            }
+        }
+
+        self.access_count += 1;
+        let cached = Cached {
+            value: column,
+            last_access: self.access_count,
+        };
+        self.columns.insert(coord, cached);
+    }
+
+    /// Returns the region file of the column, and creates it if it does not exist
+    fn get_region(
+        &mut self,
+        coord: &ChunkColumnCoordinate,
+    ) -> Result<&mut RegionFile, StorageError> {
+        let region_coord = RegionCoordinate::containing(coord);
+        if !self.regions.contains_key(&region_coord) {
+            let path = self.directory.join(region_coord.file_name());
+            let region = RegionFile::create(&path)?;
+            self.insert_region(region_coord, region);
+        }
+
+        Ok(self.touch_region(&region_coord).unwrap())
+    }
+
+    /// Returns the region file of the column, or `None` if it does not exist
+    fn get_existing_region(
+        &mut self,
+        coord: &ChunkColumnCoordinate,
+    ) -> Result<Option<&mut RegionFile>, StorageError> {
+        let region_coord = RegionCoordinate::containing(coord);
+        if !self.regions.contains_key(&region_coord) {
+            let path = self.directory.join(region_coord.file_name());
+            if !path.exists() {
+                return Ok(None);
+            }
+            let region = RegionFile::open(&path)?;
+            self.insert_region(region_coord, region);
+        }
+
+        Ok(self.touch_region(&region_coord))
+    }
+
+    fn touch_region(&mut self, region_coord: &RegionCoordinate) -> Option<&mut RegionFile> {
+        self.access_count += 1;
+        let cached = self.regions.get_mut(region_coord)?;
+        cached.last_access = self.access_count;
+        Some(&mut cached.value)
+    }
+
+    // every write is flushed, so a region file can be closed at any time
+    fn insert_region(&mut self, region_coord: RegionCoordinate, region: RegionFile) {
+        if self.regions.len() >= self.max_regions {
+            if let Some(evicted) = least_recently_used(&self.regions, |_| true) {
+                self.regions.remove(&evicted);
+            }
+        }
+
+        let cached = Cached {
+            value: region,
+            last_access: self.access_count,
+        };
+        self.regions.insert(region_coord, cached);
+    }
+}
+
+fn least_recently_used<K: Copy + Eq + Hash, T>(
+    entries: &HashMap<K, Cached<T>>,
+    can_evict: impl Fn(&K) -> bool,
+) -> Option<K> {
+    entries
+        .iter()
+        .filter(|(key, _)| can_evict(key))
+        .min_by_key(|(_, cached)| cached.last_access)
+        .map(|(key, _)| *key)
+}
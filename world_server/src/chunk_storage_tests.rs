@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+    use crate::chunk_storage::ChunkStorage;
+    use crate::region_file::RegionCoordinate;
+    use sol_voxel_lib::chunk_column::ChunkColumn;
+    use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+    use std::path::PathBuf;
+
+    fn temp_storage_directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sol_storage_test_{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    fn coord(x: i32, z: i32) -> ChunkColumnCoordinate {
+        ChunkColumnCoordinate { x, z }
+    }
+
+    #[test]
+    fn test_reading_does_not_create_regions() {
+        let directory = temp_storage_directory("read_only");
+        let mut storage = ChunkStorage::new(directory.clone()).unwrap();
+
+        assert!(storage.get_column(&coord(5, -40)).unwrap().is_none());
+        assert!(storage.get_column_mut(&coord(5, -40)).unwrap().is_none());
+        storage.save_column(&coord(5, -40)).unwrap();
+        assert!(storage.stored_regions().unwrap().is_empty());
+
+        storage.store_column(ChunkColumn::new(5, -40)).unwrap();
+        assert_eq!(
+            storage.stored_regions().unwrap(),
+            vec![RegionCoordinate { x: 0, z: -2 }]
+        );
+    }
+
+    #[test]
+    fn test_column_cache_is_bounded() {
+        let directory = temp_storage_directory("column_cache");
+        let mut storage = ChunkStorage::with_cache_sizes(directory, 2, 1).unwrap();
+        for x in 0..3 {
+            storage.store_column(ChunkColumn::new(x, 0)).unwrap();
+        }
+
+        // the least recently used column was dropped
+        assert!(!storage.is_loaded(&coord(0, 0)));
+        assert!(storage.is_loaded(&coord(1, 0)));
+        assert!(storage.is_loaded(&coord(2, 0)));
+
+        // and is read from its region file again
+        storage.get_column(&coord(1, 0)).unwrap();
+        let column = storage.get_column(&coord(0, 0)).unwrap().unwrap();
+        assert_eq!(column.coordinate(), coord(0, 0));
+        assert!(storage.is_loaded(&coord(1, 0)));
+        assert!(!storage.is_loaded(&coord(2, 0)));
+    }
+
+    #[test]
+    fn test_unsaved_columns_are_kept() {
+        let directory = temp_storage_directory("unsaved_columns");
+        let mut storage = ChunkStorage::with_cache_sizes(directory, 1, 1).unwrap();
+        storage.store_column(ChunkColumn::new(0, 0)).unwrap();
+        storage.get_column_mut(&coord(0, 0)).unwrap().unwrap();
+
+        // the column in a different region is loaded as well, instead of dropping the change
+        storage.store_column(ChunkColumn::new(-1, 0)).unwrap();
+        assert!(storage.is_loaded(&coord(0, 0)));
+        assert!(storage.is_loaded(&coord(-1, 0)));
+
+        // once saved, the column may be dropped again
+        storage.save_column(&coord(0, 0)).unwrap();
+        storage.store_column(ChunkColumn::new(40, 0)).unwrap();
+        assert!(!storage.is_loaded(&coord(0, 0)));
+        assert!(storage.get_column(&coord(0, 0)).unwrap().is_some());
+    }
+}
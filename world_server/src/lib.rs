@@ -0,0 +1,10 @@
+pub mod anvil;
+pub mod chunk_storage;
+pub mod region_file;
+
+#[cfg(test)]
+mod anvil_tests;
+#[cfg(test)]
+mod chunk_storage_tests;
+#[cfg(test)]
+mod region_file_tests;
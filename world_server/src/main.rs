@@ -1,26 +1,77 @@
 #![allow(dead_code)]
 
 extern crate zmq;
+
+#[cfg(test)]
+mod set_voxel_tests;
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use minecraft_vanilla::registries::Registries;
 use sol_address_server::static_addresses;
-use sol_log_server::logger::Logger;
+use sol_log_server::logger_mt::LoggerMt;
 use sol_log_server::Severity;
 use sol_network_lib::network::{NetworkError, ReplyLoop};
+use sol_voxel_lib::chunk_column::{ChunkColumn, NUM_CHUNK_SECTIONS_PER_COLUMN};
+use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate};
+use sol_voxel_lib::voxel::Voxel;
+use sol_world_generator::terrain::{TerrainGenerator, TerrainSettings};
 use sol_world_messages::{WorldServerRep, WorldServerReq};
+use sol_world_server::chunk_storage::ChunkStorage;
+use sol_world_server::region_file::StorageError;
+
+const DEFAULT_WORLD_DIRECTORY: &str = "world";
+const DEFAULT_SEED: u64 = 0;
 
 fn main() {
     let context = zmq::Context::new();
-    let logger = Logger::new(
+    let logger = LoggerMt::new(
         "World Server",
         context.clone(),
         String::from(static_addresses::LOG_SERVER),
     )
     .expect("Could not connect logger");
 
-    let reply_loop = {
+    let world_directory = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| String::from(DEFAULT_WORLD_DIRECTORY));
+
+    let mut storage = match ChunkStorage::new(PathBuf::from(&world_directory)) {
+        Ok(storage) => storage,
+        Err(error) => {
+            logger.log(
+                Severity::FatalError,
+                &format!("Could not open world directory {world_directory}: {error}"),
+            );
+            return;
+        },
+    };
+
+    // columns are generated once, and stored afterwards.
+    // Changing the seed of an existing world only affects columns that were never requested
+    let seed = std::env::args()
+        .nth(2)
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    let generator = TerrainGenerator::new(seed, TerrainSettings::default());
+
+    let registries = minecraft_vanilla::registries::get_registries();
+
+    let mut reply_loop = {
+        let handler_logger = logger.clone();
         let reply_loop_result = ReplyLoop::new(
             context.clone(),
             String::from(static_addresses::WORLD_SERVER),
-            handle_message,
+            move |message| {
+                handle_message(
+                    &mut storage,
+                    &generator,
+                    &registries,
+                    &handler_logger,
+                    message,
+                )
+            },
         );
 
         match reply_loop_result {
@@ -35,8 +86,6 @@ fn main() {
         }
     };
 
-    // let world = World::new();
-
     logger.send_status("World server online");
 
     let stop_reason = reply_loop.listen_until_stop();
@@ -55,10 +104,161 @@ fn main() {
     }
 }
 
-fn handle_message(message: WorldServerReq) -> WorldServerRep {
-    println!("Received something");
+fn handle_message(
+    storage: &mut ChunkStorage,
+    generator: &TerrainGenerator,
+    registries: &Registries,
+    logger: &LoggerMt,
+    message: WorldServerReq,
+) -> WorldServerRep {
     match message {
         WorldServerReq::Ping(msg) => WorldServerRep::Pong(msg),
-        _ => WorldServerRep::Empty,
+        WorldServerReq::ContentChunkColumn(coord) => {
+            match get_or_generate_column(storage, generator, registries, coord) {
+                Ok(column) => WorldServerRep::ContentChunkColumn(coord, Box::new(column.clone())),
+                Err(error) => {
+                    logger.log(
+                        Severity::RecoverableError,
+                        &format!("Could not load chunk column {coord:?}: {error}"),
+                    );
+                    WorldServerRep::Empty
+                },
+            }
+        },
+        WorldServerReq::ContentChunk16(coord) => {
+            let column_coord = ChunkColumnCoordinate::from(coord);
+            match get_or_generate_column(storage, generator, registries, column_coord) {
+                Ok(column) => match column.get_chunk(coord.inner().y) {
+                    Some(chunk) => WorldServerRep::ContentChunk16(coord, Box::new(chunk.clone())),
+                    None => WorldServerRep::Empty,
+                },
+                Err(error) => {
+                    logger.log(
+                        Severity::RecoverableError,
+                        &format!("Could not load chunk column {column_coord:?}: {error}"),
+                    );
+                    WorldServerRep::Empty
+                },
+            }
+        },
+        WorldServerReq::SetVoxel(coord, voxel) => {
+            handle_set_voxel(storage, registries, logger, coord, voxel)
+        },
+        WorldServerReq::SetVoxels(changes) => {
+            handle_set_voxels(storage, registries, logger, changes)
+        },
+    }
+}
+
+// Columns that were never stored are generated, and stored right away so that later changes to
+// them are persisted like any other column
+fn get_or_generate_column<'a>(
+    storage: &'a mut ChunkStorage,
+    generator: &TerrainGenerator,
+    registries: &Registries,
+    coord: ChunkColumnCoordinate,
+) -> Result<&'a ChunkColumn, StorageError> {
+    if storage.get_column(&coord)?.is_none() {
+        let column =
+            generator.generate_column(coord, registries.block_properties(), registries.block_states());
+        storage.store_column(column)?;
+    }
+
+    Ok(storage
+        .get_column(&coord)?
+        .expect("column was stored right before"))
+}
+
+// Only columns that are present in this server's storage may be changed.
+// A denied change leaves the world untouched; the requester is expected to revert its own copy.
+fn handle_set_voxel(
+    storage: &mut ChunkStorage,
+    registries: &Registries,
+    logger: &LoggerMt,
+    coord: Coordinate,
+    voxel: Voxel,
+) -> WorldServerRep {
+    if !set_voxel(storage, registries, logger, coord, voxel) {
+        return WorldServerRep::SetVoxelDenied(coord);
+    }
+
+    let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+    save_column(storage, logger, column_coord);
+    WorldServerRep::SetVoxelAcknowledged(coord)
+}
+
+// Applies the changes in order, like one `SetVoxel` each, and saves every changed column once
+fn handle_set_voxels(
+    storage: &mut ChunkStorage,
+    registries: &Registries,
+    logger: &LoggerMt,
+    changes: Vec<(Coordinate, Voxel)>,
+) -> WorldServerRep {
+    let mut denied = Vec::new();
+    let mut changed_columns = HashSet::new();
+
+    for (coord, voxel) in changes {
+        if set_voxel(storage, registries, logger, coord, voxel) {
+            changed_columns.insert(ChunkColumnCoordinate::containing_coord(&coord));
+        } else {
+            denied.push(coord);
+        }
+    }
+
+    for column_coord in changed_columns {
+        save_column(storage, logger, column_coord);
+    }
+
+    WorldServerRep::SetVoxelsDenied(denied)
+}
+
+// returns false if the change is denied
+fn set_voxel(
+    storage: &mut ChunkStorage,
+    registries: &Registries,
+    logger: &LoggerMt,
+    coord: Coordinate,
+    voxel: Voxel,
+) -> bool {
+    let world_height = (NUM_CHUNK_SECTIONS_PER_COLUMN * 16) as i32;
+    if coord.y < 0 || coord.y >= world_height {
+        return false;
+    }
+
+    let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+    let column = match storage.get_column_mut(&column_coord) {
+        Ok(Some(column)) => column,
+        Ok(None) => return false,
+        Err(error) => {
+            logger.log(
+                Severity::RecoverableError,
+                &format!("Could not load chunk column {column_coord:?}: {error}"),
+            );
+            return false;
+        },
+    };
+
+    let result = column.set_voxel(
+        coord,
+        voxel,
+        registries.block_properties(),
+        registries.block_states(),
+    );
+
+    if let Err(error) = result {
+        logger.log(Severity::RecoverableError, &error.to_string());
+        return false;
+    }
+
+    true
+}
+
+fn save_column(storage: &mut ChunkStorage, logger: &LoggerMt, column_coord: ChunkColumnCoordinate) {
+    if let Err(error) = storage.save_column(&column_coord) {
+        // the change is applied in memory, and will be written with the next change to this column
+        logger.log(
+            Severity::RecoverableError,
+            &format!("Could not save chunk column {column_coord:?}: {error}"),
+        );
     }
 }
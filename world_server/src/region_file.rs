@@ -0,0 +1,254 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use sol_voxel_lib::chunk_column::ChunkColumn;
+use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+
+// A region file groups REGION_SIZE x REGION_SIZE chunk columns, much like the anvil format.
+// The file starts with a header of one entry per column, followed by the serialized columns.
+// Every column occupies a whole number of sectors, so that a column that is stored again can be
+// overwritten in place as long as it did not grow beyond its sectors.
+
+/// number of chunk columns along one side of a region
+pub const REGION_SIZE: i32 = 32;
+const NUM_COLUMNS_PER_REGION: usize = (REGION_SIZE * REGION_SIZE) as usize;
+
+const SECTOR_SIZE: u64 = 4096;
+// sector_offset, sector_count and byte_length, each an u32
+const HEADER_ENTRY_SIZE: usize = 12;
+// 1024 * 12 bytes = 3 sectors
+const NUM_HEADER_SECTORS: u32 =
+    ((NUM_COLUMNS_PER_REGION * HEADER_ENTRY_SIZE) as u64 / SECTOR_SIZE) as u32;
+
+#[derive(Debug)]
+pub enum StorageError {
+    IoError(io::Error),
+    SerialisationError(bincode::Error),
+    /// the entry of the column with this index points outside of the file
+    InvalidHeaderEntry(usize),
+}
+
+impl From<io::Error> for StorageError {
+    fn from(value: io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+impl From<bincode::Error> for StorageError {
+    fn from(value: bincode::Error) -> Self {
+        Self::SerialisationError(value)
+    }
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StorageError::IoError(error) => write!(formatter, "IO error: {error}"),
+            StorageError::SerialisationError(error) => {
+                write!(formatter, "Serialisation error: {error}")
+            },
+            StorageError::InvalidHeaderEntry(index) => {
+                write!(formatter, "Invalid region header entry of column {index}")
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct RegionCoordinate {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl RegionCoordinate {
+    pub fn containing(coord: &ChunkColumnCoordinate) -> RegionCoordinate {
+        RegionCoordinate {
+            x: coord.x.div_euclid(REGION_SIZE),
+            z: coord.z.div_euclid(REGION_SIZE),
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("r.{}.{}.sol", self.x, self.z)
+    }
+
+    /// The inverse of `file_name`
+    pub fn from_file_name(file_name: &str) -> Option<RegionCoordinate> {
+        Self::parse_file_name(file_name, "sol")
+    }
+
+    /// Parses a file name like `r.-1.2.<extension>`; anvil region files are named the same way
+    pub fn parse_file_name(file_name: &str, extension: &str) -> Option<RegionCoordinate> {
+        let mut parts = file_name.split('.');
+
+        if parts.next()? != "r" {
+            return None;
+        }
+        let x = parts.next()?.parse().ok()?;
+        let z = parts.next()?.parse().ok()?;
+        if parts.next()? != extension || parts.next().is_some() {
+            return None;
+        }
+
+        Some(RegionCoordinate { x, z })
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct HeaderEntry {
+    sector_offset: u32,
+    sector_count: u32,
+    byte_length: u32,
+}
+
+impl HeaderEntry {
+    fn from_bytes(bytes: &[u8]) -> HeaderEntry {
+        let read_u32 = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        HeaderEntry {
+            sector_offset: read_u32(0),
+            sector_count: read_u32(4),
+            byte_length: read_u32(8),
+        }
+    }
+
+    fn to_bytes(&self) -> [u8; HEADER_ENTRY_SIZE] {
+        let mut bytes = [0; HEADER_ENTRY_SIZE];
+        bytes[0..4].copy_from_slice(&self.sector_offset.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.sector_count.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.byte_length.to_le_bytes());
+        bytes
+    }
+
+    fn is_empty(&self) -> bool {
+        self.byte_length == 0
+    }
+
+    // the column lies behind the header, within its sectors, and within the file
+    fn is_valid(&self, file_length: u64) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let start = self.sector_offset as u64 * SECTOR_SIZE;
+        self.sector_offset >= NUM_HEADER_SECTORS
+            && self.byte_length as u64 <= self.sector_count as u64 * SECTOR_SIZE
+            && self.sector_offset.checked_add(self.sector_count).is_some()
+            && start + self.byte_length as u64 <= file_length
+    }
+}
+
+pub struct RegionFile {
+    file: File,
+    header: Vec<HeaderEntry>,
+    // first sector that is not used by any column
+    end_sector: u32,
+}
+
+impl RegionFile {
+    /// Opens the region file at the given path, or creates an empty one if it does not exist
+    pub fn create(path: &Path) -> Result<RegionFile, StorageError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)?;
+        Self::from_file(file)
+    }
+
+    /// Opens an existing region file; fails if there is none at the given path
+    pub fn open(path: &Path) -> Result<RegionFile, StorageError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::from_file(file)
+    }
+
+    fn from_file(mut file: File) -> Result<RegionFile, StorageError> {
+        let mut header = vec![HeaderEntry::default(); NUM_COLUMNS_PER_REGION];
+
+        let file_length = file.metadata()?.len();
+        if file_length == 0 {
+            let empty_header = vec![0; NUM_HEADER_SECTORS as usize * SECTOR_SIZE as usize];
+            file.write_all(&empty_header)?;
+            file.flush()?;
+        } else {
+            let mut header_bytes = vec![0; NUM_COLUMNS_PER_REGION * HEADER_ENTRY_SIZE];
+            file.seek(SeekFrom::Start(0))?;
+            file.read_exact(&mut header_bytes)?;
+
+            for (idx, entry) in header.iter_mut().enumerate() {
+                let start = idx * HEADER_ENTRY_SIZE;
+                *entry = HeaderEntry::from_bytes(&header_bytes[start..start + HEADER_ENTRY_SIZE]);
+                if !entry.is_valid(file_length) {
+                    return Err(StorageError::InvalidHeaderEntry(idx));
+                }
+            }
+        }
+
+        let end_sector = header
+            .iter()
+            .map(|entry| entry.sector_offset + entry.sector_count)
+            .fold(NUM_HEADER_SECTORS, u32::max);
+
+        Ok(RegionFile {
+            file,
+            header,
+            end_sector,
+        })
+    }
+
+    pub fn contains(&self, coord: &ChunkColumnCoordinate) -> bool {
+        !self.header[Self::column_index(coord)].is_empty()
+    }
+
+    pub fn read_column(
+        &mut self,
+        coord: &ChunkColumnCoordinate,
+    ) -> Result<Option<ChunkColumn>, StorageError> {
+        let entry = self.header[Self::column_index(coord)];
+        if entry.is_empty() {
+            return Ok(None);
+        }
+
+        let mut data = vec![0; entry.byte_length as usize];
+        self.file
+            .seek(SeekFrom::Start(entry.sector_offset as u64 * SECTOR_SIZE))?;
+        self.file.read_exact(&mut data)?;
+
+        let column: ChunkColumn = bincode::deserialize(&data)?;
+        Ok(Some(column))
+    }
+
+    pub fn write_column(&mut self, column: &ChunkColumn) -> Result<(), StorageError> {
+        let index = Self::column_index(&column.coordinate());
+        let data = bincode::serialize(column)?;
+
+        let sectors_needed = (data.len() as u64).div_ceil(SECTOR_SIZE) as u32;
+        let mut entry = self.header[index];
+
+        if entry.is_empty() || entry.sector_count < sectors_needed {
+            // does not fit in the old location; append to the end of the file.
+            // The old sectors are abandoned.
+            entry.sector_offset = self.end_sector;
+            entry.sector_count = sectors_needed;
+            self.end_sector += sectors_needed;
+        }
+        entry.byte_length = data.len() as u32;
+
+        self.file
+            .seek(SeekFrom::Start(entry.sector_offset as u64 * SECTOR_SIZE))?;
+        self.file.write_all(&data)?;
+
+        self.file
+            .seek(SeekFrom::Start((index * HEADER_ENTRY_SIZE) as u64))?;
+        self.file.write_all(&entry.to_bytes())?;
+        self.file.flush()?;
+
+        self.header[index] = entry;
+        Ok(())
+    }
+
+    fn column_index(coord: &ChunkColumnCoordinate) -> usize {
+        let x = coord.x.rem_euclid(REGION_SIZE);
+        let z = coord.z.rem_euclid(REGION_SIZE);
+        (z * REGION_SIZE + x) as usize
+    }
+}
@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use crate::region_file::{RegionCoordinate, RegionFile, StorageError};
+    use sol_voxel_lib::chunk_column::ChunkColumn;
+    use sol_voxel_lib::vector_alias::ChunkColumnCoordinate;
+    use std::fs::OpenOptions;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::path::{Path, PathBuf};
+
+    fn temp_region_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("sol_region_test_{name}.sol"));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_region_coordinate() {
+        let coord = ChunkColumnCoordinate { x: -1, z: 32 };
+        assert_eq!(
+            RegionCoordinate::containing(&coord),
+            RegionCoordinate { x: -1, z: 1 }
+        );
+    }
+
+    #[test]
+    fn test_region_file_name() {
+        let region = RegionCoordinate { x: -3, z: 7 };
+        assert_eq!(
+            RegionCoordinate::from_file_name(&region.file_name()),
+            Some(region)
+        );
+        assert_eq!(RegionCoordinate::from_file_name("r.-3.7.mca"), None);
+        assert_eq!(RegionCoordinate::from_file_name("r.-3.7.sol.tmp"), None);
+    }
+
+    #[test]
+    fn test_empty_region() {
+        let path = temp_region_path("empty");
+        let mut region = RegionFile::create(&path).unwrap();
+        let coord = ChunkColumnCoordinate { x: 3, z: 4 };
+
+        assert!(!region.contains(&coord));
+        assert!(region.read_column(&coord).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_read_reopen() {
+        let path = temp_region_path("reopen");
+        let first = ChunkColumn::new(3, 4);
+        let second = ChunkColumn::new(-29, 31);
+
+        {
+            let mut region = RegionFile::create(&path).unwrap();
+            region.write_column(&first).unwrap();
+            region.write_column(&second).unwrap();
+            // overwriting in place must not corrupt the other column
+            region.write_column(&first).unwrap();
+        }
+
+        let mut region = RegionFile::open(&path).unwrap();
+        for original in [first, second] {
+            let read = region
+                .read_column(&original.coordinate())
+                .unwrap()
+                .expect("column was written");
+
+            assert_eq!(read.coordinate(), original.coordinate());
+            assert_eq!(
+                bincode::serialize(&read).unwrap(),
+                bincode::serialize(&original).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_open_missing() {
+        let path = temp_region_path("missing");
+        assert!(matches!(
+            RegionFile::open(&path),
+            Err(StorageError::IoError(_))
+        ));
+        assert!(!path.exists());
+    }
+
+    // overwrites the header entry of the column at index 0
+    fn write_header_entry(path: &Path, sector_offset: u32, sector_count: u32, length: u32) {
+        let mut file = OpenOptions::new().write(true).open(path).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        for value in [sector_offset, sector_count, length] {
+            file.write_all(&value.to_le_bytes()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_invalid_header_entry() {
+        let path = temp_region_path("invalid_header");
+        let column = ChunkColumn::new(0, 0);
+        RegionFile::create(&path)
+            .unwrap()
+            .write_column(&column)
+            .unwrap();
+        let length = bincode::serialize(&column).unwrap().len() as u32;
+        let sector_count = length.div_ceil(4096);
+        assert!(RegionFile::open(&path).is_ok());
+
+        for (sector_offset, sector_count, length) in [
+            // inside the header
+            (0, sector_count, length),
+            // beyond the end of the file
+            (3, sector_count, length + 1),
+            (1000, sector_count, length),
+            (u32::MAX, 2, length),
+            // longer than its sectors
+            (3, 0, length),
+        ] {
+            write_header_entry(&path, sector_offset, sector_count, length);
+            assert!(matches!(
+                RegionFile::open(&path),
+                Err(StorageError::InvalidHeaderEntry(0))
+            ));
+        }
+
+        write_header_entry(&path, 3, sector_count, length);
+        let mut region = RegionFile::open(&path).unwrap();
+        assert!(region.read_column(&column.coordinate()).unwrap().is_some());
+    }
+}
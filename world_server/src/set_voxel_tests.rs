@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use minecraft_protocol::data::block_states::BlockWithState;
+    use minecraft_vanilla::ids::blocks::BlockId;
+    use minecraft_vanilla::registries::{get_registries, Registries};
+    use sol_log_server::logger_mt::LoggerMt;
+    use sol_voxel_lib::chunk_column::ChunkColumn;
+    use sol_voxel_lib::vector_alias::{ChunkColumnCoordinate, Coordinate};
+    use sol_voxel_lib::voxel::Voxel;
+    use sol_world_messages::WorldServerRep;
+    use sol_world_server::chunk_storage::ChunkStorage;
+
+    use crate::{handle_set_voxel, handle_set_voxels};
+
+    fn temp_storage(name: &str) -> (ChunkStorage, PathBuf) {
+        let path = std::env::temp_dir().join(format!("sol_set_voxel_test_{name}"));
+        let _ = std::fs::remove_dir_all(&path);
+        (ChunkStorage::new(path.clone()).unwrap(), path)
+    }
+
+    fn logger() -> LoggerMt {
+        LoggerMt::new("test", zmq::Context::new(), String::from("inproc://logger")).unwrap()
+    }
+
+    fn stone() -> Voxel {
+        let block: BlockWithState = BlockId::Stone.into();
+        Voxel::from_block(block)
+    }
+
+    fn is_stone(storage: &mut ChunkStorage, coord: Coordinate) -> bool {
+        let column_coord = ChunkColumnCoordinate::containing_coord(&coord);
+        let column = storage.get_column(&column_coord).unwrap().unwrap();
+        let block: BlockWithState = BlockId::Stone.into();
+        column.get_voxel(coord).unwrap().get_block_id() == block.id()
+    }
+
+    fn set_voxel(
+        storage: &mut ChunkStorage,
+        registries: &Registries,
+        coord: Coordinate,
+    ) -> WorldServerRep {
+        handle_set_voxel(storage, registries, &logger(), coord, stone())
+    }
+
+    fn is_acknowledged(rep: WorldServerRep, coord: Coordinate) -> bool {
+        matches!(rep, WorldServerRep::SetVoxelAcknowledged(acknowledged) if acknowledged == coord)
+    }
+
+    fn is_denied(rep: WorldServerRep, coord: Coordinate) -> bool {
+        matches!(rep, WorldServerRep::SetVoxelDenied(denied) if denied == coord)
+    }
+
+    #[test]
+    fn test_set_voxel_acknowledged() {
+        let registries = get_registries();
+        let (mut storage, path) = temp_storage("acknowledged");
+        storage.store_column(ChunkColumn::new(0, 0)).unwrap();
+
+        let coord = Coordinate::new(3, 70, 12);
+        assert!(is_acknowledged(
+            set_voxel(&mut storage, &registries, coord),
+            coord
+        ));
+        assert!(is_stone(&mut storage, coord));
+
+        // the change was saved to disk
+        let mut storage = ChunkStorage::new(path).unwrap();
+        assert!(is_stone(&mut storage, coord));
+    }
+
+    #[test]
+    fn test_set_voxel_outside_of_the_world() {
+        let registries = get_registries();
+        let (mut storage, _) = temp_storage("outside_of_the_world");
+        storage.store_column(ChunkColumn::new(0, 0)).unwrap();
+
+        for coord in [Coordinate::new(3, -1, 12), Coordinate::new(3, 384, 12)] {
+            assert!(is_denied(
+                set_voxel(&mut storage, &registries, coord),
+                coord
+            ));
+        }
+    }
+
+    #[test]
+    fn test_set_voxel_missing_column() {
+        let registries = get_registries();
+        let (mut storage, _) = temp_storage("missing_column");
+        storage.store_column(ChunkColumn::new(0, 0)).unwrap();
+
+        let coord = Coordinate::new(16, 70, 0);
+        assert!(is_denied(
+            set_voxel(&mut storage, &registries, coord),
+            coord
+        ));
+        // the column is not created by the denied change
+        assert!(storage
+            .get_column(&ChunkColumnCoordinate { x: 1, z: 0 })
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_set_voxel_negative_coordinates() {
+        let registries = get_registries();
+        let (mut storage, _) = temp_storage("negative_coordinates");
+        storage.store_column(ChunkColumn::new(-1, -1)).unwrap();
+
+        // the last voxel of the column at (-1, -1), in its second section
+        let coord = Coordinate::new(-1, 31, -1);
+        assert!(is_acknowledged(
+            set_voxel(&mut storage, &registries, coord),
+            coord
+        ));
+        assert!(is_stone(&mut storage, coord));
+        let column = storage
+            .get_column(&ChunkColumnCoordinate { x: -1, z: -1 })
+            .unwrap()
+            .unwrap();
+        let section = column.get_chunk(1).unwrap();
+        assert_eq!(section.zero_coordinate(), Coordinate::new(-16, 16, -16));
+
+        // rounding toward zero would have picked the column at (0, 0), which is missing
+        let coord = Coordinate::new(-16, 0, -16);
+        assert!(is_acknowledged(
+            set_voxel(&mut storage, &registries, coord),
+            coord
+        ));
+    }
+
+    #[test]
+    fn test_set_voxels() {
+        let registries = get_registries();
+        let (mut storage, _) = temp_storage("set_voxels");
+        storage.store_column(ChunkColumn::new(0, 0)).unwrap();
+
+        let valid = Coordinate::new(0, 64, 0);
+        let changes = vec![
+            (valid, stone()),
+            (Coordinate::new(0, -1, 0), stone()),
+            (Coordinate::new(16, 64, 0), stone()),
+        ];
+        let rep = handle_set_voxels(&mut storage, &registries, &logger(), changes);
+        let WorldServerRep::SetVoxelsDenied(denied) = rep else {
+            panic!("Expected the denied changes");
+        };
+        assert_eq!(
+            denied,
+            vec![Coordinate::new(0, -1, 0), Coordinate::new(16, 64, 0)]
+        );
+        assert!(is_stone(&mut storage, valid));
+    }
+}